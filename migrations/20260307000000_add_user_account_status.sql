@@ -0,0 +1,9 @@
+-- Account status columns for admin user management
+-- disabled_at: account disabled by an admin (NULL = active)
+-- sessions_revoked_at: access tokens issued before this time are rejected (force logout)
+ALTER TABLE users
+ADD COLUMN disabled_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN sessions_revoked_at TIMESTAMP WITH TIME ZONE;
+
+-- Create index for filtering users by account status
+CREATE INDEX idx_users_disabled_at ON users(disabled_at);
@@ -0,0 +1,46 @@
+-- Roles defined as sets of named permissions
+CREATE TABLE roles (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE permissions (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE role_permissions (
+    role TEXT NOT NULL REFERENCES roles(name) ON DELETE CASCADE,
+    permission TEXT NOT NULL REFERENCES permissions(name) ON DELETE CASCADE,
+    PRIMARY KEY (role, permission)
+);
+
+-- Seed built-in roles
+INSERT INTO roles (name, description) VALUES
+    ('admin', 'Full access to all management endpoints'),
+    ('user', 'Customer account'),
+    ('barista', 'Shop staff who progress orders');
+
+-- Seed permissions
+INSERT INTO permissions (name, description) VALUES
+    ('menu:write', 'Create, update and delete coffees, availability and prep times'),
+    ('orders:manage', 'Update order and payment status'),
+    ('pricing:write', 'Manage pricing rules and loyalty configuration'),
+    ('analytics:read', 'View analytics reports'),
+    ('users:manage', 'Manage user accounts, roles and role permissions');
+
+-- Admins get every permission; baristas can only manage orders
+INSERT INTO role_permissions (role, permission)
+SELECT 'admin', name FROM permissions;
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('barista', 'orders:manage');
+
+-- Users reference roles instead of a fixed list
+ALTER TABLE users DROP CONSTRAINT chk_user_role;
+
+ALTER TABLE users
+    ADD CONSTRAINT fk_users_role
+    FOREIGN KEY (role)
+    REFERENCES roles(name);
@@ -0,0 +1,26 @@
+-- Asymmetric JWT signing keys, published via /.well-known/jwks.json
+CREATE TABLE jwt_signing_keys (
+    kid TEXT PRIMARY KEY,
+    algorithm VARCHAR(16) NOT NULL,
+    private_key BYTEA NOT NULL,
+    public_jwk TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Signing starts here; rotated keys are published before they sign
+    activated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Set when a newer key takes over signing
+    retired_at TIMESTAMP WITH TIME ZONE,
+    -- Tokens signed with a retired key verify until this time
+    expires_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT chk_jwt_signing_key_algorithm CHECK (algorithm IN ('RS256', 'EdDSA'))
+);
+
+-- At most one current (or pending) key
+CREATE UNIQUE INDEX idx_jwt_signing_keys_active ON jwt_signing_keys ((retired_at IS NULL))
+    WHERE retired_at IS NULL;
+
+-- Permission for listing and rotating signing keys
+INSERT INTO permissions (name, description) VALUES
+    ('keys:manage', 'List and rotate JWT signing keys');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'keys:manage');
@@ -0,0 +1,9 @@
+-- Each refresh token row is a login session; refreshing rotates the token in place
+ALTER TABLE refresh_tokens
+    ADD COLUMN device_name TEXT,
+    ADD COLUMN user_agent TEXT,
+    ADD COLUMN ip_address TEXT,
+    ADD COLUMN last_used_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
+
+-- Index for idle-session expiry
+CREATE INDEX idx_refresh_tokens_last_used_at ON refresh_tokens(last_used_at);
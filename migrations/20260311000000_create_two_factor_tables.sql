@@ -0,0 +1,53 @@
+-- TOTP two-factor authentication
+-- enabled_at: NULL while enrolment is pending confirmation
+-- last_used_step: most recent accepted time step, so a code cannot be replayed
+CREATE TABLE user_two_factor (
+    user_id INTEGER PRIMARY KEY,
+    secret VARCHAR(64) NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE,
+    last_used_step BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE user_two_factor
+    ADD CONSTRAINT fk_user_two_factor_user_id
+    FOREIGN KEY (user_id)
+    REFERENCES users(id)
+    ON DELETE CASCADE;
+
+-- Single-use recovery codes, hashed with Argon2id like passwords
+CREATE TABLE two_factor_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    code_hash VARCHAR(255) NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE two_factor_recovery_codes
+    ADD CONSTRAINT fk_two_factor_recovery_codes_user_id
+    FOREIGN KEY (user_id)
+    REFERENCES users(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX idx_two_factor_recovery_codes_user_id ON two_factor_recovery_codes(user_id);
+
+-- Short-lived challenges issued after a correct password for accounts with 2FA
+CREATE TABLE two_factor_challenges (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    token_hash VARCHAR(64) NOT NULL,
+    device_name VARCHAR(100),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE two_factor_challenges
+    ADD CONSTRAINT fk_two_factor_challenges_user_id
+    FOREIGN KEY (user_id)
+    REFERENCES users(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX idx_two_factor_challenges_user_id ON two_factor_challenges(user_id);
+CREATE UNIQUE INDEX idx_two_factor_challenges_token_hash ON two_factor_challenges(token_hash);
@@ -0,0 +1,21 @@
+-- Account deletion anonymises the user instead of deleting the row
+-- deleted_at: account erased at the user's request (email and credentials scrubbed)
+ALTER TABLE users
+ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- Reviews outlive their author: authorship is cleared, the rating is kept
+ALTER TABLE reviews
+    ALTER COLUMN user_id DROP NOT NULL,
+    DROP CONSTRAINT reviews_user_id_fkey,
+    ADD CONSTRAINT reviews_user_id_fkey
+    FOREIGN KEY (user_id)
+    REFERENCES users(id)
+    ON DELETE SET NULL;
+
+-- Orders are financial records: never remove them together with a user
+ALTER TABLE orders
+    DROP CONSTRAINT orders_user_id_fkey,
+    ADD CONSTRAINT orders_user_id_fkey
+    FOREIGN KEY (user_id)
+    REFERENCES users(id)
+    ON DELETE RESTRICT;
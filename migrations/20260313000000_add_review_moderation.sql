@@ -0,0 +1,32 @@
+-- Review moderation: publication state, user reports and the moderation permission
+ALTER TABLE reviews
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'published'
+        CHECK (status IN ('published', 'pending', 'hidden', 'removed')),
+    ADD COLUMN moderation_reason TEXT,
+    ADD COLUMN moderated_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN moderated_at TIMESTAMP WITH TIME ZONE;
+
+-- Public listings and rating averages only read published reviews
+CREATE INDEX idx_reviews_coffee_status ON reviews(coffee_id, status);
+
+-- Reports filed by users against a review; resolved when a moderator acts on it
+CREATE TABLE review_flags (
+    id SERIAL PRIMARY KEY,
+    review_id INTEGER NOT NULL REFERENCES reviews(id) ON DELETE CASCADE,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    reason TEXT NOT NULL CHECK (reason IN ('spam', 'offensive', 'off_topic', 'other')),
+    details TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT review_flags_review_user_unique UNIQUE(review_id, user_id)
+);
+
+-- Moderation queue lookups only care about open flags
+CREATE INDEX idx_review_flags_open ON review_flags(review_id) WHERE resolved_at IS NULL;
+
+-- Permission for the moderation queue
+INSERT INTO permissions (name, description) VALUES
+    ('reviews:moderate', 'Review the moderation queue and publish, hide or remove reviews');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'reviews:moderate');
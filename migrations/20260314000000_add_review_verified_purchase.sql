@@ -0,0 +1,14 @@
+-- Reviews written by customers who bought the coffee in a completed order
+ALTER TABLE reviews
+    ADD COLUMN verified_purchase BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE reviews r
+SET verified_purchase = EXISTS (
+    SELECT 1
+    FROM orders o
+    JOIN order_items oi ON oi.order_id = o.id
+    WHERE o.user_id = r.user_id
+      AND oi.coffee_item_id = r.coffee_id
+      AND o.status = 'completed'
+);
+
@@ -0,0 +1,14 @@
+-- "Helpful" votes on reviews, one per user per review
+CREATE TABLE review_votes (
+    review_id INTEGER NOT NULL REFERENCES reviews(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (review_id, user_id)
+);
+
+-- Vote count kept on the review so listings can sort by it
+ALTER TABLE reviews
+    ADD COLUMN helpful_count INTEGER NOT NULL DEFAULT 0 CHECK (helpful_count >= 0);
+
+-- Keyset pagination for the default (newest first) listing
+CREATE INDEX idx_reviews_coffee_created ON reviews(coffee_id, created_at DESC, id DESC);
@@ -0,0 +1,16 @@
+-- Public replies from the shop, at most one per review
+CREATE TABLE review_replies (
+    review_id INTEGER PRIMARY KEY REFERENCES reviews(id) ON DELETE CASCADE,
+    author_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    body TEXT NOT NULL CHECK (length(body) BETWEEN 1 AND 1000),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Permission for replying to reviews, granted to admins and shop staff
+INSERT INTO permissions (name, description) VALUES
+    ('reviews:reply', 'Post, edit and delete the shop reply on a review');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'reviews:reply'),
+    ('barista', 'reviews:reply');
@@ -0,0 +1,10 @@
+-- Confidence-weighted rating (Bayesian average or Wilson lower bound)
+-- Maintained by the rating calculator next to the raw average_rating,
+-- and recalculated for every coffee at startup so setting changes apply
+ALTER TABLE coffees ADD COLUMN weighted_rating NUMERIC(3,2);
+
+ALTER TABLE coffees ADD CONSTRAINT coffees_weighted_rating_check
+    CHECK (weighted_rating IS NULL OR (weighted_rating >= 0.0 AND weighted_rating <= 5.0));
+
+-- Rating sorts and highest-rated rankings order by the weighted score
+CREATE INDEX idx_coffees_weighted_rating ON coffees(weighted_rating DESC NULLS LAST);
@@ -0,0 +1,106 @@
+-- Running totals of published ratings per coffee
+-- Kept up to date by a trigger on reviews, so each review write adjusts the
+-- coffee in the same transaction instead of re-reading every rating
+ALTER TABLE coffees
+    ADD COLUMN rating_sum BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN rating_1_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN rating_2_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN rating_3_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN rating_4_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN rating_5_count INTEGER NOT NULL DEFAULT 0;
+
+-- Add (delta = 1) or remove (delta = -1) one published rating from a coffee
+CREATE OR REPLACE FUNCTION adjust_coffee_rating(p_coffee_id INTEGER, p_rating SMALLINT, p_delta INTEGER)
+RETURNS VOID AS $$
+BEGIN
+    UPDATE coffees
+    SET rating_sum = rating_sum + p_delta * p_rating,
+        review_count = review_count + p_delta,
+        rating_1_count = rating_1_count + CASE WHEN p_rating = 1 THEN p_delta ELSE 0 END,
+        rating_2_count = rating_2_count + CASE WHEN p_rating = 2 THEN p_delta ELSE 0 END,
+        rating_3_count = rating_3_count + CASE WHEN p_rating = 3 THEN p_delta ELSE 0 END,
+        rating_4_count = rating_4_count + CASE WHEN p_rating = 4 THEN p_delta ELSE 0 END,
+        rating_5_count = rating_5_count + CASE WHEN p_rating = 5 THEN p_delta ELSE 0 END,
+        average_rating = CASE
+            WHEN review_count + p_delta > 0
+            THEN ROUND((rating_sum + p_delta * p_rating)::NUMERIC / (review_count + p_delta), 2)
+        END
+    WHERE id = p_coffee_id;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Only published reviews count towards a coffee's rating
+CREATE OR REPLACE FUNCTION update_coffee_rating_aggregates()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND OLD.status = NEW.status
+        AND OLD.rating = NEW.rating
+        AND OLD.coffee_id = NEW.coffee_id THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'published' THEN
+        PERFORM adjust_coffee_rating(OLD.coffee_id, OLD.rating, -1);
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'published' THEN
+        PERFORM adjust_coffee_rating(NEW.coffee_id, NEW.rating, 1);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_coffee_rating_aggregates
+    AFTER INSERT OR DELETE OR UPDATE OF rating, status, coffee_id ON reviews
+    FOR EACH ROW
+    EXECUTE FUNCTION update_coffee_rating_aggregates();
+
+-- Rebuild the totals from reviews; returns the number of coffees that had drifted
+-- Review writes are blocked while it runs so no adjustment is lost
+CREATE OR REPLACE FUNCTION rebuild_coffee_rating_aggregates()
+RETURNS INTEGER AS $$
+DECLARE
+    repaired INTEGER;
+BEGIN
+    LOCK TABLE reviews IN SHARE MODE;
+
+    UPDATE coffees c
+    SET rating_sum = a.rating_sum,
+        review_count = a.review_count,
+        rating_1_count = a.rating_1_count,
+        rating_2_count = a.rating_2_count,
+        rating_3_count = a.rating_3_count,
+        rating_4_count = a.rating_4_count,
+        rating_5_count = a.rating_5_count,
+        average_rating = a.average_rating
+    FROM (
+        SELECT
+            c.id,
+            COALESCE(SUM(r.rating), 0) AS rating_sum,
+            COUNT(r.id)::INTEGER AS review_count,
+            COUNT(r.id) FILTER (WHERE r.rating = 1)::INTEGER AS rating_1_count,
+            COUNT(r.id) FILTER (WHERE r.rating = 2)::INTEGER AS rating_2_count,
+            COUNT(r.id) FILTER (WHERE r.rating = 3)::INTEGER AS rating_3_count,
+            COUNT(r.id) FILTER (WHERE r.rating = 4)::INTEGER AS rating_4_count,
+            COUNT(r.id) FILTER (WHERE r.rating = 5)::INTEGER AS rating_5_count,
+            ROUND(AVG(r.rating), 2) AS average_rating
+        FROM coffees c
+        LEFT JOIN reviews r ON r.coffee_id = c.id AND r.status = 'published'
+        GROUP BY c.id
+    ) a
+    WHERE c.id = a.id
+        AND (c.rating_sum, c.review_count, c.rating_1_count, c.rating_2_count, c.rating_3_count,
+             c.rating_4_count, c.rating_5_count, c.average_rating)
+        IS DISTINCT FROM
+            (a.rating_sum, a.review_count, a.rating_1_count, a.rating_2_count, a.rating_3_count,
+             a.rating_4_count, a.rating_5_count, a.average_rating);
+
+    GET DIAGNOSTICS repaired = ROW_COUNT;
+    RETURN repaired;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Backfill existing coffees
+SELECT rebuild_coffee_rating_aggregates();
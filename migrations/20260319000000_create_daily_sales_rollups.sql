@@ -0,0 +1,179 @@
+-- Pre-aggregated daily sales of completed orders, so analytics read one row
+-- per day (and coffee) instead of scanning orders and order_items
+-- Days are UTC calendar days
+
+-- Totals per day; gross is the orders' item subtotals (or total_price for an
+-- order without items) and discounts is gross minus the price actually charged
+CREATE TABLE daily_sales (
+    day DATE PRIMARY KEY,
+    order_count INTEGER NOT NULL DEFAULT 0,
+    units INTEGER NOT NULL DEFAULT 0,
+    gross DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    discounts DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    net DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Totals per day and coffee
+-- gross is the sum of item subtotals; the order's discount (item subtotals
+-- minus total_price) is shared between its items in proportion to their subtotal
+CREATE TABLE daily_coffee_sales (
+    day DATE NOT NULL,
+    coffee_id INTEGER NOT NULL REFERENCES coffees(id) ON DELETE CASCADE,
+    order_count INTEGER NOT NULL DEFAULT 0,
+    units INTEGER NOT NULL DEFAULT 0,
+    gross DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    discounts DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    net DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (day, coffee_id)
+);
+
+CREATE INDEX idx_daily_coffee_sales_coffee ON daily_coffee_sales(coffee_id, day);
+
+-- Recompute one day's rollups from the raw tables
+-- Concurrent refreshes of the same day are serialized, and each statement
+-- sees the orders committed by the refresh that ran before it
+CREATE OR REPLACE FUNCTION refresh_daily_sales(p_day DATE)
+RETURNS VOID AS $$
+DECLARE
+    day_start TIMESTAMPTZ := p_day::TIMESTAMP AT TIME ZONE 'UTC';
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('daily_sales'), p_day - DATE '2000-01-01');
+
+    DELETE FROM daily_coffee_sales WHERE day = p_day;
+    DELETE FROM daily_sales WHERE day = p_day;
+
+    WITH day_orders AS (
+        SELECT
+            o.id,
+            o.total_price,
+            COALESCE(SUM(oi.subtotal), 0) AS items_total,
+            COALESCE(SUM(oi.quantity), 0) AS units
+        FROM orders o
+        LEFT JOIN order_items oi ON oi.order_id = o.id
+        WHERE o.status = 'completed'
+          AND o.created_at >= day_start
+          AND o.created_at < day_start + INTERVAL '1 day'
+        GROUP BY o.id, o.total_price
+    )
+    INSERT INTO daily_sales (day, order_count, units, gross, discounts, net)
+    SELECT
+        p_day,
+        COUNT(*),
+        SUM(units),
+        SUM(CASE WHEN items_total > 0 THEN items_total ELSE total_price END),
+        SUM(CASE WHEN items_total > 0 THEN items_total ELSE total_price END - total_price),
+        SUM(total_price)
+    FROM day_orders
+    HAVING COUNT(*) > 0;
+
+    WITH day_items AS (
+        SELECT
+            o.id AS order_id,
+            oi.coffee_item_id AS coffee_id,
+            oi.quantity,
+            oi.subtotal,
+            ROUND(CASE
+                WHEN SUM(oi.subtotal) OVER (PARTITION BY o.id) > 0
+                THEN oi.subtotal * o.total_price / SUM(oi.subtotal) OVER (PARTITION BY o.id)
+                ELSE 0
+            END, 2) AS net
+        FROM orders o
+        INNER JOIN order_items oi ON oi.order_id = o.id
+        WHERE o.status = 'completed'
+          AND o.created_at >= day_start
+          AND o.created_at < day_start + INTERVAL '1 day'
+    )
+    INSERT INTO daily_coffee_sales (day, coffee_id, order_count, units, gross, discounts, net)
+    SELECT
+        p_day,
+        coffee_id,
+        COUNT(DISTINCT order_id),
+        SUM(quantity),
+        SUM(subtotal),
+        SUM(subtotal - net),
+        SUM(net)
+    FROM day_items
+    GROUP BY coffee_id;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Refresh the day of every completed order that is created, completed,
+-- cancelled after completion, edited or deleted
+CREATE OR REPLACE FUNCTION update_daily_sales_from_orders()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'completed' THEN
+        PERFORM refresh_daily_sales((OLD.created_at AT TIME ZONE 'UTC')::DATE);
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'completed'
+        AND (TG_OP = 'INSERT'
+             OR OLD.status <> 'completed'
+             OR (OLD.created_at AT TIME ZONE 'UTC')::DATE <> (NEW.created_at AT TIME ZONE 'UTC')::DATE) THEN
+        PERFORM refresh_daily_sales((NEW.created_at AT TIME ZONE 'UTC')::DATE);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_daily_sales_from_orders
+    AFTER INSERT OR DELETE OR UPDATE OF status, total_price, created_at ON orders
+    FOR EACH ROW
+    EXECUTE FUNCTION update_daily_sales_from_orders();
+
+-- Items only change the rollups once their order is completed
+CREATE OR REPLACE FUNCTION update_daily_sales_from_order_items()
+RETURNS TRIGGER AS $$
+DECLARE
+    order_day DATE;
+BEGIN
+    SELECT (created_at AT TIME ZONE 'UTC')::DATE INTO order_day
+    FROM orders
+    WHERE id = COALESCE(NEW.order_id, OLD.order_id)
+      AND status = 'completed';
+
+    IF order_day IS NOT NULL THEN
+        PERFORM refresh_daily_sales(order_day);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_daily_sales_from_order_items
+    AFTER INSERT OR DELETE OR UPDATE ON order_items
+    FOR EACH ROW
+    EXECUTE FUNCTION update_daily_sales_from_order_items();
+
+-- Rebuild every day's rollups from the raw tables; returns the number of days rebuilt
+-- Order writes are blocked while it runs so no refresh is lost
+CREATE OR REPLACE FUNCTION rebuild_daily_sales()
+RETURNS INTEGER AS $$
+DECLARE
+    sales_day DATE;
+    rebuilt INTEGER := 0;
+BEGIN
+    LOCK TABLE orders, order_items IN SHARE MODE;
+
+    DELETE FROM daily_coffee_sales;
+    DELETE FROM daily_sales;
+
+    FOR sales_day IN
+        SELECT DISTINCT (created_at AT TIME ZONE 'UTC')::DATE
+        FROM orders
+        WHERE status = 'completed'
+        ORDER BY 1
+    LOOP
+        PERFORM refresh_daily_sales(sales_day);
+        rebuilt := rebuilt + 1;
+    END LOOP;
+
+    RETURN rebuilt;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Backfill existing orders
+SELECT rebuild_daily_sales();
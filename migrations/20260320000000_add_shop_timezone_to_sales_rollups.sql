@@ -0,0 +1,143 @@
+-- Bucket the daily sales rollups by the shop's local day instead of the UTC day
+-- The application stores its SHOP_TIMEZONE here at startup and rebuilds the
+-- rollups when it changes
+CREATE TABLE shop_settings (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO shop_settings (id) VALUES (1);
+
+CREATE OR REPLACE FUNCTION shop_timezone()
+RETURNS TEXT AS $$
+    SELECT COALESCE((SELECT timezone FROM shop_settings WHERE id = 1), 'UTC');
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION refresh_daily_sales(p_day DATE)
+RETURNS VOID AS $$
+DECLARE
+    day_start TIMESTAMPTZ := p_day::TIMESTAMP AT TIME ZONE shop_timezone();
+    day_end TIMESTAMPTZ := (p_day + 1)::TIMESTAMP AT TIME ZONE shop_timezone();
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('daily_sales'), p_day - DATE '2000-01-01');
+
+    DELETE FROM daily_coffee_sales WHERE day = p_day;
+    DELETE FROM daily_sales WHERE day = p_day;
+
+    WITH day_orders AS (
+        SELECT
+            o.id,
+            o.total_price,
+            COALESCE(SUM(oi.subtotal), 0) AS items_total,
+            COALESCE(SUM(oi.quantity), 0) AS units
+        FROM orders o
+        LEFT JOIN order_items oi ON oi.order_id = o.id
+        WHERE o.status = 'completed'
+          AND o.created_at >= day_start
+          AND o.created_at < day_end
+        GROUP BY o.id, o.total_price
+    )
+    INSERT INTO daily_sales (day, order_count, units, gross, discounts, net)
+    SELECT
+        p_day,
+        COUNT(*),
+        SUM(units),
+        SUM(CASE WHEN items_total > 0 THEN items_total ELSE total_price END),
+        SUM(CASE WHEN items_total > 0 THEN items_total ELSE total_price END - total_price),
+        SUM(total_price)
+    FROM day_orders
+    HAVING COUNT(*) > 0;
+
+    WITH day_items AS (
+        SELECT
+            o.id AS order_id,
+            oi.coffee_item_id AS coffee_id,
+            oi.quantity,
+            oi.subtotal,
+            ROUND(CASE
+                WHEN SUM(oi.subtotal) OVER (PARTITION BY o.id) > 0
+                THEN oi.subtotal * o.total_price / SUM(oi.subtotal) OVER (PARTITION BY o.id)
+                ELSE 0
+            END, 2) AS net
+        FROM orders o
+        INNER JOIN order_items oi ON oi.order_id = o.id
+        WHERE o.status = 'completed'
+          AND o.created_at >= day_start
+          AND o.created_at < day_end
+    )
+    INSERT INTO daily_coffee_sales (day, coffee_id, order_count, units, gross, discounts, net)
+    SELECT
+        p_day,
+        coffee_id,
+        COUNT(DISTINCT order_id),
+        SUM(quantity),
+        SUM(subtotal),
+        SUM(subtotal - net),
+        SUM(net)
+    FROM day_items
+    GROUP BY coffee_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION update_daily_sales_from_orders()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'completed' THEN
+        PERFORM refresh_daily_sales((OLD.created_at AT TIME ZONE shop_timezone())::DATE);
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'completed'
+        AND (TG_OP = 'INSERT'
+             OR OLD.status <> 'completed'
+             OR (OLD.created_at AT TIME ZONE shop_timezone())::DATE
+                <> (NEW.created_at AT TIME ZONE shop_timezone())::DATE) THEN
+        PERFORM refresh_daily_sales((NEW.created_at AT TIME ZONE shop_timezone())::DATE);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION update_daily_sales_from_order_items()
+RETURNS TRIGGER AS $$
+DECLARE
+    order_day DATE;
+BEGIN
+    SELECT (created_at AT TIME ZONE shop_timezone())::DATE INTO order_day
+    FROM orders
+    WHERE id = COALESCE(NEW.order_id, OLD.order_id)
+      AND status = 'completed';
+
+    IF order_day IS NOT NULL THEN
+        PERFORM refresh_daily_sales(order_day);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION rebuild_daily_sales()
+RETURNS INTEGER AS $$
+DECLARE
+    sales_day DATE;
+    rebuilt INTEGER := 0;
+BEGIN
+    LOCK TABLE orders, order_items IN SHARE MODE;
+
+    DELETE FROM daily_coffee_sales;
+    DELETE FROM daily_sales;
+
+    FOR sales_day IN
+        SELECT DISTINCT (created_at AT TIME ZONE shop_timezone())::DATE
+        FROM orders
+        WHERE status = 'completed'
+        ORDER BY 1
+    LOOP
+        PERFORM refresh_daily_sales(sales_day);
+        rebuilt := rebuilt + 1;
+    END LOOP;
+
+    RETURN rebuilt;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,30 @@
+-- Frequently-bought-together statistics of coffee pairs in completed orders
+-- Rebuilt by the market basket job over a trailing window; every pair is
+-- stored in both directions because confidence depends on the direction
+CREATE TABLE coffee_pairs (
+    coffee_id INTEGER NOT NULL REFERENCES coffees(id) ON DELETE CASCADE,
+    paired_coffee_id INTEGER NOT NULL REFERENCES coffees(id) ON DELETE CASCADE,
+    -- Orders containing both coffees
+    pair_orders INTEGER NOT NULL CHECK (pair_orders > 0),
+    -- Orders containing coffee_id, paired_coffee_id and any coffee in the window
+    coffee_orders INTEGER NOT NULL CHECK (coffee_orders >= pair_orders),
+    paired_orders INTEGER NOT NULL CHECK (paired_orders >= pair_orders),
+    total_orders INTEGER NOT NULL CHECK (total_orders >= coffee_orders),
+    -- pair_orders / total_orders
+    support DECIMAL(10, 6) NOT NULL,
+    -- pair_orders / coffee_orders: how often buyers of coffee_id also took paired_coffee_id
+    confidence DECIMAL(10, 6) NOT NULL,
+    -- confidence / (paired_orders / total_orders): above 1 when bought together more than by chance
+    lift DECIMAL(14, 4) NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    window_end TIMESTAMPTZ NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (coffee_id, paired_coffee_id),
+    CHECK (coffee_id <> paired_coffee_id)
+);
+
+-- Create index for the add-on suggestions of one coffee
+CREATE INDEX idx_coffee_pairs_confidence ON coffee_pairs(coffee_id, confidence DESC);
+
+-- Create index for the admin report, strongest associations first
+CREATE INDEX idx_coffee_pairs_lift ON coffee_pairs(lift DESC);
@@ -0,0 +1,103 @@
+-- Refresh the daily sales rollups outside the order transactions
+-- The triggers only record which days changed; the application recomputes
+-- those days periodically with refresh_changed_daily_sales(), and reports read
+-- a changed day from the raw tables until then
+
+-- Days whose rollups are out of date, one row per change
+-- Append-only so concurrent orders of the same day never wait on each other
+CREATE TABLE daily_sales_changes (
+    id BIGSERIAL PRIMARY KEY,
+    day DATE NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_daily_sales_changes_day ON daily_sales_changes(day);
+
+-- Mark the day of every completed order that is created, completed,
+-- cancelled after completion, edited or deleted
+CREATE OR REPLACE FUNCTION update_daily_sales_from_orders()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'completed' THEN
+        INSERT INTO daily_sales_changes (day)
+        VALUES ((OLD.created_at AT TIME ZONE shop_timezone())::DATE);
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'completed'
+        AND (TG_OP = 'INSERT'
+             OR OLD.status <> 'completed'
+             OR (OLD.created_at AT TIME ZONE shop_timezone())::DATE
+                <> (NEW.created_at AT TIME ZONE shop_timezone())::DATE) THEN
+        INSERT INTO daily_sales_changes (day)
+        VALUES ((NEW.created_at AT TIME ZONE shop_timezone())::DATE);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Items only change the rollups once their order is completed
+CREATE OR REPLACE FUNCTION update_daily_sales_from_order_items()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO daily_sales_changes (day)
+    SELECT (created_at AT TIME ZONE shop_timezone())::DATE
+    FROM orders
+    WHERE id = COALESCE(NEW.order_id, OLD.order_id)
+      AND status = 'completed';
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Recompute the days changed since the last refresh; returns the number of days
+-- Changes committed while it runs are left for the next refresh
+CREATE OR REPLACE FUNCTION refresh_changed_daily_sales()
+RETURNS INTEGER AS $$
+DECLARE
+    changed_days DATE[];
+    sales_day DATE;
+BEGIN
+    WITH taken AS (
+        DELETE FROM daily_sales_changes
+        RETURNING day
+    )
+    SELECT COALESCE(ARRAY_AGG(DISTINCT day ORDER BY day), '{}') INTO changed_days
+    FROM taken;
+
+    FOREACH sales_day IN ARRAY changed_days
+    LOOP
+        PERFORM refresh_daily_sales(sales_day);
+    END LOOP;
+
+    RETURN COALESCE(ARRAY_LENGTH(changed_days, 1), 0);
+END;
+$$ LANGUAGE plpgsql;
+
+-- Rebuild every day's rollups from the raw tables; returns the number of days rebuilt
+-- Order writes are blocked while it runs so no change is lost
+CREATE OR REPLACE FUNCTION rebuild_daily_sales()
+RETURNS INTEGER AS $$
+DECLARE
+    sales_day DATE;
+    rebuilt INTEGER := 0;
+BEGIN
+    LOCK TABLE orders, order_items IN SHARE MODE;
+
+    DELETE FROM daily_sales_changes;
+    DELETE FROM daily_coffee_sales;
+    DELETE FROM daily_sales;
+
+    FOR sales_day IN
+        SELECT DISTINCT (created_at AT TIME ZONE shop_timezone())::DATE
+        FROM orders
+        WHERE status = 'completed'
+        ORDER BY 1
+    LOOP
+        PERFORM refresh_daily_sales(sales_day);
+        rebuilt := rebuilt + 1;
+    END LOOP;
+
+    RETURN rebuilt;
+END;
+$$ LANGUAGE plpgsql;
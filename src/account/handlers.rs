@@ -0,0 +1,57 @@
+// HTTP handlers for personal data endpoints
+
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::account::models::{DeleteAccountRequest, PersonalDataExport};
+use crate::auth::{error::AuthError, middleware::AuthenticatedUser};
+use crate::AppState;
+
+/// Export all personal data of the current user (protected endpoint)
+/// GET /api/me/export
+#[utoipa::path(
+    get,
+    path = "/api/me/export",
+    responses(
+        (status = 200, description = "JSON archive of the user's data", body = PersonalDataExport),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String)
+    ),
+    tag = "account",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn export_personal_data_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<PersonalDataExport>, AuthError> {
+    let export = state.account_service.export_personal_data(user.user_id).await?;
+
+    Ok(Json(export))
+}
+
+/// Delete the current user's account (protected endpoint)
+/// DELETE /api/me
+///
+/// Personal data is erased; orders are kept anonymously for accounting.
+#[utoipa::path(
+    delete,
+    path = "/api/me",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 401, description = "Unauthorized or wrong password", body = String)
+    ),
+    tag = "account",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_account_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<DeleteAccountRequest>,
+) -> Result<StatusCode, AuthError> {
+    state.account_service.delete_account(user.user_id, &request.password).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
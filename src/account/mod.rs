@@ -0,0 +1,11 @@
+// Account module
+// Personal data export and account deletion (data-subject requests)
+
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod service;
+
+pub use repository::*;
+pub use service::*;
+pub use handlers::*;
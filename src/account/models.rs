@@ -0,0 +1,124 @@
+// Data models for personal data export and account deletion
+
+use crate::auth::models::{SessionResponse, UserResponse};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Everything stored about a user, returned by GET /api/me/export
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PersonalDataExport {
+    pub exported_at: DateTime<Utc>,
+    pub user: UserResponse,
+    pub two_factor_enabled: bool,
+    pub sessions: Vec<SessionResponse>,
+    pub orders: Vec<ExportedOrder>,
+    pub reviews: Vec<ExportedReview>,
+    pub review_flags: Vec<ExportedReviewFlag>,
+    pub review_votes: Vec<ExportedReviewVote>,
+    pub review_replies: Vec<ExportedReviewReply>,
+    pub loyalty: Option<ExportedLoyalty>,
+    pub security_events: Vec<ExportedSecurityEvent>,
+}
+
+/// Order with its line items
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedOrder {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    pub status: String,
+    pub payment_status: String,
+    #[schema(value_type = String)]
+    pub total_price: Decimal,
+    #[schema(value_type = Option<String>)]
+    pub final_price: Option<Decimal>,
+    pub loyalty_points_awarded: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub items: Vec<ExportedOrderItem>,
+}
+
+/// Line item of an exported order
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedOrderItem {
+    #[serde(skip)]
+    pub order_id: Uuid,
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    pub quantity: i32,
+    #[schema(value_type = String)]
+    pub price_snapshot: Decimal,
+    #[schema(value_type = String)]
+    pub subtotal: Decimal,
+}
+
+/// Review written by the user
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedReview {
+    pub id: i32,
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    pub rating: i16,
+    pub comment: Option<String>,
+    /// Moderation state (published, pending, hidden or removed)
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Report the user filed against a review
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedReviewFlag {
+    pub review_id: i32,
+    /// spam, offensive, off_topic or other
+    pub reason: String,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// "Helpful" vote the user cast on a review
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedReviewVote {
+    pub review_id: i32,
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Shop reply the user wrote on a review
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedReviewReply {
+    pub review_id: i32,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Loyalty points balance
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedLoyalty {
+    pub points_balance: i32,
+    pub lifetime_points: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Security event recorded for the account (logins, lockouts, ...)
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ExportedSecurityEvent {
+    pub event_type: String,
+    pub ip_address: Option<String>,
+    #[schema(value_type = Object)]
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Account deletion request DTO (the password confirms the request)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
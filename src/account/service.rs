@@ -0,0 +1,65 @@
+use crate::account::{models::PersonalDataExport, repository::AccountRepository};
+use crate::auth::{error::AuthError, password::PasswordService, service::AuthService};
+use chrono::Utc;
+use rand::RngCore;
+use std::sync::Arc;
+use tracing::info;
+
+/// Service handling data-subject requests: export and erasure of personal data
+#[derive(Clone)]
+pub struct AccountService {
+    repository: AccountRepository,
+    auth_service: Arc<AuthService>,
+}
+
+impl AccountService {
+    /// Create a new AccountService
+    pub fn new(repository: AccountRepository, auth_service: Arc<AuthService>) -> Self {
+        Self {
+            repository,
+            auth_service,
+        }
+    }
+
+    /// Collect everything stored about the user
+    pub async fn export_personal_data(&self, user_id: i32) -> Result<PersonalDataExport, AuthError> {
+        let user = self.auth_service.get_current_user(user_id).await?;
+        let two_factor = self.auth_service.two_factor_status(user_id).await?;
+        let sessions = self.auth_service.list_sessions(user_id, None).await?;
+
+        Ok(PersonalDataExport {
+            exported_at: Utc::now(),
+            user,
+            two_factor_enabled: two_factor.enabled,
+            sessions,
+            orders: self.repository.find_orders(user_id).await?,
+            reviews: self.repository.find_reviews(user_id).await?,
+            review_flags: self.repository.find_review_flags(user_id).await?,
+            review_votes: self.repository.find_review_votes(user_id).await?,
+            review_replies: self.repository.find_review_replies(user_id).await?,
+            loyalty: self.repository.find_loyalty(user_id).await?,
+            security_events: self.repository.find_security_events(user_id).await?,
+        })
+    }
+
+    /// Delete the user's account after confirming their password
+    ///
+    /// The account is anonymised rather than removed so order financials stay
+    /// intact for revenue reporting. All sessions end immediately.
+    pub async fn delete_account(&self, user_id: i32, password: &str) -> Result<(), AuthError> {
+        self.auth_service.confirm_password(user_id, password).await?;
+
+        // Replace the password with a hash nobody knows the input of
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let secret: String = secret.iter().map(|b| format!("{:02x}", b)).collect();
+        let unusable_password_hash = PasswordService::hash_password(&secret)?;
+
+        if !self.repository.anonymize_user(user_id, &unusable_password_hash).await? {
+            return Err(AuthError::UserNotFound);
+        }
+
+        info!("Account deleted and anonymised: user_id={}", user_id);
+        Ok(())
+    }
+}
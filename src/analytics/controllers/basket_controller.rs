@@ -0,0 +1,289 @@
+// Basket analytics API controller
+// Handles endpoints for order value, units per order, order sizes and discount usage
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
+    services::BasketAnalysisService,
+    types::{ApiResponse, DateRange, ResponseMetadata, TimePeriod},
+    utils::TimePeriodFilter,
+    validation::AnalyticsValidator,
+};
+use super::{bad_request, resolve_tz, ErrorResponse};
+
+/// Query parameters for basket endpoints
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasketQueryParams {
+    /// Start date for the period (ISO 8601 format)
+    pub start_date: Option<DateTime<Utc>>,
+    /// End date for the period (ISO 8601 format)
+    pub end_date: Option<DateTime<Utc>>,
+    /// Granularity for aggregation (daily, weekly, monthly, custom)
+    pub period: Option<String>,
+    /// IANA timezone for day, week and month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl BasketQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "startDate": self.start_date,
+            "endDate": self.end_date,
+            "period": self.period,
+            "tz": self.tz,
+        }))
+    }
+}
+
+/// Basket analytics controller
+pub struct BasketAnalyticsController {
+    service: Arc<BasketAnalysisService>,
+    timezone: Tz,
+}
+
+/// Basket metric computed for a date range and granularity
+#[derive(Clone, Copy)]
+enum BasketMetric {
+    AverageOrderValue,
+    UnitsPerOrder,
+    DiscountShare,
+}
+
+impl BasketMetric {
+    fn report_name(self) -> &'static str {
+        match self {
+            BasketMetric::AverageOrderValue => "average-order-value",
+            BasketMetric::UnitsPerOrder => "units-per-order",
+            BasketMetric::DiscountShare => "discount-share",
+        }
+    }
+}
+
+impl BasketAnalyticsController {
+    /// Create a new basket analytics controller
+    pub fn new(service: Arc<BasketAnalysisService>) -> Self {
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// GET /api/v1/admin/analytics/baskets/average-order-value
+    /// Returns the average charged total per order by period, with changes
+    pub async fn get_average_order_value(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<BasketQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        controller
+            .basket_trends(BasketMetric::AverageOrderValue, &headers, &params)
+            .await
+    }
+
+    /// GET /api/v1/admin/analytics/baskets/units-per-order
+    /// Returns the average units per order by period, with changes
+    pub async fn get_units_per_order(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<BasketQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        controller
+            .basket_trends(BasketMetric::UnitsPerOrder, &headers, &params)
+            .await
+    }
+
+    /// GET /api/v1/admin/analytics/baskets/discount-share
+    /// Returns the percentage of orders that used a discount by period, with changes
+    pub async fn get_discount_share(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<BasketQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        controller
+            .basket_trends(BasketMetric::DiscountShare, &headers, &params)
+            .await
+    }
+
+    /// GET /api/v1/admin/analytics/baskets/order-sizes
+    /// Returns the number and share of orders by size (units) per period, with changes
+    pub async fn get_order_size_distribution(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<BasketQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let (date_range, period) = controller.parse_params(&params)?;
+
+        let buckets = controller
+            .service
+            .calculate_order_size_distribution(date_range.clone(), period)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to calculate order sizes: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("order-sizes", Some(&date_range), format);
+            return export_report(buckets, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(buckets.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(buckets, metadata)),
+        )
+            .into_response())
+    }
+
+    async fn basket_trends(
+        &self,
+        metric: BasketMetric,
+        headers: &HeaderMap,
+        params: &BasketQueryParams,
+    ) -> Result<axum::response::Response, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), headers)?;
+        let (date_range, period) = self.parse_params(params)?;
+
+        let result = match metric {
+            BasketMetric::AverageOrderValue => {
+                self.service.calculate_average_order_value(date_range.clone(), period).await
+            }
+            BasketMetric::UnitsPerOrder => {
+                self.service.calculate_units_per_order(date_range.clone(), period).await
+            }
+            BasketMetric::DiscountShare => {
+                self.service.calculate_discount_share(date_range.clone(), period).await
+            }
+        };
+        let trends = result.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    format!("Failed to calculate {}: {}", metric.report_name().replace('-', " "), e),
+                    params.metadata(),
+                )),
+            )
+        })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename(metric.report_name(), Some(&date_range), format);
+            return export_report(trends, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(trends.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(trends, metadata)),
+        )
+            .into_response())
+    }
+
+    /// Resolve the timezone, date range and period shared by every basket endpoint
+    fn parse_params(
+        &self,
+        params: &BasketQueryParams,
+    ) -> Result<(DateRange, TimePeriod), ErrorResponse> {
+        // Validate date range (order, not in the future, at most a year) and period
+        AnalyticsValidator::validate_query_params(
+            params.start_date,
+            params.end_date,
+            params.period.as_deref(),
+            None,
+            None,
+        )
+        .map_err(|e| (e.status_code(), Json(e.to_response(params.metadata()))))?;
+
+        let invalid = |e: String| bad_request(e, params.metadata());
+
+        let timezone = resolve_tz(params.tz.as_deref(), self.timezone, params.metadata())?;
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
+            .map_err(invalid)?;
+        let period = match &params.period {
+            Some(period_str) => TimePeriodFilter::validate_period(period_str).map_err(invalid)?,
+            None => TimePeriod::Daily, // Default to daily
+        };
+
+        Ok((date_range, period))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::repositories::OrdersAnalyticsRepository;
+    use sqlx::PgPool;
+
+    fn create_test_controller() -> BasketAnalyticsController {
+        let pool = PgPool::connect_lazy("postgresql://test").unwrap();
+        let service = Arc::new(BasketAnalysisService::new(OrdersAnalyticsRepository::new(pool)));
+        BasketAnalyticsController::new(service)
+    }
+
+    #[tokio::test]
+    async fn test_parse_params_defaults_to_daily_in_shop_timezone() {
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        let controller = create_test_controller().with_timezone(paris);
+        let params: BasketQueryParams = serde_json::from_str(r#"{}"#).unwrap();
+
+        let (date_range, period) = controller.parse_params(&params).unwrap();
+
+        assert_eq!(period, TimePeriod::Daily);
+        assert_eq!(date_range.timezone, paris);
+    }
+
+    #[tokio::test]
+    async fn test_parse_params_rejects_invalid_values() {
+        let controller = create_test_controller();
+
+        for json in [
+            r#"{"period":"yearly"}"#,
+            r#"{"tz":"Mars/Base"}"#,
+            r#"{"startDate":"2026-03-10T00:00:00Z","endDate":"2026-03-01T00:00:00Z"}"#,
+            r#"{"startDate":"2020-01-01T00:00:00Z","endDate":"2022-01-01T00:00:00Z"}"#,
+            r#"{"endDate":"2999-01-01T00:00:00Z"}"#,
+        ] {
+            let params: BasketQueryParams = serde_json::from_str(json).unwrap();
+            let (status, _) = controller.parse_params(&params).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", json);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+// Customer analytics API controller
+// Handles endpoints for signup cohorts, lifetime value and RFM segmentation
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
+    services::CustomerAnalyticsService,
+    types::{ApiResponse, DateRange, ResponseMetadata, RfmSegment},
+    utils::TimePeriodFilter,
+};
+use super::{bad_request, resolve_tz, validated_limit, ErrorResponse};
+
+/// Signup window of the cohort report when no start date is given
+const DEFAULT_COHORT_DAYS: i64 = 365;
+
+/// Query parameters for the cohort retention endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortQueryParams {
+    /// Earliest signup date (ISO 8601 format); defaults to a year before the end date
+    pub start_date: Option<DateTime<Utc>>,
+    /// Latest signup date, exclusive (ISO 8601 format)
+    pub end_date: Option<DateTime<Utc>>,
+    /// IANA timezone for month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl CohortQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "startDate": self.start_date,
+            "endDate": self.end_date,
+            "tz": self.tz,
+        }))
+    }
+}
+
+/// Query parameters for per-customer endpoints
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerQueryParams {
+    /// Maximum number of customers to return (1-1000, default 10)
+    pub limit: Option<i32>,
+    /// Only customers in this RFM segment (e.g. at_risk)
+    pub segment: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl CustomerQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "limit": self.limit,
+            "segment": self.segment,
+        }))
+    }
+}
+
+/// Customer analytics controller
+pub struct CustomerAnalyticsController {
+    service: Arc<CustomerAnalyticsService>,
+    timezone: Tz,
+}
+
+impl CustomerAnalyticsController {
+    /// Create a new customer analytics controller
+    pub fn new(service: Arc<CustomerAnalyticsService>) -> Self {
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// GET /api/v1/admin/analytics/customers/cohorts
+    /// Returns monthly signup cohorts with the share of each that ordered in
+    /// every month since signing up
+    pub async fn get_cohort_retention(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<CohortQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let date_range = controller.parse_cohort_range(&params)?;
+
+        let cohorts = controller
+            .service
+            .calculate_cohort_retention(date_range.clone())
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to calculate cohort retention: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("customer-cohorts", Some(&date_range), format);
+            let cells = cohorts.into_iter().flat_map(|cohort| cohort.months).collect();
+            return export_report(cells, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(cohorts.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(cohorts, metadata)),
+        )
+            .into_response())
+    }
+
+    /// GET /api/v1/admin/analytics/customers/lifetime-value
+    /// Returns customers ranked by lifetime value, with their order frequency
+    pub async fn get_lifetime_values(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<CustomerQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let limit = validated_limit(params.limit, params.metadata())?;
+
+        let customers = controller
+            .service
+            .get_lifetime_values(limit)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to calculate lifetime values: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("customer-lifetime-value", None, format);
+            return export_report(customers, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(customers.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(customers, metadata)),
+        )
+            .into_response())
+    }
+
+    /// GET /api/v1/admin/analytics/customers/rfm
+    /// Returns customers' recency, frequency and monetary scores and segment
+    pub async fn get_rfm_scores(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<CustomerQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let limit = validated_limit(params.limit, params.metadata())?;
+        let segment = params
+            .segment
+            .as_deref()
+            .map(RfmSegment::parse)
+            .transpose()
+            .map_err(|e| bad_request(e, params.metadata()))?;
+
+        let customers = controller
+            .service
+            .get_rfm_scores(segment, limit)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to calculate RFM scores: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("customer-rfm", None, format);
+            return export_report(customers, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(customers.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(customers, metadata)),
+        )
+            .into_response())
+    }
+
+    /// GET /api/v1/admin/analytics/customers/rfm/segments
+    /// Returns the size and average recency, frequency and spend of every RFM segment
+    pub async fn get_rfm_segments(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<CustomerQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let segments = controller
+            .service
+            .get_rfm_segments()
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to summarize RFM segments: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("customer-rfm-segments", None, format);
+            return export_report(segments, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = ResponseMetadata::new(serde_json::json!({}))
+            .with_result_count(segments.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(segments, metadata)),
+        )
+            .into_response())
+    }
+
+    /// Resolve the timezone and signup window of the cohort report
+    fn parse_cohort_range(&self, params: &CohortQueryParams) -> Result<DateRange, ErrorResponse> {
+        let invalid = |e: String| bad_request(e, params.metadata());
+
+        let timezone = resolve_tz(params.tz.as_deref(), self.timezone, params.metadata())?;
+        let end_date = params.end_date.unwrap_or_else(Utc::now);
+        let start_date = params
+            .start_date
+            .unwrap_or_else(|| end_date - Duration::days(DEFAULT_COHORT_DAYS));
+
+        TimePeriodFilter::parse_date_range(Some(start_date), Some(end_date), timezone)
+            .map_err(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::repositories::CustomersAnalyticsRepository;
+    use sqlx::PgPool;
+
+    fn create_test_controller() -> CustomerAnalyticsController {
+        let pool = PgPool::connect_lazy("postgresql://test").unwrap();
+        let service = Arc::new(CustomerAnalyticsService::new(CustomersAnalyticsRepository::new(pool)));
+        CustomerAnalyticsController::new(service)
+    }
+
+    #[tokio::test]
+    async fn test_cohort_range_defaults_to_a_year_in_shop_timezone() {
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        let controller = create_test_controller().with_timezone(paris);
+        let params: CohortQueryParams = serde_json::from_str(r#"{}"#).unwrap();
+
+        let date_range = controller.parse_cohort_range(&params).unwrap();
+
+        assert_eq!(date_range.timezone, paris);
+        assert_eq!((date_range.end_date - date_range.start_date).num_days(), DEFAULT_COHORT_DAYS);
+    }
+
+    #[tokio::test]
+    async fn test_cohort_range_rejects_invalid_values() {
+        let controller = create_test_controller();
+
+        for json in [
+            r#"{"tz":"Mars/Base"}"#,
+            r#"{"startDate":"2026-03-10T00:00:00Z","endDate":"2026-03-01T00:00:00Z"}"#,
+        ] {
+            let params: CohortQueryParams = serde_json::from_str(json).unwrap();
+            let (status, _) = controller.parse_cohort_range(&params).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_customer_limit_validation() {
+        let params: CustomerQueryParams = serde_json::from_str(r#"{}"#).unwrap();
+        assert_eq!(validated_limit(params.limit, params.metadata()).unwrap(), 10);
+
+        for json in [r#"{"limit":0}"#, r#"{"limit":5000}"#] {
+            let params: CustomerQueryParams = serde_json::from_str(json).unwrap();
+            let (status, _) = validated_limit(params.limit, params.metadata()).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", json);
+        }
+    }
+}
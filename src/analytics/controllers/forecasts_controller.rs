@@ -0,0 +1,385 @@
+// Demand forecast API controller
+// Handles endpoints for per-coffee daily demand forecasts and their backtests
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
+    services::DemandForecastService,
+    types::{ApiResponse, ForecastModel, ResponseMetadata},
+    validation::AnalyticsValidator,
+};
+use super::{bad_request, resolve_tz, ErrorResponse};
+
+/// Days forecast when no horizon is given
+const DEFAULT_HORIZON_DAYS: i64 = 7;
+/// Longest forecast horizon
+const MAX_HORIZON_DAYS: i64 = 14;
+/// Days of history the models are fitted on when none is given (twelve weeks)
+const DEFAULT_HISTORY_DAYS: i64 = 84;
+/// Longest history the models are fitted on
+const MAX_HISTORY_DAYS: i64 = 365;
+/// Days held out of a backtest when none are given
+const DEFAULT_HOLDOUT_DAYS: i64 = 14;
+/// Prediction interval level when none is given, in percent
+const DEFAULT_CONFIDENCE_LEVEL: u32 = 95;
+
+/// Query parameters for the demand forecast endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandForecastQueryParams {
+    /// Only forecast this coffee
+    pub coffee_id: Option<i32>,
+    /// Forecasting model (seasonal_naive or holt_winters, default holt_winters)
+    pub model: Option<String>,
+    /// Days to forecast, starting today (1-14, default 7)
+    pub horizon_days: Option<i64>,
+    /// Whole days of history before today to fit on (14-365, default 84)
+    pub history_days: Option<i64>,
+    /// Prediction interval level in percent (80, 90 or 95, default 95)
+    pub confidence_level: Option<u32>,
+    /// IANA timezone for local days; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl DemandForecastQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "coffeeId": self.coffee_id,
+            "model": self.model,
+            "horizonDays": self.horizon_days,
+            "historyDays": self.history_days,
+            "confidenceLevel": self.confidence_level,
+            "tz": self.tz,
+        }))
+    }
+
+    /// Resolve defaults and reject out-of-range values
+    fn settings(&self, default_timezone: Tz) -> Result<ForecastSettings, ErrorResponse> {
+        let invalid = |e: String| bad_request(e, self.metadata());
+
+        let coffee_id = AnalyticsValidator::validate_coffee_id(self.coffee_id)
+            .map_err(|e| (e.status_code(), Json(e.to_response(self.metadata()))))?;
+        let model = self
+            .model
+            .as_deref()
+            .map(ForecastModel::parse)
+            .transpose()
+            .map_err(invalid)?
+            .unwrap_or(ForecastModel::HoltWinters);
+        let days = days_param("horizonDays", self.horizon_days, DEFAULT_HORIZON_DAYS, 1, MAX_HORIZON_DAYS)
+            .map_err(invalid)?;
+        let history_days = days_param(
+            "historyDays",
+            self.history_days,
+            DEFAULT_HISTORY_DAYS,
+            DemandForecastService::MIN_HISTORY_DAYS as i64,
+            MAX_HISTORY_DAYS,
+        )
+        .map_err(invalid)?;
+        let confidence_level = confidence_level_param(self.confidence_level).map_err(invalid)?;
+        let timezone = resolve_tz(self.tz.as_deref(), default_timezone, self.metadata())?;
+
+        Ok(ForecastSettings {
+            coffee_id,
+            model: Some(model),
+            days,
+            history_days,
+            confidence_level,
+            timezone,
+        })
+    }
+}
+
+/// Query parameters for the forecast backtest endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestQueryParams {
+    /// Only backtest this coffee
+    pub coffee_id: Option<i32>,
+    /// Forecasting model to backtest; every model when absent
+    pub model: Option<String>,
+    /// Last days of the history held out and forecast (1-14, default 14)
+    pub holdout_days: Option<i64>,
+    /// Whole days of history before today, held-out days included (default 84)
+    pub history_days: Option<i64>,
+    /// Prediction interval level in percent (80, 90 or 95, default 95)
+    pub confidence_level: Option<u32>,
+    /// IANA timezone for local days; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl BacktestQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "coffeeId": self.coffee_id,
+            "model": self.model,
+            "holdoutDays": self.holdout_days,
+            "historyDays": self.history_days,
+            "confidenceLevel": self.confidence_level,
+            "tz": self.tz,
+        }))
+    }
+
+    /// Resolve defaults and reject out-of-range values
+    /// The history must leave two weeks to fit on before the held-out days
+    fn settings(&self, default_timezone: Tz) -> Result<ForecastSettings, ErrorResponse> {
+        let invalid = |e: String| bad_request(e, self.metadata());
+
+        let coffee_id = AnalyticsValidator::validate_coffee_id(self.coffee_id)
+            .map_err(|e| (e.status_code(), Json(e.to_response(self.metadata()))))?;
+        let model = self
+            .model
+            .as_deref()
+            .map(ForecastModel::parse)
+            .transpose()
+            .map_err(invalid)?;
+        let days = days_param("holdoutDays", self.holdout_days, DEFAULT_HOLDOUT_DAYS, 1, MAX_HORIZON_DAYS)
+            .map_err(invalid)?;
+        let history_days = days_param(
+            "historyDays",
+            self.history_days,
+            DEFAULT_HISTORY_DAYS,
+            (days + DemandForecastService::MIN_HISTORY_DAYS) as i64,
+            MAX_HISTORY_DAYS,
+        )
+        .map_err(invalid)?;
+        let confidence_level = confidence_level_param(self.confidence_level).map_err(invalid)?;
+        let timezone = resolve_tz(self.tz.as_deref(), default_timezone, self.metadata())?;
+
+        Ok(ForecastSettings {
+            coffee_id,
+            model,
+            days,
+            history_days,
+            confidence_level,
+            timezone,
+        })
+    }
+}
+
+/// Validated forecast or backtest parameters
+#[derive(Debug, PartialEq)]
+struct ForecastSettings {
+    coffee_id: Option<i32>,
+    model: Option<ForecastModel>,
+    /// Days forecast: the horizon, or the held-out days of a backtest
+    days: usize,
+    history_days: usize,
+    confidence_level: u32,
+    timezone: Tz,
+}
+
+/// A day count parameter, or `default` when absent
+fn days_param(name: &str, value: Option<i64>, default: i64, min: i64, max: i64) -> Result<usize, String> {
+    match value.unwrap_or(default) {
+        days if (min..=max).contains(&days) => Ok(days as usize),
+        days => Err(format!("Invalid {}: {}. Must be between {} and {}", name, days, min, max)),
+    }
+}
+
+/// A prediction interval level, or the default when absent
+fn confidence_level_param(value: Option<u32>) -> Result<u32, String> {
+    match value.unwrap_or(DEFAULT_CONFIDENCE_LEVEL) {
+        level if DemandForecastService::CONFIDENCE_LEVELS.contains(&level) => Ok(level),
+        level => Err(format!("Invalid confidenceLevel: {}. Must be one of: 80, 90, 95", level)),
+    }
+}
+
+/// Demand forecast controller
+pub struct DemandForecastController {
+    service: Arc<DemandForecastService>,
+    timezone: Tz,
+}
+
+impl DemandForecastController {
+    /// Create a new demand forecast controller
+    pub fn new(service: Arc<DemandForecastService>) -> Self {
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Forecast in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// GET /api/v1/admin/analytics/forecasts/demand
+    /// Returns each coffee's forecast units per day from today, with
+    /// prediction intervals, fitted on its daily sales history
+    pub async fn get_demand_forecast(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<DemandForecastQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let settings = params.settings(controller.timezone)?;
+
+        let forecasts = controller
+            .service
+            .forecast_demand(
+                settings.coffee_id,
+                settings.model.unwrap_or(ForecastModel::HoltWinters),
+                settings.days,
+                settings.history_days,
+                settings.confidence_level,
+                settings.timezone,
+            )
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to forecast demand: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("demand-forecast", None, format);
+            let days = forecasts.into_iter().flat_map(|forecast| forecast.days).collect();
+            return export_report(days, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(forecasts.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(forecasts, metadata)),
+        )
+            .into_response())
+    }
+
+    /// GET /api/v1/admin/analytics/forecasts/backtest
+    /// Returns the forecast error of each model on the last held-out days of
+    /// every coffee's history, fitted on the days before them
+    pub async fn get_backtest(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<BacktestQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let settings = params.settings(controller.timezone)?;
+
+        let backtests = controller
+            .service
+            .backtest(
+                settings.coffee_id,
+                settings.model,
+                settings.days,
+                settings.history_days,
+                settings.confidence_level,
+                settings.timezone,
+            )
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to backtest forecasts: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("demand-forecast-backtest", None, format);
+            return export_report(backtests, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(backtests.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(backtests, metadata)),
+        )
+            .into_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forecast_settings_default_to_a_week_in_shop_timezone() {
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        let params: DemandForecastQueryParams = serde_json::from_str(r#"{}"#).unwrap();
+
+        let settings = params.settings(paris).unwrap();
+
+        assert_eq!(
+            settings,
+            ForecastSettings {
+                coffee_id: None,
+                model: Some(ForecastModel::HoltWinters),
+                days: 7,
+                history_days: 84,
+                confidence_level: 95,
+                timezone: paris,
+            }
+        );
+    }
+
+    #[test]
+    fn test_forecast_settings_reject_invalid_values() {
+        for json in [
+            r#"{"horizonDays":0}"#,
+            r#"{"horizonDays":30}"#,
+            r#"{"historyDays":10}"#,
+            r#"{"model":"arima"}"#,
+            r#"{"confidenceLevel":99}"#,
+            r#"{"coffeeId":0}"#,
+            r#"{"tz":"Mars/Base"}"#,
+        ] {
+            let params: DemandForecastQueryParams = serde_json::from_str(json).unwrap();
+            let (status, _) = params.settings(Tz::UTC).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_backtest_settings_leave_two_weeks_to_fit_on() {
+        let params: BacktestQueryParams = serde_json::from_str(r#"{"model":"seasonal_naive"}"#).unwrap();
+        let settings = params.settings(Tz::UTC).unwrap();
+        assert_eq!(settings.model, Some(ForecastModel::SeasonalNaive));
+        assert_eq!((settings.days, settings.history_days), (14, 84));
+
+        let params: BacktestQueryParams = serde_json::from_str(r#"{}"#).unwrap();
+        assert_eq!(params.settings(Tz::UTC).unwrap().model, None);
+
+        for json in [r#"{"holdoutDays":7,"historyDays":20}"#, r#"{"holdoutDays":15}"#] {
+            let params: BacktestQueryParams = serde_json::from_str(json).unwrap();
+            let (status, _) = params.settings(Tz::UTC).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", json);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Market basket API controller
+// Handles the frequently-bought-together coffee pairs endpoint
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
+    services::MarketBasketService,
+    types::{ApiResponse, ResponseMetadata},
+};
+use super::{validated_limit, ErrorResponse};
+
+/// Query parameters for the coffee pairs endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoffeePairQueryParams {
+    /// Only pairs starting from this coffee
+    pub coffee_id: Option<i32>,
+    /// Maximum number of pairs to return (1-1000, default 10)
+    pub limit: Option<i32>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl CoffeePairQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "coffeeId": self.coffee_id,
+            "limit": self.limit,
+        }))
+    }
+}
+
+/// Market basket controller
+pub struct MarketBasketController {
+    service: Arc<MarketBasketService>,
+}
+
+impl MarketBasketController {
+    /// Create a new market basket controller
+    pub fn new(service: Arc<MarketBasketService>) -> Self {
+        Self { service }
+    }
+
+    /// GET /api/v1/admin/analytics/market-basket/pairs
+    /// Returns the coffee pairs of the last market basket refresh with their
+    /// support, confidence and lift, strongest association first
+    pub async fn get_pairs(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<CoffeePairQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let limit = validated_limit(params.limit, params.metadata())?;
+
+        let pairs = controller
+            .service
+            .get_pairs(params.coffee_id, limit)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to get coffee pairs: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("coffee-pairs", None, format);
+            return export_report(pairs, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(pairs.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(pairs, metadata)),
+        )
+            .into_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_limit_validation() {
+        let params: CoffeePairQueryParams = serde_json::from_str(r#"{"coffeeId":3}"#).unwrap();
+        assert_eq!(validated_limit(params.limit, params.metadata()).unwrap(), 10);
+
+        for json in [r#"{"limit":0}"#, r#"{"limit":5000}"#] {
+            let params: CoffeePairQueryParams = serde_json::from_str(json).unwrap();
+            let (status, _) = validated_limit(params.limit, params.metadata()).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", json);
+        }
+    }
+}
@@ -1,12 +1,53 @@
 // Analytics API controllers
-// Contains handlers for sales, revenue, popular coffees, and ratings endpoints
+// Contains handlers for sales, revenue, popular coffees, ratings, basket, pricing rule, customer, market basket and demand forecast endpoints
 
 pub mod sales_controller;
 pub mod popular_coffees_controller;
 pub mod revenue_controller;
 pub mod rating_insights_controller;
+pub mod basket_controller;
+pub mod pricing_rules_controller;
+pub mod customers_controller;
+pub mod market_basket_controller;
+pub mod forecasts_controller;
 
 pub use sales_controller::SalesStatisticsController;
 pub use popular_coffees_controller::PopularCoffeesController;
 pub use revenue_controller::RevenueReportsController;
 pub use rating_insights_controller::RatingInsightsController;
+pub use basket_controller::BasketAnalyticsController;
+pub use pricing_rules_controller::PricingRuleReportsController;
+pub use customers_controller::CustomerAnalyticsController;
+pub use market_basket_controller::MarketBasketController;
+pub use forecasts_controller::DemandForecastController;
+
+use axum::{http::StatusCode, Json};
+use chrono_tz::Tz;
+
+use crate::analytics::{
+    types::{ApiResponse, ResponseMetadata},
+    utils::TimePeriodFilter,
+    validation::AnalyticsValidator,
+};
+
+/// Error response returned by the handlers
+pub(crate) type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+/// 400 response carrying the request's metadata
+pub(crate) fn bad_request(message: String, metadata: ResponseMetadata) -> ErrorResponse {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse::error(message, metadata)),
+    )
+}
+
+/// Row limit of a request (1-1000, default 10)
+pub(crate) fn validated_limit(limit: Option<i32>, metadata: ResponseMetadata) -> Result<i32, ErrorResponse> {
+    AnalyticsValidator::validate_limit(limit).map_err(|e| (e.status_code(), Json(e.to_response(metadata))))
+}
+
+/// Timezone a report is bucketed in: `tz` when given, else `default` (the shop
+/// timezone); an unknown `tz` is a 400 with `metadata`
+pub(crate) fn resolve_tz(tz: Option<&str>, default: Tz, metadata: ResponseMetadata) -> Result<Tz, ErrorResponse> {
+    TimePeriodFilter::resolve_timezone(tz, default).map_err(|e| bad_request(e, metadata))
+}
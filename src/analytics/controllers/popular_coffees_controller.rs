@@ -3,19 +3,22 @@
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
     services::{PopularCoffeesService, TrendCalculationService},
     types::{ApiResponse, ResponseMetadata, PopularCoffee},
     utils::TimePeriodFilter,
 };
+use super::resolve_tz;
 
 /// Query parameters for popular coffees endpoints
 #[derive(Debug, Deserialize)]
@@ -27,6 +30,10 @@ pub struct PopularCoffeesQueryParams {
     pub start_date: Option<DateTime<Utc>>,
     /// End date for the period (ISO 8601 format)
     pub end_date: Option<DateTime<Utc>>,
+    /// IANA timezone for day, week and month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
 }
 
 /// Query parameters for trending coffees endpoint
@@ -43,12 +50,17 @@ pub struct TrendingQueryParams {
     pub previous_end: Option<DateTime<Utc>>,
     /// Maximum number of results to return
     pub limit: Option<i32>,
+    /// IANA timezone for day, week and month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
 }
 
 /// Popular coffees controller
 pub struct PopularCoffeesController {
     popular_service: Arc<PopularCoffeesService>,
     trend_service: Arc<TrendCalculationService>,
+    timezone: Tz,
 }
 
 impl PopularCoffeesController {
@@ -60,19 +72,34 @@ impl PopularCoffeesController {
         Self {
             popular_service,
             trend_service,
+            timezone: Tz::UTC,
         }
     }
 
+    /// Report in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
     /// GET /api/v1/admin/analytics/coffees/most-ordered
     /// Returns the most ordered coffees ranked by order count
     pub async fn get_most_ordered(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<PopularCoffeesQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -105,7 +132,7 @@ impl PopularCoffeesController {
         // Get most ordered coffees
         let coffees = controller
             .popular_service
-            .get_most_ordered(date_range, limit)
+            .get_most_ordered(date_range.clone(), limit)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -122,6 +149,11 @@ impl PopularCoffeesController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("most-ordered-coffees", Some(&date_range), format);
+            return export_report(coffees, format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
@@ -135,16 +167,19 @@ impl PopularCoffeesController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(coffees, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/coffees/highest-rated
     /// Returns the highest rated coffees ranked by average rating
     pub async fn get_highest_rated(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<PopularCoffeesQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
 
         // Validate limit parameter
         let limit = params.limit.unwrap_or(10);
@@ -181,6 +216,11 @@ impl PopularCoffeesController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("highest-rated-coffees", None, format);
+            return export_report(coffees, format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
@@ -193,21 +233,31 @@ impl PopularCoffeesController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(coffees, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/coffees/trending
     /// Returns trending coffees by comparing two time periods
     pub async fn get_trending(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<TrendingQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate current period
         let current_range = TimePeriodFilter::parse_date_range(
             params.current_start,
             params.current_end,
+            timezone,
         )
         .map_err(|e| {
             let metadata = ResponseMetadata::new(serde_json::json!({
@@ -230,6 +280,7 @@ impl PopularCoffeesController {
         let previous_range = TimePeriodFilter::parse_date_range(
             params.previous_start,
             params.previous_end,
+            timezone,
         )
         .map_err(|e| {
             let metadata = ResponseMetadata::new(serde_json::json!({
@@ -270,7 +321,7 @@ impl PopularCoffeesController {
         // Calculate trending coffees
         let trending = controller
             .trend_service
-            .calculate_trending_items(current_range, previous_range, limit)
+            .calculate_trending_items(current_range.clone(), previous_range, limit)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -289,6 +340,11 @@ impl PopularCoffeesController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("trending-coffees", Some(&current_range), format);
+            return export_report(trending, format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
@@ -304,7 +360,8 @@ impl PopularCoffeesController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(trending, metadata)),
-        ))
+        )
+            .into_response())
     }
 }
 
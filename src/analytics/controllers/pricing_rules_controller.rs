@@ -0,0 +1,152 @@
+// Pricing rule reports API controller
+// Handles the pricing rule effectiveness endpoint
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
+    services::PricingRuleAnalysisService,
+    types::{ApiResponse, DateRange, ResponseMetadata},
+    utils::TimePeriodFilter,
+};
+use super::{bad_request, ErrorResponse};
+
+/// Query parameters for pricing rule endpoints
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingRuleQueryParams {
+    /// Start date for the period (ISO 8601 format)
+    pub start_date: Option<DateTime<Utc>>,
+    /// End date for the period (ISO 8601 format)
+    pub end_date: Option<DateTime<Utc>>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+impl PricingRuleQueryParams {
+    fn metadata(&self) -> ResponseMetadata {
+        ResponseMetadata::new(serde_json::json!({
+            "startDate": self.start_date,
+            "endDate": self.end_date,
+        }))
+    }
+}
+
+/// Pricing rule reports controller
+pub struct PricingRuleReportsController {
+    service: Arc<PricingRuleAnalysisService>,
+    timezone: Tz,
+}
+
+impl PricingRuleReportsController {
+    /// Create a new pricing rule reports controller
+    pub fn new(service: Arc<PricingRuleAnalysisService>) -> Self {
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report in the shop's timezone
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// GET /api/v1/admin/analytics/pricing-rules/effectiveness
+    /// Returns per pricing rule the times applied, discount given, orders
+    /// affected and average basket with and without the rule
+    pub async fn get_rule_effectiveness(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<PricingRuleQueryParams>,
+    ) -> Result<impl IntoResponse, ErrorResponse> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+        let date_range = controller.parse_date_range(&params)?;
+
+        let rules = controller
+            .service
+            .calculate_rule_effectiveness(date_range.clone())
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to calculate pricing rule effectiveness: {}", e),
+                        params.metadata(),
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("pricing-rule-effectiveness", Some(&date_range), format);
+            return export_report(rules, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = params
+            .metadata()
+            .with_result_count(rules.len())
+            .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(rules, metadata)),
+        )
+            .into_response())
+    }
+
+    /// Parse and validate the date range of a request
+    /// The report is not bucketed, so the shop timezone is only carried along
+    fn parse_date_range(&self, params: &PricingRuleQueryParams) -> Result<DateRange, ErrorResponse> {
+        TimePeriodFilter::parse_date_range(params.start_date, params.end_date, self.timezone)
+            .map_err(|e| bad_request(e, params.metadata()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::repositories::OrdersAnalyticsRepository;
+    use sqlx::PgPool;
+
+    fn create_test_controller() -> PricingRuleReportsController {
+        let pool = PgPool::connect_lazy("postgresql://test").unwrap();
+        let service = Arc::new(PricingRuleAnalysisService::new(OrdersAnalyticsRepository::new(pool)));
+        PricingRuleReportsController::new(service)
+    }
+
+    #[tokio::test]
+    async fn test_parse_date_range_carries_shop_timezone() {
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        let controller = create_test_controller().with_timezone(paris);
+        let params: PricingRuleQueryParams = serde_json::from_str(r#"{}"#).unwrap();
+
+        let date_range = controller.parse_date_range(&params).unwrap();
+
+        assert_eq!(date_range.timezone, paris);
+    }
+
+    #[tokio::test]
+    async fn test_parse_date_range_rejects_reversed_range() {
+        let controller = create_test_controller();
+        let params: PricingRuleQueryParams = serde_json::from_str(
+            r#"{"startDate":"2026-03-10T00:00:00Z","endDate":"2026-03-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let (status, _) = controller.parse_date_range(&params).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
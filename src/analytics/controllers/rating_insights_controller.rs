@@ -3,19 +3,22 @@
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::analytics::{
+    export::{export_filename, export_report, negotiate_format, ExportFormat},
     services::RatingAnalysisService,
     types::{ApiResponse, ResponseMetadata, RatingStatistics, RatingDistribution, RatingTrend},
     utils::TimePeriodFilter,
 };
+use super::resolve_tz;
 
 /// Query parameters for rating endpoints
 #[derive(Debug, Deserialize)]
@@ -27,31 +30,50 @@ pub struct RatingQueryParams {
     pub end_date: Option<DateTime<Utc>>,
     /// Optional coffee ID to filter ratings for a specific coffee
     pub coffee_id: Option<i32>,
+    /// Only count reviews from verified purchases
+    #[serde(default)]
+    pub verified_only: bool,
+    /// IANA timezone for day, week and month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
 }
 
 /// Rating insights controller
 pub struct RatingInsightsController {
     service: Arc<RatingAnalysisService>,
+    timezone: Tz,
 }
 
 impl RatingInsightsController {
     /// Create a new rating insights controller
     pub fn new(service: Arc<RatingAnalysisService>) -> Self {
-        Self { service }
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
 
     /// GET /api/v1/admin/analytics/ratings/average
     /// Returns average rating with optional coffee filter
     pub async fn get_average_rating(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<RatingQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
 
         // Calculate average rating (no date range filtering for this endpoint)
         let rating_stats = controller
             .service
-            .calculate_average_rating(params.coffee_id)
+            .calculate_average_rating(params.coffee_id, params.verified_only)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -66,31 +88,40 @@ impl RatingInsightsController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("rating-average", None, format);
+            return export_report(vec![rating_stats], format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
             "coffeeId": params.coffee_id,
+            "verifiedOnly": params.verified_only,
         }))
         .with_execution_time(execution_time);
 
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(rating_stats, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/ratings/distribution
     /// Returns rating distribution grouped by rating value (1-5 stars)
     pub async fn get_rating_distribution(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<RatingQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
 
         // Analyze rating distribution (no date range filtering for this endpoint)
         let distribution = controller
             .service
-            .analyze_rating_distribution(params.coffee_id)
+            .analyze_rating_distribution(params.coffee_id, params.verified_only)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -105,10 +136,16 @@ impl RatingInsightsController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("rating-distribution", None, format);
+            return export_report(distribution, format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
             "coffeeId": params.coffee_id,
+            "verifiedOnly": params.verified_only,
         }))
         .with_result_count(distribution.len())
         .with_execution_time(execution_time);
@@ -116,19 +153,28 @@ impl RatingInsightsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(distribution, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/ratings/trends
     /// Returns rating trends as time-series data
     pub async fn get_rating_trends(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<RatingQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -144,7 +190,7 @@ impl RatingInsightsController {
         // Calculate rating trends
         let trends = controller
             .service
-            .analyze_trends(date_range, params.coffee_id)
+            .analyze_trends(date_range.clone(), params.coffee_id, params.verified_only)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -161,12 +207,18 @@ impl RatingInsightsController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("rating-trends", Some(&date_range), format);
+            return export_report(trends, format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
             "startDate": params.start_date,
             "endDate": params.end_date,
             "coffeeId": params.coffee_id,
+            "verifiedOnly": params.verified_only,
         }))
         .with_result_count(trends.len())
         .with_execution_time(execution_time);
@@ -174,7 +226,8 @@ impl RatingInsightsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(trends, metadata)),
-        ))
+        )
+            .into_response())
     }
 }
 
@@ -3,19 +3,22 @@
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::analytics::{
+    export::{export_filename, export_report_stream, negotiate_format, ExportFormat},
     services::RevenueCalculationService,
     types::{ApiResponse, ResponseMetadata, RevenueByPeriod, RevenueByCoffee, TimePeriod},
     utils::TimePeriodFilter,
 };
+use super::resolve_tz;
 
 /// Query parameters for revenue endpoints
 #[derive(Debug, Deserialize)]
@@ -27,17 +30,31 @@ pub struct RevenueQueryParams {
     pub end_date: Option<DateTime<Utc>>,
     /// Granularity for aggregation (daily, weekly, monthly, custom)
     pub period: Option<String>,
+    /// IANA timezone for day, week and month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
 }
 
 /// Revenue reports controller
 pub struct RevenueReportsController {
     service: Arc<RevenueCalculationService>,
+    timezone: Tz,
 }
 
 impl RevenueReportsController {
     /// Create a new revenue reports controller
     pub fn new(service: Arc<RevenueCalculationService>) -> Self {
-        Self { service }
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
 
     /// GET /api/v1/admin/analytics/revenue/by-period
@@ -45,12 +62,20 @@ impl RevenueReportsController {
     /// All monetary values have exactly 2 decimal places
     pub async fn get_revenue_by_period(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<RevenueQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -80,10 +105,32 @@ impl RevenueReportsController {
             TimePeriod::Daily // Default to daily
         };
 
+        if format != ExportFormat::Json {
+            let rows = controller
+                .service
+                .stream_revenue_by_period(date_range.clone(), period)
+                .map_err(|e| {
+                    let metadata = ResponseMetadata::new(serde_json::json!({
+                        "startDate": params.start_date,
+                        "endDate": params.end_date,
+                        "period": params.period,
+                    }));
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::error(
+                            format!("Failed to calculate revenue: {}", e),
+                            metadata,
+                        )),
+                    )
+                })?;
+            let filename = export_filename("revenue-by-period", Some(&date_range), format);
+            return export_report_stream(rows, format, &filename).await;
+        }
+
         // Calculate revenue by period
         let revenue_by_period = controller
             .service
-            .calculate_revenue_by_period(date_range, period)
+            .calculate_revenue_by_period(date_range.clone(), period)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -113,7 +160,8 @@ impl RevenueReportsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(revenue_by_period, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/revenue/by-coffee
@@ -121,12 +169,20 @@ impl RevenueReportsController {
     /// All monetary values have exactly 2 decimal places
     pub async fn get_revenue_by_coffee(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<RevenueQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -138,10 +194,31 @@ impl RevenueReportsController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let rows = controller
+                .service
+                .stream_revenue_by_coffee(date_range.clone())
+                .map_err(|e| {
+                    let metadata = ResponseMetadata::new(serde_json::json!({
+                        "startDate": params.start_date,
+                        "endDate": params.end_date,
+                    }));
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::error(
+                            format!("Failed to calculate revenue by coffee: {}", e),
+                            metadata,
+                        )),
+                    )
+                })?;
+            let filename = export_filename("revenue-by-coffee", Some(&date_range), format);
+            return export_report_stream(rows, format, &filename).await;
+        }
+
         // Calculate revenue by coffee
         let revenue_by_coffee = controller
             .service
-            .calculate_revenue_by_coffee(date_range)
+            .calculate_revenue_by_coffee(date_range.clone())
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -169,7 +246,8 @@ impl RevenueReportsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(revenue_by_coffee, metadata)),
-        ))
+        )
+            .into_response())
     }
 }
 
@@ -3,19 +3,23 @@
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::analytics::{
+    export::{export_filename, export_report, export_report_stream, negotiate_format, ExportFormat},
     services::SalesAggregationService,
     types::{ApiResponse, ResponseMetadata, SalesStatistics, SalesByPeriod, SalesTrend, TimePeriod},
     utils::TimePeriodFilter,
+    validation::AnalyticsValidator,
 };
+use super::resolve_tz;
 
 /// Query parameters for sales endpoints
 #[derive(Debug, Deserialize)]
@@ -27,29 +31,67 @@ pub struct SalesQueryParams {
     pub end_date: Option<DateTime<Utc>>,
     /// Granularity for aggregation (daily, weekly, monthly, custom)
     pub period: Option<String>,
+    /// IANA timezone for day, week and month buckets; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
+}
+
+/// Query parameters for the sales heatmap endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SalesHeatmapQueryParams {
+    /// Start date for the period (ISO 8601 format)
+    pub start_date: Option<DateTime<Utc>>,
+    /// End date for the period (ISO 8601 format)
+    pub end_date: Option<DateTime<Utc>>,
+    /// Only count orders containing this coffee
+    pub coffee_id: Option<i32>,
+    /// IANA timezone for weekdays and hours; defaults to the shop timezone
+    pub tz: Option<String>,
+    /// Report format (json, csv or xlsx); defaults to the Accept header
+    pub format: Option<String>,
 }
 
 /// Sales statistics controller
 pub struct SalesStatisticsController {
     service: Arc<SalesAggregationService>,
+    timezone: Tz,
 }
 
 impl SalesStatisticsController {
     /// Create a new sales statistics controller
     pub fn new(service: Arc<SalesAggregationService>) -> Self {
-        Self { service }
+        Self {
+            service,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report in the shop's timezone unless a request overrides it
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
 
     /// GET /api/v1/admin/analytics/sales/total
     /// Returns total sales count for the specified period
     pub async fn get_total_sales(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<SalesQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -64,7 +106,7 @@ impl SalesStatisticsController {
         // Calculate total sales
         let sales = controller
             .service
-            .calculate_total_sales(date_range)
+            .calculate_total_sales(date_range.clone())
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -80,6 +122,11 @@ impl SalesStatisticsController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("sales-total", Some(&date_range), format);
+            return export_report(vec![sales], format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
@@ -91,19 +138,28 @@ impl SalesStatisticsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(sales, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/sales/by-period
     /// Returns sales aggregated by time period (daily, weekly, monthly)
     pub async fn get_sales_by_period(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<SalesQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -133,10 +189,32 @@ impl SalesStatisticsController {
             TimePeriod::Daily // Default to daily
         };
 
+        if format != ExportFormat::Json {
+            let rows = controller
+                .service
+                .stream_sales_by_period(date_range.clone(), period)
+                .map_err(|e| {
+                    let metadata = ResponseMetadata::new(serde_json::json!({
+                        "startDate": params.start_date,
+                        "endDate": params.end_date,
+                        "period": params.period,
+                    }));
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::error(
+                            format!("Failed to aggregate sales: {}", e),
+                            metadata,
+                        )),
+                    )
+                })?;
+            let filename = export_filename("sales-by-period", Some(&date_range), format);
+            return export_report_stream(rows, format, &filename).await;
+        }
+
         // Aggregate sales by period
         let sales_by_period = controller
             .service
-            .aggregate_sales_by_period(date_range, period)
+            .aggregate_sales_by_period(date_range.clone(), period)
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -166,19 +244,28 @@ impl SalesStatisticsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(sales_by_period, metadata)),
-        ))
+        )
+            .into_response())
     }
 
     /// GET /api/v1/admin/analytics/sales/trends
     /// Returns sales trends as time-series data
     pub async fn get_sales_trends(
         State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
         Query(params): Query<SalesQueryParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
         let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
 
         // Parse and validate date range
-        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date)
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
                     "startDate": params.start_date,
@@ -193,7 +280,7 @@ impl SalesStatisticsController {
         // Calculate sales trends
         let trends = controller
             .service
-            .calculate_trends(date_range)
+            .calculate_trends(date_range.clone())
             .await
             .map_err(|e| {
                 let metadata = ResponseMetadata::new(serde_json::json!({
@@ -209,6 +296,11 @@ impl SalesStatisticsController {
                 )
             })?;
 
+        if format != ExportFormat::Json {
+            let filename = export_filename("sales-trends", Some(&date_range), format);
+            return export_report(trends, format, &filename);
+        }
+
         // Build response metadata
         let execution_time = start_time.elapsed().as_millis() as u64;
         let metadata = ResponseMetadata::new(serde_json::json!({
@@ -221,7 +313,96 @@ impl SalesStatisticsController {
         Ok((
             StatusCode::OK,
             Json(ApiResponse::success(trends, metadata)),
-        ))
+        )
+            .into_response())
+    }
+
+    /// GET /api/v1/admin/analytics/sales/heatmap
+    /// Returns order count, revenue and average prep time per weekday and hour
+    pub async fn get_sales_heatmap(
+        State(controller): State<Arc<Self>>,
+        headers: HeaderMap,
+        Query(params): Query<SalesHeatmapQueryParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+        let start_time = std::time::Instant::now();
+        let format = negotiate_format(params.format.as_deref(), &headers)?;
+
+        // Validate date range (order, not in the future, at most a year) and coffee ID
+        AnalyticsValidator::validate_query_params(
+            params.start_date,
+            params.end_date,
+            None,
+            None,
+            params.coffee_id,
+        )
+        .map_err(|e| {
+            let metadata = ResponseMetadata::new(serde_json::json!({
+                "startDate": params.start_date,
+                "endDate": params.end_date,
+                "coffeeId": params.coffee_id,
+            }));
+            (e.status_code(), Json(e.to_response(metadata)))
+        })?;
+
+        let timezone = resolve_tz(
+            params.tz.as_deref(),
+            controller.timezone,
+            ResponseMetadata::new(serde_json::json!({ "tz": params.tz })),
+        )?;
+
+        // Parse and validate date range
+        let date_range = TimePeriodFilter::parse_date_range(params.start_date, params.end_date, timezone)
+            .map_err(|e| {
+                let metadata = ResponseMetadata::new(serde_json::json!({
+                    "startDate": params.start_date,
+                    "endDate": params.end_date,
+                }));
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::error(e, metadata)),
+                )
+            })?;
+
+        // Build the weekday by hour matrix
+        let heatmap = controller
+            .service
+            .build_sales_heatmap(date_range.clone(), params.coffee_id)
+            .await
+            .map_err(|e| {
+                let metadata = ResponseMetadata::new(serde_json::json!({
+                    "startDate": params.start_date,
+                    "endDate": params.end_date,
+                    "coffeeId": params.coffee_id,
+                }));
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        format!("Failed to build sales heatmap: {}", e),
+                        metadata,
+                    )),
+                )
+            })?;
+
+        if format != ExportFormat::Json {
+            let filename = export_filename("sales-heatmap", Some(&date_range), format);
+            let cells = heatmap.days.into_iter().flatten().collect();
+            return export_report(cells, format, &filename);
+        }
+
+        // Build response metadata
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let metadata = ResponseMetadata::new(serde_json::json!({
+            "startDate": params.start_date,
+            "endDate": params.end_date,
+            "coffeeId": params.coffee_id,
+        }))
+        .with_execution_time(execution_time);
+
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse::success(heatmap, metadata)),
+        )
+            .into_response())
     }
 }
 
@@ -255,6 +436,17 @@ mod tests {
         assert_eq!(params.period, Some("daily".to_string()));
     }
 
+    #[test]
+    fn test_sales_heatmap_query_params_deserialization() {
+        let json = r#"{"startDate":"2024-01-01T00:00:00Z","coffeeId":3,"tz":"Europe/Paris"}"#;
+        let params: SalesHeatmapQueryParams = serde_json::from_str(json).unwrap();
+
+        assert!(params.start_date.is_some());
+        assert!(params.end_date.is_none());
+        assert_eq!(params.coffee_id, Some(3));
+        assert_eq!(params.tz.as_deref(), Some("Europe/Paris"));
+    }
+
     #[test]
     fn test_sales_query_params_optional_fields() {
         let json = r#"{}"#;
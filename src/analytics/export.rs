@@ -0,0 +1,803 @@
+// Report export formats
+// Renders analytics report rows as CSV or XLSX file downloads
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::{future, stream, Stream, StreamExt, TryStreamExt};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::convert::Infallible;
+
+use crate::analytics::repositories::RowStream;
+use crate::analytics::types::{
+    ApiResponse, BasketTrend, CoffeePair, CohortRetentionCell, CustomerLifetimeValue, CustomerRfm, DateRange,
+    DemandForecastDay, ForecastBacktest, OrderSizeBucket, PopularCoffee, PricingRuleEffectiveness, RatingDistribution,
+    RatingStatistics, RatingTrend, ResponseMetadata, RevenueByCoffee, RevenueByPeriod, RfmSegmentSummary, SalesByPeriod,
+    SalesHeatmapCell, SalesStatistics, SalesTrend,
+};
+
+/// Rows per chunk when streaming CSV
+const CSV_CHUNK_ROWS: usize = 500;
+
+const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Output format of an analytics report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The `ApiResponse` JSON envelope (default)
+    Json,
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    /// Pick the format from the `format` query parameter, falling back to the Accept header
+    pub fn negotiate(format: Option<&str>, headers: &HeaderMap) -> Result<Self, String> {
+        if let Some(format) = format {
+            return match format.to_lowercase().as_str() {
+                "json" => Ok(ExportFormat::Json),
+                "csv" => Ok(ExportFormat::Csv),
+                "xlsx" => Ok(ExportFormat::Xlsx),
+                other => Err(format!("Invalid format '{}'. Must be json, csv or xlsx", other)),
+            };
+        }
+
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains(XLSX_CONTENT_TYPE) {
+            Ok(ExportFormat::Xlsx)
+        } else if accept.contains("text/csv") {
+            Ok(ExportFormat::Csv)
+        } else {
+            Ok(ExportFormat::Json)
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Typed value of an exported cell
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Integer(i64),
+    Decimal(Decimal),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+    Empty,
+}
+
+impl Cell {
+    /// CSV text: plain numbers and ISO 8601 timestamps in UTC
+    fn to_csv(&self) -> String {
+        match self {
+            Cell::Integer(value) => value.to_string(),
+            Cell::Decimal(value) => value.to_string(),
+            Cell::Text(value) => value.clone(),
+            Cell::Timestamp(value) => value.to_rfc3339_opts(SecondsFormat::Secs, true),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+impl From<Option<Decimal>> for Cell {
+    fn from(value: Option<Decimal>) -> Self {
+        value.map(Cell::Decimal).unwrap_or(Cell::Empty)
+    }
+}
+
+/// Report row that can be exported as a spreadsheet line
+pub trait ExportRow {
+    /// Column headers, in the order of `cells`
+    fn headers() -> &'static [&'static str];
+    fn cells(&self) -> Vec<Cell>;
+}
+
+/// Download filename: report name plus the date range, e.g. `revenue-by-period_2026-01-01_2026-01-31.csv`
+pub fn export_filename(report: &str, date_range: Option<&DateRange>, format: ExportFormat) -> String {
+    match date_range {
+        Some(range) => format!(
+            "{}_{}_{}.{}",
+            report,
+            range.start_date.format("%Y-%m-%d"),
+            range.end_date.format("%Y-%m-%d"),
+            format.extension()
+        ),
+        None => format!("{}.{}", report, format.extension()),
+    }
+}
+
+/// Render report rows as a CSV or XLSX download
+///
+/// CSV is streamed to the client in chunks of rows; XLSX is a zip archive
+/// and is built in memory before it is sent.
+pub fn export_response<T>(rows: Vec<T>, format: ExportFormat, filename: &str) -> Result<Response, String>
+where
+    T: ExportRow + Send + 'static,
+{
+    let body = match format {
+        ExportFormat::Csv => csv_body(stream::iter(rows.into_iter().map(Ok::<_, Infallible>))),
+        ExportFormat::Xlsx => {
+            Body::from(xlsx_bytes(&rows).map_err(|e| format!("Failed to build spreadsheet: {}", e))?)
+        }
+        ExportFormat::Json => return Err("JSON reports use the API response envelope".to_string()),
+    };
+
+    download_response(body, format, filename)
+}
+
+/// Render report rows fetched from the database as a CSV or XLSX download
+///
+/// CSV lines are written to the body as the query returns rows, so the report
+/// is never held in memory; a query error after the first chunk aborts the
+/// download. XLSX collects the rows first to build the workbook.
+pub async fn export_stream<T>(rows: RowStream<T>, format: ExportFormat, filename: &str) -> Result<Response, String>
+where
+    T: ExportRow + Send + 'static,
+{
+    let body = match format {
+        ExportFormat::Csv => csv_body(rows),
+        ExportFormat::Xlsx => {
+            let rows: Vec<T> = rows
+                .try_collect()
+                .await
+                .map_err(|e| format!("Failed to load report rows: {}", e))?;
+            Body::from(xlsx_bytes(&rows).map_err(|e| format!("Failed to build spreadsheet: {}", e))?)
+        }
+        ExportFormat::Json => return Err("JSON reports use the API response envelope".to_string()),
+    };
+
+    download_response(body, format, filename)
+}
+
+/// Attach the content type and download filename to an export body
+fn download_response(body: Body, format: ExportFormat, filename: &str) -> Result<Response, String> {
+    let content_type = match format {
+        ExportFormat::Csv => CSV_CONTENT_TYPE,
+        ExportFormat::Xlsx => XLSX_CONTENT_TYPE,
+        ExportFormat::Json => return Err("JSON reports use the API response envelope".to_string()),
+    };
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename))
+        .map_err(|e| format!("Invalid export filename: {}", e))?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Negotiate the report format, rejecting an unknown `format` with 400 in the API envelope
+pub fn negotiate_format(
+    format: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<ExportFormat, (StatusCode, Json<ApiResponse<()>>)> {
+    ExportFormat::negotiate(format, headers).map_err(|e| {
+        let metadata = ResponseMetadata::new(serde_json::json!({ "format": format }));
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e, metadata)))
+    })
+}
+
+/// Render report rows as a download, reporting failures with 500 in the API envelope
+pub fn export_report<T>(
+    rows: Vec<T>,
+    format: ExportFormat,
+    filename: &str,
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)>
+where
+    T: ExportRow + Send + 'static,
+{
+    export_response(rows, format, filename).map_err(|e| export_error(e, format))
+}
+
+/// Render streamed report rows as a download, reporting failures with 500 in the API envelope
+pub async fn export_report_stream<T>(
+    rows: RowStream<T>,
+    format: ExportFormat,
+    filename: &str,
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)>
+where
+    T: ExportRow + Send + 'static,
+{
+    export_stream(rows, format, filename)
+        .await
+        .map_err(|e| export_error(e, format))
+}
+
+fn export_error(message: String, format: ExportFormat) -> (StatusCode, Json<ApiResponse<()>>) {
+    let metadata = ResponseMetadata::new(serde_json::json!({ "format": format.extension() }));
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error(message, metadata)))
+}
+
+/// Encode one CSV record
+fn csv_record<I, S>(fields: I) -> Result<Vec<u8>, csv::Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields)?;
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// CSV body streamed in chunks: the header line, then the rows that are
+/// ready, at most CSV_CHUNK_ROWS at a time
+fn csv_body<T, E, S>(rows: S) -> Body
+where
+    T: ExportRow,
+    E: std::error::Error + Send + Sync + 'static,
+    S: Stream<Item = Result<T, E>> + Send + 'static,
+{
+    let header = csv_record(T::headers()).map_err(std::io::Error::from);
+    let chunks = rows.ready_chunks(CSV_CHUNK_ROWS).map(|rows| {
+        let mut chunk = Vec::new();
+        for row in rows {
+            let row = row.map_err(std::io::Error::other)?;
+            chunk.extend(csv_record(row.cells().iter().map(Cell::to_csv))?);
+        }
+        Ok::<_, std::io::Error>(chunk)
+    });
+
+    Body::from_stream(stream::once(future::ready(header)).chain(chunks))
+}
+
+/// Build an XLSX workbook with a bold header row and typed cells
+fn xlsx_bytes<T: ExportRow>(rows: &[T]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    // Spreadsheet datetimes have no offset: cells hold the UTC wall clock,
+    // shown with a literal Z like the CSV timestamps
+    let timestamp_format = Format::new().set_num_format("yyyy-mm-dd\"T\"hh:mm:ss\"Z\"");
+
+    let worksheet = workbook.add_worksheet();
+    for (col, name) in T::headers().iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *name, &header_format)?;
+        worksheet.set_column_width(col as u16, 20)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    for (index, row) in rows.iter().enumerate() {
+        let row_num = index as u32 + 1;
+        for (col, cell) in row.cells().into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Integer(value) => {
+                    worksheet.write_number(row_num, col, value as f64)?;
+                }
+                Cell::Decimal(value) => {
+                    worksheet.write_number(row_num, col, value.to_f64().unwrap_or_default())?;
+                }
+                Cell::Text(value) => {
+                    worksheet.write_string(row_num, col, value)?;
+                }
+                Cell::Timestamp(value) => {
+                    worksheet.write_datetime_with_format(row_num, col, value.naive_utc(), &timestamp_format)?;
+                }
+                Cell::Empty => {}
+            }
+        }
+    }
+
+    workbook.save_to_buffer()
+}
+
+impl ExportRow for SalesStatistics {
+    fn headers() -> &'static [&'static str] {
+        &["totalSales", "startDate", "endDate"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        let (start, end) = match &self.date_range {
+            Some(range) => (Cell::Timestamp(range.start_date), Cell::Timestamp(range.end_date)),
+            None => (Cell::Empty, Cell::Empty),
+        };
+        vec![Cell::Integer(self.total_sales), start, end]
+    }
+}
+
+impl ExportRow for SalesByPeriod {
+    fn headers() -> &'static [&'static str] {
+        &["period", "timestamp", "salesCount"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.period.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Integer(self.sales_count),
+        ]
+    }
+}
+
+impl ExportRow for SalesTrend {
+    fn headers() -> &'static [&'static str] {
+        &["timestamp", "value"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![Cell::Timestamp(self.timestamp), Cell::Integer(self.value)]
+    }
+}
+
+impl ExportRow for SalesHeatmapCell {
+    fn headers() -> &'static [&'static str] {
+        &["dayOfWeek", "hour", "orderCount", "revenue", "averagePrepMinutes"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.day_of_week as i64),
+            Cell::Integer(self.hour as i64),
+            Cell::Integer(self.order_count),
+            Cell::Decimal(self.revenue),
+            self.average_prep_minutes.into(),
+        ]
+    }
+}
+
+impl ExportRow for BasketTrend {
+    fn headers() -> &'static [&'static str] {
+        &["period", "timestamp", "orderCount", "value", "changePercentage"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.period.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Integer(self.order_count),
+            Cell::Decimal(self.value),
+            self.change_percentage.into(),
+        ]
+    }
+}
+
+impl ExportRow for OrderSizeBucket {
+    fn headers() -> &'static [&'static str] {
+        &["period", "timestamp", "size", "orderCount", "share", "changePercentage"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.period.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Text(self.size.clone()),
+            Cell::Integer(self.order_count),
+            Cell::Decimal(self.share),
+            self.change_percentage.into(),
+        ]
+    }
+}
+
+impl ExportRow for PricingRuleEffectiveness {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "ruleId",
+            "ruleType",
+            "description",
+            "timesApplied",
+            "ordersAffected",
+            "totalDiscount",
+            "revenue",
+            "averageBasketWith",
+            "averageBasketWithout",
+            "basketUpliftPercentage",
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.rule_id.to_string()),
+            self.rule_type.clone().map(Cell::Text).unwrap_or(Cell::Empty),
+            self.description.clone().map(Cell::Text).unwrap_or(Cell::Empty),
+            Cell::Integer(self.times_applied),
+            Cell::Integer(self.orders_affected),
+            Cell::Decimal(self.total_discount),
+            Cell::Decimal(self.revenue),
+            Cell::Decimal(self.average_basket_with),
+            self.average_basket_without.into(),
+            self.basket_uplift_percentage.into(),
+        ]
+    }
+}
+
+impl ExportRow for CohortRetentionCell {
+    fn headers() -> &'static [&'static str] {
+        &["cohort", "cohortSize", "month", "activeCustomers", "retentionRate"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.cohort.clone()),
+            Cell::Integer(self.cohort_size),
+            Cell::Integer(self.month as i64),
+            Cell::Integer(self.active_customers),
+            Cell::Decimal(self.retention_rate),
+        ]
+    }
+}
+
+impl ExportRow for CustomerLifetimeValue {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "customerId",
+            "signedUpAt",
+            "firstOrderAt",
+            "lastOrderAt",
+            "orderCount",
+            "lifetimeValue",
+            "averageOrderValue",
+            "ordersPerMonth",
+            "averageDaysBetweenOrders",
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.customer_id as i64),
+            Cell::Timestamp(self.signed_up_at),
+            Cell::Timestamp(self.first_order_at),
+            Cell::Timestamp(self.last_order_at),
+            Cell::Integer(self.order_count),
+            Cell::Decimal(self.lifetime_value),
+            Cell::Decimal(self.average_order_value),
+            Cell::Decimal(self.orders_per_month),
+            self.average_days_between_orders.into(),
+        ]
+    }
+}
+
+impl ExportRow for CustomerRfm {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "customerId",
+            "recencyDays",
+            "frequency",
+            "monetary",
+            "recencyScore",
+            "frequencyScore",
+            "monetaryScore",
+            "segment",
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.customer_id as i64),
+            Cell::Integer(self.recency_days),
+            Cell::Integer(self.frequency),
+            Cell::Decimal(self.monetary),
+            Cell::Integer(self.recency_score as i64),
+            Cell::Integer(self.frequency_score as i64),
+            Cell::Integer(self.monetary_score as i64),
+            Cell::Text(self.segment.as_str().to_string()),
+        ]
+    }
+}
+
+impl ExportRow for RfmSegmentSummary {
+    fn headers() -> &'static [&'static str] {
+        &["segment", "customerCount", "share", "averageRecencyDays", "averageFrequency", "averageMonetary"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.segment.as_str().to_string()),
+            Cell::Integer(self.customer_count),
+            Cell::Decimal(self.share),
+            Cell::Decimal(self.average_recency_days),
+            Cell::Decimal(self.average_frequency),
+            Cell::Decimal(self.average_monetary),
+        ]
+    }
+}
+
+impl ExportRow for CoffeePair {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "coffeeId",
+            "coffeeName",
+            "pairedCoffeeId",
+            "pairedCoffeeName",
+            "pairOrders",
+            "totalOrders",
+            "supportPercentage",
+            "confidencePercentage",
+            "lift",
+            "windowStart",
+            "windowEnd",
+            "computedAt",
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.coffee_id as i64),
+            Cell::Text(self.coffee_name.clone()),
+            Cell::Integer(self.paired_coffee_id as i64),
+            Cell::Text(self.paired_coffee_name.clone()),
+            Cell::Integer(self.pair_orders),
+            Cell::Integer(self.total_orders),
+            Cell::Decimal(self.support_percentage),
+            Cell::Decimal(self.confidence_percentage),
+            Cell::Decimal(self.lift),
+            Cell::Timestamp(self.window_start),
+            Cell::Timestamp(self.window_end),
+            Cell::Timestamp(self.computed_at),
+        ]
+    }
+}
+
+impl ExportRow for DemandForecastDay {
+    fn headers() -> &'static [&'static str] {
+        &["coffeeId", "coffeeName", "date", "timestamp", "forecastUnits", "lowerBound", "upperBound"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.coffee_id as i64),
+            Cell::Text(self.coffee_name.clone()),
+            Cell::Text(self.date.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Decimal(self.forecast_units),
+            Cell::Decimal(self.lower_bound),
+            Cell::Decimal(self.upper_bound),
+        ]
+    }
+}
+
+impl ExportRow for ForecastBacktest {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "coffeeId",
+            "coffeeName",
+            "model",
+            "trainingDays",
+            "holdoutDays",
+            "actualUnits",
+            "forecastUnits",
+            "meanAbsoluteError",
+            "rootMeanSquaredError",
+            "absolutePercentageError",
+            "bias",
+            "intervalCoverage",
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.coffee_id as i64),
+            Cell::Text(self.coffee_name.clone()),
+            Cell::Text(self.model.as_str().to_string()),
+            Cell::Integer(self.training_days),
+            Cell::Integer(self.holdout_days),
+            Cell::Integer(self.actual_units),
+            Cell::Decimal(self.forecast_units),
+            Cell::Decimal(self.mean_absolute_error),
+            Cell::Decimal(self.root_mean_squared_error),
+            self.absolute_percentage_error.into(),
+            Cell::Decimal(self.bias),
+            Cell::Decimal(self.interval_coverage),
+        ]
+    }
+}
+
+impl ExportRow for PopularCoffee {
+    fn headers() -> &'static [&'static str] {
+        &["coffeeId", "coffeeName", "orderCount", "averageRating", "weightedRating", "trendPercentage"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.coffee_id as i64),
+            Cell::Text(self.coffee_name.clone()),
+            Cell::Integer(self.order_count),
+            self.average_rating.into(),
+            self.weighted_rating.into(),
+            self.trend_percentage.into(),
+        ]
+    }
+}
+
+impl ExportRow for RevenueByPeriod {
+    fn headers() -> &'static [&'static str] {
+        &["period", "timestamp", "revenue"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.period.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Decimal(self.revenue),
+        ]
+    }
+}
+
+impl ExportRow for RevenueByCoffee {
+    fn headers() -> &'static [&'static str] {
+        &["coffeeId", "coffeeName", "revenue"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Integer(self.coffee_id as i64),
+            Cell::Text(self.coffee_name.clone()),
+            Cell::Decimal(self.revenue),
+        ]
+    }
+}
+
+impl ExportRow for RatingStatistics {
+    fn headers() -> &'static [&'static str] {
+        &["coffeeId", "averageRating", "totalReviews"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            self.coffee_id.map(|id| Cell::Integer(id as i64)).unwrap_or(Cell::Empty),
+            Cell::Decimal(self.average_rating),
+            Cell::Integer(self.total_reviews),
+        ]
+    }
+}
+
+impl ExportRow for RatingDistribution {
+    fn headers() -> &'static [&'static str] {
+        &["rating", "count"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![Cell::Integer(self.rating as i64), Cell::Integer(self.count)]
+    }
+}
+
+impl ExportRow for RatingTrend {
+    fn headers() -> &'static [&'static str] {
+        &["timestamp", "averageRating"]
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![Cell::Timestamp(self.timestamp), Cell::Decimal(self.average_rating)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::Tz;
+    use std::str::FromStr;
+
+    fn revenue_rows() -> Vec<RevenueByPeriod> {
+        vec![
+            RevenueByPeriod {
+                period: "2026-01-01".to_string(),
+                revenue: Decimal::from_str("1250.50").unwrap(),
+                timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            },
+            RevenueByPeriod {
+                period: "2026-01-02".to_string(),
+                revenue: Decimal::from_str("99.00").unwrap(),
+                timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_negotiate_prefers_query_parameter() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/csv"));
+
+        assert_eq!(ExportFormat::negotiate(Some("XLSX"), &headers), Ok(ExportFormat::Xlsx));
+        assert_eq!(ExportFormat::negotiate(None, &headers), Ok(ExportFormat::Csv));
+        assert_eq!(ExportFormat::negotiate(None, &HeaderMap::new()), Ok(ExportFormat::Json));
+        assert!(ExportFormat::negotiate(Some("pdf"), &headers).is_err());
+    }
+
+    #[test]
+    fn test_export_filename_includes_date_range() {
+        let range = DateRange {
+            start_date: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            end_date: Utc.with_ymd_and_hms(2026, 1, 31, 23, 59, 59).unwrap(),
+            timezone: Tz::UTC,
+        };
+
+        assert_eq!(
+            export_filename("revenue-by-period", Some(&range), ExportFormat::Csv),
+            "revenue-by-period_2026-01-01_2026-01-31.csv"
+        );
+        assert_eq!(export_filename("highest-rated", None, ExportFormat::Xlsx), "highest-rated.xlsx");
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_has_typed_columns() {
+        let response = export_response(revenue_rows(), ExportFormat::Csv, "revenue.csv").unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CSV_CONTENT_TYPE);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"revenue.csv\""
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            "period,timestamp,revenue\n\
+             2026-01-01,2026-01-01T00:00:00Z,1250.50\n\
+             2026-01-02,2026-01-02T00:00:00Z,99.00\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_escapes_text() {
+        let rows = vec![RevenueByCoffee {
+            coffee_id: 1,
+            coffee_name: "Mocha, \"Deep Foam\"".to_string(),
+            revenue: Decimal::from_str("4.53").unwrap(),
+        }];
+        let response = export_response(rows, ExportFormat::Csv, "coffees.csv").unwrap();
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            "coffeeId,coffeeName,revenue\n1,\"Mocha, \"\"Deep Foam\"\"\",4.53\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_streams_fetched_rows() {
+        let rows = stream::iter(revenue_rows().into_iter().map(Ok)).boxed();
+        let response = export_stream(rows, ExportFormat::Csv, "revenue.csv").await.unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CSV_CONTENT_TYPE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            "period,timestamp,revenue\n\
+             2026-01-01,2026-01-01T00:00:00Z,1250.50\n\
+             2026-01-02,2026-01-02T00:00:00Z,99.00\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streamed_export_fails_on_query_error() {
+        let failing = || {
+            let rows = revenue_rows().into_iter().map(Ok).chain([Err(sqlx::Error::PoolTimedOut)]);
+            stream::iter(rows).boxed()
+        };
+
+        // CSV headers are already sent, so the download is aborted
+        let response = export_stream(failing(), ExportFormat::Csv, "revenue.csv").await.unwrap();
+        assert!(axum::body::to_bytes(response.into_body(), usize::MAX).await.is_err());
+
+        // XLSX is built before anything is sent
+        assert!(export_stream(failing(), ExportFormat::Xlsx, "revenue.xlsx").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_xlsx_export_is_a_workbook() {
+        let response = export_response(revenue_rows(), ExportFormat::Xlsx, "revenue.xlsx").unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], XLSX_CONTENT_TYPE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        // XLSX files are zip archives
+        assert!(body.starts_with(b"PK"));
+    }
+}
@@ -93,7 +93,7 @@ mod tests {
         std::env::set_var("JWT_SECRET", "test_secret_key_for_testing_purposes");
 
         let service = test_token_service();
-        let token = service.generate_access_token(1, "user@example.com", Role::User).unwrap();
+        let token = service.generate_access_token(1, "user@example.com", Role::user()).unwrap();
         
         let app = create_test_app();
         
@@ -121,7 +121,7 @@ mod tests {
         ];
 
         for (user_id, email) in test_users {
-            let token = service.generate_access_token(user_id, email, Role::User).unwrap();
+            let token = service.generate_access_token(user_id, email, Role::user()).unwrap();
             let app = create_test_app();
             
             let request = Request::builder()
@@ -207,7 +207,7 @@ mod tests {
         std::env::set_var("JWT_SECRET", "test_secret_key_for_testing_purposes");
 
         let service = test_token_service();
-        let token = service.generate_access_token(1, "admin@example.com", Role::Admin).unwrap();
+        let token = service.generate_access_token(1, "admin@example.com", Role::admin()).unwrap();
         
         let app = create_test_app();
         
@@ -235,7 +235,7 @@ mod tests {
         ];
 
         for (user_id, email) in test_admins {
-            let token = service.generate_access_token(user_id, email, Role::Admin).unwrap();
+            let token = service.generate_access_token(user_id, email, Role::admin()).unwrap();
             let app = create_test_app();
             
             let request = Request::builder()
@@ -264,9 +264,11 @@ mod tests {
         let claims = Claims {
             sub: 1,
             email: "admin@example.com".to_string(),
-            role: Role::Admin,
+            role: Role::admin(),
             iat: Utc::now().timestamp() - 1000,
             exp: Utc::now().timestamp() - 500, // Expired 500 seconds ago
+            permissions: Vec::new(),
+            sid: None,
         };
 
         let token = encode(
@@ -322,7 +324,7 @@ mod tests {
             std::env::set_var("JWT_SECRET", "test_secret_key_for_testing_purposes");
 
             let service = test_token_service();
-            let token = service.generate_access_token(user_id, &email, Role::User)?;
+            let token = service.generate_access_token(user_id, &email, Role::user())?;
             
             let rt = tokio::runtime::Runtime::new().unwrap();
             let app = create_test_app();
@@ -347,7 +349,7 @@ mod tests {
             std::env::set_var("JWT_SECRET", "test_secret_key_for_testing_purposes");
 
             let service = test_token_service();
-            let token = service.generate_access_token(user_id, &email, Role::Admin)?;
+            let token = service.generate_access_token(user_id, &email, Role::admin())?;
             
             let rt = tokio::runtime::Runtime::new().unwrap();
             let app = create_test_app();
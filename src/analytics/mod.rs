@@ -6,12 +6,13 @@ pub mod middleware;
 pub mod utils;
 pub mod routes;
 pub mod error;
+pub mod export;
 pub mod validation;
 pub mod formatting;
 
 pub use types::*;
 pub use middleware::AnalyticsAuthMiddleware;
-pub use routes::create_analytics_router;
+pub use routes::{create_analytics_router, AnalyticsControllers};
 pub use error::AnalyticsError;
 pub use validation::AnalyticsValidator;
 pub use formatting::ResponseFormatter;
@@ -1,11 +1,17 @@
 // Analytics data access layer
-// Contains database queries for orders and reviews analytics
+// Contains database queries for orders, reviews, customers and market basket analytics
 
 mod orders_repository;
 mod reviews_repository;
+mod customers_repository;
+mod market_basket_repository;
 
-pub use orders_repository::OrdersAnalyticsRepository;
+pub use orders_repository::{OrdersAnalyticsRepository, PricingRuleApplications, RowStream};
 pub use reviews_repository::ReviewsAnalyticsRepository;
+pub use customers_repository::{CustomerOrderTotals, CustomersAnalyticsRepository, RfmSegmentTotals};
+pub use market_basket_repository::{CoffeePairRow, MarketBasketRepository};
 
+#[cfg(test)]
+mod test_support;
 #[cfg(test)]
 mod tests;
@@ -2,6 +2,7 @@
 // Provides aggregated data about reviews and ratings for analytics
 
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
 use sqlx::PgPool;
 
@@ -20,73 +21,51 @@ impl ReviewsAnalyticsRepository {
     }
 
     /// Calculate average rating across all coffees or for a specific coffee
-    /// Returns the mean rating value
+    /// Returns the mean rating value of published reviews, optionally only verified purchases
     pub async fn calculate_average_rating(
         &self,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<Option<Decimal>, sqlx::Error> {
-        let result: (Option<Decimal>,) = match coffee_id {
-            Some(id) => {
-                sqlx::query_as(
-                    r#"
-                    SELECT AVG(rating)
-                    FROM reviews
-                    WHERE coffee_id = $1
-                    "#
-                )
-                .bind(id)
-                .fetch_one(&self.pool)
-                .await?
-            }
-            None => {
-                sqlx::query_as(
-                    r#"
-                    SELECT AVG(rating)
-                    FROM reviews
-                    "#
-                )
-                .fetch_one(&self.pool)
-                .await?
-            }
-        };
+        let result: (Option<Decimal>,) = sqlx::query_as(
+            r#"
+            SELECT AVG(rating)
+            FROM reviews
+            WHERE status = 'published'
+              AND ($1::INT IS NULL OR coffee_id = $1)
+              AND (NOT $2 OR verified_purchase)
+            "#
+        )
+        .bind(coffee_id)
+        .bind(verified_only)
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok(result.0)
     }
 
     /// Get rating distribution grouped by rating value (1-5 stars)
-    /// Returns count of reviews for each rating level
+    /// Returns count of published reviews for each rating level
     pub async fn get_rating_distribution(
         &self,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<Vec<RatingDistribution>, sqlx::Error> {
-        let results = match coffee_id {
-            Some(id) => {
-                sqlx::query_as::<_, (i32, i64)>(
-                    r#"
-                    SELECT rating, COUNT(*) as count
-                    FROM reviews
-                    WHERE coffee_id = $1
-                    GROUP BY rating
-                    ORDER BY rating ASC
-                    "#
-                )
-                .bind(id)
-                .fetch_all(&self.pool)
-                .await?
-            }
-            None => {
-                sqlx::query_as::<_, (i32, i64)>(
-                    r#"
-                    SELECT rating, COUNT(*) as count
-                    FROM reviews
-                    GROUP BY rating
-                    ORDER BY rating ASC
-                    "#
-                )
-                .fetch_all(&self.pool)
-                .await?
-            }
-        };
+        let results = sqlx::query_as::<_, (i32, i64)>(
+            r#"
+            SELECT rating::INT, COUNT(*) as count
+            FROM reviews
+            WHERE status = 'published'
+              AND ($1::INT IS NULL OR coffee_id = $1)
+              AND (NOT $2 OR verified_purchase)
+            GROUP BY rating
+            ORDER BY rating ASC
+            "#
+        )
+        .bind(coffee_id)
+        .bind(verified_only)
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(results
             .into_iter()
@@ -95,53 +74,37 @@ impl ReviewsAnalyticsRepository {
     }
 
     /// Get review trends over time as time-series data
-    /// Returns average rating per day for trend analysis
+    /// Returns average rating of published reviews per local day for trend analysis
     pub async fn get_review_trends(
         &self,
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        timezone: Tz,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<Vec<RatingTrend>, sqlx::Error> {
-        let results = match coffee_id {
-            Some(id) => {
-                sqlx::query_as::<_, (DateTime<Utc>, Option<Decimal>)>(
-                    r#"
-                    SELECT 
-                        DATE_TRUNC('day', created_at) as timestamp,
-                        AVG(rating) as average_rating
-                    FROM reviews
-                    WHERE created_at >= $1 
-                      AND created_at < $2
-                      AND coffee_id = $3
-                    GROUP BY timestamp
-                    ORDER BY timestamp ASC
-                    "#
-                )
-                .bind(start_date)
-                .bind(end_date)
-                .bind(id)
-                .fetch_all(&self.pool)
-                .await?
-            }
-            None => {
-                sqlx::query_as::<_, (DateTime<Utc>, Option<Decimal>)>(
-                    r#"
-                    SELECT 
-                        DATE_TRUNC('day', created_at) as timestamp,
-                        AVG(rating) as average_rating
-                    FROM reviews
-                    WHERE created_at >= $1 
-                      AND created_at < $2
-                    GROUP BY timestamp
-                    ORDER BY timestamp ASC
-                    "#
-                )
-                .bind(start_date)
-                .bind(end_date)
-                .fetch_all(&self.pool)
-                .await?
-            }
-        };
+        let results = sqlx::query_as::<_, (DateTime<Utc>, Option<Decimal>)>(
+            r#"
+            SELECT 
+                DATE_TRUNC('day', created_at AT TIME ZONE $5) AT TIME ZONE $5 as timestamp,
+                AVG(rating) as average_rating
+            FROM reviews
+            WHERE created_at >= $1 
+              AND created_at < $2
+              AND status = 'published'
+              AND ($3::INT IS NULL OR coffee_id = $3)
+              AND (NOT $4 OR verified_purchase)
+            GROUP BY timestamp
+            ORDER BY timestamp ASC
+            "#
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .bind(coffee_id)
+        .bind(verified_only)
+        .bind(timezone.name())
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(results
             .into_iter()
@@ -152,57 +115,51 @@ impl ReviewsAnalyticsRepository {
             .collect())
     }
 
-    /// Count total reviews with optional coffee filter
+    /// Count published reviews with optional coffee and verified-purchase filters
     pub async fn count_reviews(
         &self,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<i64, sqlx::Error> {
-        let result: (i64,) = match coffee_id {
-            Some(id) => {
-                sqlx::query_as(
-                    r#"
-                    SELECT COUNT(*)
-                    FROM reviews
-                    WHERE coffee_id = $1
-                    "#
-                )
-                .bind(id)
-                .fetch_one(&self.pool)
-                .await?
-            }
-            None => {
-                sqlx::query_as(
-                    r#"
-                    SELECT COUNT(*)
-                    FROM reviews
-                    "#
-                )
-                .fetch_one(&self.pool)
-                .await?
-            }
-        };
+        let result: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*)
+            FROM reviews
+            WHERE status = 'published'
+              AND ($1::INT IS NULL OR coffee_id = $1)
+              AND (NOT $2 OR verified_purchase)
+            "#
+        )
+        .bind(coffee_id)
+        .bind(verified_only)
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok(result.0)
     }
 
     /// Get highest rated coffees with their average ratings
+    /// Ranked by the confidence-weighted score kept on the coffee; returns
+    /// (coffee_id, coffee_name, average_rating, weighted_rating, review_count)
     pub async fn get_highest_rated_coffees(
         &self,
         limit: i32,
         min_reviews: i32,
-    ) -> Result<Vec<(i32, String, Decimal, i64)>, sqlx::Error> {
-        let results = sqlx::query_as::<_, (i32, String, Option<Decimal>, i64)>(
+    ) -> Result<Vec<(i32, String, Decimal, Option<Decimal>, i64)>, sqlx::Error> {
+        let results = sqlx::query_as::<_, (i32, String, Option<Decimal>, Option<Decimal>, i64)>(
             r#"
             SELECT 
                 c.id as coffee_id,
                 c.name as coffee_name,
                 AVG(r.rating) as average_rating,
+                c.weighted_rating,
                 COUNT(r.id) as review_count
             FROM coffees c
             INNER JOIN reviews r ON c.id = r.coffee_id
-            GROUP BY c.id, c.name
+            WHERE r.status = 'published'
+            GROUP BY c.id, c.name, c.weighted_rating
             HAVING COUNT(r.id) >= $1
-            ORDER BY average_rating DESC, review_count DESC
+            ORDER BY c.weighted_rating DESC NULLS LAST, average_rating DESC, review_count DESC
             LIMIT $2
             "#
         )
@@ -213,11 +170,12 @@ impl ReviewsAnalyticsRepository {
 
         Ok(results
             .into_iter()
-            .map(|(coffee_id, coffee_name, average_rating, review_count)| {
+            .map(|(coffee_id, coffee_name, average_rating, weighted_rating, review_count)| {
                 (
                     coffee_id,
                     coffee_name,
                     average_rating.unwrap_or(Decimal::ZERO),
+                    weighted_rating,
                     review_count,
                 )
             })
@@ -0,0 +1,24 @@
+// Shared helpers for the analytics repository tests
+
+use chrono::{DateTime, Duration, Utc};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Width of a window returned by `unique_window`
+const WINDOW_SECONDS: i64 = 4;
+
+/// Start of a window of a few seconds no other test run writes to
+///
+/// Tests that assert exact totals put their orders in `[start, start + 4s)`.
+/// The window is picked from the clock among the 4-second slots of
+/// `[base, base + span)`, so runs and parallel tests land in different
+/// windows. Slots are aligned to `base`, so with an hour-aligned base a
+/// window never crosses an hour boundary.
+pub(super) fn unique_window(base: DateTime<Utc>, span: Duration) -> DateTime<Utc> {
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let slots = (span.num_seconds() / WINDOW_SECONDS).max(1) as u128;
+
+    base + Duration::seconds((unique % slots) as i64 * WINDOW_SECONDS)
+}
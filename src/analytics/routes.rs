@@ -8,54 +8,100 @@ use axum::{
 };
 use std::sync::Arc;
 
-use crate::analytics::controllers::{PopularCoffeesController, RatingInsightsController, RevenueReportsController, SalesStatisticsController};
+use crate::analytics::controllers::{BasketAnalyticsController, CustomerAnalyticsController, DemandForecastController, MarketBasketController, PopularCoffeesController, PricingRuleReportsController, RatingInsightsController, RevenueReportsController, SalesStatisticsController};
 use crate::analytics::middleware::logging_middleware;
-use crate::auth::middleware::RequireRole;
+use crate::auth::{middleware::RequirePermission, models::Permission};
+
+/// Controllers behind the analytics endpoints, one per route group
+pub struct AnalyticsControllers {
+    pub sales: Arc<SalesStatisticsController>,
+    pub popular_coffees: Arc<PopularCoffeesController>,
+    pub revenue: Arc<RevenueReportsController>,
+    pub rating_insights: Arc<RatingInsightsController>,
+    pub basket: Arc<BasketAnalyticsController>,
+    pub pricing_rules: Arc<PricingRuleReportsController>,
+    pub customers: Arc<CustomerAnalyticsController>,
+    pub market_basket: Arc<MarketBasketController>,
+    pub forecasts: Arc<DemandForecastController>,
+}
 
 /// Create the analytics router with all endpoints
 /// Base path: /api/v1/admin/analytics
-pub fn create_analytics_router(
-    sales_controller: Arc<SalesStatisticsController>,
-    popular_coffees_controller: Arc<PopularCoffeesController>,
-    revenue_controller: Arc<RevenueReportsController>,
-    rating_insights_controller: Arc<RatingInsightsController>,
-) -> Router {
+pub fn create_analytics_router(controllers: AnalyticsControllers) -> Router {
     // Sales statistics routes
     let sales_routes = Router::new()
         .route("/total", get(SalesStatisticsController::get_total_sales))
         .route("/by-period", get(SalesStatisticsController::get_sales_by_period))
         .route("/trends", get(SalesStatisticsController::get_sales_trends))
-        .with_state(sales_controller);
+        .route("/heatmap", get(SalesStatisticsController::get_sales_heatmap))
+        .with_state(controllers.sales);
 
     // Popular coffees routes
     let coffees_routes = Router::new()
         .route("/most-ordered", get(PopularCoffeesController::get_most_ordered))
         .route("/highest-rated", get(PopularCoffeesController::get_highest_rated))
         .route("/trending", get(PopularCoffeesController::get_trending))
-        .with_state(popular_coffees_controller);
+        .with_state(controllers.popular_coffees);
 
     // Revenue reports routes
     let revenue_routes = Router::new()
         .route("/by-period", get(RevenueReportsController::get_revenue_by_period))
         .route("/by-coffee", get(RevenueReportsController::get_revenue_by_coffee))
-        .with_state(revenue_controller);
+        .with_state(controllers.revenue);
 
     // Rating insights routes
     let rating_routes = Router::new()
         .route("/average", get(RatingInsightsController::get_average_rating))
         .route("/distribution", get(RatingInsightsController::get_rating_distribution))
         .route("/trends", get(RatingInsightsController::get_rating_trends))
-        .with_state(rating_insights_controller);
+        .with_state(controllers.rating_insights);
+
+    // Basket and order-value routes
+    let basket_routes = Router::new()
+        .route("/average-order-value", get(BasketAnalyticsController::get_average_order_value))
+        .route("/units-per-order", get(BasketAnalyticsController::get_units_per_order))
+        .route("/order-sizes", get(BasketAnalyticsController::get_order_size_distribution))
+        .route("/discount-share", get(BasketAnalyticsController::get_discount_share))
+        .with_state(controllers.basket);
+
+    // Pricing rule reports routes
+    let pricing_rules_routes = Router::new()
+        .route("/effectiveness", get(PricingRuleReportsController::get_rule_effectiveness))
+        .with_state(controllers.pricing_rules);
+
+    // Customer cohort, lifetime value and RFM routes
+    let customers_routes = Router::new()
+        .route("/cohorts", get(CustomerAnalyticsController::get_cohort_retention))
+        .route("/lifetime-value", get(CustomerAnalyticsController::get_lifetime_values))
+        .route("/rfm", get(CustomerAnalyticsController::get_rfm_scores))
+        .route("/rfm/segments", get(CustomerAnalyticsController::get_rfm_segments))
+        .with_state(controllers.customers);
+
+    // Frequently-bought-together routes
+    let market_basket_routes = Router::new()
+        .route("/pairs", get(MarketBasketController::get_pairs))
+        .with_state(controllers.market_basket);
 
-    // Combine all analytics routes with admin authentication and logging
+    // Demand forecast routes
+    let forecasts_routes = Router::new()
+        .route("/demand", get(DemandForecastController::get_demand_forecast))
+        .route("/backtest", get(DemandForecastController::get_backtest))
+        .with_state(controllers.forecasts);
+
+    // Combine all analytics routes with analytics:read authorization and logging
     Router::new()
         .nest("/sales", sales_routes)
         .nest("/coffees", coffees_routes)
         .nest("/revenue", revenue_routes)
         .nest("/ratings", rating_routes)
+        .nest("/baskets", basket_routes)
+        .nest("/pricing-rules", pricing_rules_routes)
+        .nest("/customers", customers_routes)
+        .nest("/market-basket", market_basket_routes)
+        .nest("/forecasts", forecasts_routes)
         .layer(middleware::from_fn(logging_middleware))
         .layer(middleware::from_fn(|req, next| {
-            RequireRole::admin().middleware(req, next)
+            RequirePermission::new(Permission::AnalyticsRead).middleware(req, next)
         }))
 }
 
@@ -63,8 +109,8 @@ pub fn create_analytics_router(
 mod tests {
     use super::*;
     use crate::analytics::{
-        repositories::{OrdersAnalyticsRepository, ReviewsAnalyticsRepository},
-        services::{PopularCoffeesService, RatingAnalysisService, RevenueCalculationService, SalesAggregationService, TrendCalculationService},
+        repositories::{CustomersAnalyticsRepository, MarketBasketRepository, OrdersAnalyticsRepository, ReviewsAnalyticsRepository},
+        services::{BasketAnalysisService, CustomerAnalyticsService, DemandForecastService, MarketBasketService, PopularCoffeesService, PricingRuleAnalysisService, RatingAnalysisService, RevenueCalculationService, SalesAggregationService, TrendCalculationService},
     };
     use sqlx::PgPool;
 
@@ -97,12 +143,37 @@ mod tests {
         let rating_service = Arc::new(RatingAnalysisService::new(reviews_repo));
         let rating_insights_controller = Arc::new(RatingInsightsController::new(rating_service));
         
-        let router = create_analytics_router(
-            sales_controller,
-            popular_coffees_controller,
-            revenue_controller,
-            rating_insights_controller,
-        );
+        // Basket controller
+        let basket_service = Arc::new(BasketAnalysisService::new(OrdersAnalyticsRepository::new(pool.clone())));
+        let basket_controller = Arc::new(BasketAnalyticsController::new(basket_service));
+        
+        // Pricing rules controller
+        let pricing_rules_service = Arc::new(PricingRuleAnalysisService::new(OrdersAnalyticsRepository::new(pool.clone())));
+        let pricing_rules_controller = Arc::new(PricingRuleReportsController::new(pricing_rules_service));
+        
+        // Customers controller
+        let customers_service = Arc::new(CustomerAnalyticsService::new(CustomersAnalyticsRepository::new(pool.clone())));
+        let customers_controller = Arc::new(CustomerAnalyticsController::new(customers_service));
+        
+        // Market basket controller
+        let market_basket_service = Arc::new(MarketBasketService::new(MarketBasketRepository::new(pool.clone())));
+        let market_basket_controller = Arc::new(MarketBasketController::new(market_basket_service));
+        
+        // Demand forecast controller
+        let forecasts_service = Arc::new(DemandForecastService::new(OrdersAnalyticsRepository::new(pool)));
+        let forecasts_controller = Arc::new(DemandForecastController::new(forecasts_service));
+        
+        let router = create_analytics_router(AnalyticsControllers {
+            sales: sales_controller,
+            popular_coffees: popular_coffees_controller,
+            revenue: revenue_controller,
+            rating_insights: rating_insights_controller,
+            basket: basket_controller,
+            pricing_rules: pricing_rules_controller,
+            customers: customers_controller,
+            market_basket: market_basket_controller,
+            forecasts: forecasts_controller,
+        });
         
         // Router should be created successfully
         assert!(std::mem::size_of_val(&router) > 0);
@@ -0,0 +1,321 @@
+// Basket analysis service
+// Business logic for order value, basket size and discount usage by period
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::analytics::{
+    repositories::OrdersAnalyticsRepository,
+    services::TrendCalculationService,
+    types::{BasketTrend, DateRange, OrderSizeBucket, TimePeriod},
+    utils::TimePeriodFilter,
+};
+
+/// Orders with this many units or more share the last order-size bucket
+const MAX_ORDER_SIZE: i32 = 5;
+
+/// Basket figures of one period, as returned by the repository
+struct BasketTotals {
+    timestamp: DateTime<Utc>,
+    order_count: i64,
+    net_revenue: Decimal,
+    units: i64,
+    discounted_orders: i64,
+}
+
+/// Service for basket and order-value analytics
+#[derive(Clone)]
+pub struct BasketAnalysisService {
+    orders_repo: OrdersAnalyticsRepository,
+}
+
+impl BasketAnalysisService {
+    /// Create a new BasketAnalysisService
+    pub fn new(orders_repo: OrdersAnalyticsRepository) -> Self {
+        Self { orders_repo }
+    }
+
+    /// Average charged total per completed order, by period
+    pub async fn calculate_average_order_value(
+        &self,
+        date_range: DateRange,
+        granularity: TimePeriod,
+    ) -> Result<Vec<BasketTrend>, sqlx::Error> {
+        let totals = self.get_basket_totals(&date_range, granularity).await?;
+
+        Ok(Self::build_trends(&totals, &date_range, granularity, |t| {
+            Self::ratio(t.net_revenue, t.order_count)
+        }))
+    }
+
+    /// Average units per completed order, by period
+    pub async fn calculate_units_per_order(
+        &self,
+        date_range: DateRange,
+        granularity: TimePeriod,
+    ) -> Result<Vec<BasketTrend>, sqlx::Error> {
+        let totals = self.get_basket_totals(&date_range, granularity).await?;
+
+        Ok(Self::build_trends(&totals, &date_range, granularity, |t| {
+            Self::ratio(Decimal::from(t.units), t.order_count)
+        }))
+    }
+
+    /// Percentage of completed orders charged less than their item subtotals, by period
+    pub async fn calculate_discount_share(
+        &self,
+        date_range: DateRange,
+        granularity: TimePeriod,
+    ) -> Result<Vec<BasketTrend>, sqlx::Error> {
+        let totals = self.get_basket_totals(&date_range, granularity).await?;
+
+        Ok(Self::build_trends(&totals, &date_range, granularity, |t| {
+            Self::ratio(Decimal::from(t.discounted_orders * 100), t.order_count)
+        }))
+    }
+
+    /// Completed orders by size (1, 2, 3, 4 or 5+ units), by period
+    pub async fn calculate_order_size_distribution(
+        &self,
+        date_range: DateRange,
+        granularity: TimePeriod,
+    ) -> Result<Vec<OrderSizeBucket>, sqlx::Error> {
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        let rows = self.orders_repo
+            .get_order_sizes_by_period(
+                date_range.start_date,
+                date_range.end_date,
+                date_range.timezone,
+                granularity,
+                MAX_ORDER_SIZE,
+            )
+            .await?;
+
+        Ok(Self::build_size_buckets(rows, &date_range, granularity))
+    }
+
+    async fn get_basket_totals(
+        &self,
+        date_range: &DateRange,
+        granularity: TimePeriod,
+    ) -> Result<Vec<BasketTotals>, sqlx::Error> {
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        let rows = self.orders_repo
+            .get_basket_totals_by_period(
+                date_range.start_date,
+                date_range.end_date,
+                date_range.timezone,
+                granularity,
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(timestamp, order_count, net_revenue, units, discounted_orders)| BasketTotals {
+                timestamp,
+                order_count,
+                net_revenue,
+                units,
+                discounted_orders,
+            })
+            .collect())
+    }
+
+    /// Turn per-period totals into a metric with period-over-period changes
+    /// A period is only compared with the one right before it; after a period
+    /// without orders the change is None
+    fn build_trends(
+        totals: &[BasketTotals],
+        date_range: &DateRange,
+        granularity: TimePeriod,
+        metric: impl Fn(&BasketTotals) -> Decimal,
+    ) -> Vec<BasketTrend> {
+        let mut trends: Vec<BasketTrend> = Vec::with_capacity(totals.len());
+        let mut previous: Option<(DateTime<Utc>, Decimal)> = None;
+
+        for period in totals {
+            let value = metric(period);
+            let change_percentage = previous
+                .filter(|(timestamp, _)| {
+                    TimePeriodFilter::next_period(*timestamp, granularity, date_range.timezone)
+                        == period.timestamp
+                })
+                .map(|(_, previous_value)| TrendCalculationService::percentage_change(value, previous_value));
+
+            trends.push(BasketTrend {
+                period: TimePeriodFilter::format_period_label(period.timestamp, granularity, date_range.timezone),
+                timestamp: period.timestamp,
+                order_count: period.order_count,
+                value,
+                change_percentage,
+            });
+            previous = Some((period.timestamp, value));
+        }
+
+        trends
+    }
+
+    /// Lay out (period, size, order_count) rows with each size's share of the
+    /// period and its change against the same size in the previous period
+    fn build_size_buckets(
+        rows: Vec<(DateTime<Utc>, i32, i64)>,
+        date_range: &DateRange,
+        granularity: TimePeriod,
+    ) -> Vec<OrderSizeBucket> {
+        let mut period_totals: HashMap<DateTime<Utc>, i64> = HashMap::new();
+        let mut counts: HashMap<(DateTime<Utc>, i32), i64> = HashMap::new();
+        for (timestamp, size, order_count) in &rows {
+            *period_totals.entry(*timestamp).or_insert(0) += order_count;
+            counts.insert((*timestamp, *size), *order_count);
+        }
+
+        // The previous period start, when it had orders
+        let mut previous_periods: HashMap<DateTime<Utc>, DateTime<Utc>> = HashMap::new();
+        for timestamp in period_totals.keys() {
+            let next = TimePeriodFilter::next_period(*timestamp, granularity, date_range.timezone);
+            if period_totals.contains_key(&next) {
+                previous_periods.insert(next, *timestamp);
+            }
+        }
+
+        rows.into_iter()
+            .map(|(timestamp, size, order_count)| {
+                let share = Self::ratio(Decimal::from(order_count * 100), period_totals[&timestamp]);
+                let change_percentage = previous_periods.get(&timestamp).map(|previous| {
+                    let previous_count = counts.get(&(*previous, size)).copied().unwrap_or(0);
+                    TrendCalculationService::percentage_change(
+                        Decimal::from(order_count),
+                        Decimal::from(previous_count),
+                    )
+                });
+
+                OrderSizeBucket {
+                    period: TimePeriodFilter::format_period_label(timestamp, granularity, date_range.timezone),
+                    timestamp,
+                    size: if size >= MAX_ORDER_SIZE {
+                        format!("{}+", MAX_ORDER_SIZE)
+                    } else {
+                        size.to_string()
+                    },
+                    order_count,
+                    share,
+                    change_percentage,
+                }
+            })
+            .collect()
+    }
+
+    /// `numerator / count` rounded to 2 decimal places (0 without orders)
+    fn ratio(numerator: Decimal, count: i64) -> Decimal {
+        if count == 0 {
+            Decimal::ZERO
+        } else {
+            (numerator / Decimal::from(count)).round_dp(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::Tz;
+    use std::str::FromStr;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, d, 0, 0, 0).unwrap()
+    }
+
+    fn march() -> DateRange {
+        DateRange {
+            start_date: day(1),
+            end_date: day(31),
+            timezone: Tz::UTC,
+        }
+    }
+
+    fn totals(d: u32, order_count: i64, net_revenue: &str, units: i64, discounted_orders: i64) -> BasketTotals {
+        BasketTotals {
+            timestamp: day(d),
+            order_count,
+            net_revenue: Decimal::from_str(net_revenue).unwrap(),
+            units,
+            discounted_orders,
+        }
+    }
+
+    #[test]
+    fn test_average_order_value_with_changes() {
+        let periods = vec![
+            totals(1, 4, "20.00", 6, 1),
+            totals(2, 5, "30.00", 10, 0),
+            // No orders on the 3rd, so the 4th has nothing to compare with
+            totals(4, 2, "9.00", 2, 2),
+        ];
+
+        let trends = BasketAnalysisService::build_trends(&periods, &march(), TimePeriod::Daily, |t| {
+            BasketAnalysisService::ratio(t.net_revenue, t.order_count)
+        });
+
+        let values: Vec<Decimal> = trends.iter().map(|t| t.value).collect();
+        assert_eq!(values, vec![Decimal::from(5), Decimal::from(6), Decimal::from_str("4.50").unwrap()]);
+        assert_eq!(trends[0].change_percentage, None);
+        assert_eq!(trends[1].change_percentage, Some(Decimal::from(20)));
+        assert_eq!(trends[2].change_percentage, None);
+        assert_eq!(trends[1].period, "2026-03-02");
+        assert_eq!(trends[1].order_count, 5);
+    }
+
+    #[test]
+    fn test_discount_share_and_units_per_order() {
+        let periods = vec![totals(1, 4, "20.00", 6, 1), totals(2, 3, "12.00", 3, 3)];
+
+        let share = BasketAnalysisService::build_trends(&periods, &march(), TimePeriod::Daily, |t| {
+            BasketAnalysisService::ratio(Decimal::from(t.discounted_orders * 100), t.order_count)
+        });
+        assert_eq!(share[0].value, Decimal::from(25));
+        assert_eq!(share[1].value, Decimal::from(100));
+        assert_eq!(share[1].change_percentage, Some(Decimal::from(300)));
+
+        let units = BasketAnalysisService::build_trends(&periods, &march(), TimePeriod::Daily, |t| {
+            BasketAnalysisService::ratio(Decimal::from(t.units), t.order_count)
+        });
+        assert_eq!(units[0].value, Decimal::from_str("1.50").unwrap());
+        assert_eq!(units[1].change_percentage, Some(Decimal::from_str("-33.33").unwrap()));
+    }
+
+    #[test]
+    fn test_order_size_distribution_shares_and_changes() {
+        let rows = vec![
+            (day(1), 1, 3),
+            (day(1), 2, 1),
+            (day(2), 1, 1),
+            (day(2), 5, 1),
+        ];
+
+        let buckets = BasketAnalysisService::build_size_buckets(rows, &march(), TimePeriod::Daily);
+
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0].share, Decimal::from(75));
+        assert_eq!(buckets[0].change_percentage, None);
+        assert_eq!(buckets[2].size, "1");
+        assert_eq!(buckets[2].change_percentage, Some(Decimal::from_str("-66.67").unwrap()));
+        assert_eq!(buckets[3].size, "5+");
+        assert_eq!(buckets[3].share, Decimal::from(50));
+        // Not ordered the day before
+        assert_eq!(buckets[3].change_percentage, Some(Decimal::from(100)));
+    }
+
+    #[test]
+    fn test_ratio_without_orders_is_zero() {
+        assert_eq!(BasketAnalysisService::ratio(Decimal::from(10), 0), Decimal::ZERO);
+        assert_eq!(BasketAnalysisService::ratio(Decimal::from(10), 3), Decimal::from_str("3.33").unwrap());
+    }
+}
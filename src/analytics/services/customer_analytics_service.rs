@@ -0,0 +1,337 @@
+// Customer analytics service
+// Business logic for signup cohorts, lifetime value and RFM segmentation
+
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::analytics::{
+    repositories::{CustomerOrderTotals, CustomersAnalyticsRepository, RfmSegmentTotals},
+    types::{
+        CohortRetentionCell, CustomerCohort, CustomerLifetimeValue, CustomerRfm, DateRange, RfmSegment,
+        RfmSegmentSummary, TimePeriod,
+    },
+    utils::TimePeriodFilter,
+};
+
+/// Service for customer-level analytics
+#[derive(Clone)]
+pub struct CustomerAnalyticsService {
+    customers_repo: CustomersAnalyticsRepository,
+}
+
+impl CustomerAnalyticsService {
+    /// Create a new CustomerAnalyticsService
+    pub fn new(customers_repo: CustomersAnalyticsRepository) -> Self {
+        Self { customers_repo }
+    }
+
+    /// Monthly cohorts of users who signed up in the date range, with the
+    /// share of each cohort that placed a completed order in every month
+    /// from the signup month up to the current month
+    pub async fn calculate_cohort_retention(
+        &self,
+        date_range: DateRange,
+    ) -> Result<Vec<CustomerCohort>, sqlx::Error> {
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        let rows = self.customers_repo
+            .get_cohort_activity(date_range.start_date, date_range.end_date, date_range.timezone)
+            .await?;
+
+        Ok(Self::build_cohorts(rows, date_range.timezone, Utc::now()))
+    }
+
+    /// Customers ranked by lifetime value (charged total of completed orders)
+    pub async fn get_lifetime_values(&self, limit: i32) -> Result<Vec<CustomerLifetimeValue>, sqlx::Error> {
+        let now = Utc::now();
+        let totals = self.customers_repo.get_customer_order_totals(now, None, limit.max(0)).await?;
+
+        Ok(totals
+            .iter()
+            .map(|customer| Self::build_lifetime_value(customer, now))
+            .collect())
+    }
+
+    /// RFM scores and segment of customers, optionally of one segment only,
+    /// ranked by spend
+    pub async fn get_rfm_scores(
+        &self,
+        segment: Option<RfmSegment>,
+        limit: i32,
+    ) -> Result<Vec<CustomerRfm>, sqlx::Error> {
+        let now = Utc::now();
+        let totals = self.customers_repo.get_customer_order_totals(now, segment, limit.max(0)).await?;
+
+        Ok(totals.iter().map(|customer| Self::build_rfm(customer, now)).collect())
+    }
+
+    /// Customer count, share and average RFM values of every segment
+    pub async fn get_rfm_segments(&self) -> Result<Vec<RfmSegmentSummary>, sqlx::Error> {
+        let totals = self.customers_repo.get_rfm_segment_totals(Utc::now()).await?;
+
+        Ok(Self::summarize_segments(totals))
+    }
+
+    /// Lay out (cohort_month, cohort_size, month_offset, active_customers) rows
+    /// as one cohort per signup month with a cell for every month up to `now`
+    fn build_cohorts(
+        rows: Vec<(DateTime<Utc>, i64, Option<i32>, i64)>,
+        timezone: Tz,
+        now: DateTime<Utc>,
+    ) -> Vec<CustomerCohort> {
+        let mut cohorts: Vec<(DateTime<Utc>, i64)> = Vec::new();
+        let mut active: HashMap<(DateTime<Utc>, i32), i64> = HashMap::new();
+        for (cohort_month, cohort_size, month_offset, active_customers) in rows {
+            if cohorts.last().map(|(month, _)| *month) != Some(cohort_month) {
+                cohorts.push((cohort_month, cohort_size));
+            }
+            if let Some(offset) = month_offset {
+                active.insert((cohort_month, offset), active_customers);
+            }
+        }
+
+        cohorts
+            .into_iter()
+            .map(|(cohort_month, cohort_size)| {
+                let cohort = TimePeriodFilter::format_period_label(cohort_month, TimePeriod::Monthly, timezone);
+                let months = (0..=Self::months_between(cohort_month, now, timezone))
+                    .map(|month| {
+                        let active_customers = active.get(&(cohort_month, month)).copied().unwrap_or(0);
+                        CohortRetentionCell {
+                            cohort: cohort.clone(),
+                            cohort_size,
+                            month,
+                            active_customers,
+                            retention_rate: Self::percentage(active_customers, cohort_size),
+                        }
+                    })
+                    .collect();
+
+                CustomerCohort {
+                    cohort,
+                    timestamp: cohort_month,
+                    cohort_size,
+                    months,
+                }
+            })
+            .collect()
+    }
+
+    fn build_lifetime_value(customer: &CustomerOrderTotals, now: DateTime<Utc>) -> CustomerLifetimeValue {
+        let order_count = Decimal::from(customer.order_count);
+        // Months of 30 days since the first order, counting at least one
+        let months = Decimal::from((now - customer.first_order_at).num_days().max(30)) / Decimal::from(30);
+        let average_days_between_orders = if customer.order_count > 1 {
+            let span_days = Decimal::from((customer.last_order_at - customer.first_order_at).num_seconds())
+                / Decimal::from(86_400);
+            Some((span_days / Decimal::from(customer.order_count - 1)).round_dp(2))
+        } else {
+            None
+        };
+
+        CustomerLifetimeValue {
+            customer_id: customer.customer_id,
+            signed_up_at: customer.signed_up_at,
+            first_order_at: customer.first_order_at,
+            last_order_at: customer.last_order_at,
+            order_count: customer.order_count,
+            lifetime_value: customer.total_spent.round_dp(2),
+            average_order_value: (customer.total_spent / order_count).round_dp(2),
+            orders_per_month: (order_count / months).round_dp(2),
+            average_days_between_orders,
+        }
+    }
+
+    fn build_rfm(customer: &CustomerOrderTotals, now: DateTime<Utc>) -> CustomerRfm {
+        CustomerRfm {
+            customer_id: customer.customer_id,
+            recency_days: (now - customer.last_order_at).num_days().max(0),
+            frequency: customer.order_count,
+            monetary: customer.total_spent.round_dp(2),
+            recency_score: customer.recency_score,
+            frequency_score: customer.frequency_score,
+            monetary_score: customer.monetary_score,
+            segment: customer.segment,
+        }
+    }
+
+    /// One summary per segment, in `RfmSegment::ALL` order, empty segments included
+    fn summarize_segments(rows: Vec<RfmSegmentTotals>) -> Vec<RfmSegmentSummary> {
+        let total: i64 = rows.iter().map(|row| row.customer_count).sum();
+
+        RfmSegment::ALL
+            .into_iter()
+            .map(|segment| match rows.iter().find(|row| row.segment == segment) {
+                Some(row) => RfmSegmentSummary {
+                    segment,
+                    customer_count: row.customer_count,
+                    share: Self::percentage(row.customer_count, total),
+                    average_recency_days: row.average_recency_days.round_dp(2),
+                    average_frequency: row.average_frequency.round_dp(2),
+                    average_monetary: row.average_monetary.round_dp(2),
+                },
+                None => RfmSegmentSummary {
+                    segment,
+                    customer_count: 0,
+                    share: Decimal::ZERO,
+                    average_recency_days: Decimal::ZERO,
+                    average_frequency: Decimal::ZERO,
+                    average_monetary: Decimal::ZERO,
+                },
+            })
+            .collect()
+    }
+
+    /// Calendar months from the month of `from` to the month of `to`, in `timezone`
+    fn months_between(from: DateTime<Utc>, to: DateTime<Utc>, timezone: Tz) -> i32 {
+        let from = from.with_timezone(&timezone);
+        let to = to.with_timezone(&timezone);
+        ((to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32).max(0)
+    }
+
+    /// `part / whole` in percent, rounded to 2 decimal places (0 for an empty whole)
+    fn percentage(part: i64, whole: i64) -> Decimal {
+        if whole == 0 {
+            Decimal::ZERO
+        } else {
+            (Decimal::from(part * 100) / Decimal::from(whole)).round_dp(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    fn month(year: i32, month: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+    }
+
+    fn customer(order_count: i64, total_spent: &str, first_days_ago: i64, last_days_ago: i64) -> CustomerOrderTotals {
+        let now = month(2026, 3);
+        CustomerOrderTotals {
+            customer_id: 7,
+            signed_up_at: now - chrono::Duration::days(400),
+            first_order_at: now - chrono::Duration::days(first_days_ago),
+            last_order_at: now - chrono::Duration::days(last_days_ago),
+            order_count,
+            total_spent: Decimal::from_str(total_spent).unwrap(),
+            recency_score: 5,
+            frequency_score: 4,
+            monetary_score: 3,
+            segment: RfmSegment::Champions,
+        }
+    }
+
+    #[test]
+    fn test_cohorts_fill_every_month_up_to_now() {
+        let rows = vec![
+            (month(2026, 1), 4, Some(0), 3),
+            (month(2026, 1), 4, Some(2), 1),
+            // Nobody from February has ordered yet
+            (month(2026, 2), 2, None, 0),
+        ];
+
+        let cohorts = CustomerAnalyticsService::build_cohorts(rows, Tz::UTC, month(2026, 3) + chrono::Duration::days(5));
+
+        assert_eq!(cohorts.len(), 2);
+        assert_eq!(cohorts[0].cohort, "2026-01");
+        let january: Vec<(i32, i64, Decimal)> = cohorts[0]
+            .months
+            .iter()
+            .map(|cell| (cell.month, cell.active_customers, cell.retention_rate))
+            .collect();
+        assert_eq!(
+            january,
+            vec![
+                (0, 3, Decimal::from(75)),
+                (1, 0, Decimal::ZERO),
+                (2, 1, Decimal::from(25)),
+            ]
+        );
+        assert_eq!(cohorts[1].cohort_size, 2);
+        assert_eq!(cohorts[1].months.len(), 2);
+        assert!(cohorts[1].months.iter().all(|cell| cell.active_customers == 0));
+    }
+
+    #[test]
+    fn test_months_between_uses_local_months() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        // 2026-01-01 03:00 UTC is still December in New York
+        let from = Utc.with_ymd_and_hms(2026, 1, 1, 3, 0, 0).unwrap();
+
+        assert_eq!(CustomerAnalyticsService::months_between(from, month(2026, 3), Tz::UTC), 2);
+        assert_eq!(CustomerAnalyticsService::months_between(from, month(2026, 3) + chrono::Duration::days(1), new_york), 3);
+    }
+
+    #[test]
+    fn test_lifetime_value_and_frequency() {
+        // 4 orders over the last 90 days, the first one 90 days ago
+        let ltv = CustomerAnalyticsService::build_lifetime_value(&customer(4, "30.00", 90, 0), month(2026, 3));
+
+        assert_eq!(ltv.lifetime_value, Decimal::from(30));
+        assert_eq!(ltv.average_order_value, Decimal::from_str("7.50").unwrap());
+        assert_eq!(ltv.orders_per_month, Decimal::from_str("1.33").unwrap());
+        assert_eq!(ltv.average_days_between_orders, Some(Decimal::from(30)));
+    }
+
+    #[test]
+    fn test_single_order_customer_counts_one_month() {
+        let ltv = CustomerAnalyticsService::build_lifetime_value(&customer(1, "4.50", 3, 3), month(2026, 3));
+
+        assert_eq!(ltv.orders_per_month, Decimal::ONE);
+        assert_eq!(ltv.average_days_between_orders, None);
+    }
+
+    #[test]
+    fn test_rfm_from_customer_totals() {
+        let rfm = CustomerAnalyticsService::build_rfm(&customer(3, "12.00", 60, 2), month(2026, 3));
+
+        assert_eq!(rfm.recency_days, 2);
+        assert_eq!(rfm.frequency, 3);
+        assert_eq!(rfm.monetary, Decimal::from(12));
+        assert_eq!((rfm.recency_score, rfm.frequency_score, rfm.monetary_score), (5, 4, 3));
+        assert_eq!(rfm.segment, RfmSegment::Champions);
+    }
+
+    #[test]
+    fn test_segment_summaries_cover_every_segment() {
+        let rows = vec![
+            RfmSegmentTotals {
+                segment: RfmSegment::Hibernating,
+                customer_count: 1,
+                average_recency_days: Decimal::from(200),
+                average_frequency: Decimal::ONE,
+                average_monetary: Decimal::from(3),
+            },
+            RfmSegmentTotals {
+                segment: RfmSegment::Champions,
+                customer_count: 2,
+                average_recency_days: Decimal::from(3),
+                average_frequency: Decimal::from(4),
+                average_monetary: Decimal::from_str("16.004").unwrap(),
+            },
+        ];
+
+        let summaries = CustomerAnalyticsService::summarize_segments(rows);
+
+        assert_eq!(summaries.len(), RfmSegment::ALL.len());
+        let champions = &summaries[0];
+        assert_eq!(champions.segment, RfmSegment::Champions);
+        assert_eq!(champions.customer_count, 2);
+        assert_eq!(champions.share, Decimal::from_str("66.67").unwrap());
+        assert_eq!(champions.average_recency_days, Decimal::from(3));
+        assert_eq!(champions.average_monetary, Decimal::from_str("16.00").unwrap());
+        let hibernating = summaries.iter().find(|s| s.segment == RfmSegment::Hibernating).unwrap();
+        assert_eq!(hibernating.customer_count, 1);
+        assert_eq!(hibernating.average_frequency, Decimal::ONE);
+        assert_eq!(summaries[1].customer_count, 0);
+        assert_eq!(summaries[1].average_monetary, Decimal::ZERO);
+    }
+}
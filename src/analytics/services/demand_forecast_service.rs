@@ -0,0 +1,542 @@
+// Demand forecast service
+// Business logic for forecasting daily coffee demand with weekly seasonality
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::analytics::{
+    repositories::OrdersAnalyticsRepository,
+    types::{CoffeeDemandForecast, DateRange, DemandForecastDay, ForecastBacktest, ForecastModel, TimePeriod},
+    utils::TimePeriodFilter,
+};
+
+/// Days in the weekly season
+const SEASON: usize = 7;
+
+/// Holt-Winters smoothing parameters tried on each coffee's history
+const ALPHAS: [f64; 5] = [0.1, 0.2, 0.3, 0.5, 0.7];
+const BETAS: [f64; 3] = [0.01, 0.05, 0.1];
+const GAMMAS: [f64; 4] = [0.05, 0.1, 0.2, 0.4];
+
+/// Daily units of one coffee, oldest day first, zero on days without sales
+#[derive(Debug, Clone, PartialEq)]
+struct DailySeries {
+    coffee_id: i32,
+    coffee_name: String,
+    units: Vec<f64>,
+}
+
+/// Point forecast and forecast error standard deviation for each day ahead
+#[derive(Debug, Clone, PartialEq)]
+struct ModelFit {
+    forecasts: Vec<f64>,
+    std_devs: Vec<f64>,
+}
+
+/// Final state of additive Holt-Winters smoothing over a history
+#[derive(Debug, Clone, PartialEq)]
+struct HoltWintersState {
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    level: f64,
+    trend: f64,
+    /// Seasonal component by day index modulo the season
+    seasonals: [f64; SEASON],
+    /// Root mean square of the one-step-ahead errors
+    sigma: f64,
+}
+
+/// Service for daily demand forecasts per coffee
+#[derive(Clone)]
+pub struct DemandForecastService {
+    orders_repo: OrdersAnalyticsRepository,
+}
+
+impl DemandForecastService {
+    /// Fewest days of history the models can be fitted on: two weekly seasons
+    pub const MIN_HISTORY_DAYS: usize = 2 * SEASON;
+
+    /// Supported prediction interval levels, in percent
+    pub const CONFIDENCE_LEVELS: [u32; 3] = [80, 90, 95];
+
+    /// Create a new DemandForecastService
+    pub fn new(orders_repo: OrdersAnalyticsRepository) -> Self {
+        Self { orders_repo }
+    }
+
+    /// Forecast the daily units of every coffee sold in the last `history_days`
+    /// local days, for `horizon_days` days starting today
+    /// With `coffee_id`, only that coffee
+    pub async fn forecast_demand(
+        &self,
+        coffee_id: Option<i32>,
+        model: ForecastModel,
+        horizon_days: usize,
+        history_days: usize,
+        confidence_level: u32,
+        timezone: Tz,
+    ) -> Result<Vec<CoffeeDemandForecast>, sqlx::Error> {
+        let today = TimePeriodFilter::start_of_day(Utc::now(), timezone);
+        let history = self.daily_history(coffee_id, today, history_days, timezone).await?;
+        let days = Self::local_days(today, horizon_days, timezone);
+        let z = Self::z_score(confidence_level);
+
+        Ok(history
+            .into_iter()
+            .map(|series| {
+                let fit = Self::fit(model, &series.units, horizon_days);
+                Self::build_forecast(series, model, &fit, &days, z, timezone)
+            })
+            .collect())
+    }
+
+    /// Refit each coffee's history without its last `holdout_days` days and
+    /// compare the forecasts of those days with what actually sold
+    /// Without `model`, every model is backtested
+    pub async fn backtest(
+        &self,
+        coffee_id: Option<i32>,
+        model: Option<ForecastModel>,
+        holdout_days: usize,
+        history_days: usize,
+        confidence_level: u32,
+        timezone: Tz,
+    ) -> Result<Vec<ForecastBacktest>, sqlx::Error> {
+        if history_days < holdout_days + Self::MIN_HISTORY_DAYS {
+            return Err(sqlx::Error::Protocol(format!(
+                "history must cover the {} held-out days and at least {} days before them",
+                holdout_days,
+                Self::MIN_HISTORY_DAYS
+            )));
+        }
+
+        let today = TimePeriodFilter::start_of_day(Utc::now(), timezone);
+        let history = self.daily_history(coffee_id, today, history_days, timezone).await?;
+        let models = model.map_or_else(|| ForecastModel::ALL.to_vec(), |model| vec![model]);
+        let z = Self::z_score(confidence_level);
+
+        Ok(history
+            .iter()
+            .flat_map(|series| {
+                models
+                    .iter()
+                    .map(move |&model| Self::backtest_series(series, model, holdout_days, z))
+            })
+            .collect())
+    }
+
+    /// Zero-filled daily units of each coffee over the `days` local days before `end`
+    async fn daily_history(
+        &self,
+        coffee_id: Option<i32>,
+        end: DateTime<Utc>,
+        days: usize,
+        timezone: Tz,
+    ) -> Result<Vec<DailySeries>, sqlx::Error> {
+        if days < Self::MIN_HISTORY_DAYS {
+            return Err(sqlx::Error::Protocol(format!(
+                "history must cover at least {} days",
+                Self::MIN_HISTORY_DAYS
+            )));
+        }
+
+        let start = TimePeriodFilter::start_of_day(end - Duration::days(days as i64) + Duration::hours(12), timezone);
+        let date_range = DateRange {
+            start_date: start,
+            end_date: end,
+            timezone,
+        };
+
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        let rows = self
+            .orders_repo
+            .get_daily_coffee_units(date_range.start_date, date_range.end_date, timezone, coffee_id)
+            .await?;
+
+        Ok(Self::daily_series(rows, &Self::local_days(start, days, timezone), timezone))
+    }
+
+    /// Starts of the `count` local days from `start` (23 or 25 hours apart
+    /// across a DST change)
+    fn local_days(start: DateTime<Utc>, count: usize, timezone: Tz) -> Vec<DateTime<Utc>> {
+        (0..count as i64)
+            .map(|day| {
+                TimePeriodFilter::start_of_day(start + Duration::days(day) + Duration::hours(12), timezone)
+            })
+            .collect()
+    }
+
+    /// One series per coffee over `days`, by coffee id
+    /// Rows outside `days` are ignored
+    fn daily_series(
+        rows: Vec<(DateTime<Utc>, i32, String, i64)>,
+        days: &[DateTime<Utc>],
+        timezone: Tz,
+    ) -> Vec<DailySeries> {
+        let positions: HashMap<NaiveDate, usize> = days
+            .iter()
+            .enumerate()
+            .map(|(position, day)| (day.with_timezone(&timezone).date_naive(), position))
+            .collect();
+        let mut series: BTreeMap<i32, DailySeries> = BTreeMap::new();
+
+        for (day, coffee_id, coffee_name, units) in rows {
+            if let Some(&position) = positions.get(&day.with_timezone(&timezone).date_naive()) {
+                let coffee = series.entry(coffee_id).or_insert_with(|| DailySeries {
+                    coffee_id,
+                    coffee_name,
+                    units: vec![0.0; days.len()],
+                });
+                coffee.units[position] += units as f64;
+            }
+        }
+
+        series.into_values().collect()
+    }
+
+    /// Forecast `horizon` days after a history of at least two weeks
+    fn fit(model: ForecastModel, history: &[f64], horizon: usize) -> ModelFit {
+        match model {
+            ForecastModel::SeasonalNaive => Self::seasonal_naive(history, horizon),
+            ForecastModel::HoltWinters => Self::holt_winters(history, horizon),
+        }
+    }
+
+    /// Each day ahead repeats the same weekday of the last week
+    /// The error grows with the square root of the number of weeks ahead
+    fn seasonal_naive(history: &[f64], horizon: usize) -> ModelFit {
+        let n = history.len();
+        let residuals: Vec<f64> = (SEASON..n).map(|t| history[t] - history[t - SEASON]).collect();
+        let sigma = Self::root_mean_square(&residuals);
+
+        let (forecasts, std_devs) = (1..=horizon)
+            .map(|h| {
+                let weeks = (h - 1) / SEASON + 1;
+                (history[n + h - 1 - SEASON * weeks], sigma * (weeks as f64).sqrt())
+            })
+            .unzip();
+
+        ModelFit { forecasts, std_devs }
+    }
+
+    /// Additive Holt-Winters with a weekly season, using the smoothing
+    /// parameters with the smallest one-step-ahead error on the history
+    fn holt_winters(history: &[f64], horizon: usize) -> ModelFit {
+        let state = ALPHAS
+            .iter()
+            .flat_map(|&alpha| {
+                BETAS.iter().flat_map(move |&beta| {
+                    GAMMAS
+                        .iter()
+                        .map(move |&gamma| Self::smooth(history, alpha, beta, gamma))
+                })
+            })
+            .min_by(|a, b| a.sigma.total_cmp(&b.sigma))
+            .unwrap_or_else(|| Self::smooth(history, ALPHAS[0], BETAS[0], GAMMAS[0]));
+
+        let n = history.len();
+        let (forecasts, std_devs) = (1..=horizon)
+            .map(|h| {
+                let forecast = state.level + h as f64 * state.trend + state.seasonals[(n + h - 1) % SEASON];
+                (forecast, state.sigma * Self::variance_multiplier(&state, h).sqrt())
+            })
+            .unzip();
+
+        ModelFit { forecasts, std_devs }
+    }
+
+    /// Run Holt-Winters smoothing over the history
+    /// Level, trend and season start from the first two weeks; the one-step
+    /// errors are taken from the second week on
+    fn smooth(history: &[f64], alpha: f64, beta: f64, gamma: f64) -> HoltWintersState {
+        let season = SEASON as f64;
+        let first_week = history[..SEASON].iter().sum::<f64>() / season;
+        let second_week = history[SEASON..2 * SEASON].iter().sum::<f64>() / season;
+
+        // The first week's mean is the level mid-week; move it to the week's last day
+        let mut trend = (second_week - first_week) / season;
+        let mut level = first_week + trend * (season - 1.0) / 2.0;
+        let mut seasonals = [0.0; SEASON];
+        for (day, seasonal) in seasonals.iter_mut().enumerate() {
+            *seasonal = history[day] - (first_week + trend * (day as f64 - (season - 1.0) / 2.0));
+        }
+
+        let mut residuals = Vec::with_capacity(history.len() - SEASON);
+        for (t, &actual) in history.iter().enumerate().skip(SEASON) {
+            let seasonal = seasonals[t % SEASON];
+            residuals.push(actual - (level + trend + seasonal));
+
+            let previous_level = level;
+            level = alpha * (actual - seasonal) + (1.0 - alpha) * (level + trend);
+            trend = beta * (level - previous_level) + (1.0 - beta) * trend;
+            seasonals[t % SEASON] = gamma * (actual - level) + (1.0 - gamma) * seasonal;
+        }
+
+        HoltWintersState {
+            alpha,
+            beta,
+            gamma,
+            level,
+            trend,
+            seasonals,
+            sigma: Self::root_mean_square(&residuals),
+        }
+    }
+
+    /// Forecast error variance `h` days ahead relative to the one-step variance
+    /// Formula for additive Holt-Winters from Hyndman et al., with the
+    /// smoothing parameters in error-correction form
+    fn variance_multiplier(state: &HoltWintersState, h: usize) -> f64 {
+        let alpha = state.alpha;
+        let beta = state.alpha * state.beta;
+        let gamma = (1.0 - state.alpha) * state.gamma;
+        let h_f = h as f64;
+        let m = SEASON as f64;
+        let k = ((h - 1) / SEASON) as f64;
+
+        1.0 + (h_f - 1.0) * (alpha * alpha + alpha * beta * h_f + beta * beta * h_f * (2.0 * h_f - 1.0) / 6.0)
+            + gamma * k * (2.0 * alpha + gamma + beta * m * (k + 1.0))
+    }
+
+    /// Forecast days with prediction intervals `z` standard deviations wide
+    /// Demand cannot go below zero, so neither can forecasts or bounds
+    fn build_forecast(
+        series: DailySeries,
+        model: ForecastModel,
+        fit: &ModelFit,
+        days: &[DateTime<Utc>],
+        z: f64,
+        timezone: Tz,
+    ) -> CoffeeDemandForecast {
+        let forecast_days: Vec<DemandForecastDay> = days
+            .iter()
+            .zip(fit.forecasts.iter().zip(&fit.std_devs))
+            .map(|(&timestamp, (&forecast, &std_dev))| DemandForecastDay {
+                coffee_id: series.coffee_id,
+                coffee_name: series.coffee_name.clone(),
+                date: TimePeriodFilter::format_period_label(timestamp, TimePeriod::Daily, timezone),
+                timestamp,
+                forecast_units: Self::units(forecast),
+                lower_bound: Self::units(forecast - z * std_dev),
+                upper_bound: Self::units(forecast + z * std_dev),
+            })
+            .collect();
+
+        CoffeeDemandForecast {
+            coffee_id: series.coffee_id,
+            coffee_name: series.coffee_name,
+            model,
+            history_units: series.units.iter().sum::<f64>() as i64,
+            forecast_units: forecast_days.iter().map(|day| day.forecast_units).sum(),
+            days: forecast_days,
+        }
+    }
+
+    /// Fit `model` on all but the last `holdout_days` of the series and score
+    /// its forecasts of those days
+    fn backtest_series(series: &DailySeries, model: ForecastModel, holdout_days: usize, z: f64) -> ForecastBacktest {
+        let training_days = series.units.len() - holdout_days;
+        let (training, actual) = series.units.split_at(training_days);
+        let fit = Self::fit(model, training, holdout_days);
+
+        let forecasts: Vec<f64> = fit.forecasts.iter().map(|forecast| forecast.max(0.0)).collect();
+        let errors: Vec<f64> = forecasts.iter().zip(actual).map(|(forecast, actual)| forecast - actual).collect();
+        let actual_units: f64 = actual.iter().sum();
+        let absolute_error: f64 = errors.iter().map(|error| error.abs()).sum();
+        let covered = actual
+            .iter()
+            .zip(fit.forecasts.iter().zip(&fit.std_devs))
+            .filter(|&(&actual, (&forecast, &std_dev))| (actual - forecast).abs() <= z * std_dev)
+            .count();
+        let days = holdout_days as f64;
+
+        ForecastBacktest {
+            coffee_id: series.coffee_id,
+            coffee_name: series.coffee_name.clone(),
+            model,
+            training_days: training_days as i64,
+            holdout_days: holdout_days as i64,
+            actual_units: actual_units as i64,
+            forecast_units: Self::units(forecasts.iter().sum()),
+            mean_absolute_error: Self::round(absolute_error / days),
+            root_mean_squared_error: Self::round(Self::root_mean_square(&errors)),
+            absolute_percentage_error: (actual_units > 0.0)
+                .then(|| Self::round(absolute_error / actual_units * 100.0)),
+            bias: Self::round(errors.iter().sum::<f64>() / days),
+            interval_coverage: Self::round(covered as f64 / days * 100.0),
+        }
+    }
+
+    /// Two-sided standard normal quantile of a prediction interval level in percent
+    fn z_score(confidence_level: u32) -> f64 {
+        match confidence_level {
+            80 => 1.2816,
+            90 => 1.6449,
+            _ => 1.96,
+        }
+    }
+
+    fn root_mean_square(values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        (values.iter().map(|value| value * value).sum::<f64>() / values.len() as f64).sqrt()
+    }
+
+    /// Units rounded to two decimals, never negative
+    fn units(value: f64) -> Decimal {
+        Self::round(value.max(0.0))
+    }
+
+    fn round(value: f64) -> Decimal {
+        Decimal::from_f64(value).unwrap_or_default().round_dp(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    /// Units per weekday of a shop without trend
+    const WEEK: [f64; 7] = [10.0, 12.0, 14.0, 16.0, 18.0, 30.0, 40.0];
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn weekly(weeks: usize) -> Vec<f64> {
+        WEEK.iter().copied().cycle().take(weeks * SEASON).collect()
+    }
+
+    fn series(units: Vec<f64>) -> DailySeries {
+        DailySeries {
+            coffee_id: 1,
+            coffee_name: "Latte".to_string(),
+            units,
+        }
+    }
+
+    #[test]
+    fn test_seasonal_naive_repeats_last_week() {
+        let fit = DemandForecastService::seasonal_naive(&weekly(3), 9);
+
+        assert_eq!(fit.forecasts, vec![10.0, 12.0, 14.0, 16.0, 18.0, 30.0, 40.0, 10.0, 12.0]);
+        assert!(fit.std_devs.iter().all(|std_dev| *std_dev == 0.0));
+    }
+
+    #[test]
+    fn test_holt_winters_follows_trend_and_weekly_season() {
+        let offsets = [-5.0, -3.0, -1.0, 0.0, 1.0, 3.0, 5.0];
+        let demand = |t: usize| 20.0 + 0.5 * t as f64 + offsets[t % SEASON];
+        let history: Vec<f64> = (0..56).map(demand).collect();
+
+        let fit = DemandForecastService::holt_winters(&history, 14);
+
+        for (h, forecast) in fit.forecasts.iter().enumerate() {
+            assert!((forecast - demand(56 + h)).abs() < 1e-6, "day {}: {}", h + 1, forecast);
+        }
+        assert!(fit.std_devs.iter().all(|std_dev| *std_dev < 1e-6));
+    }
+
+    #[test]
+    fn test_prediction_intervals_widen_with_horizon() {
+        let history: Vec<f64> = weekly(8)
+            .into_iter()
+            .enumerate()
+            .map(|(t, units)| units + ((t * 37) % 11) as f64 - 5.0)
+            .collect();
+
+        for model in ForecastModel::ALL {
+            let fit = DemandForecastService::fit(model, &history, 14);
+
+            assert_eq!(fit.forecasts.len(), 14);
+            assert!(fit.std_devs[0] > 0.0, "{:?}", model);
+            assert!(fit.std_devs.windows(2).all(|pair| pair[1] >= pair[0]), "{:?}", model);
+            assert!(fit.std_devs[13] > fit.std_devs[0], "{:?}", model);
+        }
+    }
+
+    #[test]
+    fn test_daily_series_fills_days_without_sales() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let days = DemandForecastService::local_days(start, 3, Tz::UTC);
+        let rows = vec![
+            (days[0], 1, "Latte".to_string(), 4),
+            (days[2], 1, "Latte".to_string(), 2),
+            (days[1], 2, "Mocha".to_string(), 5),
+            (start + Duration::days(5), 1, "Latte".to_string(), 9),
+        ];
+
+        let series = DemandForecastService::daily_series(rows, &days, Tz::UTC);
+
+        assert_eq!(series.len(), 2);
+        assert_eq!((series[0].coffee_id, series[0].units.clone()), (1, vec![4.0, 0.0, 2.0]));
+        assert_eq!((series[1].coffee_id, series[1].units.clone()), (2, vec![0.0, 5.0, 0.0]));
+    }
+
+    #[test]
+    fn test_build_forecast_never_goes_negative() {
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        let today = TimePeriodFilter::start_of_day(Utc.with_ymd_and_hms(2026, 3, 28, 12, 0, 0).unwrap(), paris);
+        // Clocks in Paris go forward on 2026-03-29
+        let days = DemandForecastService::local_days(today, 2, paris);
+        let fit = ModelFit {
+            forecasts: vec![-2.0, 3.456],
+            std_devs: vec![1.0, 1.0],
+        };
+
+        let forecast = DemandForecastService::build_forecast(
+            series(weekly(2)),
+            ForecastModel::HoltWinters,
+            &fit,
+            &days,
+            1.96,
+            paris,
+        );
+
+        assert_eq!(forecast.history_units, 280);
+        assert_eq!(forecast.forecast_units, dec("3.46"));
+        assert_eq!(forecast.days[0].date, "2026-03-28");
+        assert_eq!(
+            (forecast.days[0].forecast_units, forecast.days[0].lower_bound, forecast.days[0].upper_bound),
+            (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO)
+        );
+        assert_eq!(forecast.days[1].date, "2026-03-29");
+        assert_eq!(forecast.days[1].timestamp, Utc.with_ymd_and_hms(2026, 3, 28, 23, 0, 0).unwrap());
+        assert_eq!(
+            (forecast.days[1].forecast_units, forecast.days[1].lower_bound, forecast.days[1].upper_bound),
+            (dec("3.46"), dec("1.50"), dec("5.42"))
+        );
+    }
+
+    #[test]
+    fn test_backtest_scores_held_out_days() {
+        let exact = DemandForecastService::backtest_series(&series(weekly(4)), ForecastModel::SeasonalNaive, 7, 1.96);
+
+        assert_eq!((exact.training_days, exact.holdout_days, exact.actual_units), (21, 7, 140));
+        assert_eq!(exact.mean_absolute_error, Decimal::ZERO);
+        assert_eq!(exact.absolute_percentage_error, Some(Decimal::ZERO));
+        assert_eq!(exact.interval_coverage, dec("100"));
+
+        // The shop was closed for the held-out week
+        let mut closed = weekly(4);
+        closed[21..].fill(0.0);
+        let missed = DemandForecastService::backtest_series(&series(closed), ForecastModel::SeasonalNaive, 7, 1.96);
+
+        assert_eq!(missed.actual_units, 0);
+        assert_eq!(missed.forecast_units, dec("140"));
+        assert_eq!(missed.mean_absolute_error, dec("20"));
+        assert_eq!(missed.bias, dec("20"));
+        assert_eq!(missed.absolute_percentage_error, None);
+        assert_eq!(missed.interval_coverage, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,140 @@
+// Market basket service
+// Business logic for the frequently-bought-together analysis of coffee pairs
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+
+use crate::analytics::{
+    repositories::{CoffeePairRow, MarketBasketRepository},
+    types::{CoffeePair, DateRange},
+};
+use crate::config::MarketBasketConfig;
+
+/// Service for the market basket analysis
+#[derive(Clone)]
+pub struct MarketBasketService {
+    repo: MarketBasketRepository,
+    config: MarketBasketConfig,
+}
+
+impl MarketBasketService {
+    /// Create a new MarketBasketService with the default window and threshold
+    pub fn new(repo: MarketBasketRepository) -> Self {
+        Self {
+            repo,
+            config: MarketBasketConfig::default(),
+        }
+    }
+
+    /// Use a configured window and minimum pair orders
+    pub fn with_config(mut self, config: MarketBasketConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Recompute the coffee pairs over the configured window ending now
+    /// Returns the number of pairs stored in either direction
+    pub async fn refresh_pairs(&self) -> Result<u64, sqlx::Error> {
+        let date_range = Self::pair_window(Utc::now(), self.config.window_days);
+
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        self.repo
+            .rebuild_pairs(date_range.start_date, date_range.end_date, self.config.min_pair_orders)
+            .await
+    }
+
+    /// Stored coffee pairs, strongest association first
+    /// With `coffee_id`, only what is bought together with that coffee
+    pub async fn get_pairs(
+        &self,
+        coffee_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<CoffeePair>, sqlx::Error> {
+        let rows = self.repo.get_pairs(coffee_id, limit).await?;
+
+        Ok(rows.into_iter().map(Self::build_pair).collect())
+    }
+
+    /// The `window_days` before `now`
+    fn pair_window(now: DateTime<Utc>, window_days: i64) -> DateRange {
+        DateRange {
+            start_date: now - Duration::days(window_days),
+            end_date: now,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Report support and confidence in percent
+    fn build_pair(row: CoffeePairRow) -> CoffeePair {
+        let hundred = Decimal::from(100);
+
+        CoffeePair {
+            coffee_id: row.coffee_id,
+            coffee_name: row.coffee_name,
+            paired_coffee_id: row.paired_coffee_id,
+            paired_coffee_name: row.paired_coffee_name,
+            pair_orders: row.pair_orders as i64,
+            total_orders: row.total_orders as i64,
+            support_percentage: (row.support * hundred).round_dp(2),
+            confidence_percentage: (row.confidence * hundred).round_dp(2),
+            lift: row.lift.round_dp(2),
+            window_start: row.window_start,
+            window_end: row.window_end,
+            computed_at: row.computed_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_pair_window_ends_now() {
+        let now = Utc::now();
+
+        let date_range = MarketBasketService::pair_window(now, 90);
+
+        assert_eq!(date_range.end_date, now);
+        assert_eq!((date_range.end_date - date_range.start_date).num_days(), 90);
+        assert!(date_range.validate().is_ok());
+    }
+
+    #[test]
+    fn test_build_pair_reports_percentages() {
+        let computed_at = Utc::now();
+        let row = CoffeePairRow {
+            coffee_id: 1,
+            coffee_name: "Latte".to_string(),
+            paired_coffee_id: 2,
+            paired_coffee_name: "Croissant".to_string(),
+            pair_orders: 2,
+            coffee_orders: 3,
+            paired_orders: 3,
+            total_orders: 4,
+            support: dec("0.500000"),
+            confidence: dec("0.666667"),
+            lift: dec("0.8889"),
+            window_start: computed_at - Duration::days(90),
+            window_end: computed_at,
+            computed_at,
+        };
+
+        let pair = MarketBasketService::build_pair(row);
+
+        assert_eq!(pair.pair_orders, 2);
+        assert_eq!(pair.total_orders, 4);
+        assert_eq!(pair.support_percentage, dec("50.00"));
+        assert_eq!(pair.confidence_percentage, dec("66.67"));
+        assert_eq!(pair.lift, dec("0.89"));
+    }
+}
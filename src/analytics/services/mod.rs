@@ -1,17 +1,27 @@
 // Analytics business logic services
-// Contains sales aggregation, revenue calculation, popular coffees, and rating analysis services
+// Contains sales aggregation, revenue calculation, popular coffees, rating analysis, basket analysis, pricing rule analysis, customer analytics, market basket and demand forecast services
 
 mod sales_aggregation_service;
 mod revenue_calculation_service;
 mod popular_coffees_service;
 mod trend_calculation_service;
 mod rating_analysis_service;
+mod basket_analysis_service;
+mod pricing_rule_analysis_service;
+mod customer_analytics_service;
+mod market_basket_service;
+mod demand_forecast_service;
 
 pub use sales_aggregation_service::SalesAggregationService;
 pub use revenue_calculation_service::RevenueCalculationService;
 pub use popular_coffees_service::PopularCoffeesService;
 pub use trend_calculation_service::TrendCalculationService;
 pub use rating_analysis_service::RatingAnalysisService;
+pub use basket_analysis_service::BasketAnalysisService;
+pub use pricing_rule_analysis_service::PricingRuleAnalysisService;
+pub use customer_analytics_service::CustomerAnalyticsService;
+pub use market_basket_service::MarketBasketService;
+pub use demand_forecast_service::DemandForecastService;
 
 #[cfg(test)]
 mod tests;
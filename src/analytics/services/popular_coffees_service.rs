@@ -45,6 +45,7 @@ impl PopularCoffeesService {
             .get_most_ordered_coffees(
                 date_range.start_date,
                 date_range.end_date,
+                date_range.timezone,
                 validated_limit,
             )
             .await?;
@@ -53,7 +54,7 @@ impl PopularCoffeesService {
         let mut result = Vec::new();
         for (coffee_id, coffee_name, order_count) in most_ordered {
             let average_rating = self.reviews_repo
-                .calculate_average_rating(Some(coffee_id))
+                .calculate_average_rating(Some(coffee_id), false)
                 .await?;
 
             result.push(PopularCoffee {
@@ -61,6 +62,7 @@ impl PopularCoffeesService {
                 coffee_name,
                 order_count,
                 average_rating,
+                weighted_rating: None,
                 trend_percentage: None,
             });
         }
@@ -71,8 +73,9 @@ impl PopularCoffeesService {
         Ok(result)
     }
 
-    /// Get highest rated coffees ranked by average rating
-    /// Returns top N coffees in descending order by rating
+    /// Get highest rated coffees ranked by confidence-weighted rating
+    /// Returns top N coffees in descending order by weighted score, so a single
+    /// 5-star review does not outrank hundreds of slightly lower ones
     /// Requires minimum number of reviews to ensure statistical significance
     pub async fn get_highest_rated(
         &self,
@@ -93,12 +96,13 @@ impl PopularCoffeesService {
         // Convert to PopularCoffee format
         let result: Vec<PopularCoffee> = highest_rated
             .into_iter()
-            .map(|(coffee_id, coffee_name, average_rating, review_count)| {
+            .map(|(coffee_id, coffee_name, average_rating, weighted_rating, review_count)| {
                 PopularCoffee {
                     coffee_id,
                     coffee_name,
                     order_count: review_count,
                     average_rating: Some(average_rating),
+                    weighted_rating,
                     trend_percentage: None,
                 }
             })
@@ -140,7 +144,7 @@ impl PopularCoffeesService {
     fn verify_descending_order_by_rating(coffees: &[PopularCoffee]) {
         for i in 1..coffees.len() {
             if let (Some(prev_rating), Some(curr_rating)) = 
-                (coffees[i - 1].average_rating, coffees[i].average_rating) {
+                (coffees[i - 1].weighted_rating, coffees[i].weighted_rating) {
                 debug_assert!(
                     prev_rating >= curr_rating,
                     "Highest rated coffees should be in descending order by rating"
@@ -178,6 +182,7 @@ mod tests {
                 coffee_name: "Espresso".to_string(),
                 order_count: 100,
                 average_rating: Some(Decimal::from_str("4.5").unwrap()),
+                weighted_rating: None,
                 trend_percentage: None,
             },
             PopularCoffee {
@@ -185,6 +190,7 @@ mod tests {
                 coffee_name: "Latte".to_string(),
                 order_count: 75,
                 average_rating: Some(Decimal::from_str("4.3").unwrap()),
+                weighted_rating: None,
                 trend_percentage: None,
             },
             PopularCoffee {
@@ -192,6 +198,7 @@ mod tests {
                 coffee_name: "Cappuccino".to_string(),
                 order_count: 50,
                 average_rating: Some(Decimal::from_str("4.7").unwrap()),
+                weighted_rating: None,
                 trend_percentage: None,
             },
         ];
@@ -211,6 +218,7 @@ mod tests {
                 coffee_name: "Cappuccino".to_string(),
                 order_count: 50,
                 average_rating: Some(Decimal::from_str("4.9").unwrap()),
+                weighted_rating: None,
                 trend_percentage: None,
             },
             PopularCoffee {
@@ -218,6 +226,7 @@ mod tests {
                 coffee_name: "Espresso".to_string(),
                 order_count: 100,
                 average_rating: Some(Decimal::from_str("4.7").unwrap()),
+                weighted_rating: None,
                 trend_percentage: None,
             },
             PopularCoffee {
@@ -225,6 +234,7 @@ mod tests {
                 coffee_name: "Latte".to_string(),
                 order_count: 75,
                 average_rating: Some(Decimal::from_str("4.5").unwrap()),
+                weighted_rating: None,
                 trend_percentage: None,
             },
         ];
@@ -280,6 +290,7 @@ mod tests {
             coffee_name: "Espresso".to_string(),
             order_count: 100,
             average_rating: Some(Decimal::from_str("4.5").unwrap()),
+            weighted_rating: None,
             trend_percentage: Some(Decimal::from_str("15.5").unwrap()),
         };
 
@@ -0,0 +1,130 @@
+// Pricing rule analysis service
+// Business logic for how often pricing rules apply and what they cost or earn
+
+use rust_decimal::Decimal;
+
+use crate::analytics::{
+    repositories::{OrdersAnalyticsRepository, PricingRuleApplications},
+    services::TrendCalculationService,
+    types::{DateRange, PricingRuleEffectiveness},
+};
+
+/// Service for pricing rule effectiveness analytics
+#[derive(Clone)]
+pub struct PricingRuleAnalysisService {
+    orders_repo: OrdersAnalyticsRepository,
+}
+
+impl PricingRuleAnalysisService {
+    /// Create a new PricingRuleAnalysisService
+    pub fn new(orders_repo: OrdersAnalyticsRepository) -> Self {
+        Self { orders_repo }
+    }
+
+    /// Applications, discount and average basket with and without each pricing
+    /// rule, for completed orders in the date range
+    /// Sorted by total discount given, largest first
+    pub async fn calculate_rule_effectiveness(
+        &self,
+        date_range: DateRange,
+    ) -> Result<Vec<PricingRuleEffectiveness>, sqlx::Error> {
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        let rows = self.orders_repo
+            .get_pricing_rule_applications(date_range.start_date, date_range.end_date)
+            .await?;
+
+        Ok(rows.into_iter().map(Self::build_effectiveness).collect())
+    }
+
+    /// Derive the average baskets with and without a rule from its audit totals
+    fn build_effectiveness(row: PricingRuleApplications) -> PricingRuleEffectiveness {
+        let average_basket_with = Self::average(row.revenue_with, row.orders_affected)
+            .unwrap_or(Decimal::ZERO);
+        let average_basket_without = Self::average(
+            row.revenue - row.revenue_with,
+            row.order_count - row.orders_affected,
+        );
+        let basket_uplift_percentage = average_basket_without
+            .map(|without| TrendCalculationService::percentage_change(average_basket_with, without));
+
+        PricingRuleEffectiveness {
+            rule_id: row.rule_id,
+            rule_type: row.rule_type,
+            description: row.description,
+            times_applied: row.times_applied,
+            orders_affected: row.orders_affected,
+            total_discount: row.total_discount.round_dp(2),
+            revenue: row.revenue_with.round_dp(2),
+            average_basket_with,
+            average_basket_without,
+            basket_uplift_percentage,
+        }
+    }
+
+    /// `total / count` rounded to 2 decimal places (None without orders)
+    fn average(total: Decimal, count: i64) -> Option<Decimal> {
+        if count <= 0 {
+            None
+        } else {
+            Some((total / Decimal::from(count)).round_dp(2))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn applications(orders_affected: i64, revenue_with: &str, order_count: i64, revenue: &str) -> PricingRuleApplications {
+        PricingRuleApplications {
+            rule_id: Uuid::new_v4(),
+            rule_type: Some("time_based".to_string()),
+            description: Some("Happy hour".to_string()),
+            times_applied: orders_affected,
+            orders_affected,
+            total_discount: dec("3.5"),
+            revenue_with: dec(revenue_with),
+            order_count,
+            revenue: dec(revenue),
+        }
+    }
+
+    #[test]
+    fn test_baskets_with_and_without_rule() {
+        // 2 discounted orders averaging 9.00 against 2 others averaging 6.00
+        let report = PricingRuleAnalysisService::build_effectiveness(applications(2, "18.00", 4, "30.00"));
+
+        assert_eq!(report.average_basket_with, dec("9.00"));
+        assert_eq!(report.average_basket_without, Some(dec("6.00")));
+        assert_eq!(report.basket_uplift_percentage, Some(dec("50")));
+        assert_eq!(report.revenue, dec("18.00"));
+        assert_eq!(report.total_discount, dec("3.50"));
+    }
+
+    #[test]
+    fn test_smaller_baskets_with_rule_give_negative_uplift() {
+        let report = PricingRuleAnalysisService::build_effectiveness(applications(3, "12.00", 4, "20.00"));
+
+        assert_eq!(report.average_basket_with, dec("4.00"));
+        assert_eq!(report.average_basket_without, Some(dec("8.00")));
+        assert_eq!(report.basket_uplift_percentage, Some(dec("-50")));
+    }
+
+    #[test]
+    fn test_rule_applied_to_every_order_has_no_comparison() {
+        let report = PricingRuleAnalysisService::build_effectiveness(applications(2, "10.00", 2, "10.00"));
+
+        assert_eq!(report.average_basket_with, dec("5.00"));
+        assert_eq!(report.average_basket_without, None);
+        assert_eq!(report.basket_uplift_percentage, None);
+    }
+}
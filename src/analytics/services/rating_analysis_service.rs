@@ -20,18 +20,20 @@ impl RatingAnalysisService {
     }
 
     /// Calculate average rating with optional coffee filter
-    /// Returns mean rating across all reviews or for a specific coffee
+    /// Returns mean rating across all reviews or for a specific coffee;
+    /// `verified_only` restricts it to reviews from verified purchases
     pub async fn calculate_average_rating(
         &self,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<RatingStatistics, sqlx::Error> {
         let average_rating = self.reviews_repo
-            .calculate_average_rating(coffee_id)
+            .calculate_average_rating(coffee_id, verified_only)
             .await?
             .unwrap_or(Decimal::ZERO);
 
         let total_reviews = self.reviews_repo
-            .count_reviews(coffee_id)
+            .count_reviews(coffee_id, verified_only)
             .await?;
 
         Ok(RatingStatistics {
@@ -47,9 +49,10 @@ impl RatingAnalysisService {
     pub async fn analyze_rating_distribution(
         &self,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<Vec<RatingDistribution>, sqlx::Error> {
         let mut distribution = self.reviews_repo
-            .get_rating_distribution(coffee_id)
+            .get_rating_distribution(coffee_id, verified_only)
             .await?;
 
         // Ensure all rating values 1-5 are present (fill missing with 0)
@@ -76,6 +79,7 @@ impl RatingAnalysisService {
         &self,
         date_range: DateRange,
         coffee_id: Option<i32>,
+        verified_only: bool,
     ) -> Result<Vec<RatingTrend>, sqlx::Error> {
         // Validate date range
         date_range.validate()
@@ -85,7 +89,9 @@ impl RatingAnalysisService {
             .get_review_trends(
                 date_range.start_date,
                 date_range.end_date,
+                date_range.timezone,
                 coffee_id,
+                verified_only,
             )
             .await?;
 
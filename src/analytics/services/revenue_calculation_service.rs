@@ -2,11 +2,13 @@
 // Business logic for calculating revenue statistics and reports
 
 use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::*;
 use crate::analytics::{
-    repositories::OrdersAnalyticsRepository,
+    repositories::{OrdersAnalyticsRepository, RowStream},
     types::{DateRange, TimePeriod, RevenueByPeriod, RevenueByCoffee},
+    utils::TimePeriodFilter,
 };
 
 /// Service for revenue calculation and reporting
@@ -33,7 +35,7 @@ impl RevenueCalculationService {
             .map_err(|e| sqlx::Error::Protocol(e))?;
 
         let revenue = self.orders_repo
-            .calculate_total_revenue(date_range.start_date, date_range.end_date)
+            .calculate_total_revenue(date_range.start_date, date_range.end_date, date_range.timezone)
             .await?;
 
         // Ensure exactly 2 decimal places for monetary values
@@ -56,6 +58,7 @@ impl RevenueCalculationService {
             .aggregate_revenue_by_period(
                 date_range.start_date,
                 date_range.end_date,
+                date_range.timezone,
                 granularity,
             )
             .await?;
@@ -64,7 +67,7 @@ impl RevenueCalculationService {
         let result = revenue_data
             .into_iter()
             .map(|(timestamp, revenue)| RevenueByPeriod {
-                period: timestamp.format("%Y-%m-%d").to_string(),
+                period: TimePeriodFilter::format_period_label(timestamp, granularity, date_range.timezone),
                 revenue: Self::round_to_two_decimals(revenue),
                 timestamp,
             })
@@ -85,7 +88,7 @@ impl RevenueCalculationService {
             .map_err(|e| sqlx::Error::Protocol(e))?;
 
         let mut revenue_by_coffee = self.orders_repo
-            .calculate_revenue_by_coffee(date_range.start_date, date_range.end_date)
+            .calculate_revenue_by_coffee(date_range.start_date, date_range.end_date, date_range.timezone)
             .await?;
 
         // Apply two decimal precision to all revenue values
@@ -96,6 +99,43 @@ impl RevenueCalculationService {
         Ok(revenue_by_coffee)
     }
 
+    /// Stream revenue by period for exports, rounded like `calculate_revenue_by_period`
+    pub fn stream_revenue_by_period(
+        &self,
+        date_range: DateRange,
+        granularity: TimePeriod,
+    ) -> Result<RowStream<RevenueByPeriod>, sqlx::Error> {
+        date_range.validate()
+            .map_err(|e| sqlx::Error::Protocol(e))?;
+
+        let timezone = date_range.timezone;
+        Ok(self.orders_repo
+            .stream_revenue_by_period(date_range.start_date, date_range.end_date, timezone, granularity)
+            .map_ok(move |(timestamp, revenue)| RevenueByPeriod {
+                period: TimePeriodFilter::format_period_label(timestamp, granularity, timezone),
+                revenue: Self::round_to_two_decimals(revenue),
+                timestamp,
+            })
+            .boxed())
+    }
+
+    /// Stream revenue by coffee for exports, rounded like `calculate_revenue_by_coffee`
+    pub fn stream_revenue_by_coffee(
+        &self,
+        date_range: DateRange,
+    ) -> Result<RowStream<RevenueByCoffee>, sqlx::Error> {
+        date_range.validate()
+            .map_err(|e| sqlx::Error::Protocol(e))?;
+
+        Ok(self.orders_repo
+            .stream_revenue_by_coffee(date_range.start_date, date_range.end_date, date_range.timezone)
+            .map_ok(|mut item| {
+                item.revenue = Self::round_to_two_decimals(item.revenue);
+                item
+            })
+            .boxed())
+    }
+
     /// Round decimal to exactly 2 decimal places
     /// Ensures all monetary values have consistent precision
     fn round_to_two_decimals(value: Decimal) -> Decimal {
@@ -1,10 +1,13 @@
 // Sales aggregation service
 // Business logic for calculating sales statistics and trends
 
-use chrono::{DateTime, Duration, Timelike, Utc};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
 use crate::analytics::{
-    repositories::OrdersAnalyticsRepository,
-    types::{SalesStatistics, SalesByPeriod, SalesTrend, TimePeriod, DateRange},
+    repositories::{OrdersAnalyticsRepository, RowStream},
+    types::{SalesStatistics, SalesByPeriod, SalesHeatmap, SalesHeatmapCell, SalesTrend, TimePeriod, DateRange},
+    utils::TimePeriodFilter,
 };
 
 /// Service for sales aggregation and statistics
@@ -29,6 +32,7 @@ impl SalesAggregationService {
             .count_orders_by_period(
                 date_range.start_date,
                 date_range.end_date,
+                date_range.timezone,
                 None, // Uses default completed status
             )
             .await?;
@@ -56,16 +60,36 @@ impl SalesAggregationService {
         let (start, end) = self.calculate_period_boundaries(
             date_range.start_date,
             date_range.end_date,
+            date_range.timezone,
             granularity,
         );
 
         let sales_by_period = self.orders_repo
-            .aggregate_orders_by_period(start, end, granularity)
+            .aggregate_orders_by_period(start, end, date_range.timezone, granularity)
             .await?;
 
         Ok(sales_by_period)
     }
 
+    /// Stream sales by period for exports, with the boundaries of `aggregate_sales_by_period`
+    pub fn stream_sales_by_period(
+        &self,
+        date_range: DateRange,
+        granularity: TimePeriod,
+    ) -> Result<RowStream<SalesByPeriod>, sqlx::Error> {
+        date_range.validate()
+            .map_err(|e| sqlx::Error::Protocol(e))?;
+
+        let (start, end) = self.calculate_period_boundaries(
+            date_range.start_date,
+            date_range.end_date,
+            date_range.timezone,
+            granularity,
+        );
+
+        Ok(self.orders_repo.stream_orders_by_period(start, end, date_range.timezone, granularity))
+    }
+
     /// Calculate sales trends as time-series data
     /// Returns daily sales counts in chronological order
     pub async fn calculate_trends(
@@ -77,7 +101,7 @@ impl SalesAggregationService {
             .map_err(|e| sqlx::Error::Protocol(e))?;
 
         let mut trends = self.orders_repo
-            .get_order_trends(date_range.start_date, date_range.end_date)
+            .get_order_trends(date_range.start_date, date_range.end_date, date_range.timezone)
             .await?;
 
         // Ensure trends are in chronological order (ascending by timestamp)
@@ -86,44 +110,79 @@ impl SalesAggregationService {
         Ok(trends)
     }
 
+    /// Build the hour-of-day by day-of-week heatmap of completed orders
+    /// Hours without orders are included with zero counts, so the matrix is always 7x24
+    pub async fn build_sales_heatmap(
+        &self,
+        date_range: DateRange,
+        coffee_id: Option<i32>,
+    ) -> Result<SalesHeatmap, sqlx::Error> {
+        // Validate date range
+        date_range.validate()
+            .map_err(sqlx::Error::Protocol)?;
+
+        let rows = self.orders_repo
+            .get_sales_heatmap(date_range.start_date, date_range.end_date, date_range.timezone, coffee_id)
+            .await?;
+
+        Ok(SalesHeatmap {
+            coffee_id,
+            timezone: date_range.timezone,
+            days: Self::fill_heatmap(rows),
+        })
+    }
+
+    /// Lay out (day_of_week, hour, ...) rows as seven days (Monday first) of 24 hours
+    fn fill_heatmap(
+        rows: Vec<(i32, i32, i64, Decimal, Option<Decimal>)>,
+    ) -> Vec<Vec<SalesHeatmapCell>> {
+        let mut days: Vec<Vec<SalesHeatmapCell>> = (1..=7)
+            .map(|day_of_week| {
+                (0..24)
+                    .map(|hour| SalesHeatmapCell {
+                        day_of_week,
+                        hour,
+                        order_count: 0,
+                        revenue: Decimal::ZERO,
+                        average_prep_minutes: None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (day_of_week, hour, order_count, revenue, average_prep_minutes) in rows {
+            if let Some(cell) = days
+                .get_mut((day_of_week - 1) as usize)
+                .and_then(|hours| hours.get_mut(hour as usize))
+            {
+                cell.order_count = order_count;
+                cell.revenue = revenue;
+                cell.average_prep_minutes = average_prep_minutes;
+            }
+        }
+
+        days
+    }
+
     /// Calculate period boundaries based on granularity
     /// Ensures proper alignment for daily, weekly, and monthly periods
+    /// Days start at local midnight in `timezone`
     fn calculate_period_boundaries(
         &self,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
+        timezone: Tz,
         granularity: TimePeriod,
     ) -> (DateTime<Utc>, DateTime<Utc>) {
         match granularity {
-            TimePeriod::Daily => {
-                // Align to start of day
-                let aligned_start = start.date_naive().and_hms_opt(0, 0, 0)
-                    .map(|dt| dt.and_utc())
-                    .unwrap_or(start);
-                let aligned_end = end.date_naive().and_hms_opt(23, 59, 59)
-                    .map(|dt| dt.and_utc())
-                    .unwrap_or(end);
-                (aligned_start, aligned_end)
-            }
-            TimePeriod::Weekly => {
-                // Align to start of week (Monday)
-                let aligned_start = start.date_naive().and_hms_opt(0, 0, 0)
-                    .map(|dt| dt.and_utc())
-                    .unwrap_or(start);
-                let aligned_end = end.date_naive().and_hms_opt(23, 59, 59)
-                    .map(|dt| dt.and_utc())
-                    .unwrap_or(end);
-                (aligned_start, aligned_end)
-            }
-            TimePeriod::Monthly => {
-                // Align to start of month
-                let aligned_start = start.date_naive().and_hms_opt(0, 0, 0)
-                    .map(|dt| dt.and_utc())
-                    .unwrap_or(start);
-                let aligned_end = end.date_naive().and_hms_opt(23, 59, 59)
-                    .map(|dt| dt.and_utc())
-                    .unwrap_or(end);
-                (aligned_start, aligned_end)
+            TimePeriod::Daily | TimePeriod::Weekly | TimePeriod::Monthly => {
+                // Align to the start of the first day and the last second of the last day
+                let aligned_start = TimePeriodFilter::start_of_day(start, timezone);
+                let next_day = TimePeriodFilter::start_of_day(
+                    TimePeriodFilter::start_of_day(end, timezone) + Duration::hours(36),
+                    timezone,
+                );
+                (aligned_start, next_day - Duration::seconds(1))
             }
             TimePeriod::Custom => (start, end),
         }
@@ -136,6 +195,7 @@ impl SalesAggregationService {
         DateRange {
             start_date: start,
             end_date: end,
+            timezone: Tz::UTC,
         }
     }
 }
@@ -143,6 +203,7 @@ impl SalesAggregationService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Timelike;
     use sqlx::PgPool;
 
     fn create_test_service() -> SalesAggregationService {
@@ -178,6 +239,7 @@ mod tests {
         let (aligned_start, aligned_end) = service.calculate_period_boundaries(
             start,
             now,
+            Tz::UTC,
             TimePeriod::Daily,
         );
         
@@ -196,6 +258,7 @@ mod tests {
         let (aligned_start, aligned_end) = service.calculate_period_boundaries(
             start,
             now,
+            Tz::UTC,
             TimePeriod::Custom,
         );
         
@@ -204,6 +267,26 @@ mod tests {
         assert_eq!(aligned_end, now);
     }
 
+    #[test]
+    fn test_fill_heatmap_is_seven_by_twenty_four() {
+        let rows = vec![
+            (1, 8, 12, Decimal::new(4550, 2), Some(Decimal::new(45, 1))),
+            (7, 23, 1, Decimal::new(350, 2), None),
+        ];
+
+        let days = SalesAggregationService::fill_heatmap(rows);
+
+        assert_eq!(days.len(), 7);
+        assert!(days.iter().all(|hours| hours.len() == 24));
+        assert_eq!(days[0][8].order_count, 12);
+        assert_eq!(days[0][8].revenue, Decimal::new(4550, 2));
+        assert_eq!(days[0][8].average_prep_minutes, Some(Decimal::new(45, 1)));
+        assert_eq!((days[6][23].day_of_week, days[6][23].hour), (7, 23));
+        assert_eq!(days[6][23].order_count, 1);
+        assert_eq!(days[3][12].order_count, 0);
+        assert_eq!(days[3][12].revenue, Decimal::ZERO);
+    }
+
     // Property 5: Sales grouping by period - Non-overlapping periods, sum equals total
     #[test]
     fn test_sales_grouping_non_overlapping() {
@@ -215,12 +298,14 @@ mod tests {
         let (period1_start, period1_end) = service.calculate_period_boundaries(
             start,
             start + Duration::days(10),
+            Tz::UTC,
             TimePeriod::Daily,
         );
         
         let (period2_start, period2_end) = service.calculate_period_boundaries(
             start + Duration::days(10),
             start + Duration::days(20),
+            Tz::UTC,
             TimePeriod::Daily,
         );
         
@@ -6,6 +6,7 @@ mod tests {
     use super::super::*;
     use crate::analytics::types::*;
     use chrono::{Duration, Utc};
+    use chrono_tz::Tz;
     use rust_decimal::Decimal;
     use std::str::FromStr;
 
@@ -271,6 +272,7 @@ mod tests {
         let date_range = DateRange {
             start_date: Utc::now() - Duration::days(30),
             end_date: Utc::now(),
+            timezone: Tz::UTC,
         };
         
         assert!(date_range.validate().is_ok());
@@ -281,6 +283,7 @@ mod tests {
         let date_range = DateRange {
             start_date: Utc::now(),
             end_date: Utc::now() - Duration::days(30),
+            timezone: Tz::UTC,
         };
         
         assert!(date_range.validate().is_err());
@@ -43,6 +43,7 @@ impl TrendCalculationService {
             .get_most_ordered_coffees(
                 current_period.start_date,
                 current_period.end_date,
+                current_period.timezone,
                 100, // Get more to calculate trends
             )
             .await?;
@@ -52,6 +53,7 @@ impl TrendCalculationService {
             .get_most_ordered_coffees(
                 previous_period.start_date,
                 previous_period.end_date,
+                previous_period.timezone,
                 100,
             )
             .await?;
@@ -77,6 +79,7 @@ impl TrendCalculationService {
                     coffee_name,
                     order_count: current_count,
                     average_rating: None,
+                    weighted_rating: None,
                     trend_percentage: Some(trend_percentage),
                 }
             })
@@ -101,19 +104,23 @@ impl TrendCalculationService {
     /// Handles division by zero when previous period has no orders
     /// Returns 100% for new items (0 previous orders)
     fn calculate_trend_percentage(current: i64, previous: i64) -> Decimal {
-        if previous == 0 {
+        Self::percentage_change(Decimal::from(current), Decimal::from(previous))
+    }
+
+    /// Period-over-period change of any metric, with the same rules as
+    /// `calculate_trend_percentage`: 100% when it grew from zero, 0% when both are zero
+    pub fn percentage_change(current: Decimal, previous: Decimal) -> Decimal {
+        if previous.is_zero() {
             // New item or no previous orders
-            if current > 0 {
+            if current > Decimal::ZERO {
                 // Return 100% growth for new items
                 Decimal::from(100)
             } else {
                 Decimal::ZERO
             }
         } else {
-            let current_dec = Decimal::from(current);
-            let previous_dec = Decimal::from(previous);
-            let difference = current_dec - previous_dec;
-            let percentage = (difference / previous_dec) * Decimal::from(100);
+            let difference = current - previous;
+            let percentage = (difference / previous) * Decimal::from(100);
             
             // Round to 2 decimal places
             percentage.round_dp(2)
@@ -249,6 +256,7 @@ mod tests {
                 coffee_name: "Coffee A".to_string(),
                 order_count: 100,
                 average_rating: None,
+                weighted_rating: None,
                 trend_percentage: Some(Decimal::from(25)),
             },
             PopularCoffee {
@@ -256,6 +264,7 @@ mod tests {
                 coffee_name: "Coffee B".to_string(),
                 order_count: 150,
                 average_rating: None,
+                weighted_rating: None,
                 trend_percentage: Some(Decimal::from(75)),
             },
             PopularCoffee {
@@ -263,6 +272,7 @@ mod tests {
                 coffee_name: "Coffee C".to_string(),
                 order_count: 125,
                 average_rating: None,
+                weighted_rating: None,
                 trend_percentage: Some(Decimal::from(50)),
             },
         ];
@@ -5,6 +5,7 @@
 mod tests {
     use crate::analytics::types::*;
     use chrono::Utc;
+    use chrono_tz::Tz;
     use rust_decimal::Decimal;
     use std::str::FromStr;
 
@@ -17,6 +18,7 @@ mod tests {
         let valid_range = DateRange {
             start_date: Utc::now() - chrono::Duration::days(7),
             end_date: Utc::now(),
+            timezone: Tz::UTC,
         };
         assert!(valid_range.validate().is_ok());
     }
@@ -26,6 +28,7 @@ mod tests {
         let invalid_range = DateRange {
             start_date: Utc::now(),
             end_date: Utc::now() - chrono::Duration::days(7),
+            timezone: Tz::UTC,
         };
         assert!(invalid_range.validate().is_err());
         assert_eq!(
@@ -40,6 +43,7 @@ mod tests {
         let equal_range = DateRange {
             start_date: now,
             end_date: now,
+            timezone: Tz::UTC,
         };
         assert!(equal_range.validate().is_err());
     }
@@ -176,6 +180,7 @@ mod tests {
             coffee_name: "Espresso".to_string(),
             order_count: 100,
             average_rating: Some(Decimal::from_str("4.5").unwrap()),
+            weighted_rating: None,
             trend_percentage: Some(Decimal::from_str("15.5").unwrap()),
         };
         
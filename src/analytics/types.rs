@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use uuid::Uuid;
 
 /// Time period granularity for analytics aggregation
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
@@ -18,6 +20,14 @@ pub enum TimePeriod {
 pub struct DateRange {
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
+    /// IANA timezone whose days, weeks and months the range is bucketed by
+    #[serde(default = "default_timezone")]
+    #[schema(value_type = String, example = "America/New_York")]
+    pub timezone: Tz,
+}
+
+fn default_timezone() -> Tz {
+    Tz::UTC
 }
 
 impl DateRange {
@@ -56,6 +66,346 @@ pub struct SalesTrend {
     pub value: i64,
 }
 
+/// Completed orders placed in one hour of one weekday
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SalesHeatmapCell {
+    /// ISO weekday: 1 = Monday ... 7 = Sunday
+    pub day_of_week: i32,
+    /// Hour of the day (0-23) in the report timezone
+    pub hour: i32,
+    pub order_count: i64,
+    pub revenue: Decimal,
+    /// Mean estimated preparation time of the orders, in minutes
+    pub average_prep_minutes: Option<Decimal>,
+}
+
+/// Hour-of-day by day-of-week sales matrix
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SalesHeatmap {
+    /// Only orders containing this coffee, with revenue from its items
+    pub coffee_id: Option<i32>,
+    #[schema(value_type = String, example = "America/New_York")]
+    pub timezone: Tz,
+    /// Seven rows (Monday first) of 24 hourly cells
+    pub days: Vec<Vec<SalesHeatmapCell>>,
+}
+
+/// One basket metric (e.g. average order value) for a period
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BasketTrend {
+    pub period: String,
+    pub timestamp: DateTime<Utc>,
+    /// Completed orders in the period
+    pub order_count: i64,
+    pub value: Decimal,
+    /// Change of `value` against the previous period, in percent
+    /// None for the first period or when the previous period had no orders
+    pub change_percentage: Option<Decimal>,
+}
+
+/// Orders of one size (units per order) in a period
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSizeBucket {
+    pub period: String,
+    pub timestamp: DateTime<Utc>,
+    /// Units in the order: "1" to "4", or "5+"
+    pub size: String,
+    pub order_count: i64,
+    /// Share of the period's orders, in percent
+    pub share: Decimal,
+    /// Change of `order_count` against the same size in the previous period, in percent
+    pub change_percentage: Option<Decimal>,
+}
+
+/// How one pricing rule performed over a date range
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingRuleEffectiveness {
+    pub rule_id: Uuid,
+    /// None when the rule has since been deleted
+    pub rule_type: Option<String>,
+    /// Description of the rule's most recent application
+    pub description: Option<String>,
+    pub times_applied: i64,
+    /// Completed orders the rule was applied to
+    pub orders_affected: i64,
+    pub total_discount: Decimal,
+    /// Charged total of the affected orders
+    pub revenue: Decimal,
+    pub average_basket_with: Decimal,
+    /// Average charged total of the other completed orders in the range
+    /// None when the rule was applied to every order
+    pub average_basket_without: Option<Decimal>,
+    /// Change of the average basket with the rule against without it, in percent
+    pub basket_uplift_percentage: Option<Decimal>,
+}
+
+/// Customers of one signup cohort active in one month after signing up
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortRetentionCell {
+    /// Signup month, e.g. "2026-01"
+    pub cohort: String,
+    pub cohort_size: i64,
+    /// Months since the signup month (0 = the signup month itself)
+    pub month: i32,
+    /// Cohort customers with a completed order in that month
+    pub active_customers: i64,
+    /// Share of the cohort that was active, in percent
+    pub retention_rate: Decimal,
+}
+
+/// Monthly signup cohort with its retention by month
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerCohort {
+    pub cohort: String,
+    pub timestamp: DateTime<Utc>,
+    pub cohort_size: i64,
+    /// One cell per month from the signup month up to the current month
+    pub months: Vec<CohortRetentionCell>,
+}
+
+/// Lifetime value and order frequency of one customer
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerLifetimeValue {
+    pub customer_id: i32,
+    pub signed_up_at: DateTime<Utc>,
+    pub first_order_at: DateTime<Utc>,
+    pub last_order_at: DateTime<Utc>,
+    pub order_count: i64,
+    /// Charged total of all completed orders
+    pub lifetime_value: Decimal,
+    pub average_order_value: Decimal,
+    /// Orders per 30 days since the first order (at least one month)
+    pub orders_per_month: Decimal,
+    /// None for customers with a single order
+    pub average_days_between_orders: Option<Decimal>,
+}
+
+/// RFM (recency, frequency, monetary) segment of a customer
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RfmSegment {
+    Champions,
+    LoyalCustomers,
+    Promising,
+    NeedsAttention,
+    CantLoseThem,
+    AtRisk,
+    Hibernating,
+}
+
+impl RfmSegment {
+    pub const ALL: [RfmSegment; 7] = [
+        RfmSegment::Champions,
+        RfmSegment::LoyalCustomers,
+        RfmSegment::Promising,
+        RfmSegment::NeedsAttention,
+        RfmSegment::CantLoseThem,
+        RfmSegment::AtRisk,
+        RfmSegment::Hibernating,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RfmSegment::Champions => "champions",
+            RfmSegment::LoyalCustomers => "loyal_customers",
+            RfmSegment::Promising => "promising",
+            RfmSegment::NeedsAttention => "needs_attention",
+            RfmSegment::CantLoseThem => "cant_lose_them",
+            RfmSegment::AtRisk => "at_risk",
+            RfmSegment::Hibernating => "hibernating",
+        }
+    }
+
+    /// Parse a `segment` query parameter
+    pub fn parse(value: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|segment| segment.as_str() == value.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|segment| segment.as_str()).collect();
+                format!("Invalid segment: {}. Must be one of: {}", value, names.join(", "))
+            })
+    }
+
+    /// Segment of a recency score and the mean of the frequency and monetary scores (1-5 each)
+    pub fn from_scores(recency: i32, frequency_monetary: i32) -> Self {
+        match (recency, frequency_monetary) {
+            (r, fm) if r >= 4 && fm >= 4 => RfmSegment::Champions,
+            (r, fm) if r >= 3 && fm >= 3 => RfmSegment::LoyalCustomers,
+            (r, _) if r >= 4 => RfmSegment::Promising,
+            (3, _) => RfmSegment::NeedsAttention,
+            (_, fm) if fm >= 4 => RfmSegment::CantLoseThem,
+            (_, 3) => RfmSegment::AtRisk,
+            _ => RfmSegment::Hibernating,
+        }
+    }
+
+    /// `(recency, frequency_monetary, segment)` rows of every score pair, for a
+    /// SQL `VALUES` list that segments customers in the database like `from_scores`
+    pub fn score_table() -> String {
+        (1..=5)
+            .flat_map(|recency| {
+                (1..=5).map(move |fm| format!("({}, {}, '{}')", recency, fm, Self::from_scores(recency, fm).as_str()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// RFM scores and segment of one customer
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRfm {
+    pub customer_id: i32,
+    /// Days since the last completed order
+    pub recency_days: i64,
+    /// Completed orders
+    pub frequency: i64,
+    /// Charged total of completed orders
+    pub monetary: Decimal,
+    /// Scores from 1 to 5 by quintile among all customers (5 = most recent,
+    /// most frequent, highest spending)
+    pub recency_score: i32,
+    pub frequency_score: i32,
+    pub monetary_score: i32,
+    pub segment: RfmSegment,
+}
+
+/// Customers and their averages in one RFM segment
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RfmSegmentSummary {
+    pub segment: RfmSegment,
+    pub customer_count: i64,
+    /// Share of all customers with orders, in percent
+    pub share: Decimal,
+    pub average_recency_days: Decimal,
+    pub average_frequency: Decimal,
+    pub average_monetary: Decimal,
+}
+
+/// How often one coffee is bought together with another, from the last
+/// market basket refresh
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CoffeePair {
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    pub paired_coffee_id: i32,
+    pub paired_coffee_name: String,
+    /// Completed orders containing both coffees
+    pub pair_orders: i64,
+    /// Completed orders in the window, with either coffee or neither
+    pub total_orders: i64,
+    /// Share of all orders containing both coffees, in percent
+    pub support_percentage: Decimal,
+    /// Share of the coffee's orders that also contained the paired coffee, in percent
+    pub confidence_percentage: Decimal,
+    /// How much more often the pair is bought together than by chance (1 = independent)
+    pub lift: Decimal,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Model used to forecast daily coffee demand
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastModel {
+    /// Each day repeats the same weekday of the last week
+    SeasonalNaive,
+    /// Additive Holt-Winters smoothing of level, trend and weekly season
+    HoltWinters,
+}
+
+impl ForecastModel {
+    pub const ALL: [ForecastModel; 2] = [ForecastModel::SeasonalNaive, ForecastModel::HoltWinters];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ForecastModel::SeasonalNaive => "seasonal_naive",
+            ForecastModel::HoltWinters => "holt_winters",
+        }
+    }
+
+    /// Parse a `model` query parameter
+    pub fn parse(value: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|model| model.as_str() == value.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|model| model.as_str()).collect();
+                format!("Invalid model: {}. Must be one of: {}", value, names.join(", "))
+            })
+    }
+}
+
+/// Forecast units of one coffee on one local day
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandForecastDay {
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    /// Local date, e.g. "2026-04-02"
+    pub date: String,
+    pub timestamp: DateTime<Utc>,
+    pub forecast_units: Decimal,
+    /// Bounds of the prediction interval at the requested confidence level
+    pub lower_bound: Decimal,
+    pub upper_bound: Decimal,
+}
+
+/// Daily demand forecast of one coffee
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CoffeeDemandForecast {
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    pub model: ForecastModel,
+    /// Units sold over the history the model was fitted on
+    pub history_units: i64,
+    /// Forecast units over the whole horizon
+    pub forecast_units: Decimal,
+    /// One forecast per day, starting today
+    pub days: Vec<DemandForecastDay>,
+}
+
+/// Forecast error of one model on the held-out last days of a coffee's history
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastBacktest {
+    pub coffee_id: i32,
+    pub coffee_name: String,
+    pub model: ForecastModel,
+    /// Days the model was fitted on
+    pub training_days: i64,
+    /// Days held out and forecast
+    pub holdout_days: i64,
+    pub actual_units: i64,
+    pub forecast_units: Decimal,
+    /// Mean absolute error in units per day
+    pub mean_absolute_error: Decimal,
+    /// Root mean squared error in units per day
+    pub root_mean_squared_error: Decimal,
+    /// Total absolute error as a share of the actual units (WAPE), in percent
+    /// None when nothing sold in the held-out days
+    pub absolute_percentage_error: Option<Decimal>,
+    /// Mean of forecast minus actual units per day (positive = over-forecast)
+    pub bias: Decimal,
+    /// Share of held-out days whose actual units fell inside the prediction
+    /// interval, in percent
+    pub interval_coverage: Decimal,
+}
+
 /// Popular coffee item with order statistics
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -63,7 +413,10 @@ pub struct PopularCoffee {
     pub coffee_id: i32,
     pub coffee_name: String,
     pub order_count: i64,
+    /// Raw mean of published ratings
     pub average_rating: Option<Decimal>,
+    /// Confidence-weighted score used to rank highest rated coffees
+    pub weighted_rating: Option<Decimal>,
     pub trend_percentage: Option<Decimal>,
 }
 
@@ -233,12 +586,14 @@ mod tests {
         let valid_range = DateRange {
             start_date: Utc::now() - chrono::Duration::days(7),
             end_date: Utc::now(),
+            timezone: Tz::UTC,
         };
         assert!(valid_range.validate().is_ok());
 
         let invalid_range = DateRange {
             start_date: Utc::now(),
             end_date: Utc::now() - chrono::Duration::days(7),
+            timezone: Tz::UTC,
         };
         assert!(invalid_range.validate().is_err());
     }
@@ -250,6 +605,35 @@ mod tests {
         assert!(!OrderStatus::Cancelled.is_completed());
     }
 
+    #[test]
+    fn test_rfm_segment_from_scores() {
+        assert_eq!(RfmSegment::from_scores(5, 5), RfmSegment::Champions);
+        assert_eq!(RfmSegment::from_scores(4, 3), RfmSegment::LoyalCustomers);
+        assert_eq!(RfmSegment::from_scores(3, 4), RfmSegment::LoyalCustomers);
+        assert_eq!(RfmSegment::from_scores(5, 1), RfmSegment::Promising);
+        assert_eq!(RfmSegment::from_scores(3, 2), RfmSegment::NeedsAttention);
+        assert_eq!(RfmSegment::from_scores(1, 5), RfmSegment::CantLoseThem);
+        assert_eq!(RfmSegment::from_scores(2, 3), RfmSegment::AtRisk);
+        assert_eq!(RfmSegment::from_scores(1, 1), RfmSegment::Hibernating);
+    }
+
+    #[test]
+    fn test_rfm_score_table_covers_every_score_pair() {
+        let table = RfmSegment::score_table();
+
+        assert_eq!(table.matches('(').count(), 25);
+        assert!(table.starts_with("(1, 1, 'hibernating'), (1, 2, 'hibernating')"));
+        assert!(table.contains("(1, 5, 'cant_lose_them')"));
+        assert!(table.ends_with("(5, 5, 'champions')"));
+    }
+
+    #[test]
+    fn test_rfm_segment_parse() {
+        assert_eq!(RfmSegment::parse("at_risk"), Ok(RfmSegment::AtRisk));
+        assert_eq!(RfmSegment::parse("Champions"), Ok(RfmSegment::Champions));
+        assert!(RfmSegment::parse("whales").unwrap_err().contains("cant_lose_them"));
+    }
+
     #[test]
     fn test_api_response_success() {
         let metadata = ResponseMetadata::new(serde_json::json!({}));
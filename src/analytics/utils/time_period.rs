@@ -1,15 +1,20 @@
-use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use crate::analytics::types::{DateRange, TimePeriod};
 
 /// Utility for handling time period filtering in analytics queries
+///
+/// Days, weeks and months follow the wall clock of the given timezone, so an
+/// evening sale in UTC-5 stays on its local day.
 pub struct TimePeriodFilter;
 
 impl TimePeriodFilter {
     /// Parse and validate a date range, applying defaults if not specified
-    /// Returns a validated DateRange with UTC timestamps
+    /// Returns a validated DateRange bucketed in `timezone`
     pub fn parse_date_range(
         start_date: Option<DateTime<Utc>>,
         end_date: Option<DateTime<Utc>>,
+        timezone: Tz,
     ) -> Result<DateRange, String> {
         let end = end_date.unwrap_or_else(Utc::now);
         let start = start_date.unwrap_or_else(|| end - Duration::days(30));
@@ -17,26 +22,38 @@ impl TimePeriodFilter {
         let range = DateRange {
             start_date: start,
             end_date: end,
+            timezone,
         };
 
         range.validate()?;
         Ok(range)
     }
 
+    /// Resolve the `tz` query override (an IANA name), falling back to the shop timezone
+    pub fn resolve_timezone(tz: Option<&str>, default: Tz) -> Result<Tz, String> {
+        match tz {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| format!("Invalid timezone: {}. Must be an IANA name such as America/New_York", name)),
+            None => Ok(default),
+        }
+    }
+
     /// Calculate period boundaries for a given granularity
     /// Returns a vector of (period_start, period_end) tuples
     pub fn calculate_period_boundaries(
         date_range: &DateRange,
         period: TimePeriod,
     ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let timezone = date_range.timezone;
         let mut boundaries = Vec::new();
         let mut current = date_range.start_date;
 
         while current < date_range.end_date {
             let next = match period {
-                TimePeriod::Daily => Self::next_day(current),
-                TimePeriod::Weekly => Self::next_week(current),
-                TimePeriod::Monthly => Self::next_month(current),
+                TimePeriod::Daily => Self::next_day(current, timezone),
+                TimePeriod::Weekly => Self::next_week(current, timezone),
+                TimePeriod::Monthly => Self::next_month(current, timezone),
                 TimePeriod::Custom => date_range.end_date,
             };
 
@@ -58,55 +75,92 @@ impl TimePeriodFilter {
         boundaries
     }
 
-    /// Get the start of the day in UTC
-    pub fn start_of_day(dt: DateTime<Utc>) -> DateTime<Utc> {
-        Utc.with_ymd_and_hms(dt.year(), dt.month(), dt.day(), 0, 0, 0)
-            .single()
-            .unwrap_or(dt)
+    /// Instant of a wall-clock time in `timezone`
+    /// A time skipped by a DST change resolves to the first valid instant after it
+    fn local_to_utc(local: NaiveDateTime, timezone: Tz) -> DateTime<Utc> {
+        (0..=3)
+            .find_map(|hours| {
+                timezone
+                    .from_local_datetime(&(local + Duration::hours(hours)))
+                    .earliest()
+            })
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| local.and_utc())
+    }
+
+    /// Instant of local midnight at the start of `date`
+    fn start_of_date(date: NaiveDate, timezone: Tz) -> DateTime<Utc> {
+        Self::local_to_utc(date.and_hms_opt(0, 0, 0).unwrap_or_default(), timezone)
+    }
+
+    /// Get the start of the local day
+    pub fn start_of_day(dt: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        Self::start_of_date(dt.with_timezone(&timezone).date_naive(), timezone)
+    }
+
+    /// Get the start of the local week (Monday)
+    pub fn start_of_week(dt: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        let date = dt.with_timezone(&timezone).date_naive();
+        let days_from_monday = date.weekday().num_days_from_monday();
+        Self::start_of_date(date - Duration::days(days_from_monday as i64), timezone)
     }
 
-    /// Get the start of the week (Monday) in UTC
-    pub fn start_of_week(dt: DateTime<Utc>) -> DateTime<Utc> {
-        let days_from_monday = dt.weekday().num_days_from_monday();
-        let start = dt - Duration::days(days_from_monday as i64);
-        Self::start_of_day(start)
+    /// Get the start of the local month
+    pub fn start_of_month(dt: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        let date = dt.with_timezone(&timezone).date_naive();
+        Self::start_of_date(date.with_day(1).unwrap_or(date), timezone)
     }
 
-    /// Get the start of the month in UTC
-    pub fn start_of_month(dt: DateTime<Utc>) -> DateTime<Utc> {
-        Utc.with_ymd_and_hms(dt.year(), dt.month(), 1, 0, 0, 0)
-            .single()
-            .unwrap_or(dt)
+    /// Same wall-clock time `days` local days later (23 or 25 hours across a DST change)
+    fn add_local_days(dt: DateTime<Utc>, days: i64, timezone: Tz) -> DateTime<Utc> {
+        Self::local_to_utc(dt.with_timezone(&timezone).naive_local() + Duration::days(days), timezone)
     }
 
     /// Get the next day
-    fn next_day(dt: DateTime<Utc>) -> DateTime<Utc> {
-        dt + Duration::days(1)
+    fn next_day(dt: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        Self::add_local_days(dt, 1, timezone)
     }
 
     /// Get the next week
-    fn next_week(dt: DateTime<Utc>) -> DateTime<Utc> {
-        dt + Duration::weeks(1)
+    fn next_week(dt: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        Self::add_local_days(dt, 7, timezone)
     }
 
     /// Get the next month
-    fn next_month(dt: DateTime<Utc>) -> DateTime<Utc> {
-        let year = dt.year();
-        let month = dt.month();
-        
-        if month == 12 {
-            Utc.with_ymd_and_hms(year + 1, 1, dt.day(), dt.hour(), dt.minute(), dt.second())
-                .single()
-                .unwrap_or_else(|| dt + Duration::days(31))
+    fn next_month(dt: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        let local = dt.with_timezone(&timezone).naive_local();
+        let (year, month) = if local.month() == 12 {
+            (local.year() + 1, 1)
         } else {
-            Utc.with_ymd_and_hms(year, month + 1, dt.day(), dt.hour(), dt.minute(), dt.second())
-                .single()
-                .unwrap_or_else(|| dt + Duration::days(30))
+            (local.year(), local.month() + 1)
+        };
+
+        match local.with_day(1).and_then(|d| d.with_year(year)).and_then(|d| d.with_month(month)) {
+            Some(first) => {
+                // Clamp to the last day of a shorter month
+                let day = (1..=local.day())
+                    .rev()
+                    .find(|day| first.with_day(*day).is_some())
+                    .unwrap_or(1);
+                Self::local_to_utc(first.with_day(day).unwrap_or(first), timezone)
+            }
+            None => dt + Duration::days(30),
         }
     }
 
-    /// Format a period label for display
-    pub fn format_period_label(start: DateTime<Utc>, period: TimePeriod) -> String {
+    /// Start of the bucket after the one starting at `start`
+    /// Custom ranges are bucketed by day, like the reports' SQL `DATE_TRUNC`
+    pub fn next_period(start: DateTime<Utc>, period: TimePeriod, timezone: Tz) -> DateTime<Utc> {
+        match period {
+            TimePeriod::Daily | TimePeriod::Custom => Self::next_day(start, timezone),
+            TimePeriod::Weekly => Self::next_week(start, timezone),
+            TimePeriod::Monthly => Self::next_month(start, timezone),
+        }
+    }
+
+    /// Format a period label for display, using the local date in `timezone`
+    pub fn format_period_label(start: DateTime<Utc>, period: TimePeriod, timezone: Tz) -> String {
+        let start = start.with_timezone(&timezone);
         match period {
             TimePeriod::Daily => start.format("%Y-%m-%d").to_string(),
             TimePeriod::Weekly => format!("Week of {}", start.format("%Y-%m-%d")),
@@ -139,11 +193,11 @@ impl TimePeriodFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::TimeZone;
+    use chrono::{TimeZone, Timelike};
 
     #[test]
     fn test_parse_date_range_with_defaults() {
-        let result = TimePeriodFilter::parse_date_range(None, None);
+        let result = TimePeriodFilter::parse_date_range(None, None, Tz::UTC);
         assert!(result.is_ok());
         
         let range = result.unwrap();
@@ -156,7 +210,7 @@ mod tests {
         let end = Utc::now();
         let start = end + Duration::days(1);
         
-        let result = TimePeriodFilter::parse_date_range(Some(start), Some(end));
+        let result = TimePeriodFilter::parse_date_range(Some(start), Some(end), Tz::UTC);
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("start_date must be before end_date"));
     }
@@ -166,7 +220,7 @@ mod tests {
         let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
         let end = Utc.with_ymd_and_hms(2024, 1, 31, 23, 59, 59).unwrap();
         
-        let result = TimePeriodFilter::parse_date_range(Some(start), Some(end));
+        let result = TimePeriodFilter::parse_date_range(Some(start), Some(end), Tz::UTC);
         assert!(result.is_ok());
         
         let range = result.unwrap();
@@ -178,7 +232,7 @@ mod tests {
     fn test_calculate_period_boundaries_daily() {
         let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
         let end = Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap();
-        let range = DateRange { start_date: start, end_date: end };
+        let range = DateRange { start_date: start, end_date: end, timezone: Tz::UTC };
         
         let boundaries = TimePeriodFilter::calculate_period_boundaries(&range, TimePeriod::Daily);
         assert_eq!(boundaries.len(), 3);
@@ -191,7 +245,7 @@ mod tests {
     fn test_calculate_period_boundaries_weekly() {
         let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
         let end = Utc.with_ymd_and_hms(2024, 1, 22, 0, 0, 0).unwrap();
-        let range = DateRange { start_date: start, end_date: end };
+        let range = DateRange { start_date: start, end_date: end, timezone: Tz::UTC };
         
         let boundaries = TimePeriodFilter::calculate_period_boundaries(&range, TimePeriod::Weekly);
         assert_eq!(boundaries.len(), 3);
@@ -204,7 +258,7 @@ mod tests {
     fn test_calculate_period_boundaries_monthly() {
         let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
         let end = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
-        let range = DateRange { start_date: start, end_date: end };
+        let range = DateRange { start_date: start, end_date: end, timezone: Tz::UTC };
         
         let boundaries = TimePeriodFilter::calculate_period_boundaries(&range, TimePeriod::Monthly);
         assert_eq!(boundaries.len(), 3);
@@ -214,7 +268,7 @@ mod tests {
     fn test_calculate_period_boundaries_custom() {
         let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
         let end = Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap();
-        let range = DateRange { start_date: start, end_date: end };
+        let range = DateRange { start_date: start, end_date: end, timezone: Tz::UTC };
         
         let boundaries = TimePeriodFilter::calculate_period_boundaries(&range, TimePeriod::Custom);
         assert_eq!(boundaries.len(), 1);
@@ -225,7 +279,7 @@ mod tests {
     #[test]
     fn test_start_of_day() {
         let dt = Utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 45).unwrap();
-        let start = TimePeriodFilter::start_of_day(dt);
+        let start = TimePeriodFilter::start_of_day(dt, Tz::UTC);
         
         assert_eq!(start.hour(), 0);
         assert_eq!(start.minute(), 0);
@@ -237,14 +291,14 @@ mod tests {
     fn test_start_of_week() {
         // January 15, 2024 is a Monday
         let monday = Utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 45).unwrap();
-        let start = TimePeriodFilter::start_of_week(monday);
+        let start = TimePeriodFilter::start_of_week(monday, Tz::UTC);
         
         assert_eq!(start.day(), 15);
         assert_eq!(start.hour(), 0);
         
         // January 17, 2024 is a Wednesday
         let wednesday = Utc.with_ymd_and_hms(2024, 1, 17, 14, 30, 45).unwrap();
-        let start = TimePeriodFilter::start_of_week(wednesday);
+        let start = TimePeriodFilter::start_of_week(wednesday, Tz::UTC);
         
         assert_eq!(start.day(), 15); // Should be Monday
     }
@@ -252,7 +306,7 @@ mod tests {
     #[test]
     fn test_start_of_month() {
         let dt = Utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 45).unwrap();
-        let start = TimePeriodFilter::start_of_month(dt);
+        let start = TimePeriodFilter::start_of_month(dt, Tz::UTC);
         
         assert_eq!(start.day(), 1);
         assert_eq!(start.hour(), 0);
@@ -260,24 +314,45 @@ mod tests {
         assert_eq!(start.second(), 0);
     }
 
+    #[test]
+    fn test_next_period() {
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        // Local midnight on 2024-03-31 (the day clocks go forward) and 2024-01-31
+        let start = Utc.with_ymd_and_hms(2024, 3, 30, 23, 0, 0).unwrap();
+        let end_of_january = Utc.with_ymd_and_hms(2024, 1, 30, 23, 0, 0).unwrap();
+
+        assert_eq!(
+            TimePeriodFilter::next_period(start, TimePeriod::Daily, paris),
+            Utc.with_ymd_and_hms(2024, 3, 31, 22, 0, 0).unwrap()
+        );
+        assert_eq!(
+            TimePeriodFilter::next_period(start, TimePeriod::Weekly, paris),
+            Utc.with_ymd_and_hms(2024, 4, 6, 22, 0, 0).unwrap()
+        );
+        assert_eq!(
+            TimePeriodFilter::next_period(end_of_january, TimePeriod::Monthly, paris),
+            Utc.with_ymd_and_hms(2024, 2, 28, 23, 0, 0).unwrap()
+        );
+    }
+
     #[test]
     fn test_format_period_label_daily() {
         let dt = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
-        let label = TimePeriodFilter::format_period_label(dt, TimePeriod::Daily);
+        let label = TimePeriodFilter::format_period_label(dt, TimePeriod::Daily, Tz::UTC);
         assert_eq!(label, "2024-01-15");
     }
 
     #[test]
     fn test_format_period_label_weekly() {
         let dt = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
-        let label = TimePeriodFilter::format_period_label(dt, TimePeriod::Weekly);
+        let label = TimePeriodFilter::format_period_label(dt, TimePeriod::Weekly, Tz::UTC);
         assert_eq!(label, "Week of 2024-01-15");
     }
 
     #[test]
     fn test_format_period_label_monthly() {
         let dt = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
-        let label = TimePeriodFilter::format_period_label(dt, TimePeriod::Monthly);
+        let label = TimePeriodFilter::format_period_label(dt, TimePeriod::Monthly, Tz::UTC);
         assert_eq!(label, "2024-01");
     }
 
@@ -303,7 +378,7 @@ mod tests {
     fn test_leap_year_handling() {
         // February 29, 2024 (leap year)
         let leap_day = Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap();
-        let next = TimePeriodFilter::next_month(leap_day);
+        let next = TimePeriodFilter::next_month(leap_day, Tz::UTC);
         
         // Should handle leap year correctly
         assert_eq!(next.month(), 3);
@@ -312,7 +387,7 @@ mod tests {
     #[test]
     fn test_year_boundary() {
         let dec = Utc.with_ymd_and_hms(2024, 12, 15, 0, 0, 0).unwrap();
-        let next = TimePeriodFilter::next_month(dec);
+        let next = TimePeriodFilter::next_month(dec, Tz::UTC);
         
         assert_eq!(next.year(), 2025);
         assert_eq!(next.month(), 1);
@@ -324,4 +399,50 @@ mod tests {
         let result = TimePeriodFilter::ensure_utc(dt);
         assert_eq!(dt, result);
     }
+
+    #[test]
+    fn test_start_of_day_in_shop_timezone() {
+        // 21:30 in New York (UTC-5) is already the next day in UTC
+        let evening = Utc.with_ymd_and_hms(2024, 1, 16, 2, 30, 0).unwrap();
+        let start = TimePeriodFilter::start_of_day(evening, Tz::America__New_York);
+
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 15, 5, 0, 0).unwrap());
+        assert_eq!(
+            TimePeriodFilter::format_period_label(start, TimePeriod::Daily, Tz::America__New_York),
+            "2024-01-15"
+        );
+    }
+
+    #[test]
+    fn test_daily_boundaries_follow_dst_change() {
+        // Clocks in New York go forward on 2024-03-10, so that local day is 23 hours long
+        let timezone = Tz::America__New_York;
+        let start = TimePeriodFilter::start_of_day(Utc.with_ymd_and_hms(2024, 3, 9, 12, 0, 0).unwrap(), timezone);
+        let range = DateRange { start_date: start, end_date: start + Duration::days(3), timezone };
+
+        let boundaries = TimePeriodFilter::calculate_period_boundaries(&range, TimePeriod::Daily);
+
+        assert_eq!(boundaries[1].0, Utc.with_ymd_and_hms(2024, 3, 10, 5, 0, 0).unwrap());
+        assert_eq!(boundaries[1].1, Utc.with_ymd_and_hms(2024, 3, 11, 4, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_start_of_week_and_month_in_shop_timezone() {
+        let timezone = Tz::Asia__Tokyo;
+        // Sunday 2024-03-31 20:00 UTC is Monday 2024-04-01 05:00 in Tokyo
+        let dt = Utc.with_ymd_and_hms(2024, 3, 31, 20, 0, 0).unwrap();
+
+        assert_eq!(TimePeriodFilter::start_of_week(dt, timezone), Utc.with_ymd_and_hms(2024, 3, 31, 15, 0, 0).unwrap());
+        assert_eq!(TimePeriodFilter::start_of_month(dt, timezone), Utc.with_ymd_and_hms(2024, 3, 31, 15, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_resolve_timezone() {
+        assert_eq!(TimePeriodFilter::resolve_timezone(None, Tz::Europe__Paris), Ok(Tz::Europe__Paris));
+        assert_eq!(
+            TimePeriodFilter::resolve_timezone(Some("America/Chicago"), Tz::UTC),
+            Ok(Tz::America__Chicago)
+        );
+        assert!(TimePeriodFilter::resolve_timezone(Some("Mars/Olympus"), Tz::UTC).is_err());
+    }
 }
@@ -1,14 +1,14 @@
 // Authentication and authorization error types
 
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use std::fmt;
 use tracing::{error, warn};
-use crate::auth::models::Role;
+use crate::auth::models::{Permission, Role};
 
 /// Authentication and authorization error types
 #[derive(Debug)]
@@ -24,6 +24,26 @@ pub enum AuthError {
     PasswordHashError,
     InvalidPasswordFormat(String),
     TokenGenerationError(String),
+    /// Email verification token is unknown, already used or expired
+    InvalidVerificationToken,
+    /// Email address is already verified
+    EmailAlreadyVerified,
+    /// Referenced user does not exist
+    UserNotFound,
+    /// Session does not exist or belongs to another user
+    SessionNotFound,
+    /// Two-factor challenge token is unknown, already used or expired
+    InvalidTwoFactorChallenge,
+    /// Submitted TOTP or recovery code is wrong or was already used
+    InvalidTwoFactorCode,
+    /// Two-factor authentication is already enabled for the account
+    TwoFactorAlreadyEnabled,
+    /// Two-factor authentication has not been set up or enabled for the account
+    TwoFactorNotEnabled,
+    /// Account temporarily locked after repeated failed logins
+    AccountLocked { retry_after_secs: i64 },
+    /// Client IP temporarily blocked after repeated failed logins
+    TooManyLoginAttempts { retry_after_secs: i64 },
     
     // Authorization errors
     /// User lacks required permissions for the operation
@@ -32,10 +52,20 @@ pub enum AuthError {
         required: Role,
         actual: Role,
     },
+    /// User's role does not grant the required permission
+    MissingPermission(Permission),
     /// Invalid role value encountered
     InvalidRole(String),
     /// Configuration error in authorization system
     ConfigError(String),
+    /// Operation requires a verified email address
+    EmailNotVerified,
+    /// Account has been disabled by an admin
+    AccountDisabled,
+    /// Policy requires two-factor authentication for the account's role
+    TwoFactorRequired,
+    /// Signing keys are managed only for asymmetric algorithms
+    KeyRotationDisabled,
 }
 
 impl fmt::Display for AuthError {
@@ -51,11 +81,32 @@ impl fmt::Display for AuthError {
             AuthError::PasswordHashError => write!(f, "Password hashing error"),
             AuthError::InvalidPasswordFormat(msg) => write!(f, "Invalid password: {}", msg),
             AuthError::TokenGenerationError(msg) => write!(f, "Token generation error: {}", msg),
+            AuthError::InvalidVerificationToken => write!(f, "Invalid or expired verification token"),
+            AuthError::EmailAlreadyVerified => write!(f, "Email address is already verified"),
+            AuthError::UserNotFound => write!(f, "User not found"),
+            AuthError::SessionNotFound => write!(f, "Session not found"),
+            AuthError::InvalidTwoFactorChallenge => write!(f, "Invalid or expired two-factor challenge"),
+            AuthError::InvalidTwoFactorCode => write!(f, "Invalid two-factor code"),
+            AuthError::TwoFactorAlreadyEnabled => write!(f, "Two-factor authentication is already enabled"),
+            AuthError::TwoFactorNotEnabled => write!(f, "Two-factor authentication is not enabled"),
+            AuthError::AccountLocked { retry_after_secs } => {
+                write!(f, "Account temporarily locked, retry after {} seconds", retry_after_secs)
+            }
+            AuthError::TooManyLoginAttempts { retry_after_secs } => {
+                write!(f, "Too many login attempts, retry after {} seconds", retry_after_secs)
+            }
             AuthError::InsufficientPermissions { required, actual } => {
                 write!(f, "Insufficient permissions: required role '{}', but user has role '{}'", required, actual)
             }
+            AuthError::MissingPermission(permission) => {
+                write!(f, "Insufficient permissions: missing permission '{}'", permission)
+            }
             AuthError::InvalidRole(msg) => write!(f, "Invalid role: {}", msg),
             AuthError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
+            AuthError::EmailNotVerified => write!(f, "Email address must be verified"),
+            AuthError::AccountDisabled => write!(f, "Account is disabled"),
+            AuthError::TwoFactorRequired => write!(f, "Two-factor authentication is required for this account"),
+            AuthError::KeyRotationDisabled => write!(f, "Key rotation requires JWT_ALGORITHM=RS256 or EdDSA"),
         }
     }
 }
@@ -97,6 +148,37 @@ impl IntoResponse for AuthError {
                 error!("Token generation error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
             }
+            AuthError::InvalidVerificationToken => {
+                warn!("Invalid email verification token attempt");
+                (StatusCode::BAD_REQUEST, "Invalid or expired verification token".to_string())
+            }
+            AuthError::EmailAlreadyVerified => {
+                (StatusCode::CONFLICT, "Email address is already verified".to_string())
+            }
+            AuthError::UserNotFound => (StatusCode::NOT_FOUND, "User not found".to_string()),
+            AuthError::SessionNotFound => (StatusCode::NOT_FOUND, "Session not found".to_string()),
+            AuthError::InvalidTwoFactorChallenge => {
+                warn!("Invalid two-factor challenge attempt");
+                (StatusCode::UNAUTHORIZED, "Invalid or expired two-factor challenge".to_string())
+            }
+            AuthError::InvalidTwoFactorCode => {
+                warn!("Invalid two-factor code attempt");
+                (StatusCode::BAD_REQUEST, "Invalid two-factor code".to_string())
+            }
+            AuthError::TwoFactorAlreadyEnabled => {
+                (StatusCode::CONFLICT, "Two-factor authentication is already enabled".to_string())
+            }
+            AuthError::TwoFactorNotEnabled => {
+                (StatusCode::CONFLICT, "Two-factor authentication is not enabled".to_string())
+            }
+            AuthError::AccountLocked { .. } => {
+                warn!("Login attempt on locked account");
+                (StatusCode::LOCKED, "Account temporarily locked due to too many failed login attempts".to_string())
+            }
+            AuthError::TooManyLoginAttempts { .. } => {
+                warn!("Login attempt from blocked client");
+                (StatusCode::TOO_MANY_REQUESTS, "Too many failed login attempts".to_string())
+            }
             AuthError::InsufficientPermissions { required, actual } => {
                 warn!("Authorization failed: required role '{}', user has role '{}'", required, actual);
                 (
@@ -104,6 +186,13 @@ impl IntoResponse for AuthError {
                     format!("Insufficient permissions: required role '{}'", required)
                 )
             }
+            AuthError::MissingPermission(permission) => {
+                warn!("Authorization failed: missing permission '{}'", permission);
+                (
+                    StatusCode::FORBIDDEN,
+                    format!("Insufficient permissions: required permission '{}'", permission)
+                )
+            }
             AuthError::InvalidRole(msg) => {
                 warn!("Invalid role encountered: {}", msg);
                 (StatusCode::BAD_REQUEST, format!("Invalid role: {}", msg))
@@ -112,13 +201,37 @@ impl IntoResponse for AuthError {
                 error!("Authorization configuration error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
             }
+            AuthError::EmailNotVerified => {
+                (StatusCode::FORBIDDEN, "Email address must be verified".to_string())
+            }
+            AuthError::AccountDisabled => {
+                warn!("Request from disabled account");
+                (StatusCode::FORBIDDEN, "Account is disabled".to_string())
+            }
+            AuthError::TwoFactorRequired => {
+                (StatusCode::FORBIDDEN, "Two-factor authentication is required for this account".to_string())
+            }
+            AuthError::KeyRotationDisabled => {
+                (StatusCode::CONFLICT, "Key rotation requires JWT_ALGORITHM=RS256 or EdDSA".to_string())
+            }
         };
 
         let body = Json(json!({
             "error": message,
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+
+        // Tell clients when a temporary lock expires
+        if let AuthError::AccountLocked { retry_after_secs }
+        | AuthError::TooManyLoginAttempts { retry_after_secs } = &self
+        {
+            if let Ok(value) = header::HeaderValue::from_str(&retry_after_secs.to_string()) {
+                response.headers_mut().insert(header::RETRY_AFTER, value);
+            }
+        }
+
+        response
     }
 }
 
@@ -136,9 +249,24 @@ impl AuthError {
             AuthError::PasswordHashError => StatusCode::INTERNAL_SERVER_ERROR,
             AuthError::InvalidPasswordFormat(_) => StatusCode::BAD_REQUEST,
             AuthError::TokenGenerationError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AuthError::InvalidVerificationToken => StatusCode::BAD_REQUEST,
+            AuthError::EmailAlreadyVerified => StatusCode::CONFLICT,
+            AuthError::UserNotFound => StatusCode::NOT_FOUND,
+            AuthError::SessionNotFound => StatusCode::NOT_FOUND,
+            AuthError::InvalidTwoFactorChallenge => StatusCode::UNAUTHORIZED,
+            AuthError::InvalidTwoFactorCode => StatusCode::BAD_REQUEST,
+            AuthError::TwoFactorAlreadyEnabled => StatusCode::CONFLICT,
+            AuthError::TwoFactorNotEnabled => StatusCode::CONFLICT,
+            AuthError::AccountLocked { .. } => StatusCode::LOCKED,
+            AuthError::TooManyLoginAttempts { .. } => StatusCode::TOO_MANY_REQUESTS,
             AuthError::InsufficientPermissions { .. } => StatusCode::FORBIDDEN,
+            AuthError::MissingPermission(_) => StatusCode::FORBIDDEN,
             AuthError::InvalidRole(_) => StatusCode::BAD_REQUEST,
             AuthError::ConfigError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AuthError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AuthError::AccountDisabled => StatusCode::FORBIDDEN,
+            AuthError::TwoFactorRequired => StatusCode::FORBIDDEN,
+            AuthError::KeyRotationDisabled => StatusCode::CONFLICT,
         }
     }
     
@@ -156,11 +284,30 @@ impl AuthError {
             AuthError::PasswordHashError => "Internal server error".to_string(),
             AuthError::InvalidPasswordFormat(msg) => msg.clone(),
             AuthError::TokenGenerationError(_) => "Internal server error".to_string(),
+            AuthError::InvalidVerificationToken => "Invalid or expired verification token".to_string(),
+            AuthError::EmailAlreadyVerified => "Email address is already verified".to_string(),
+            AuthError::UserNotFound => "User not found".to_string(),
+            AuthError::SessionNotFound => "Session not found".to_string(),
+            AuthError::InvalidTwoFactorChallenge => "Invalid or expired two-factor challenge".to_string(),
+            AuthError::InvalidTwoFactorCode => "Invalid two-factor code".to_string(),
+            AuthError::TwoFactorAlreadyEnabled => "Two-factor authentication is already enabled".to_string(),
+            AuthError::TwoFactorNotEnabled => "Two-factor authentication is not enabled".to_string(),
+            AuthError::AccountLocked { .. } => {
+                "Account temporarily locked due to too many failed login attempts".to_string()
+            }
+            AuthError::TooManyLoginAttempts { .. } => "Too many failed login attempts".to_string(),
             AuthError::InsufficientPermissions { required, .. } => {
                 format!("Insufficient permissions: required role '{}'", required)
             }
+            AuthError::MissingPermission(permission) => {
+                format!("Insufficient permissions: required permission '{}'", permission)
+            }
             AuthError::InvalidRole(msg) => format!("Invalid role: {}", msg),
             AuthError::ConfigError(_) => "Internal server error".to_string(),
+            AuthError::EmailNotVerified => "Email address must be verified".to_string(),
+            AuthError::AccountDisabled => "Account is disabled".to_string(),
+            AuthError::TwoFactorRequired => "Two-factor authentication is required for this account".to_string(),
+            AuthError::KeyRotationDisabled => "Key rotation requires JWT_ALGORITHM=RS256 or EdDSA".to_string(),
         }
     }
 }
@@ -1,10 +1,22 @@
 // HTTP handlers for authentication endpoints
 
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
 use crate::auth::{
     error::AuthError,
-    models::{AuthResponse, LoginRequest, RefreshRequest, RegisterRequest, UserResponse},
+    models::{
+        AdminUserDetail, AuthResponse, LoginRequest, LoginResponse, MessageResponse,
+        RecoveryCodesResponse, RefreshRequest, RegisterRequest, Role, RoleDefinition,
+        SessionResponse, SigningKeyInfo, TwoFactorCodeRequest, TwoFactorSetupResponse,
+        TwoFactorStatusResponse, TwoFactorVerifyRequest, UpdateRolePermissionsRequest,
+        UpdateRoleRequest, UserListQuery, UserResponse, VerifyEmailRequest,
+    },
 };
+use jsonwebtoken::jwk::JwkSet;
+use crate::pagination::PaginatedResponse;
 use validator::Validate;
 
 /// Register a new user
@@ -22,6 +34,7 @@ use validator::Validate;
 )]
 pub async fn register_handler(
     State(state): State<crate::AppState>,
+    client: crate::auth::middleware::ClientInfo,
     Json(request): Json<RegisterRequest>,
 ) -> Result<(StatusCode, Json<AuthResponse>), AuthError> {
     // Validate request
@@ -29,7 +42,10 @@ pub async fn register_handler(
         .map_err(|e| AuthError::ValidationError(e.to_string()))?;
     
     // Register user
-    let response = state.auth_service.register(&request.email, &request.password).await?;
+    let session = client.into_session(None);
+    let response = state.auth_service
+        .register(&request.email, &request.password, &session)
+        .await?;
     
     Ok((StatusCode::CREATED, Json(response)))
 }
@@ -41,22 +57,28 @@ pub async fn register_handler(
     path = "/api/auth/login",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = AuthResponse),
+        (status = 200, description = "Login successful, or a two-factor challenge when the account has 2FA enabled", body = LoginResponse),
         (status = 400, description = "Invalid input data", body = String),
-        (status = 401, description = "Invalid credentials", body = String)
+        (status = 401, description = "Invalid credentials", body = String),
+        (status = 423, description = "Account temporarily locked after repeated failures", body = String),
+        (status = 429, description = "Too many failed attempts from this client", body = String)
     ),
     tag = "auth"
 )]
 pub async fn login_handler(
     State(state): State<crate::AppState>,
+    client: crate::auth::middleware::ClientInfo,
     Json(request): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, AuthError> {
+) -> Result<Json<LoginResponse>, AuthError> {
     // Validate request
     request.validate()
         .map_err(|e| AuthError::ValidationError(e.to_string()))?;
     
-    // Login user
-    let response = state.auth_service.login(&request.email, &request.password).await?;
+    // Login user, recording the device for the new session
+    let session = client.into_session(request.device_name.clone());
+    let response = state.auth_service
+        .login(&request.email, &request.password, &session)
+        .await?;
     
     Ok(Json(response))
 }
@@ -75,10 +97,51 @@ pub async fn login_handler(
 )]
 pub async fn refresh_handler(
     State(state): State<crate::AppState>,
+    client: crate::auth::middleware::ClientInfo,
     Json(request): Json<RefreshRequest>,
 ) -> Result<Json<AuthResponse>, AuthError> {
-    // Refresh tokens
-    let response = state.auth_service.refresh_tokens(&request.refresh_token).await?;
+    // Refresh tokens, updating the session's last activity
+    let session = client.into_session(None);
+    let response = state.auth_service
+        .refresh_tokens(&request.refresh_token, &session)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+/// Complete a two-factor login
+/// POST /api/auth/2fa/verify
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/verify",
+    request_body = TwoFactorVerifyRequest,
+    responses(
+        (status = 200, description = "Second factor accepted", body = AuthResponse),
+        (status = 400, description = "Invalid two-factor or recovery code", body = String),
+        (status = 401, description = "Invalid or expired challenge", body = String),
+        (status = 423, description = "Account temporarily locked after repeated failures", body = String),
+        (status = 429, description = "Too many failed attempts from this client", body = String)
+    ),
+    tag = "auth"
+)]
+pub async fn verify_two_factor_handler(
+    State(state): State<crate::AppState>,
+    client: crate::auth::middleware::ClientInfo,
+    Json(request): Json<TwoFactorVerifyRequest>,
+) -> Result<Json<AuthResponse>, AuthError> {
+    // Validate request
+    request.validate()
+        .map_err(|e| AuthError::ValidationError(e.to_string()))?;
+    
+    let session = client.into_session(None);
+    let response = state.auth_service
+        .verify_two_factor(
+            &request.challenge_token,
+            request.code.as_deref(),
+            request.recovery_code.as_deref(),
+            &session,
+        )
+        .await?;
     
     Ok(Json(response))
 }
@@ -106,3 +169,589 @@ pub async fn me_handler(
     
     Ok(Json(user_response))
 }
+
+/// Verify an email address
+/// POST /api/auth/verify-email
+#[utoipa::path(
+    post,
+    path = "/api/auth/verify-email",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 200, description = "Email verified successfully", body = UserResponse),
+        (status = 400, description = "Invalid or expired verification token", body = String)
+    ),
+    tag = "auth"
+)]
+pub async fn verify_email_handler(
+    State(state): State<crate::AppState>,
+    Json(request): Json<VerifyEmailRequest>,
+) -> Result<Json<UserResponse>, AuthError> {
+    // Verify email
+    let user_response = state.auth_service.verify_email(&request.token).await?;
+    
+    Ok(Json(user_response))
+}
+
+/// List the current user's active sessions (protected endpoint)
+/// GET /api/auth/sessions
+#[utoipa::path(
+    get,
+    path = "/api/auth/sessions",
+    responses(
+        (status = 200, description = "Active sessions, most recently used first", body = Vec<SessionResponse>),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_sessions_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+) -> Result<Json<Vec<SessionResponse>>, AuthError> {
+    let sessions = state.auth_service
+        .list_sessions(user.user_id, user.session_id)
+        .await?;
+    
+    Ok(Json(sessions))
+}
+
+/// Revoke one of the current user's sessions (protected endpoint)
+/// DELETE /api/auth/sessions/{id}
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions/{id}",
+    params(
+        ("id" = i32, Path, description = "Session ID")
+    ),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 404, description = "Session not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_session_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+    Path(session_id): Path<i32>,
+) -> Result<StatusCode, AuthError> {
+    state.auth_service.revoke_session(user.user_id, session_id).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the current user's two-factor status (protected endpoint)
+/// GET /api/auth/2fa
+#[utoipa::path(
+    get,
+    path = "/api/auth/2fa",
+    responses(
+        (status = 200, description = "Two-factor status", body = TwoFactorStatusResponse),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn two_factor_status_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+) -> Result<Json<TwoFactorStatusResponse>, AuthError> {
+    let status = state.auth_service.two_factor_status(user.user_id).await?;
+    
+    Ok(Json(status))
+}
+
+/// Start two-factor enrolment (protected endpoint)
+/// POST /api/auth/2fa/setup
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/setup",
+    responses(
+        (status = 200, description = "Secret and provisioning URI for the authenticator app", body = TwoFactorSetupResponse),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 409, description = "Two-factor authentication is already enabled", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn setup_two_factor_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+) -> Result<Json<TwoFactorSetupResponse>, AuthError> {
+    let setup = state.auth_service.setup_two_factor(user.user_id).await?;
+    
+    Ok(Json(setup))
+}
+
+/// Confirm two-factor enrolment with a code from the authenticator app (protected endpoint)
+/// POST /api/auth/2fa/enable
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/enable",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "Two-factor enabled; recovery codes are shown only once", body = RecoveryCodesResponse),
+        (status = 400, description = "Invalid two-factor code", body = String),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 409, description = "Enrolment not started or already enabled", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn enable_two_factor_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+    Json(request): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, AuthError> {
+    request.validate()
+        .map_err(|e| AuthError::ValidationError(e.to_string()))?;
+    
+    let codes = state.auth_service.enable_two_factor(user.user_id, &request.code).await?;
+    
+    Ok(Json(codes))
+}
+
+/// Turn off two-factor authentication (protected endpoint)
+/// POST /api/auth/2fa/disable
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/disable",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 400, description = "Invalid two-factor code", body = String),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Two-factor authentication is required for this account", body = String),
+        (status = 409, description = "Two-factor authentication is not enabled", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn disable_two_factor_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+    Json(request): Json<TwoFactorCodeRequest>,
+) -> Result<StatusCode, AuthError> {
+    request.validate()
+        .map_err(|e| AuthError::ValidationError(e.to_string()))?;
+    
+    state.auth_service.disable_two_factor(user.user_id, &request.code).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replace the current user's recovery codes (protected endpoint)
+/// POST /api/auth/2fa/recovery-codes
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/recovery-codes",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "New recovery codes; previous codes no longer work", body = RecoveryCodesResponse),
+        (status = 400, description = "Invalid two-factor code", body = String),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 409, description = "Two-factor authentication is not enabled", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn regenerate_recovery_codes_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+    Json(request): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, AuthError> {
+    request.validate()
+        .map_err(|e| AuthError::ValidationError(e.to_string()))?;
+    
+    let codes = state.auth_service.regenerate_recovery_codes(user.user_id, &request.code).await?;
+    
+    Ok(Json(codes))
+}
+
+/// Resend the verification email for the current user (protected endpoint)
+/// POST /api/auth/resend-verification
+#[utoipa::path(
+    post,
+    path = "/api/auth/resend-verification",
+    responses(
+        (status = 202, description = "Verification email sent", body = MessageResponse),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 409, description = "Email address is already verified", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn resend_verification_handler(
+    State(state): State<crate::AppState>,
+    user: crate::auth::middleware::AuthenticatedUser,
+) -> Result<(StatusCode, Json<MessageResponse>), AuthError> {
+    // Issue a new verification token
+    state.auth_service.resend_verification_email(user.user_id).await?;
+    
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(MessageResponse {
+            message: "Verification email sent".to_string(),
+        }),
+    ))
+}
+
+/// Unlock an account locked by failed logins (admin only)
+/// POST /api/admin/users/{id}/unlock
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/unlock",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Account unlocked", body = MessageResponse),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - admin role required", body = String),
+        (status = 404, description = "User not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unlock_user_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+    Path(user_id): Path<i32>,
+) -> Result<Json<MessageResponse>, AuthError> {
+    // Clear failure counters and lock
+    let had_failures = state.auth_service.unlock_account(admin.user_id, user_id).await?;
+    
+    let message = if had_failures {
+        "Account unlocked"
+    } else {
+        "Account was not locked"
+    };
+    
+    Ok(Json(MessageResponse {
+        message: message.to_string(),
+    }))
+}
+
+/// List and search users (admin only)
+/// GET /api/admin/users
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    params(
+        ("page" = Option<u32>, Query, description = "Page number (default 1)"),
+        ("limit" = Option<u32>, Query, description = "Items per page (default 20, max 100)"),
+        ("search" = Option<String>, Query, description = "Partial email match"),
+        ("role" = Option<String>, Query, description = "Filter by role (admin, user)"),
+        ("disabled" = Option<bool>, Query, description = "Filter by disabled status")
+    ),
+    responses(
+        (status = 200, description = "Paginated list of users"),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - admin role required", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_users_handler(
+    State(state): State<crate::AppState>,
+    Query(query): Query<UserListQuery>,
+) -> Result<Json<PaginatedResponse<UserResponse>>, AuthError> {
+    let users = state.auth_service.list_users(&query).await?;
+    
+    Ok(Json(users))
+}
+
+/// Get a user with order count and loyalty balance (admin only)
+/// GET /api/admin/users/{id}
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User details", body = AdminUserDetail),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - admin role required", body = String),
+        (status = 404, description = "User not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user_handler(
+    State(state): State<crate::AppState>,
+    Path(user_id): Path<i32>,
+) -> Result<Json<AdminUserDetail>, AuthError> {
+    let user = state.auth_service.get_user_detail(user_id).await?;
+    
+    Ok(Json(user))
+}
+
+/// Change a user's role (users:manage permission)
+/// PUT /api/admin/users/{id}/role
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{id}/role",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    request_body = UpdateRoleRequest,
+    responses(
+        (status = 200, description = "Role updated", body = UserResponse),
+        (status = 400, description = "Unknown role or cannot modify your own role", body = String),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - users:manage permission required", body = String),
+        (status = 404, description = "User not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_user_role_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+    Path(user_id): Path<i32>,
+    Json(request): Json<UpdateRoleRequest>,
+) -> Result<Json<UserResponse>, AuthError> {
+    let user = state.auth_service
+        .update_user_role(admin.user_id, user_id, request.role)
+        .await?;
+    
+    Ok(Json(user))
+}
+
+/// Disable a user account and end its sessions (admin only)
+/// POST /api/admin/users/{id}/disable
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/disable",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Account disabled", body = UserResponse),
+        (status = 400, description = "Cannot disable your own account", body = String),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - admin role required", body = String),
+        (status = 404, description = "User not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn disable_user_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+    Path(user_id): Path<i32>,
+) -> Result<Json<UserResponse>, AuthError> {
+    let user = state.auth_service.set_user_disabled(admin.user_id, user_id, true).await?;
+    
+    Ok(Json(user))
+}
+
+/// Re-enable a disabled user account (admin only)
+/// POST /api/admin/users/{id}/enable
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/enable",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Account enabled", body = UserResponse),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - admin role required", body = String),
+        (status = 404, description = "User not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn enable_user_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+    Path(user_id): Path<i32>,
+) -> Result<Json<UserResponse>, AuthError> {
+    let user = state.auth_service.set_user_disabled(admin.user_id, user_id, false).await?;
+    
+    Ok(Json(user))
+}
+
+/// Force logout: revoke all sessions of a user (admin only)
+/// POST /api/admin/users/{id}/logout
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/logout",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Sessions revoked", body = MessageResponse),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - admin role required", body = String),
+        (status = 404, description = "User not found", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn force_logout_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+    Path(user_id): Path<i32>,
+) -> Result<Json<MessageResponse>, AuthError> {
+    let revoked = state.auth_service.force_logout(admin.user_id, user_id).await?;
+    
+    Ok(Json(MessageResponse {
+        message: format!("Revoked {} session(s)", revoked),
+    }))
+}
+
+/// List roles and their permissions (admin only)
+/// GET /api/admin/roles
+#[utoipa::path(
+    get,
+    path = "/api/admin/roles",
+    responses(
+        (status = 200, description = "Roles with their permissions", body = Vec<RoleDefinition>),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - users:manage permission required", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_roles_handler(
+    State(state): State<crate::AppState>,
+) -> Result<Json<Vec<RoleDefinition>>, AuthError> {
+    let roles = state.auth_service.list_roles().await?;
+    
+    Ok(Json(roles))
+}
+
+/// Replace the permission set of a role (admin only)
+/// PUT /api/admin/roles/{role}/permissions
+#[utoipa::path(
+    put,
+    path = "/api/admin/roles/{role}/permissions",
+    params(
+        ("role" = String, Path, description = "Role name (admin, user, barista)")
+    ),
+    request_body = UpdateRolePermissionsRequest,
+    responses(
+        (status = 200, description = "Role permissions updated", body = RoleDefinition),
+        (status = 400, description = "Invalid role or permission set", body = String),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - users:manage permission required", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_role_permissions_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+    Path(role): Path<String>,
+    Json(request): Json<UpdateRolePermissionsRequest>,
+) -> Result<Json<RoleDefinition>, AuthError> {
+    let role = Role::from_str(&role).map_err(AuthError::InvalidRole)?;
+    
+    let definition = state.auth_service
+        .update_role_permissions(admin.user_id, role, request.permissions)
+        .await?;
+    
+    Ok(Json(definition))
+}
+
+/// Public keys for verifying access tokens (JSON Web Key Set)
+/// GET /.well-known/jwks.json
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "JSON Web Key Set; empty when tokens are signed with HS256")
+    ),
+    tag = "auth"
+)]
+pub async fn jwks_handler(
+    State(state): State<crate::AppState>,
+) -> Json<JwkSet> {
+    Json(state.auth_service.jwks())
+}
+
+/// List JWT signing keys (admin only)
+/// GET /api/admin/signing-keys
+#[utoipa::path(
+    get,
+    path = "/api/admin/signing-keys",
+    responses(
+        (status = 200, description = "Signing keys, newest first", body = Vec<SigningKeyInfo>),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - keys:manage permission required", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_signing_keys_handler(
+    State(state): State<crate::AppState>,
+) -> Result<Json<Vec<SigningKeyInfo>>, AuthError> {
+    let keys = state.auth_service.list_signing_keys().await?;
+    
+    Ok(Json(keys))
+}
+
+/// Rotate the JWT signing key (admin only)
+/// POST /api/admin/signing-keys/rotate
+#[utoipa::path(
+    post,
+    path = "/api/admin/signing-keys/rotate",
+    responses(
+        (status = 201, description = "New signing key generated and activated", body = SigningKeyInfo),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String),
+        (status = 403, description = "Forbidden - keys:manage permission required", body = String),
+        (status = 409, description = "Tokens are signed with HS256", body = String)
+    ),
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn rotate_signing_key_handler(
+    State(state): State<crate::AppState>,
+    admin: crate::auth::middleware::AuthenticatedUser,
+) -> Result<(StatusCode, Json<SigningKeyInfo>), AuthError> {
+    let key = state.auth_service.rotate_signing_key(Some(admin.user_id)).await?;
+    
+    Ok((StatusCode::CREATED, Json(key)))
+}
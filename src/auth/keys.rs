@@ -0,0 +1,396 @@
+// JWT signing keys: algorithms, key generation and the key ring used by TokenService
+//
+// HS256 keeps the single shared secret from JWT_SECRET. RS256 and EdDSA keys are
+// generated here, identified by `kid` (the RFC 7638 JWK thumbprint) and published
+// as a JWKS so other services can verify tokens without the signing key.
+
+use crate::auth::error::AuthError;
+use crate::auth::models::SigningKeyRecord;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use rsa::{pkcs1::EncodeRsaPrivateKey, traits::PublicKeyParts, RsaPrivateKey};
+use sha2::{Digest, Sha256};
+
+/// RSA modulus size for generated RS256 keys
+const RSA_KEY_BITS: usize = 2048;
+
+/// Algorithm used to sign access and refresh tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningAlgorithm {
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+impl SigningAlgorithm {
+    /// Convert algorithm to its JWT `alg` name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::HS256 => "HS256",
+            SigningAlgorithm::RS256 => "RS256",
+            SigningAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    /// Parse algorithm from its JWT `alg` name
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "HS256" => Ok(SigningAlgorithm::HS256),
+            "RS256" => Ok(SigningAlgorithm::RS256),
+            "EdDSA" => Ok(SigningAlgorithm::EdDSA),
+            _ => Err(format!("Invalid signing algorithm: {}", s)),
+        }
+    }
+
+    /// Whether keys for this algorithm are generated and published via JWKS
+    pub fn is_asymmetric(&self) -> bool {
+        !matches!(self, SigningAlgorithm::HS256)
+    }
+
+    fn jwt_algorithm(&self) -> Algorithm {
+        match self {
+            SigningAlgorithm::HS256 => Algorithm::HS256,
+            SigningAlgorithm::RS256 => Algorithm::RS256,
+            SigningAlgorithm::EdDSA => Algorithm::EdDSA,
+        }
+    }
+}
+
+impl std::fmt::Display for SigningAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Freshly generated key pair, ready to be stored
+#[derive(Debug, Clone)]
+pub struct GeneratedKey {
+    pub kid: String,
+    pub algorithm: SigningAlgorithm,
+    /// PKCS#1 DER for RS256, PKCS#8 DER for EdDSA
+    pub private_key: Vec<u8>,
+    pub public_jwk: Jwk,
+}
+
+/// Generate a new asymmetric signing key
+pub fn generate_signing_key(algorithm: SigningAlgorithm) -> Result<GeneratedKey, AuthError> {
+    let (private_key, params) = match algorithm {
+        SigningAlgorithm::HS256 => {
+            return Err(AuthError::ConfigError(
+                "HS256 uses JWT_SECRET and has no generated keys".to_string(),
+            ))
+        }
+        SigningAlgorithm::RS256 => {
+            let key = RsaPrivateKey::new(&mut rand::thread_rng(), RSA_KEY_BITS)
+                .map_err(|e| AuthError::TokenGenerationError(e.to_string()))?;
+            let der = key
+                .to_pkcs1_der()
+                .map_err(|e| AuthError::TokenGenerationError(e.to_string()))?;
+            let params = AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+            });
+            (der.as_bytes().to_vec(), params)
+        }
+        SigningAlgorithm::EdDSA => {
+            let rng = ring::rand::SystemRandom::new();
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng)
+                .map_err(|e| AuthError::TokenGenerationError(e.to_string()))?;
+            let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+                .map_err(|e| AuthError::TokenGenerationError(e.to_string()))?;
+            let params = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+            });
+            (pkcs8.as_ref().to_vec(), params)
+        }
+    };
+
+    let kid = thumbprint(&params);
+    let public_jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                SigningAlgorithm::RS256 => KeyAlgorithm::RS256,
+                _ => KeyAlgorithm::EdDSA,
+            }),
+            key_id: Some(kid.clone()),
+            ..Default::default()
+        },
+        algorithm: params,
+    };
+
+    Ok(GeneratedKey {
+        kid,
+        algorithm,
+        private_key,
+        public_jwk,
+    })
+}
+
+/// RFC 7638 JWK thumbprint: SHA-256 over the required members in lexicographic order
+fn thumbprint(params: &AlgorithmParameters) -> String {
+    let canonical = match params {
+        AlgorithmParameters::RSA(rsa) => {
+            format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, rsa.e, rsa.n)
+        }
+        AlgorithmParameters::OctetKeyPair(okp) => {
+            format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#, okp.x)
+        }
+        _ => unreachable!("only RSA and Ed25519 keys are generated"),
+    };
+    URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Key used to sign new tokens between `from` and `until`
+struct SigningKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: EncodingKey,
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+impl SigningKey {
+    fn signs_at(&self, now: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| from <= now) && self.until.is_none_or(|until| until > now)
+    }
+}
+
+/// Key accepted when validating tokens
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+/// Signing keys plus every key whose tokens are still accepted
+///
+/// Tokens without a `kid` are matched against the HS256 secret, if one is
+/// configured; tokens with a `kid` must name a key that has not expired.
+/// The token's `alg` must match the key's algorithm.
+///
+/// A rotated key is published for verification before it starts signing, so
+/// every instance knows it by the time tokens carry its `kid`. The signer is
+/// chosen by time when a token is issued.
+#[derive(Default)]
+pub struct KeyRing {
+    signing: Vec<SigningKey>,
+    verification: Vec<VerificationKey>,
+}
+
+impl KeyRing {
+    /// Key ring that signs and verifies with a single HS256 secret
+    pub fn hmac(secret: &str) -> Self {
+        Self {
+            signing: vec![SigningKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: EncodingKey::from_secret(secret.as_bytes()),
+                from: None,
+                until: None,
+            }],
+            verification: vec![Self::hmac_verification_key(secret)],
+        }
+    }
+
+    /// Build a key ring from stored keys
+    ///
+    /// Current, pending and recently retired keys sign within their window;
+    /// keys that have not expired at `now` verify.
+    /// When `legacy_secret` is given, HS256 tokens issued before the switch to
+    /// asymmetric signing keep verifying until they expire.
+    pub fn from_records(
+        records: &[SigningKeyRecord],
+        now: DateTime<Utc>,
+        legacy_secret: Option<&str>,
+    ) -> Result<Self, AuthError> {
+        let mut ring = Self::default();
+
+        for record in records.iter().filter(|record| record.verifies_at(now)) {
+            let algorithm = SigningAlgorithm::from_str(&record.algorithm)
+                .map_err(AuthError::ConfigError)?
+                .jwt_algorithm();
+            let jwk: Jwk = serde_json::from_str(&record.public_jwk)
+                .map_err(|e| AuthError::ConfigError(format!("Invalid JWK for key {}: {}", record.kid, e)))?;
+            let key = DecodingKey::from_jwk(&jwk)
+                .map_err(|e| AuthError::ConfigError(format!("Invalid JWK for key {}: {}", record.kid, e)))?;
+
+            if record.retired_at.is_none_or(|retired_at| retired_at > now) {
+                ring.signing.push(SigningKey {
+                    kid: Some(record.kid.clone()),
+                    algorithm,
+                    key: match algorithm {
+                        Algorithm::RS256 => EncodingKey::from_rsa_der(&record.private_key),
+                        _ => EncodingKey::from_ed_der(&record.private_key),
+                    },
+                    from: Some(record.activated_at),
+                    until: record.retired_at,
+                });
+            }
+
+            ring.verification.push(VerificationKey {
+                kid: Some(record.kid.clone()),
+                algorithm,
+                key,
+                jwk: Some(jwk),
+            });
+        }
+
+        if let Some(secret) = legacy_secret {
+            ring.verification.push(Self::hmac_verification_key(secret));
+        }
+
+        Ok(ring)
+    }
+
+    fn hmac_verification_key(secret: &str) -> VerificationKey {
+        VerificationKey {
+            kid: None,
+            algorithm: Algorithm::HS256,
+            key: DecodingKey::from_secret(secret.as_bytes()),
+            jwk: None,
+        }
+    }
+
+    /// Header and key for signing a new token at `now`
+    pub fn signing_key(&self, now: DateTime<Utc>) -> Result<(Header, &EncodingKey), AuthError> {
+        let signing = self
+            .signing
+            .iter()
+            .find(|key| key.signs_at(now))
+            .ok_or_else(|| AuthError::TokenGenerationError("No active signing key".to_string()))?;
+
+        let mut header = Header::new(signing.algorithm);
+        header.kid = signing.kid.clone();
+        Ok((header, &signing.key))
+    }
+
+    /// Key and algorithm to validate a token with the given header
+    pub fn verification_key(&self, header: &Header) -> Option<(&DecodingKey, Algorithm)> {
+        self.verification
+            .iter()
+            .find(|key| key.kid == header.kid && key.algorithm == header.alg)
+            .map(|key| (&key.key, key.algorithm))
+    }
+
+    /// Public keys of every asymmetric key that still verifies
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification
+                .iter()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        key: &GeneratedKey,
+        activated_at: DateTime<Utc>,
+        retired_at: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> SigningKeyRecord {
+        SigningKeyRecord {
+            kid: key.kid.clone(),
+            algorithm: key.algorithm.as_str().to_string(),
+            private_key: key.private_key.clone(),
+            public_jwk: serde_json::to_string(&key.public_jwk).unwrap(),
+            created_at: activated_at,
+            activated_at,
+            retired_at,
+            expires_at,
+        }
+    }
+
+    fn signing_kid(ring: &KeyRing, now: DateTime<Utc>) -> Option<String> {
+        ring.signing_key(now).unwrap().0.kid
+    }
+
+    #[test]
+    fn test_signing_algorithm_round_trip() {
+        for algorithm in [SigningAlgorithm::HS256, SigningAlgorithm::RS256, SigningAlgorithm::EdDSA] {
+            assert_eq!(SigningAlgorithm::from_str(algorithm.as_str()), Ok(algorithm));
+        }
+        assert!(SigningAlgorithm::from_str("none").is_err());
+    }
+
+    #[test]
+    fn test_generated_kid_is_stable_thumbprint() {
+        let key = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        assert_eq!(key.public_jwk.common.key_id.as_deref(), Some(key.kid.as_str()));
+        assert_eq!(key.kid, thumbprint(&key.public_jwk.algorithm));
+        // base64url of a SHA-256 digest
+        assert_eq!(key.kid.len(), 43);
+    }
+
+    #[test]
+    fn test_jwks_exposes_only_public_keys() {
+        let key = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let now = Utc::now();
+        let ring = KeyRing::from_records(&[record(&key, now, None, None)], now, Some("secret")).unwrap();
+
+        let jwks = serde_json::to_value(ring.jwks()).unwrap();
+        let keys = jwks["keys"].as_array().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0]["kid"], key.kid);
+        assert_eq!(keys[0]["kty"], "OKP");
+        assert!(keys[0].get("d").is_none());
+    }
+
+    #[test]
+    fn test_expired_keys_are_dropped() {
+        let old = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let new = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let now = Utc::now();
+        let an_hour_ago = now - chrono::Duration::hours(1);
+        let records = [
+            record(&new, an_hour_ago, None, None),
+            record(&old, an_hour_ago, Some(an_hour_ago), Some(now - chrono::Duration::seconds(1))),
+        ];
+
+        let ring = KeyRing::from_records(&records, now, None).unwrap();
+        assert_eq!(ring.jwks().keys.len(), 1);
+
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(old.kid.clone());
+        assert!(ring.verification_key(&header).is_none());
+    }
+
+    #[test]
+    fn test_pending_key_is_published_before_signing() {
+        let old = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let new = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let now = Utc::now();
+        let activates_at = now + chrono::Duration::seconds(60);
+        let records = [
+            record(&new, activates_at, None, None),
+            record(&old, now - chrono::Duration::hours(1), Some(activates_at), Some(activates_at + chrono::Duration::days(7))),
+        ];
+
+        let ring = KeyRing::from_records(&records, now, None).unwrap();
+        assert_eq!(ring.jwks().keys.len(), 2);
+        assert_eq!(signing_kid(&ring, now), Some(old.kid.clone()));
+        assert_eq!(signing_kid(&ring, activates_at), Some(new.kid.clone()));
+    }
+
+    #[test]
+    fn test_empty_ring_cannot_sign() {
+        let ring = KeyRing::default();
+        assert!(matches!(ring.signing_key(Utc::now()), Err(AuthError::TokenGenerationError(_))));
+    }
+}
@@ -0,0 +1,153 @@
+// Login brute-force protection rules
+// Pure helpers for failure counting, lockout windows and progressive delays
+
+use crate::auth::models::LoginFailure;
+use crate::config::LoginProtectionConfig;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Counter state after recording a failed login
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureState {
+    pub failure_count: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Failures that still count towards a lock at `now`
+/// Counters whose lock has expired or whose last failure is outside the window count as zero
+pub fn active_failure_count(
+    existing: Option<&LoginFailure>,
+    now: DateTime<Utc>,
+    lockout_duration: chrono::Duration,
+) -> i32 {
+    existing
+        .filter(|failure| {
+            let lock_expired = failure.locked_until.is_some_and(|until| until <= now);
+            let window_elapsed = failure.last_failure_at < now - lockout_duration;
+            !lock_expired && !window_elapsed
+        })
+        .map_or(0, |failure| failure.failure_count)
+}
+
+/// Compute the counter state after one more failed attempt
+///
+/// - An active lock is kept as-is (attempts while locked still count)
+/// - Counters whose last failure is older than the lockout window, or whose
+///   lock has expired, start over at 1
+/// - Reaching `max_failures` locks for `lockout_duration` from now
+pub fn next_failure_state(
+    existing: Option<&LoginFailure>,
+    now: DateTime<Utc>,
+    max_failures: i32,
+    lockout_duration: chrono::Duration,
+) -> FailureState {
+    let failure_count = active_failure_count(existing, now, lockout_duration) + 1;
+
+    let active_lock = existing.and_then(|failure| failure.locked_until).filter(|until| *until > now);
+    let locked_until = match active_lock {
+        Some(until) => Some(until),
+        None if failure_count >= max_failures => Some(now + lockout_duration),
+        None => None,
+    };
+
+    FailureState {
+        failure_count,
+        locked_until,
+    }
+}
+
+/// Delay to apply before checking a password, given the failures so far
+/// Doubles from `base_delay` for each recorded failure, capped at `max_delay`
+pub fn progressive_delay(config: &LoginProtectionConfig, failure_count: i32) -> Duration {
+    if failure_count <= 0 {
+        return Duration::ZERO;
+    }
+
+    let exponent = (failure_count - 1).min(16) as u32;
+    config
+        .base_delay
+        .saturating_mul(2u32.saturating_pow(exponent))
+        .min(config.max_delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(count: i32, last_failure_at: DateTime<Utc>, locked_until: Option<DateTime<Utc>>) -> LoginFailure {
+        LoginFailure {
+            scope: "account".to_string(),
+            key: "user@example.com".to_string(),
+            failure_count: count,
+            last_failure_at,
+            locked_until,
+        }
+    }
+
+    fn window() -> chrono::Duration {
+        chrono::Duration::minutes(15)
+    }
+
+    #[test]
+    fn test_first_failure_starts_at_one() {
+        let now = Utc::now();
+        let state = next_failure_state(None, now, 5, window());
+        assert_eq!(state, FailureState { failure_count: 1, locked_until: None });
+    }
+
+    #[test]
+    fn test_reaching_threshold_locks() {
+        let now = Utc::now();
+        let existing = failure(4, now - chrono::Duration::seconds(10), None);
+        let state = next_failure_state(Some(&existing), now, 5, window());
+        assert_eq!(state.failure_count, 5);
+        assert_eq!(state.locked_until, Some(now + window()));
+    }
+
+    #[test]
+    fn test_active_lock_is_not_extended() {
+        let now = Utc::now();
+        let until = now + chrono::Duration::minutes(3);
+        let existing = failure(5, now - chrono::Duration::seconds(10), Some(until));
+        let state = next_failure_state(Some(&existing), now, 5, window());
+        assert_eq!(state.failure_count, 6);
+        assert_eq!(state.locked_until, Some(until));
+    }
+
+    #[test]
+    fn test_expired_lock_resets_counter() {
+        let now = Utc::now();
+        let existing = failure(5, now - chrono::Duration::minutes(1), Some(now - chrono::Duration::seconds(1)));
+        let state = next_failure_state(Some(&existing), now, 5, window());
+        assert_eq!(state, FailureState { failure_count: 1, locked_until: None });
+    }
+
+    #[test]
+    fn test_stale_counter_resets() {
+        let now = Utc::now();
+        let existing = failure(3, now - chrono::Duration::minutes(20), None);
+        let state = next_failure_state(Some(&existing), now, 5, window());
+        assert_eq!(state.failure_count, 1);
+    }
+
+    #[test]
+    fn test_progressive_delay_doubles_and_caps() {
+        let config = LoginProtectionConfig::default();
+        assert_eq!(progressive_delay(&config, 0), Duration::ZERO);
+        assert_eq!(progressive_delay(&config, 1), Duration::from_millis(200));
+        assert_eq!(progressive_delay(&config, 2), Duration::from_millis(400));
+        assert_eq!(progressive_delay(&config, 3), Duration::from_millis(800));
+        assert_eq!(progressive_delay(&config, 10), Duration::from_secs(5));
+        assert_eq!(progressive_delay(&config, 1000), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_locked_for_secs() {
+        let now = Utc::now();
+        let locked = failure(5, now, Some(now + chrono::Duration::seconds(90)));
+        assert_eq!(locked.locked_for_secs(now), Some(90));
+
+        let expired = failure(5, now, Some(now - chrono::Duration::seconds(1)));
+        assert_eq!(expired.locked_for_secs(now), None);
+    }
+}
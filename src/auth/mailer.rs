@@ -0,0 +1,82 @@
+// Outbound email abstraction for authentication flows
+
+use axum::async_trait;
+use crate::auth::error::AuthError;
+use tracing::info;
+
+/// Mailer used to deliver authentication emails
+///
+/// Implementations wrap a concrete transport (SMTP, a provider API, ...).
+/// The service only depends on this trait so delivery can be swapped or
+/// stubbed in tests.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Send an email verification token to the given address
+    async fn send_verification_email(&self, to: &str, token: &str) -> Result<(), AuthError>;
+}
+
+/// Mailer that writes outgoing emails to the application log
+///
+/// Used as the default when no transport is configured, e.g. in development.
+#[derive(Debug, Clone, Default)]
+pub struct LogMailer {
+    verification_url: Option<String>,
+}
+
+impl LogMailer {
+    /// Create a new LogMailer
+    /// Reads EMAIL_VERIFICATION_URL to build a clickable link (token appended as ?token=)
+    pub fn new() -> Self {
+        Self {
+            verification_url: std::env::var("EMAIL_VERIFICATION_URL").ok(),
+        }
+    }
+
+    /// Build the link or raw token included in the verification email
+    fn verification_link(&self, token: &str) -> String {
+        match &self.verification_url {
+            Some(url) => format!("{}?token={}", url, token),
+            None => token.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send_verification_email(&self, to: &str, token: &str) -> Result<(), AuthError> {
+        info!(
+            "Verification email for {}: {}",
+            to,
+            self.verification_link(token)
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_link_without_url_is_raw_token() {
+        let mailer = LogMailer::default();
+        assert_eq!(mailer.verification_link("abc123"), "abc123");
+    }
+
+    #[test]
+    fn test_verification_link_with_url_appends_token() {
+        let mailer = LogMailer {
+            verification_url: Some("https://coffee.example/verify".to_string()),
+        };
+        assert_eq!(
+            mailer.verification_link("abc123"),
+            "https://coffee.example/verify?token=abc123"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_mailer_send_succeeds() {
+        let mailer = LogMailer::default();
+        assert!(mailer.send_verification_email("user@example.com", "abc123").await.is_ok());
+    }
+}
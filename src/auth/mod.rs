@@ -3,16 +3,32 @@
 
 pub mod error;
 pub mod handlers;
+pub mod keys;
+pub mod lockout;
+pub mod mailer;
 pub mod middleware;
 pub mod models;
 pub mod password;
 pub mod repository;
 pub mod service;
 pub mod token;
+pub mod totp;
 
 // Re-export commonly used types
 pub use error::AuthError;
-pub use handlers::{login_handler, me_handler, refresh_handler, register_handler};
-pub use middleware::{AuthenticatedUser, RequireRole};
-pub use models::{AuthResponse, LoginRequest, RefreshRequest, RegisterRequest, Role, User, UserResponse};
+pub use handlers::{
+    disable_user_handler, enable_user_handler, force_logout_handler, get_user_handler,
+    jwks_handler, list_roles_handler, list_sessions_handler, list_signing_keys_handler,
+    list_users_handler, login_handler, me_handler, refresh_handler, register_handler,
+    resend_verification_handler, revoke_session_handler, rotate_signing_key_handler,
+    disable_two_factor_handler, enable_two_factor_handler, regenerate_recovery_codes_handler,
+    setup_two_factor_handler, two_factor_status_handler, verify_two_factor_handler, unlock_user_handler, update_role_permissions_handler,
+    update_user_role_handler, verify_email_handler,
+};
+pub use mailer::{LogMailer, Mailer};
+pub use middleware::{AuthenticatedUser, ClientInfo, RequirePermission, RequireRole, VerifiedUser};
+pub use models::{
+    AuthResponse, LoginRequest, LoginResponse, Permission, RefreshRequest, RegisterRequest, Role, User,
+    UserResponse,
+};
 pub use service::AuthService;
@@ -6,37 +6,57 @@ use sqlx::FromRow;
 use validator::Validate;
 use utoipa::ToSchema;
 
-/// User role enum for authorization
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "text", rename_all = "lowercase")]
-#[serde(rename_all = "lowercase")]
-pub enum Role {
-    Admin,
-    User,
-}
+/// User role for authorization
+/// Roles are rows of the roles table; the permissions granted to each role are
+/// stored in the role_permissions table. `admin`, `user` and `barista` are
+/// seeded by the migrations, further roles can be added to the table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(transparent)]
+#[serde(try_from = "String", into = "String")]
+#[schema(value_type = String, example = "user")]
+pub struct Role(String);
 
 impl Role {
+    /// Full access to all management endpoints
+    pub fn admin() -> Self {
+        Role("admin".to_string())
+    }
+
+    /// Customer account, assigned on registration
+    pub fn user() -> Self {
+        Role("user".to_string())
+    }
+
+    /// Shop staff who progress orders
+    pub fn barista() -> Self {
+        Role("barista".to_string())
+    }
+
     /// Convert role to string representation
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Role::Admin => "admin",
-            Role::User => "user",
-        }
+    pub fn as_str(&self) -> &str {
+        &self.0
     }
     
-    /// Parse role from string
+    /// Parse a role name
+    /// Only checks the name's format; whether the role exists is checked
+    /// against the roles table when it is assigned
     pub fn from_str(s: &str) -> Result<Self, String> {
-        match s.to_lowercase().as_str() {
-            "admin" => Ok(Role::Admin),
-            "user" => Ok(Role::User),
-            _ => Err(format!("Invalid role: {}", s)),
+        let name = s.trim().to_lowercase();
+        let valid = !name.is_empty()
+            && name.len() <= 64
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+
+        if valid {
+            Ok(Role(name))
+        } else {
+            Err(format!("Invalid role: {}", s))
         }
     }
 }
 
 impl Default for Role {
     fn default() -> Self {
-        Role::User
+        Role::user()
     }
 }
 
@@ -46,6 +66,97 @@ impl std::fmt::Display for Role {
     }
 }
 
+impl TryFrom<String> for Role {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Role::from_str(&value)
+    }
+}
+
+impl From<Role> for String {
+    fn from(role: Role) -> Self {
+        role.0
+    }
+}
+
+/// Named permission checked by `RequirePermission`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum Permission {
+    #[serde(rename = "menu:write")]
+    MenuWrite,
+    #[serde(rename = "orders:manage")]
+    OrdersManage,
+    #[serde(rename = "pricing:write")]
+    PricingWrite,
+    #[serde(rename = "analytics:read")]
+    AnalyticsRead,
+    #[serde(rename = "users:manage")]
+    UsersManage,
+    #[serde(rename = "keys:manage")]
+    KeysManage,
+    #[serde(rename = "reviews:moderate")]
+    ReviewsModerate,
+    #[serde(rename = "reviews:reply")]
+    ReviewsReply,
+}
+
+impl Permission {
+    /// All known permissions
+    pub const ALL: [Permission; 8] = [
+        Permission::MenuWrite,
+        Permission::OrdersManage,
+        Permission::PricingWrite,
+        Permission::AnalyticsRead,
+        Permission::UsersManage,
+        Permission::KeysManage,
+        Permission::ReviewsModerate,
+        Permission::ReviewsReply,
+    ];
+
+    /// Convert permission to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::MenuWrite => "menu:write",
+            Permission::OrdersManage => "orders:manage",
+            Permission::PricingWrite => "pricing:write",
+            Permission::AnalyticsRead => "analytics:read",
+            Permission::UsersManage => "users:manage",
+            Permission::KeysManage => "keys:manage",
+            Permission::ReviewsModerate => "reviews:moderate",
+            Permission::ReviewsReply => "reviews:reply",
+        }
+    }
+    
+    /// Parse permission from string
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == s)
+            .ok_or_else(|| format!("Invalid permission: {}", s))
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Role with the permissions currently granted to it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleDefinition {
+    pub name: Role,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+}
+
+/// Replace the permission set of a role
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRolePermissionsRequest {
+    pub permissions: Vec<Permission>,
+}
+
 /// User database model
 #[derive(Debug, Clone, FromRow)]
 pub struct User {
@@ -54,6 +165,27 @@ pub struct User {
     pub password_hash: String,
     pub role: Role,
     pub created_at: DateTime<Utc>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub sessions_revoked_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    /// Whether the user has confirmed their email address
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    /// Whether the account has been disabled by an admin
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+
+    /// Whether a token issued at `issued_at` (unix seconds) predates a forced logout
+    pub fn is_token_revoked(&self, issued_at: i64) -> bool {
+        self.sessions_revoked_at
+            .is_some_and(|revoked_at| issued_at < revoked_at.timestamp())
+    }
 }
 
 /// User response model (excludes password_hash)
@@ -63,11 +195,15 @@ pub struct UserResponse {
     pub email: String,
     pub role: Role,
     pub created_at: DateTime<Utc>,
+    pub email_verified: bool,
+    pub disabled: bool,
 }
 
 impl From<User> for UserResponse {
     fn from(user: User) -> Self {
         Self {
+            email_verified: user.is_email_verified(),
+            disabled: user.is_disabled(),
             id: user.id,
             email: user.email,
             role: user.role,
@@ -76,6 +212,36 @@ impl From<User> for UserResponse {
     }
 }
 
+/// Admin view of a user with account activity
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AdminUserDetail {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub order_count: i64,
+    pub loyalty_balance: i32,
+}
+
+/// Query parameters for the admin user list
+#[derive(Debug, Deserialize)]
+pub struct UserListQuery {
+    /// Page number (1-indexed, defaults to 1)
+    pub page: Option<u32>,
+    /// Items per page (defaults to 20, max 100)
+    pub limit: Option<u32>,
+    /// Case-insensitive partial email match
+    pub search: Option<String>,
+    /// Filter by role
+    pub role: Option<Role>,
+    /// Filter by disabled status
+    pub disabled: Option<bool>,
+}
+
+/// Role change request DTO
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRoleRequest {
+    pub role: Role,
+}
+
 /// Refresh token database model
 #[derive(Debug, Clone, FromRow)]
 pub struct RefreshToken {
@@ -84,6 +250,218 @@ pub struct RefreshToken {
     pub token_hash: String,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// Client details recorded on a session at login and refresh
+#[derive(Debug, Clone, Default)]
+pub struct SessionMetadata {
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// Active login session (one per device) returned to its owner
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub id: i32,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session the request was made from
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn from_session(session: RefreshToken, current_session_id: Option<i32>) -> Self {
+        Self {
+            current: current_session_id == Some(session.id),
+            id: session.id,
+            device_name: session.device_name,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+        }
+    }
+}
+
+/// Stored TOTP enrolment for a user
+#[derive(Debug, Clone, FromRow)]
+pub struct TwoFactorRecord {
+    pub user_id: i32,
+    pub secret: String,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TwoFactorRecord {
+    /// Whether enrolment has been confirmed with a valid code
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+}
+
+/// Pending second login step issued after a correct password
+#[derive(Debug, Clone, FromRow)]
+pub struct TwoFactorChallenge {
+    pub id: i32,
+    pub user_id: i32,
+    pub device_name: Option<String>,
+}
+
+/// Stored JWT signing key (private key material included)
+#[derive(Debug, Clone, FromRow)]
+pub struct SigningKeyRecord {
+    pub kid: String,
+    pub algorithm: String,
+    pub private_key: Vec<u8>,
+    pub public_jwk: String,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl SigningKeyRecord {
+    /// Whether this key signs new tokens at `now`
+    pub fn signs_at(&self, now: DateTime<Utc>) -> bool {
+        self.activated_at <= now && self.retired_at.is_none_or(|retired_at| retired_at > now)
+    }
+
+    /// Whether this key signs now or will sign once activated
+    pub fn is_current(&self) -> bool {
+        self.retired_at.is_none()
+    }
+
+    /// Whether tokens signed with this key are still accepted at `now`
+    pub fn verifies_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// Signing key metadata returned to admins (never includes private material)
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SigningKeyInfo {
+    pub kid: String,
+    pub algorithm: String,
+    /// Whether the key is signing new tokens right now
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    /// When the key starts signing; rotated keys are published before this
+    pub activated_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    /// Tokens signed with this key stop verifying after this time
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<SigningKeyRecord> for SigningKeyInfo {
+    fn from(record: SigningKeyRecord) -> Self {
+        Self {
+            active: record.signs_at(Utc::now()),
+            kid: record.kid,
+            algorithm: record.algorithm,
+            created_at: record.created_at,
+            activated_at: record.activated_at,
+            retired_at: record.retired_at,
+            expires_at: record.expires_at,
+        }
+    }
+}
+
+/// Failed-login counter for an account or client IP
+#[derive(Debug, Clone, FromRow)]
+pub struct LoginFailure {
+    pub scope: String,
+    pub key: String,
+    pub failure_count: i32,
+    pub last_failure_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginFailure {
+    /// Seconds until the lock expires, if currently locked
+    pub fn locked_for_secs(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.locked_until
+            .filter(|until| *until > now)
+            .map(|until| (until - now).num_seconds().max(1))
+    }
+}
+
+/// Counter scope for failed login tracking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginFailureScope {
+    Account,
+    Ip,
+}
+
+impl LoginFailureScope {
+    /// Convert scope to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginFailureScope::Account => "account",
+            LoginFailureScope::Ip => "ip",
+        }
+    }
+}
+
+/// Security event types recorded in the security_events table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityEventType {
+    LoginFailed,
+    LoginBlocked,
+    AccountLocked,
+    IpBlocked,
+    AccountUnlocked,
+    AccountDisabled,
+    AccountEnabled,
+    SessionsRevoked,
+    RoleChanged,
+    RolePermissionsChanged,
+    SigningKeyRotated,
+    SessionRevoked,
+    TwoFactorEnabled,
+    TwoFactorDisabled,
+    RecoveryCodeUsed,
+    RecoveryCodesRegenerated,
+}
+
+impl SecurityEventType {
+    /// Convert event type to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityEventType::LoginFailed => "login_failed",
+            SecurityEventType::LoginBlocked => "login_blocked",
+            SecurityEventType::AccountLocked => "account_locked",
+            SecurityEventType::IpBlocked => "ip_blocked",
+            SecurityEventType::AccountUnlocked => "account_unlocked",
+            SecurityEventType::AccountDisabled => "account_disabled",
+            SecurityEventType::AccountEnabled => "account_enabled",
+            SecurityEventType::SessionsRevoked => "sessions_revoked",
+            SecurityEventType::RoleChanged => "role_changed",
+            SecurityEventType::RolePermissionsChanged => "role_permissions_changed",
+            SecurityEventType::SigningKeyRotated => "signing_key_rotated",
+            SecurityEventType::SessionRevoked => "session_revoked",
+            SecurityEventType::TwoFactorEnabled => "two_factor_enabled",
+            SecurityEventType::TwoFactorDisabled => "two_factor_disabled",
+            SecurityEventType::RecoveryCodeUsed => "recovery_code_used",
+            SecurityEventType::RecoveryCodesRegenerated => "recovery_codes_regenerated",
+        }
+    }
+}
+
+impl std::fmt::Display for SecurityEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 /// Registration request DTO
@@ -101,6 +479,10 @@ pub struct LoginRequest {
     #[validate(email)]
     pub email: String,
     pub password: String,
+    /// Optional label shown in the session list (e.g. "Pixel 8")
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub device_name: Option<String>,
 }
 
 /// Token refresh request DTO
@@ -109,6 +491,18 @@ pub struct RefreshRequest {
     pub refresh_token: String,
 }
 
+/// Email verification request DTO
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+/// Generic message response DTO
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
 /// Authentication response DTO
 #[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
@@ -116,3 +510,141 @@ pub struct AuthResponse {
     pub refresh_token: String,
     pub user: UserResponse,
 }
+
+/// Returned by login instead of tokens when the account has two-factor authentication
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorChallengeResponse {
+    /// Always true; lets clients tell this apart from a token response
+    pub two_factor_required: bool,
+    /// Pass to POST /api/auth/2fa/verify together with a code
+    pub challenge_token: String,
+    /// Seconds until the challenge expires
+    pub expires_in: i64,
+}
+
+/// Login response DTO: a token pair, or a challenge for the second factor
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    TwoFactorRequired(TwoFactorChallengeResponse),
+}
+
+/// Second login step request DTO
+/// Exactly one of `code` (from the authenticator app) or `recovery_code` is required
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TwoFactorVerifyRequest {
+    pub challenge_token: String,
+    #[serde(default)]
+    #[validate(length(max = 16))]
+    pub code: Option<String>,
+    #[serde(default)]
+    #[validate(length(max = 32))]
+    pub recovery_code: Option<String>,
+}
+
+/// Request DTO carrying a current authenticator code
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TwoFactorCodeRequest {
+    #[validate(length(min = 6, max = 16))]
+    pub code: String,
+}
+
+/// Two-factor enrolment details for the authenticator app
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorSetupResponse {
+    /// Base32 shared secret, for manual entry
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code
+    pub provisioning_uri: String,
+}
+
+/// Newly issued recovery codes (shown once; only hashes are stored)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Two-factor status of the current user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_user() -> User {
+        User {
+            id: 1,
+            email: "user@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::user(),
+            created_at: Utc::now(),
+            email_verified_at: None,
+            disabled_at: None,
+            sessions_revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_permission_string_round_trip() {
+        for permission in Permission::ALL {
+            assert_eq!(Permission::from_str(permission.as_str()), Ok(permission));
+            let json = serde_json::to_string(&permission).unwrap();
+            assert_eq!(json, format!("\"{}\"", permission.as_str()));
+        }
+        assert!(Permission::from_str("menu:delete").is_err());
+    }
+
+    #[test]
+    fn test_barista_role_parses() {
+        assert_eq!(Role::from_str("Barista"), Ok(Role::barista()));
+        assert_eq!(Role::barista().to_string(), "barista");
+    }
+
+    #[test]
+    fn test_roles_are_not_limited_to_the_seeded_ones() {
+        assert_eq!(Role::from_str("Shift_Lead").map(|role| role.to_string()), Ok("shift_lead".to_string()));
+        assert!(Role::from_str("").is_err());
+        assert!(Role::from_str("head barista").is_err());
+
+        let role: Role = serde_json::from_str("\"roaster\"").unwrap();
+        assert_eq!(serde_json::to_string(&role).unwrap(), "\"roaster\"");
+        assert!(serde_json::from_str::<Role>("\"drop table\"").is_err());
+    }
+
+    #[test]
+    fn test_token_not_revoked_without_forced_logout() {
+        let user = test_user();
+        assert!(!user.is_token_revoked(0));
+    }
+
+    #[test]
+    fn test_tokens_issued_before_forced_logout_are_revoked() {
+        let revoked_at = Utc::now();
+        let user = User {
+            sessions_revoked_at: Some(revoked_at),
+            ..test_user()
+        };
+
+        assert!(user.is_token_revoked(revoked_at.timestamp() - 60));
+        assert!(!user.is_token_revoked(revoked_at.timestamp()));
+        assert!(!user.is_token_revoked(revoked_at.timestamp() + 60));
+    }
+
+    #[test]
+    fn test_user_response_reflects_account_status() {
+        let user = User {
+            email_verified_at: Some(Utc::now()),
+            disabled_at: Some(Utc::now()),
+            ..test_user()
+        };
+
+        let response = UserResponse::from(user);
+        assert!(response.email_verified);
+        assert!(response.disabled);
+    }
+}
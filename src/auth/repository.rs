@@ -1,6 +1,15 @@
 // Database repositories for users and tokens
 
-use crate::auth::{error::AuthError, models::{RefreshToken, User}};
+use crate::auth::{
+    error::AuthError,
+    keys::GeneratedKey,
+    lockout::next_failure_state,
+    models::{
+        LoginFailure, LoginFailureScope, Permission, RefreshToken, Role, RoleDefinition,
+        SecurityEventType, SessionMetadata, SigningKeyRecord, TwoFactorChallenge, TwoFactorRecord,
+        User,
+    },
+};
 use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 
@@ -18,7 +27,7 @@ impl UserRepository {
     /// Create a new user
     pub async fn create_user(&self, email: &str, password_hash: &str) -> Result<User, AuthError> {
         let user = sqlx::query_as::<_, User>(
-            "INSERT INTO users (email, password_hash, role) VALUES ($1, $2, $3) RETURNING id, email, password_hash, role, created_at"
+            "INSERT INTO users (email, password_hash, role) VALUES ($1, $2, $3) RETURNING id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at"
         )
         .bind(email)
         .bind(password_hash)
@@ -41,7 +50,7 @@ impl UserRepository {
     /// Find a user by email (case-insensitive)
     pub async fn find_by_email(&self, email: &str) -> Result<Option<User>, AuthError> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, email, password_hash, role, created_at FROM users WHERE LOWER(email) = LOWER($1)"
+            "SELECT id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at FROM users WHERE LOWER(email) = LOWER($1)"
         )
         .bind(email)
         .fetch_optional(&self.pool)
@@ -54,7 +63,7 @@ impl UserRepository {
     /// Find a user by ID
     pub async fn find_by_id(&self, id: i32) -> Result<Option<User>, AuthError> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, email, password_hash, role, created_at FROM users WHERE id = $1"
+            "SELECT id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at FROM users WHERE id = $1"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -87,7 +96,7 @@ impl UserRepository {
         let role = role.unwrap_or_default(); // Use default User role if not specified
         
         let user = sqlx::query_as::<_, User>(
-            "INSERT INTO users (email, password_hash, role) VALUES ($1, $2, $3) RETURNING id, email, password_hash, role, created_at"
+            "INSERT INTO users (email, password_hash, role) VALUES ($1, $2, $3) RETURNING id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at"
         )
         .bind(email)
         .bind(password_hash)
@@ -112,10 +121,10 @@ impl UserRepository {
     pub async fn update_user_role(
         &self,
         user_id: i32,
-        new_role: crate::auth::models::Role,
+        new_role: &crate::auth::models::Role,
     ) -> Result<User, AuthError> {
         let user = sqlx::query_as::<_, User>(
-            "UPDATE users SET role = $1 WHERE id = $2 RETURNING id, email, password_hash, role, created_at"
+            "UPDATE users SET role = $1 WHERE id = $2 RETURNING id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at"
         )
         .bind(new_role)
         .bind(user_id)
@@ -126,6 +135,204 @@ impl UserRepository {
 
         Ok(user)
     }
+
+    /// Mark a user's email address as verified
+    /// Keeps the original timestamp if the user was already verified
+    pub async fn mark_email_verified(&self, user_id: i32) -> Result<User, AuthError> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1 RETURNING id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AuthError::DatabaseError("User not found".to_string()))?;
+
+        Ok(user)
+    }
+
+    /// Search users with optional email, role and status filters
+    /// Returns one page of users (newest first) and the total match count
+    pub async fn search_users(
+        &self,
+        search: Option<&str>,
+        role: Option<&Role>,
+        disabled: Option<bool>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<User>, i64), AuthError> {
+        let pattern = search.map(contains_pattern);
+        let filter = "($1::TEXT IS NULL OR email ILIKE $1 ESCAPE '\\')
+             AND ($2::TEXT IS NULL OR role = $2)
+             AND ($3::BOOLEAN IS NULL OR (disabled_at IS NOT NULL) = $3)";
+
+        let users = sqlx::query_as::<_, User>(&format!(
+            "SELECT id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at
+             FROM users
+             WHERE {}
+             ORDER BY created_at DESC, id DESC
+             LIMIT $4 OFFSET $5",
+            filter
+        ))
+        .bind(&pattern)
+        .bind(role.map(|r| r.as_str()))
+        .bind(disabled)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        let total: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM users WHERE {}", filter))
+            .bind(&pattern)
+            .bind(role.map(|r| r.as_str()))
+            .bind(disabled)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok((users, total.0))
+    }
+
+    /// Get a user's order count and loyalty points balance
+    pub async fn get_activity_summary(&self, user_id: i32) -> Result<(i64, i32), AuthError> {
+        let summary: (i64, i32) = sqlx::query_as(
+            "SELECT
+                (SELECT COUNT(*) FROM orders WHERE user_id = $1),
+                COALESCE((SELECT points_balance FROM customer_loyalty WHERE customer_id = $1), 0)"
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(summary)
+    }
+
+    /// Disable or re-enable a user account
+    /// Deleted (anonymised) accounts are reported as not found
+    pub async fn set_disabled(&self, user_id: i32, disabled: bool) -> Result<User, AuthError> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users
+             SET disabled_at = CASE WHEN $1 THEN COALESCE(disabled_at, NOW()) ELSE NULL END
+             WHERE id = $2 AND deleted_at IS NULL
+             RETURNING id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at"
+        )
+        .bind(disabled)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?
+        .ok_or(AuthError::UserNotFound)?;
+
+        Ok(user)
+    }
+
+    /// Revoke all access tokens issued before now
+    pub async fn revoke_sessions(&self, user_id: i32) -> Result<User, AuthError> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET sessions_revoked_at = NOW() WHERE id = $1
+             RETURNING id, email, password_hash, role, created_at, email_verified_at, disabled_at, sessions_revoked_at"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?
+        .ok_or(AuthError::UserNotFound)?;
+
+        Ok(user)
+    }
+
+    /// Whether a role is defined in the roles table
+    pub async fn role_exists(&self, role: &Role) -> Result<bool, AuthError> {
+        let exists: (bool,) = sqlx::query_as("SELECT EXISTS (SELECT 1 FROM roles WHERE name = $1)")
+            .bind(role.as_str())
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(exists.0)
+    }
+
+    /// Get the permissions granted to a role
+    pub async fn get_role_permissions(&self, role: &Role) -> Result<Vec<Permission>, AuthError> {
+        let names: Vec<(String,)> = sqlx::query_as(
+            "SELECT permission FROM role_permissions WHERE role = $1 ORDER BY permission"
+        )
+        .bind(role.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(Self::parse_permissions(names.into_iter().map(|(name,)| name)))
+    }
+
+    /// List roles with their permission sets
+    pub async fn list_roles(&self) -> Result<Vec<RoleDefinition>, AuthError> {
+        let rows: Vec<(Role, String, Vec<String>)> = sqlx::query_as(
+            "SELECT r.name, r.description,
+                    COALESCE(ARRAY_AGG(rp.permission ORDER BY rp.permission)
+                             FILTER (WHERE rp.permission IS NOT NULL), '{}')
+             FROM roles r
+             LEFT JOIN role_permissions rp ON rp.role = r.name
+             GROUP BY r.name, r.description
+             ORDER BY r.name"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        let roles = rows
+            .into_iter()
+            .map(|(name, description, permissions)| RoleDefinition {
+                name,
+                description,
+                permissions: Self::parse_permissions(permissions.into_iter()),
+            })
+            .collect();
+
+        Ok(roles)
+    }
+
+    /// Replace the permission set of a role
+    pub async fn set_role_permissions(&self, role: &Role, permissions: &[Permission]) -> Result<(), AuthError> {
+        let mut tx = self.pool.begin().await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("DELETE FROM role_permissions WHERE role = $1")
+            .bind(role.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        let names: Vec<&str> = permissions.iter().map(|p| p.as_str()).collect();
+        sqlx::query(
+            "INSERT INTO role_permissions (role, permission) SELECT $1, UNNEST($2::TEXT[])"
+        )
+        .bind(role.as_str())
+        .bind(&names)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Parse stored permission names, skipping unknown values
+    fn parse_permissions(names: impl Iterator<Item = String>) -> Vec<Permission> {
+        names
+            .filter_map(|name| match Permission::from_str(&name) {
+                Ok(permission) => Some(permission),
+                Err(e) => {
+                    tracing::warn!("Ignoring unknown permission: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 /// Token repository for refresh token operations
@@ -146,38 +353,49 @@ impl TokenRepository {
         format!("{:x}", hasher.finalize())
     }
 
-    /// Store a refresh token (hashed with SHA-256)
+    /// Store a refresh token (hashed with SHA-256) as a new login session
+    /// Returns the session id
     pub async fn store_refresh_token(
         &self,
         user_id: i32,
         token: &str,
         expires_at: chrono::DateTime<chrono::Utc>,
-    ) -> Result<(), AuthError> {
+        session: &SessionMetadata,
+    ) -> Result<i32, AuthError> {
         let token_hash = Self::hash_token(token);
 
-        sqlx::query(
-            "INSERT INTO refresh_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)"
+        let (session_id,): (i32,) = sqlx::query_as(
+            "INSERT INTO refresh_tokens (user_id, token_hash, expires_at, device_name, user_agent, ip_address) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING id"
         )
         .bind(user_id)
         .bind(token_hash)
         .bind(expires_at)
-        .execute(&self.pool)
+        .bind(&session.device_name)
+        .bind(&session.user_agent)
+        .bind(&session.ip_address)
+        .fetch_one(&self.pool)
         .await
         .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
-        Ok(())
+        Ok(session_id)
     }
 
-    /// Verify a refresh token exists and is not expired
-    pub async fn verify_refresh_token(&self, token: &str) -> Result<Option<RefreshToken>, AuthError> {
+    /// Verify a refresh token exists, is not expired and its session has been used since `idle_cutoff`
+    pub async fn verify_refresh_token(
+        &self,
+        token: &str,
+        idle_cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<RefreshToken>, AuthError> {
         let token_hash = Self::hash_token(token);
 
         let refresh_token = sqlx::query_as::<_, RefreshToken>(
-            "SELECT id, user_id, token_hash, expires_at, created_at 
+            "SELECT id, user_id, token_hash, expires_at, created_at, device_name, user_agent, ip_address, last_used_at 
              FROM refresh_tokens 
-             WHERE token_hash = $1 AND expires_at > NOW()"
+             WHERE token_hash = $1 AND expires_at > NOW() AND last_used_at > $2"
         )
         .bind(token_hash)
+        .bind(idle_cutoff)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
@@ -185,6 +403,88 @@ impl TokenRepository {
         Ok(refresh_token)
     }
 
+    /// Replace a session's refresh token and mark the session as used
+    /// Returns false when `old_token` was already rotated (e.g. a concurrent refresh)
+    pub async fn rotate_refresh_token(
+        &self,
+        session_id: i32,
+        old_token: &str,
+        new_token: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+        session: &SessionMetadata,
+    ) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            "UPDATE refresh_tokens \
+             SET token_hash = $3, expires_at = $4, last_used_at = NOW(), \
+                 user_agent = COALESCE($5, user_agent), ip_address = COALESCE($6, ip_address) \
+             WHERE id = $1 AND token_hash = $2"
+        )
+        .bind(session_id)
+        .bind(Self::hash_token(old_token))
+        .bind(Self::hash_token(new_token))
+        .bind(expires_at)
+        .bind(&session.user_agent)
+        .bind(&session.ip_address)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List a user's sessions that are neither expired nor idle, most recently used first
+    pub async fn list_sessions(
+        &self,
+        user_id: i32,
+        idle_cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<RefreshToken>, AuthError> {
+        sqlx::query_as::<_, RefreshToken>(
+            "SELECT id, user_id, token_hash, expires_at, created_at, device_name, user_agent, ip_address, last_used_at 
+             FROM refresh_tokens 
+             WHERE user_id = $1 AND expires_at > NOW() AND last_used_at > $2 
+             ORDER BY last_used_at DESC"
+        )
+        .bind(user_id)
+        .bind(idle_cutoff)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Whether a user's session is neither expired nor idle
+    pub async fn is_session_active(
+        &self,
+        session_id: i32,
+        user_id: i32,
+        idle_cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, AuthError> {
+        let active: Option<(i32,)> = sqlx::query_as(
+            "SELECT id FROM refresh_tokens \
+             WHERE id = $1 AND user_id = $2 AND expires_at > NOW() AND last_used_at > $3"
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(idle_cutoff)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(active.is_some())
+    }
+
+    /// Delete one of a user's sessions
+    /// Returns false when the session does not exist or belongs to another user
+    pub async fn delete_session(&self, user_id: i32, session_id: i32) -> Result<bool, AuthError> {
+        let result = sqlx::query("DELETE FROM refresh_tokens WHERE id = $1 AND user_id = $2")
+            .bind(session_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Invalidate a refresh token
     pub async fn invalidate_token(&self, token: &str) -> Result<(), AuthError> {
         let token_hash = Self::hash_token(token);
@@ -198,13 +498,499 @@ impl TokenRepository {
         Ok(())
     }
 
-    /// Delete expired tokens
-    pub async fn delete_expired_tokens(&self) -> Result<u64, AuthError> {
-        let result = sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < NOW()")
+    /// Delete all refresh tokens for a user
+    pub async fn invalidate_user_tokens(&self, user_id: i32) -> Result<u64, AuthError> {
+        let result = sqlx::query("DELETE FROM refresh_tokens WHERE user_id = $1")
+            .bind(user_id)
             .execute(&self.pool)
             .await
             .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
         Ok(result.rows_affected())
     }
+
+    /// Delete expired tokens and sessions idle since `idle_cutoff`
+    pub async fn delete_expired_tokens(
+        &self,
+        idle_cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, AuthError> {
+        let result = sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < NOW() OR last_used_at <= $1")
+            .bind(idle_cutoff)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Store an email verification token (hashed with SHA-256)
+    /// Any outstanding tokens for the user are invalidated so only the latest link works
+    pub async fn store_verification_token(
+        &self,
+        user_id: i32,
+        token: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AuthError> {
+        let token_hash = Self::hash_token(token);
+
+        sqlx::query(
+            "DELETE FROM email_verification_tokens WHERE user_id = $1 AND used_at IS NULL"
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO email_verification_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)"
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Consume an email verification token
+    /// Returns the owning user_id if the token exists, is unused and not expired
+    pub async fn consume_verification_token(&self, token: &str) -> Result<Option<i32>, AuthError> {
+        let token_hash = Self::hash_token(token);
+
+        let user_id: Option<(i32,)> = sqlx::query_as(
+            "UPDATE email_verification_tokens
+             SET used_at = NOW()
+             WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+             RETURNING user_id"
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(user_id.map(|(id,)| id))
+    }
+
+    /// Store a two-factor login challenge (hashed with SHA-256)
+    pub async fn store_two_factor_challenge(
+        &self,
+        user_id: i32,
+        token: &str,
+        device_name: Option<&str>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AuthError> {
+        sqlx::query(
+            "INSERT INTO two_factor_challenges (user_id, token_hash, device_name, expires_at) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(user_id)
+        .bind(Self::hash_token(token))
+        .bind(device_name)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Find a two-factor challenge that is unused and not expired
+    pub async fn find_two_factor_challenge(&self, token: &str) -> Result<Option<TwoFactorChallenge>, AuthError> {
+        sqlx::query_as::<_, TwoFactorChallenge>(
+            "SELECT id, user_id, device_name FROM two_factor_challenges
+             WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()"
+        )
+        .bind(Self::hash_token(token))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Mark a two-factor challenge as used
+    /// Returns false when it was already consumed (e.g. a concurrent verification)
+    pub async fn consume_two_factor_challenge(&self, challenge_id: i32) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            "UPDATE two_factor_challenges SET used_at = NOW() WHERE id = $1 AND used_at IS NULL"
+        )
+        .bind(challenge_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Security repository for failed-login counters and security events
+pub struct SecurityRepository {
+    pool: PgPool,
+}
+
+impl SecurityRepository {
+    /// Create a new SecurityRepository
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the failure counter for an account or IP
+    pub async fn get_failures(
+        &self,
+        scope: LoginFailureScope,
+        key: &str,
+    ) -> Result<Option<LoginFailure>, AuthError> {
+        let failure = sqlx::query_as::<_, LoginFailure>(
+            "SELECT scope, key, failure_count, last_failure_at, locked_until
+             FROM login_failures
+             WHERE scope = $1 AND key = $2"
+        )
+        .bind(scope.as_str())
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(failure)
+    }
+
+    /// Record a failed attempt and return the updated counter
+    /// The new count and lock are computed by `lockout::next_failure_state`
+    pub async fn record_failure(
+        &self,
+        scope: LoginFailureScope,
+        key: &str,
+        max_failures: i32,
+        lockout_duration: chrono::Duration,
+    ) -> Result<LoginFailure, AuthError> {
+        let mut tx = self.pool.begin().await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        // Lock the existing counter row (if any) so concurrent failures are serialised
+        let existing = sqlx::query_as::<_, LoginFailure>(
+            "SELECT scope, key, failure_count, last_failure_at, locked_until
+             FROM login_failures
+             WHERE scope = $1 AND key = $2
+             FOR UPDATE"
+        )
+        .bind(scope.as_str())
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        let now = chrono::Utc::now();
+        let next = next_failure_state(existing.as_ref(), now, max_failures, lockout_duration);
+
+        let failure = sqlx::query_as::<_, LoginFailure>(
+            "INSERT INTO login_failures (scope, key, failure_count, last_failure_at, locked_until)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (scope, key) DO UPDATE SET
+                failure_count = EXCLUDED.failure_count,
+                last_failure_at = EXCLUDED.last_failure_at,
+                locked_until = EXCLUDED.locked_until
+             RETURNING scope, key, failure_count, last_failure_at, locked_until"
+        )
+        .bind(scope.as_str())
+        .bind(key)
+        .bind(next.failure_count)
+        .bind(now)
+        .bind(next.locked_until)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(failure)
+    }
+
+    /// Clear the failure counter (and any lock) for an account or IP
+    /// Returns true if a counter existed
+    pub async fn clear_failures(&self, scope: LoginFailureScope, key: &str) -> Result<bool, AuthError> {
+        let result = sqlx::query("DELETE FROM login_failures WHERE scope = $1 AND key = $2")
+            .bind(scope.as_str())
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a security event
+    pub async fn record_event(
+        &self,
+        event_type: SecurityEventType,
+        user_id: Option<i32>,
+        ip_address: Option<&str>,
+        details: serde_json::Value,
+    ) -> Result<(), AuthError> {
+        sqlx::query(
+            "INSERT INTO security_events (user_id, event_type, ip_address, details) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(user_id)
+        .bind(event_type.as_str())
+        .bind(ip_address)
+        .bind(details)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Signing key repository for asymmetric JWT keys
+pub struct SigningKeyRepository {
+    pool: PgPool,
+}
+
+impl SigningKeyRepository {
+    /// Create a new SigningKeyRepository
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// List all stored keys, newest first
+    pub async fn list_keys(&self) -> Result<Vec<SigningKeyRecord>, AuthError> {
+        sqlx::query_as::<_, SigningKeyRecord>(
+            "SELECT kid, algorithm, private_key, public_jwk, created_at, activated_at, retired_at, expires_at \
+             FROM jwt_signing_keys ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Store a new key that starts signing at `activates_at`
+    /// The current key retires at the same moment and keeps verifying until `retired_key_expires_at`
+    pub async fn rotate(
+        &self,
+        key: &GeneratedKey,
+        activates_at: chrono::DateTime<chrono::Utc>,
+        retired_key_expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<SigningKeyRecord, AuthError> {
+        let mut tx = self.pool
+            .begin()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query(
+            "UPDATE jwt_signing_keys SET retired_at = $1, expires_at = $2 WHERE retired_at IS NULL"
+        )
+        .bind(activates_at)
+        .bind(retired_key_expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        let public_jwk = serde_json::to_string(&key.public_jwk)
+            .map_err(|e| AuthError::TokenGenerationError(e.to_string()))?;
+
+        let record = sqlx::query_as::<_, SigningKeyRecord>(
+            "INSERT INTO jwt_signing_keys (kid, algorithm, private_key, public_jwk, activated_at) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING kid, algorithm, private_key, public_jwk, created_at, activated_at, retired_at, expires_at"
+        )
+        .bind(&key.kid)
+        .bind(key.algorithm.as_str())
+        .bind(&key.private_key)
+        .bind(public_jwk)
+        .bind(activates_at)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(record)
+    }
+}
+
+/// Two-factor repository for TOTP secrets and recovery codes
+pub struct TwoFactorRepository {
+    pool: PgPool,
+}
+
+impl TwoFactorRepository {
+    /// Create a new TwoFactorRepository
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Find a user's TOTP enrolment (pending or enabled)
+    pub async fn find(&self, user_id: i32) -> Result<Option<TwoFactorRecord>, AuthError> {
+        sqlx::query_as::<_, TwoFactorRecord>(
+            "SELECT user_id, secret, enabled_at, last_used_step, created_at FROM user_two_factor WHERE user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Start (or restart) enrolment with a new secret
+    /// Returns false when two-factor authentication is already enabled
+    pub async fn store_pending_secret(&self, user_id: i32, secret: &str) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            "INSERT INTO user_two_factor (user_id, secret) VALUES ($1, $2)
+             ON CONFLICT (user_id) DO UPDATE
+             SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+             WHERE user_two_factor.enabled_at IS NULL"
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record `step` as the latest accepted code
+    /// Returns false when the step (or a later one) was already used, i.e. a replay
+    pub async fn record_used_step(&self, user_id: i32, step: i64) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            "UPDATE user_two_factor SET last_used_step = $2
+             WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)"
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Confirm enrolment and store its recovery code hashes
+    pub async fn enable(&self, user_id: i32, recovery_code_hashes: &[String]) -> Result<(), AuthError> {
+        let mut tx = self.pool
+            .begin()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("UPDATE user_two_factor SET enabled_at = NOW() WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Self::insert_recovery_codes(&mut tx, user_id, recovery_code_hashes).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Remove the enrolment and all recovery codes
+    pub async fn disable(&self, user_id: i32) -> Result<(), AuthError> {
+        let mut tx = self.pool
+            .begin()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("DELETE FROM two_factor_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("DELETE FROM user_two_factor WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Replace all recovery codes of a user
+    pub async fn replace_recovery_codes(&self, user_id: i32, recovery_code_hashes: &[String]) -> Result<(), AuthError> {
+        let mut tx = self.pool
+            .begin()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Self::insert_recovery_codes(&mut tx, user_id, recovery_code_hashes).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Delete existing recovery codes and insert the new hashes
+    async fn insert_recovery_codes(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user_id: i32,
+        recovery_code_hashes: &[String],
+    ) -> Result<(), AuthError> {
+        sqlx::query("DELETE FROM two_factor_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO two_factor_recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::text[])"
+        )
+        .bind(user_id)
+        .bind(recovery_code_hashes)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Unused recovery codes of a user as (id, hash) pairs
+    pub async fn unused_recovery_codes(&self, user_id: i32) -> Result<Vec<(i32, String)>, AuthError> {
+        sqlx::query_as(
+            "SELECT id, code_hash FROM two_factor_recovery_codes WHERE user_id = $1 AND used_at IS NULL"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))
+    }
+
+    /// Mark a recovery code as used
+    /// Returns false when it was already used
+    pub async fn use_recovery_code(&self, code_id: i32) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            "UPDATE two_factor_recovery_codes SET used_at = NOW() WHERE id = $1 AND used_at IS NULL"
+        )
+        .bind(code_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// ILIKE pattern matching `term` anywhere, with its wildcards taken literally
+fn contains_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_pattern_escapes_wildcards() {
+        assert_eq!(contains_pattern("alice"), "%alice%");
+        assert_eq!(contains_pattern("a_b%c"), "%a\\_b\\%c%");
+        assert_eq!(contains_pattern("back\\slash"), "%back\\\\slash%");
+    }
 }
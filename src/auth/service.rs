@@ -2,19 +2,54 @@
 
 use crate::auth::{
     error::AuthError,
-    models::{AuthResponse, User, UserResponse},
+    keys::{generate_signing_key, KeyRing},
+    lockout::{active_failure_count, progressive_delay},
+    mailer::{LogMailer, Mailer},
+    models::{
+        AdminUserDetail, AuthResponse, LoginFailure, LoginFailureScope, LoginResponse, Permission,
+        RecoveryCodesResponse, Role, RoleDefinition, SecurityEventType, SessionMetadata,
+        SessionResponse, SigningKeyInfo, TwoFactorChallengeResponse, TwoFactorRecord,
+        TwoFactorSetupResponse, TwoFactorStatusResponse, User, UserListQuery, UserResponse,
+    },
     password::PasswordService,
-    repository::{TokenRepository, UserRepository},
-    token::TokenService,
+    repository::{
+        SecurityRepository, SigningKeyRepository, TokenRepository, TwoFactorRepository, UserRepository,
+    },
+    token::{Claims, TokenService},
+    totp,
 };
+use crate::config::{AuthPolicyConfig, JwtSigningConfig, LoginProtectionConfig, SessionConfig};
+use jsonwebtoken::jwk::JwkSet;
+use crate::pagination::{PaginatedResponse, PaginationQuery};
 use chrono::Utc;
-use tracing::info;
+use rand::RngCore;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Email verification tokens are valid for 24 hours
+const VERIFICATION_TOKEN_HOURS: i64 = 24;
+
+/// Two-factor login challenges are valid for 5 minutes
+const TWO_FACTOR_CHALLENGE_SECS: i64 = 300;
+
+/// Issuer label shown in authenticator apps
+const TOTP_ISSUER: &str = "Coffee App";
 
 /// Authentication service coordinating all auth operations
 pub struct AuthService {
     user_repo: UserRepository,
     token_repo: TokenRepository,
+    security_repo: SecurityRepository,
+    two_factor_repo: TwoFactorRepository,
     token_service: TokenService,
+    mailer: Arc<dyn Mailer>,
+    policy: AuthPolicyConfig,
+    login_protection: LoginProtectionConfig,
+    sessions: SessionConfig,
+    signing_key_repo: Option<SigningKeyRepository>,
+    jwt_signing: JwtSigningConfig,
+    legacy_jwt_secret: Option<String>,
 }
 
 impl AuthService {
@@ -22,18 +57,82 @@ impl AuthService {
     pub fn new(
         user_repo: UserRepository,
         token_repo: TokenRepository,
+        security_repo: SecurityRepository,
+        two_factor_repo: TwoFactorRepository,
         _password_service: PasswordService,
         token_service: TokenService,
     ) -> Self {
         Self {
             user_repo,
             token_repo,
+            security_repo,
+            two_factor_repo,
             token_service,
+            mailer: Arc::new(LogMailer::new()),
+            policy: AuthPolicyConfig::default(),
+            login_protection: LoginProtectionConfig::default(),
+            sessions: SessionConfig::default(),
+            signing_key_repo: None,
+            jwt_signing: JwtSigningConfig::default(),
+            legacy_jwt_secret: None,
         }
     }
 
+    /// Replace the mailer used for verification emails
+    pub fn with_mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = mailer;
+        self
+    }
+
+    /// Replace the account policy switches
+    pub fn with_policy(mut self, policy: AuthPolicyConfig) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Account policy switches
+    pub fn policy(&self) -> &AuthPolicyConfig {
+        &self.policy
+    }
+
+    /// Replace the brute-force protection thresholds
+    pub fn with_login_protection(mut self, config: LoginProtectionConfig) -> Self {
+        self.login_protection = config;
+        self
+    }
+
+    /// Replace the session idle timeout
+    pub fn with_session_config(mut self, config: SessionConfig) -> Self {
+        self.sessions = config;
+        self
+    }
+
+    /// Sign tokens with rotating keys stored in the database (RS256/EdDSA)
+    /// `legacy_secret` keeps HS256 tokens issued with JWT_SECRET verifying
+    pub fn with_signing_keys(
+        mut self,
+        repo: SigningKeyRepository,
+        config: JwtSigningConfig,
+        legacy_secret: Option<String>,
+    ) -> Self {
+        self.signing_key_repo = Some(repo);
+        self.jwt_signing = config;
+        self.legacy_jwt_secret = legacy_secret;
+        self
+    }
+
+    /// Token service used to sign and validate tokens
+    pub fn token_service(&self) -> &TokenService {
+        &self.token_service
+    }
+
     /// Register a new user
-    pub async fn register(&self, email: &str, password: &str) -> Result<AuthResponse, AuthError> {
+    pub async fn register(
+        &self,
+        email: &str,
+        password: &str,
+        session: &SessionMetadata,
+    ) -> Result<AuthResponse, AuthError> {
         // Validate email format using regex
         let email_regex = regex::Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$")
             .map_err(|_| AuthError::ValidationError("Invalid email regex".to_string()))?;
@@ -56,14 +155,13 @@ impl AuthService {
         // Create user
         let user = self.user_repo.create_user(email, &password_hash).await?;
 
-        // Generate token pair
-        let (access_token, refresh_token) = self.token_service.generate_token_pair(user.id, &user.email, user.role)?;
-
-        // Calculate refresh token expiration (7 days from now)
-        let refresh_expires_at = Utc::now() + chrono::Duration::days(7);
+        // Send verification email (failure is logged; the user can request a resend)
+        if let Err(e) = self.send_verification_email(&user).await {
+            warn!("Failed to send verification email: user_id={}, error={}", user.id, e);
+        }
 
-        // Store refresh token
-        self.token_repo.store_refresh_token(user.id, &refresh_token, refresh_expires_at).await?;
+        // Start a session and issue its token pair
+        let (access_token, refresh_token) = self.start_session(&user, session).await?;
 
         // Return response
         Ok(AuthResponse {
@@ -74,26 +172,128 @@ impl AuthService {
     }
 
     /// Login a user
-    pub async fn login(&self, email: &str, password: &str) -> Result<AuthResponse, AuthError> {
-        // Find user by email
-        let user = self.user_repo.find_by_email(email).await?
-            .ok_or(AuthError::InvalidCredentials)?;
+    /// Failed attempts are counted per account and per client IP; repeated
+    /// failures slow down and then temporarily lock further attempts.
+    /// Accounts with two-factor authentication get a challenge instead of tokens.
+    pub async fn login(
+        &self,
+        email: &str,
+        password: &str,
+        session: &SessionMetadata,
+    ) -> Result<LoginResponse, AuthError> {
+        let client_ip = session.ip_address.as_deref();
+        let account_key = email.to_lowercase();
 
-        // Verify password
-        if !PasswordService::verify_password(password, &user.password_hash)? {
-            return Err(AuthError::InvalidCredentials);
+        // Reject blocked clients and locked accounts, then slow down repeated failures
+        self.throttle_login_attempt(&account_key, client_ip).await?;
+
+        // Find user by email and verify password
+        let user = self.user_repo.find_by_email(email).await?;
+        let password_valid = match &user {
+            Some(user) => PasswordService::verify_password(password, &user.password_hash)?,
+            None => false,
+        };
+
+        let user = match user {
+            Some(user) if password_valid => user,
+            user => {
+                return Err(self
+                    .handle_failed_login(&account_key, user.map(|u| u.id), client_ip)
+                    .await?);
+            }
+        };
+
+        // Successful login resets the account counter; with two-factor
+        // authentication only once the second step succeeds, so code guesses
+        // keep counting towards the lock
+        let two_factor_enabled = self.two_factor_enabled(user.id).await?;
+        if !two_factor_enabled {
+            self.security_repo.clear_failures(LoginFailureScope::Account, &account_key).await?;
         }
 
-        // Generate token pair
-        let (access_token, refresh_token) = self.token_service.generate_token_pair(user.id, &user.email, user.role)?;
+        // Disabled accounts cannot sign in
+        if user.is_disabled() {
+            self.record_security_event(
+                SecurityEventType::LoginBlocked,
+                Some(user.id),
+                client_ip,
+                json!({"email": account_key, "scope": "disabled"}),
+            ).await;
+            return Err(AuthError::AccountDisabled);
+        }
 
-        // Calculate refresh token expiration (7 days from now)
-        let refresh_expires_at = Utc::now() + chrono::Duration::days(7);
+        if two_factor_enabled {
+            let challenge = self.issue_two_factor_challenge(&user, session).await?;
+            return Ok(LoginResponse::TwoFactorRequired(challenge));
+        }
 
-        // Store refresh token
-        self.token_repo.store_refresh_token(user.id, &refresh_token, refresh_expires_at).await?;
+        // Start a session and issue its token pair
+        let (access_token, refresh_token) = self.start_session(&user, session).await?;
 
         // Return response
+        Ok(LoginResponse::Authenticated(AuthResponse {
+            access_token,
+            refresh_token,
+            user: user.into(),
+        }))
+    }
+
+    /// Complete a login with a TOTP code or a recovery code
+    /// Wrong codes count as failed logins for the account and client IP
+    pub async fn verify_two_factor(
+        &self,
+        challenge_token: &str,
+        code: Option<&str>,
+        recovery_code: Option<&str>,
+        session: &SessionMetadata,
+    ) -> Result<AuthResponse, AuthError> {
+        let client_ip = session.ip_address.as_deref();
+
+        let challenge = self.token_repo.find_two_factor_challenge(challenge_token).await?
+            .ok_or(AuthError::InvalidTwoFactorChallenge)?;
+        let user = self.user_repo.find_by_id(challenge.user_id).await?
+            .ok_or(AuthError::InvalidTwoFactorChallenge)?;
+        let account_key = user.email.to_lowercase();
+
+        self.throttle_login_attempt(&account_key, client_ip).await?;
+
+        if user.is_disabled() {
+            return Err(AuthError::AccountDisabled);
+        }
+
+        let verified = match (code, recovery_code) {
+            (Some(code), None) => match self.two_factor_repo.find(user.id).await? {
+                Some(record) if record.is_enabled() => self.verify_totp(&record, code).await?,
+                _ => false,
+            },
+            (None, Some(recovery_code)) => self.redeem_recovery_code(&user, recovery_code, client_ip).await?,
+            _ => {
+                return Err(AuthError::ValidationError(
+                    "Provide either code or recovery_code".to_string(),
+                ))
+            }
+        };
+
+        if !verified {
+            let error = self.handle_failed_login(&account_key, Some(user.id), client_ip).await?;
+            return Err(match error {
+                AuthError::InvalidCredentials => AuthError::InvalidTwoFactorCode,
+                other => other,
+            });
+        }
+
+        // Each challenge completes a single login
+        if !self.token_repo.consume_two_factor_challenge(challenge.id).await? {
+            return Err(AuthError::InvalidTwoFactorChallenge);
+        }
+        self.security_repo.clear_failures(LoginFailureScope::Account, &account_key).await?;
+
+        let session = SessionMetadata {
+            device_name: challenge.device_name,
+            ..session.clone()
+        };
+        let (access_token, refresh_token) = self.start_session(&user, &session).await?;
+
         Ok(AuthResponse {
             access_token,
             refresh_token,
@@ -102,29 +302,44 @@ impl AuthService {
     }
 
     /// Refresh access and refresh tokens
-    pub async fn refresh_tokens(&self, refresh_token: &str) -> Result<AuthResponse, AuthError> {
+    pub async fn refresh_tokens(
+        &self,
+        refresh_token: &str,
+        session: &SessionMetadata,
+    ) -> Result<AuthResponse, AuthError> {
         // Validate refresh token
         let _claims = self.token_service.validate_refresh_token(refresh_token)?;
 
-        // Verify refresh token exists in database
-        let stored_token = self.token_repo.verify_refresh_token(refresh_token).await?
+        // Verify refresh token exists in database and its session is not idle
+        let stored_token = self.token_repo.verify_refresh_token(refresh_token, self.idle_cutoff()).await?
             .ok_or(AuthError::InvalidToken)?;
 
         // Get user information
         let user = self.user_repo.find_by_id(stored_token.user_id).await?
             .ok_or(AuthError::InvalidToken)?;
 
-        // Invalidate old refresh token
-        self.token_repo.invalidate_token(refresh_token).await?;
-
-        // Generate new token pair
-        let (new_access_token, new_refresh_token) = self.token_service.generate_token_pair(user.id, &user.email, user.role)?;
+        // Disabled accounts cannot obtain new tokens
+        if user.is_disabled() {
+            return Err(AuthError::AccountDisabled);
+        }
 
-        // Calculate refresh token expiration (7 days from now)
-        let refresh_expires_at = Utc::now() + chrono::Duration::days(7);
+        // Rotate the refresh token within the same session
+        let new_refresh_token = self.token_service
+            .generate_refresh_token(user.id, &user.email, user.role.clone())?;
+        let rotated = self.token_repo
+            .rotate_refresh_token(
+                stored_token.id,
+                refresh_token,
+                &new_refresh_token,
+                Utc::now() + chrono::Duration::days(7),
+                session,
+            )
+            .await?;
+        if !rotated {
+            return Err(AuthError::InvalidToken);
+        }
 
-        // Store new refresh token
-        self.token_repo.store_refresh_token(user.id, &new_refresh_token, refresh_expires_at).await?;
+        let new_access_token = self.issue_access_token(&user, stored_token.id).await?;
 
         // Return response
         Ok(AuthResponse {
@@ -144,6 +359,220 @@ impl AuthService {
         Ok(user.into())
     }
 
+    /// Re-check the password of a signed-in user before a sensitive operation
+    pub async fn confirm_password(&self, user_id: i32, password: &str) -> Result<(), AuthError> {
+        let user = self.user_repo.find_by_id(user_id).await?
+            .ok_or(AuthError::InvalidToken)?;
+
+        if !PasswordService::verify_password(password, &user.password_hash)? {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        Ok(())
+    }
+
+    /// Load the account behind an access token and check it may still be used
+    /// Rejects disabled accounts and tokens issued before a forced logout
+    pub async fn authenticate(&self, claims: &Claims) -> Result<User, AuthError> {
+        let user = self.user_repo.find_by_id(claims.sub).await?
+            .ok_or(AuthError::InvalidToken)?;
+
+        if user.is_disabled() {
+            return Err(AuthError::AccountDisabled);
+        }
+
+        if user.is_token_revoked(claims.iat) {
+            return Err(AuthError::InvalidToken);
+        }
+
+        // Tokens bound to a revoked or idle session are rejected
+        if let Some(session_id) = claims.sid {
+            if !self.token_repo.is_session_active(session_id, user.id, self.idle_cutoff()).await? {
+                return Err(AuthError::InvalidToken);
+            }
+        }
+
+        Ok(user)
+    }
+
+    /// Authenticate a token holder and check their role grants `permission`
+    /// Permissions are read from the database so role changes apply immediately
+    pub async fn authorize(&self, claims: &Claims, permission: Permission) -> Result<User, AuthError> {
+        let user = self.authenticate(claims).await?;
+
+        // Admins must enrol before using management endpoints when the policy requires it
+        if self.two_factor_required_for(&user) && !self.two_factor_enabled(user.id).await? {
+            return Err(AuthError::TwoFactorRequired);
+        }
+
+        let permissions = self.user_repo.get_role_permissions(&user.role).await?;
+        if !permissions.contains(&permission) {
+            return Err(AuthError::MissingPermission(permission));
+        }
+
+        Ok(user)
+    }
+
+    /// List roles with their permission sets
+    pub async fn list_roles(&self) -> Result<Vec<RoleDefinition>, AuthError> {
+        self.user_repo.list_roles().await
+    }
+
+    /// Replace the permission set of a role (admin only)
+    pub async fn update_role_permissions(
+        &self,
+        admin_id: i32,
+        role: Role,
+        permissions: Vec<Permission>,
+    ) -> Result<RoleDefinition, AuthError> {
+        // Keep at least one way back in: admins must retain user management
+        if role == Role::admin() && !permissions.contains(&Permission::UsersManage) {
+            return Err(AuthError::ValidationError(
+                "The admin role must keep the users:manage permission".to_string()
+            ));
+        }
+
+        if !self.user_repo.role_exists(&role).await? {
+            return Err(AuthError::InvalidRole(role.to_string()));
+        }
+
+        let mut permissions = permissions;
+        permissions.sort_by_key(|permission| permission.as_str());
+        permissions.dedup();
+
+        self.user_repo.set_role_permissions(&role, &permissions).await?;
+
+        self.record_security_event(
+            SecurityEventType::RolePermissionsChanged,
+            None,
+            None,
+            json!({"changed_by": admin_id, "role": role, "permissions": permissions}),
+        ).await;
+
+        info!("Role permissions updated: admin_id={}, role={}", admin_id, role);
+
+        self.user_repo.list_roles().await?
+            .into_iter()
+            .find(|definition| definition.name == role)
+            .ok_or_else(|| AuthError::InvalidRole(role.to_string()))
+    }
+
+    /// List the user's active sessions, marking the one the request came from
+    pub async fn list_sessions(
+        &self,
+        user_id: i32,
+        current_session_id: Option<i32>,
+    ) -> Result<Vec<SessionResponse>, AuthError> {
+        let sessions = self.token_repo.list_sessions(user_id, self.idle_cutoff()).await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| SessionResponse::from_session(session, current_session_id))
+            .collect())
+    }
+
+    /// Delete sessions that have expired or been idle past the timeout
+    /// Returns the number of sessions deleted
+    pub async fn delete_expired_sessions(&self) -> Result<u64, AuthError> {
+        self.token_repo.delete_expired_tokens(self.idle_cutoff()).await
+    }
+
+    /// Revoke one of the user's sessions (log out that device)
+    /// Access tokens issued for the session stop working immediately
+    pub async fn revoke_session(&self, user_id: i32, session_id: i32) -> Result<(), AuthError> {
+        if !self.token_repo.delete_session(user_id, session_id).await? {
+            return Err(AuthError::SessionNotFound);
+        }
+
+        self.record_security_event(
+            SecurityEventType::SessionRevoked,
+            Some(user_id),
+            None,
+            json!({"session_id": session_id}),
+        ).await;
+
+        info!("Session revoked: user_id={}, session_id={}", user_id, session_id);
+        Ok(())
+    }
+
+    /// Load signing keys from the database into the token service
+    ///
+    /// Generates the first key when none is active. Does nothing for HS256.
+    pub async fn load_signing_keys(&self) -> Result<(), AuthError> {
+        let Some(repo) = &self.signing_key_repo else {
+            return Ok(());
+        };
+
+        let mut records = repo.list_keys().await?;
+        if !records.iter().any(|record| record.is_current()) {
+            // Concurrent bootstraps are fine: only one current key can be inserted
+            if let Err(e) = self.generate_and_store_key(repo, std::time::Duration::ZERO).await {
+                warn!("Initial signing key generation failed, reloading keys: {}", e);
+            }
+            records = repo.list_keys().await?;
+        }
+
+        let ring = KeyRing::from_records(&records, Utc::now(), self.legacy_jwt_secret.as_deref())?;
+        self.token_service.install_key_ring(ring);
+        Ok(())
+    }
+
+    /// Generate a new signing key to replace the current one
+    ///
+    /// The new key is published immediately but only starts signing after the
+    /// key refresh interval, once every instance has loaded it. Tokens signed
+    /// with the retired key keep verifying for the grace period.
+    pub async fn rotate_signing_key(&self, admin_id: Option<i32>) -> Result<SigningKeyInfo, AuthError> {
+        let repo = self.signing_key_repo.as_ref().ok_or(AuthError::KeyRotationDisabled)?;
+
+        let record = self.generate_and_store_key(repo, self.jwt_signing.key_refresh_interval).await?;
+        self.load_signing_keys().await?;
+
+        self.record_security_event(
+            SecurityEventType::SigningKeyRotated,
+            admin_id,
+            None,
+            json!({"kid": record.kid, "algorithm": record.algorithm}),
+        ).await;
+
+        info!(
+            "Signing key rotated: kid={}, algorithm={}, activates_at={}",
+            record.kid, record.algorithm, record.activated_at
+        );
+        Ok(record.into())
+    }
+
+    /// List signing key metadata, newest first (empty for HS256)
+    pub async fn list_signing_keys(&self) -> Result<Vec<SigningKeyInfo>, AuthError> {
+        match &self.signing_key_repo {
+            Some(repo) => Ok(repo.list_keys().await?.into_iter().map(SigningKeyInfo::from).collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Public keys for token verification by other services
+    pub fn jwks(&self) -> JwkSet {
+        self.token_service.jwks()
+    }
+
+    async fn generate_and_store_key(
+        &self,
+        repo: &SigningKeyRepository,
+        activation_delay: std::time::Duration,
+    ) -> Result<crate::auth::models::SigningKeyRecord, AuthError> {
+        // RSA key generation is CPU-bound
+        let algorithm = self.jwt_signing.algorithm;
+        let key = tokio::task::spawn_blocking(move || generate_signing_key(algorithm))
+            .await
+            .map_err(|e| AuthError::TokenGenerationError(e.to_string()))??;
+
+        let activates_at = Utc::now()
+            + chrono::Duration::from_std(activation_delay).unwrap_or_else(|_| chrono::Duration::zero());
+        let grace = chrono::Duration::from_std(self.jwt_signing.key_grace_period)
+            .unwrap_or_else(|_| chrono::Duration::days(7));
+        repo.rotate(&key, activates_at, activates_at + grace).await
+    }
+
     /// Get user with role information
     pub async fn get_user_with_role(&self, user_id: i32) -> Result<(User, crate::auth::models::Role), AuthError> {
         // Find user by ID
@@ -189,14 +618,10 @@ impl AuthService {
             user.id, user.email, user.role
         );
 
-        // Generate token pair
-        let (access_token, refresh_token) = self.token_service.generate_token_pair(user.id, &user.email, user.role)?;
-
-        // Calculate refresh token expiration (7 days from now)
-        let refresh_expires_at = Utc::now() + chrono::Duration::days(7);
-
-        // Store refresh token
-        self.token_repo.store_refresh_token(user.id, &refresh_token, refresh_expires_at).await?;
+        // Start a session (no client details) and issue its token pair
+        let (access_token, refresh_token) = self
+            .start_session(&user, &SessionMetadata::default())
+            .await?;
 
         // Return response
         Ok(AuthResponse {
@@ -207,31 +632,14 @@ impl AuthService {
     }
 
     /// Update a user's role
-    /// Requires admin permissions and prevents self-role-modification
+    /// Callers are authorized by the users:manage permission on the route;
+    /// prevents self-role-modification
     pub async fn update_user_role(
         &self,
         caller_id: i32,
         target_user_id: i32,
         new_role: crate::auth::models::Role,
     ) -> Result<UserResponse, AuthError> {
-        use crate::auth::models::Role;
-        use tracing::warn;
-
-        // Get caller's information
-        let caller = self.user_repo.find_by_id(caller_id).await?
-            .ok_or_else(|| AuthError::DatabaseError("Caller not found".to_string()))?;
-
-        // Verify caller is admin
-        if caller.role != Role::Admin {
-            warn!(
-                "Failed role change attempt: caller_id={}, target_user_id={}, reason=insufficient_permissions",
-                caller_id, target_user_id
-            );
-            return Err(AuthError::InsufficientPermissions {
-                required: Role::Admin,
-                actual: caller.role,
-            });
-        }
 
         // Prevent self-role-modification
         if caller_id == target_user_id {
@@ -244,19 +652,18 @@ impl AuthService {
             ));
         }
 
-        // Validate new role is valid (already validated by type system, but check anyway)
-        // This is redundant but satisfies requirement 7.4
-        match new_role {
-            Role::Admin | Role::User => {}, // Valid roles
+        // Only roles defined in the roles table can be assigned
+        if !self.user_repo.role_exists(&new_role).await? {
+            return Err(AuthError::InvalidRole(new_role.to_string()));
         }
 
         // Get target user's current role for logging
         let target_user = self.user_repo.find_by_id(target_user_id).await?
-            .ok_or_else(|| AuthError::DatabaseError("Target user not found".to_string()))?;
+            .ok_or(AuthError::UserNotFound)?;
         let old_role = target_user.role;
 
         // Update role in database
-        let updated_user = self.user_repo.update_user_role(target_user_id, new_role).await?;
+        let updated_user = self.user_repo.update_user_role(target_user_id, &new_role).await?;
 
         // Log successful role update
         info!(
@@ -264,7 +671,558 @@ impl AuthService {
             caller_id, target_user_id, old_role, new_role
         );
 
+        self.record_security_event(
+            SecurityEventType::RoleChanged,
+            Some(target_user_id),
+            None,
+            json!({"changed_by": caller_id, "old_role": old_role, "new_role": new_role}),
+        ).await;
+
         // Return updated user response
         Ok(updated_user.into())
     }
+
+    /// List users for the admin user-management API
+    pub async fn list_users(&self, query: &UserListQuery) -> Result<PaginatedResponse<UserResponse>, AuthError> {
+        let pagination = PaginationQuery {
+            page: query.page.unwrap_or(1),
+            limit: query.limit.unwrap_or(20),
+            cursor: None,
+        }
+        .validate_and_normalize();
+
+        let search = query.search.as_deref().map(str::trim).filter(|term| !term.is_empty());
+
+        let (users, total) = self.user_repo
+            .search_users(search, query.role.as_ref(), query.disabled, pagination.limit, pagination.offset)
+            .await?;
+
+        Ok(PaginatedResponse::new(
+            users.into_iter().map(UserResponse::from).collect(),
+            total as u64,
+            pagination.page,
+            pagination.limit,
+        ))
+    }
+
+    /// Get a user with order count and loyalty balance
+    pub async fn get_user_detail(&self, user_id: i32) -> Result<AdminUserDetail, AuthError> {
+        let user = self.user_repo.find_by_id(user_id).await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        let (order_count, loyalty_balance) = self.user_repo.get_activity_summary(user_id).await?;
+
+        Ok(AdminUserDetail {
+            user: user.into(),
+            order_count,
+            loyalty_balance,
+        })
+    }
+
+    /// Disable or re-enable an account (admin only)
+    /// Disabling also ends every active session of the user
+    pub async fn set_user_disabled(
+        &self,
+        admin_id: i32,
+        target_user_id: i32,
+        disabled: bool,
+    ) -> Result<UserResponse, AuthError> {
+        // Prevent admins from locking themselves out
+        if admin_id == target_user_id {
+            return Err(AuthError::ValidationError(
+                "Cannot change the status of your own account".to_string()
+            ));
+        }
+
+        let user = self.user_repo.set_disabled(target_user_id, disabled).await?;
+
+        let event_type = if disabled {
+            self.revoke_all_sessions(user.id).await?;
+            SecurityEventType::AccountDisabled
+        } else {
+            SecurityEventType::AccountEnabled
+        };
+
+        self.record_security_event(event_type, Some(user.id), None, json!({"changed_by": admin_id})).await;
+
+        info!("Account status changed: admin_id={}, target_user_id={}, disabled={}", admin_id, user.id, disabled);
+
+        Ok(user.into())
+    }
+
+    /// Force logout: revoke refresh tokens and outstanding access tokens (admin only)
+    pub async fn force_logout(&self, admin_id: i32, target_user_id: i32) -> Result<u64, AuthError> {
+        let user = self.user_repo.find_by_id(target_user_id).await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        let revoked = self.revoke_all_sessions(user.id).await?;
+
+        self.record_security_event(
+            SecurityEventType::SessionsRevoked,
+            Some(user.id),
+            None,
+            json!({"revoked_by": admin_id, "refresh_tokens": revoked}),
+        ).await;
+
+        info!("Sessions revoked: admin_id={}, target_user_id={}, refresh_tokens={}", admin_id, user.id, revoked);
+
+        Ok(revoked)
+    }
+
+    /// Delete refresh tokens and mark earlier access tokens as revoked
+    async fn revoke_all_sessions(&self, user_id: i32) -> Result<u64, AuthError> {
+        self.user_repo.revoke_sessions(user_id).await?;
+        self.token_repo.invalidate_user_tokens(user_id).await
+    }
+
+    /// Unlock an account locked by failed logins (admin only)
+    /// Returns true if the account had a failure counter or lock
+    pub async fn unlock_account(&self, admin_id: i32, target_user_id: i32) -> Result<bool, AuthError> {
+        let user = self.user_repo.find_by_id(target_user_id).await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        let cleared = self.security_repo
+            .clear_failures(LoginFailureScope::Account, &user.email.to_lowercase())
+            .await?;
+
+        self.record_security_event(
+            SecurityEventType::AccountUnlocked,
+            Some(user.id),
+            None,
+            json!({"unlocked_by": admin_id, "had_failures": cleared}),
+        ).await;
+
+        info!("Account unlocked: admin_id={}, target_user_id={}", admin_id, user.id);
+
+        Ok(cleared)
+    }
+
+    /// Reject attempts from blocked client IPs or on locked accounts, then
+    /// apply the progressive delay for the account's recent failures
+    async fn throttle_login_attempt(
+        &self,
+        account_key: &str,
+        client_ip: Option<&str>,
+    ) -> Result<(), AuthError> {
+        let now = Utc::now();
+
+        // Reject blocked client IPs before touching the account
+        if let Some(ip) = client_ip {
+            let ip_failures = self.security_repo.get_failures(LoginFailureScope::Ip, ip).await?;
+            if let Some(retry_after_secs) = ip_failures.and_then(|f| f.locked_for_secs(now)) {
+                self.record_security_event(
+                    SecurityEventType::LoginBlocked,
+                    None,
+                    client_ip,
+                    json!({"email": account_key, "scope": "ip"}),
+                ).await;
+                return Err(AuthError::TooManyLoginAttempts { retry_after_secs });
+            }
+        }
+
+        // Reject locked accounts
+        let account_failures: Option<LoginFailure> = self.security_repo
+            .get_failures(LoginFailureScope::Account, account_key)
+            .await?;
+        if let Some(retry_after_secs) = account_failures.as_ref().and_then(|f| f.locked_for_secs(now)) {
+            self.record_security_event(
+                SecurityEventType::LoginBlocked,
+                None,
+                client_ip,
+                json!({"email": account_key, "scope": "account"}),
+            ).await;
+            return Err(AuthError::AccountLocked { retry_after_secs });
+        }
+
+        // Progressive delay based on recent failures for this account
+        let prior_failures = active_failure_count(account_failures.as_ref(), now, self.lockout_window());
+        let delay = progressive_delay(&self.login_protection, prior_failures);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        Ok(())
+    }
+
+    /// Record a failed login against the account and client IP counters
+    /// Returns the error to send to the client
+    async fn handle_failed_login(
+        &self,
+        account_key: &str,
+        user_id: Option<i32>,
+        client_ip: Option<&str>,
+    ) -> Result<AuthError, AuthError> {
+        let lockout_window = self.lockout_window();
+
+        let account = self.security_repo
+            .record_failure(
+                LoginFailureScope::Account,
+                account_key,
+                self.login_protection.max_failures_per_account,
+                lockout_window,
+            )
+            .await?;
+
+        self.record_security_event(
+            SecurityEventType::LoginFailed,
+            user_id,
+            client_ip,
+            json!({"email": account_key, "failure_count": account.failure_count}),
+        ).await;
+
+        let mut ip_locked_for = None;
+        if let Some(ip) = client_ip {
+            let ip_failures = self.security_repo
+                .record_failure(
+                    LoginFailureScope::Ip,
+                    ip,
+                    self.login_protection.max_failures_per_ip,
+                    lockout_window,
+                )
+                .await?;
+
+            ip_locked_for = ip_failures.locked_for_secs(Utc::now());
+            if ip_locked_for.is_some() && ip_failures.failure_count == self.login_protection.max_failures_per_ip {
+                warn!("Client IP blocked after repeated failed logins: ip={}", ip);
+                self.record_security_event(
+                    SecurityEventType::IpBlocked,
+                    None,
+                    client_ip,
+                    json!({"failure_count": ip_failures.failure_count}),
+                ).await;
+            }
+        }
+
+        if let Some(retry_after_secs) = account.locked_for_secs(Utc::now()) {
+            warn!("Account locked after repeated failed logins: email={}", account_key);
+            self.record_security_event(
+                SecurityEventType::AccountLocked,
+                user_id,
+                client_ip,
+                json!({"email": account_key, "failure_count": account.failure_count}),
+            ).await;
+            return Ok(AuthError::AccountLocked { retry_after_secs });
+        }
+
+        if let Some(retry_after_secs) = ip_locked_for {
+            return Ok(AuthError::TooManyLoginAttempts { retry_after_secs });
+        }
+
+        Ok(AuthError::InvalidCredentials)
+    }
+
+    /// Create a session for the user and issue its token pair
+    async fn start_session(
+        &self,
+        user: &User,
+        session: &SessionMetadata,
+    ) -> Result<(String, String), AuthError> {
+        let refresh_token = self.token_service.generate_refresh_token(user.id, &user.email, user.role.clone())?;
+
+        // Calculate refresh token expiration (7 days from now)
+        let refresh_expires_at = Utc::now() + chrono::Duration::days(7);
+
+        let session_id = self.token_repo
+            .store_refresh_token(user.id, &refresh_token, refresh_expires_at, session)
+            .await?;
+        let access_token = self.issue_access_token(user, session_id).await?;
+
+        Ok((access_token, refresh_token))
+    }
+
+    /// Issue an access token for a session, embedding the role's permissions
+    async fn issue_access_token(&self, user: &User, session_id: i32) -> Result<String, AuthError> {
+        let permissions = self.user_repo.get_role_permissions(&user.role).await?;
+        self.token_service.generate_session_access_token(
+            user.id,
+            &user.email,
+            user.role.clone(),
+            &permissions,
+            Some(session_id),
+        )
+    }
+
+    /// Sessions last used before this instant have expired
+    fn idle_cutoff(&self) -> chrono::DateTime<Utc> {
+        Utc::now()
+            - chrono::Duration::from_std(self.sessions.idle_timeout)
+                .unwrap_or_else(|_| chrono::Duration::days(7))
+    }
+
+    /// Record a security event, logging (not propagating) storage failures
+    async fn record_security_event(
+        &self,
+        event_type: SecurityEventType,
+        user_id: Option<i32>,
+        ip_address: Option<&str>,
+        details: serde_json::Value,
+    ) {
+        if let Err(e) = self.security_repo.record_event(event_type, user_id, ip_address, details).await {
+            warn!("Failed to record security event {}: {}", event_type, e);
+        }
+    }
+
+    /// Lockout duration as a chrono duration (also the counter reset window)
+    fn lockout_window(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.login_protection.lockout_duration)
+            .unwrap_or_else(|_| chrono::Duration::minutes(15))
+    }
+
+    /// Start two-factor enrolment with a new secret
+    /// Restarting a pending enrolment replaces its secret
+    pub async fn setup_two_factor(&self, user_id: i32) -> Result<TwoFactorSetupResponse, AuthError> {
+        let user = self.user_repo.find_by_id(user_id).await?
+            .ok_or(AuthError::InvalidToken)?;
+
+        let secret = totp::generate_secret();
+        if !self.two_factor_repo.store_pending_secret(user.id, &secret).await? {
+            return Err(AuthError::TwoFactorAlreadyEnabled);
+        }
+
+        Ok(TwoFactorSetupResponse {
+            provisioning_uri: totp::provisioning_uri(TOTP_ISSUER, &user.email, &secret),
+            secret,
+        })
+    }
+
+    /// Confirm enrolment with a code from the authenticator app
+    /// Returns the recovery codes, which are only shown this once
+    pub async fn enable_two_factor(&self, user_id: i32, code: &str) -> Result<RecoveryCodesResponse, AuthError> {
+        let record = self.two_factor_repo.find(user_id).await?
+            .ok_or(AuthError::TwoFactorNotEnabled)?;
+        if record.is_enabled() {
+            return Err(AuthError::TwoFactorAlreadyEnabled);
+        }
+
+        if !self.verify_totp(&record, code).await? {
+            return Err(AuthError::InvalidTwoFactorCode);
+        }
+
+        let recovery_codes = totp::generate_recovery_codes();
+        let hashes = Self::hash_recovery_codes(&recovery_codes).await?;
+        self.two_factor_repo.enable(user_id, &hashes).await?;
+
+        self.record_security_event(SecurityEventType::TwoFactorEnabled, Some(user_id), None, json!({})).await;
+        info!("Two-factor authentication enabled: user_id={}", user_id);
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// Turn off two-factor authentication after checking a current code
+    /// Not allowed when the policy requires it for the user's role
+    pub async fn disable_two_factor(&self, user_id: i32, code: &str) -> Result<(), AuthError> {
+        let user = self.user_repo.find_by_id(user_id).await?
+            .ok_or(AuthError::InvalidToken)?;
+        if self.two_factor_required_for(&user) {
+            return Err(AuthError::TwoFactorRequired);
+        }
+
+        let record = self.enabled_two_factor(user_id).await?;
+        if !self.verify_totp(&record, code).await? {
+            return Err(AuthError::InvalidTwoFactorCode);
+        }
+
+        self.two_factor_repo.disable(user_id).await?;
+
+        self.record_security_event(SecurityEventType::TwoFactorDisabled, Some(user_id), None, json!({})).await;
+        info!("Two-factor authentication disabled: user_id={}", user_id);
+        Ok(())
+    }
+
+    /// Replace all recovery codes after checking a current code
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: i32,
+        code: &str,
+    ) -> Result<RecoveryCodesResponse, AuthError> {
+        let record = self.enabled_two_factor(user_id).await?;
+        if !self.verify_totp(&record, code).await? {
+            return Err(AuthError::InvalidTwoFactorCode);
+        }
+
+        let recovery_codes = totp::generate_recovery_codes();
+        let hashes = Self::hash_recovery_codes(&recovery_codes).await?;
+        self.two_factor_repo.replace_recovery_codes(user_id, &hashes).await?;
+
+        self.record_security_event(SecurityEventType::RecoveryCodesRegenerated, Some(user_id), None, json!({})).await;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// Two-factor status of a user
+    pub async fn two_factor_status(&self, user_id: i32) -> Result<TwoFactorStatusResponse, AuthError> {
+        let enabled = self.two_factor_enabled(user_id).await?;
+        let recovery_codes_remaining = if enabled {
+            self.two_factor_repo.unused_recovery_codes(user_id).await?.len() as i64
+        } else {
+            0
+        };
+
+        Ok(TwoFactorStatusResponse {
+            enabled,
+            recovery_codes_remaining,
+        })
+    }
+
+    /// Whether the user has confirmed two-factor enrolment
+    async fn two_factor_enabled(&self, user_id: i32) -> Result<bool, AuthError> {
+        Ok(self.two_factor_repo.find(user_id).await?.is_some_and(|record| record.is_enabled()))
+    }
+
+    /// The user's confirmed enrolment, or `TwoFactorNotEnabled`
+    async fn enabled_two_factor(&self, user_id: i32) -> Result<TwoFactorRecord, AuthError> {
+        self.two_factor_repo.find(user_id).await?
+            .filter(|record| record.is_enabled())
+            .ok_or(AuthError::TwoFactorNotEnabled)
+    }
+
+    /// Whether the policy requires two-factor authentication for the user's role
+    fn two_factor_required_for(&self, user: &User) -> bool {
+        self.policy.require_two_factor_for_admins && user.role == Role::admin()
+    }
+
+    /// Check a TOTP code and mark its time step as used so it cannot be replayed
+    async fn verify_totp(&self, record: &TwoFactorRecord, code: &str) -> Result<bool, AuthError> {
+        match totp::verify_code(&record.secret, code, Utc::now(), record.last_used_step) {
+            Some(step) => self.two_factor_repo.record_used_step(record.user_id, step).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Check a recovery code against the user's unused codes and burn the match
+    async fn redeem_recovery_code(
+        &self,
+        user: &User,
+        recovery_code: &str,
+        client_ip: Option<&str>,
+    ) -> Result<bool, AuthError> {
+        let candidate = totp::normalize_recovery_code(recovery_code);
+        let unused = self.two_factor_repo.unused_recovery_codes(user.id).await?;
+        let remaining = unused.len().saturating_sub(1);
+
+        // Argon2 verification is CPU-bound
+        let matched = tokio::task::spawn_blocking(move || {
+            for (id, hash) in unused {
+                if PasswordService::verify_password(&candidate, &hash)? {
+                    return Ok(Some(id));
+                }
+            }
+            Ok::<_, AuthError>(None)
+        })
+        .await
+        .map_err(|_| AuthError::PasswordHashError)??;
+
+        let Some(code_id) = matched else {
+            return Ok(false);
+        };
+        if !self.two_factor_repo.use_recovery_code(code_id).await? {
+            return Ok(false);
+        }
+
+        self.record_security_event(
+            SecurityEventType::RecoveryCodeUsed,
+            Some(user.id),
+            client_ip,
+            json!({"remaining": remaining}),
+        ).await;
+        Ok(true)
+    }
+
+    /// Hash recovery codes like passwords before storing them
+    async fn hash_recovery_codes(codes: &[String]) -> Result<Vec<String>, AuthError> {
+        let codes = codes.to_vec();
+        tokio::task::spawn_blocking(move || {
+            codes
+                .iter()
+                .map(|code| PasswordService::hash_password(code))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|_| AuthError::PasswordHashError)?
+    }
+
+    /// Store a short-lived challenge for the second login step
+    async fn issue_two_factor_challenge(
+        &self,
+        user: &User,
+        session: &SessionMetadata,
+    ) -> Result<TwoFactorChallengeResponse, AuthError> {
+        let challenge_token = Self::generate_verification_token();
+        let expires_at = Utc::now() + chrono::Duration::seconds(TWO_FACTOR_CHALLENGE_SECS);
+
+        self.token_repo
+            .store_two_factor_challenge(user.id, &challenge_token, session.device_name.as_deref(), expires_at)
+            .await?;
+
+        Ok(TwoFactorChallengeResponse {
+            two_factor_required: true,
+            challenge_token,
+            expires_in: TWO_FACTOR_CHALLENGE_SECS,
+        })
+    }
+
+    /// Verify a user's email address using a token from a verification email
+    pub async fn verify_email(&self, token: &str) -> Result<UserResponse, AuthError> {
+        // Consume token (single use, must not be expired)
+        let user_id = self.token_repo.consume_verification_token(token).await?
+            .ok_or(AuthError::InvalidVerificationToken)?;
+
+        // Mark user as verified
+        let user = self.user_repo.mark_email_verified(user_id).await?;
+
+        info!("Email verified: user_id={}", user.id);
+
+        Ok(user.into())
+    }
+
+    /// Issue a fresh verification token for a user who has not verified yet
+    pub async fn resend_verification_email(&self, user_id: i32) -> Result<(), AuthError> {
+        // Find user by ID
+        let user = self.user_repo.find_by_id(user_id).await?
+            .ok_or(AuthError::InvalidToken)?;
+
+        if user.is_email_verified() {
+            return Err(AuthError::EmailAlreadyVerified);
+        }
+
+        self.send_verification_email(&user).await
+    }
+
+    /// Generate, store and send a verification token for a user
+    async fn send_verification_email(&self, user: &User) -> Result<(), AuthError> {
+        let token = Self::generate_verification_token();
+        let expires_at = Utc::now() + chrono::Duration::hours(VERIFICATION_TOKEN_HOURS);
+
+        // Store hashed token (replaces any outstanding token)
+        self.token_repo.store_verification_token(user.id, &token, expires_at).await?;
+
+        // Deliver plaintext token to the user's address
+        self.mailer.send_verification_email(&user.email, &token).await
+    }
+
+    /// Generate a random 256-bit token encoded as hex
+    fn generate_verification_token() -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_token_is_64_hex_chars() {
+        let token = AuthService::generate_verification_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_verification_tokens_are_unique() {
+        let a = AuthService::generate_verification_token();
+        let b = AuthService::generate_verification_token();
+        assert_ne!(a, b);
+    }
 }
@@ -1,10 +1,12 @@
 // JWT token generation and validation service
 
 use crate::auth::error::AuthError;
-use crate::auth::models::Role;
+use crate::auth::keys::KeyRing;
+use crate::auth::models::{Permission, Role};
 use chrono::Utc;
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, jwk::JwkSet, Validation};
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
 
 /// JWT claims structure
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,29 +16,84 @@ pub struct Claims {
     pub role: Role,      // user role for authorization
     pub exp: i64,        // expiration timestamp
     pub iat: i64,        // issued at timestamp
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<Permission>, // permissions granted to the role when issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<i32>, // login session the access token belongs to
 }
 
 /// Token service for JWT operations
+///
+/// Clones share the same key ring, so a rotation installed through one clone
+/// is seen by all of them.
+#[derive(Clone)]
 pub struct TokenService {
-    secret: String,
+    keys: Arc<RwLock<KeyRing>>,
     access_token_duration: i64,  // in seconds
     refresh_token_duration: i64, // in seconds
 }
 
 impl TokenService {
-    /// Create a new TokenService with secret key
+    /// Create a new TokenService with secret key (HS256)
     /// Access tokens expire in 15 minutes (900 seconds)
     /// Refresh tokens expire in 7 days (604800 seconds)
     pub fn new(secret: String) -> Self {
+        Self::with_key_ring(KeyRing::hmac(&secret))
+    }
+
+    /// Create a new TokenService signing with the given key ring
+    pub fn with_key_ring(keys: KeyRing) -> Self {
         Self {
-            secret,
+            keys: Arc::new(RwLock::new(keys)),
             access_token_duration: 900,      // 15 minutes
             refresh_token_duration: 604800,  // 7 days
         }
     }
 
+    /// Replace the key ring, e.g. after a key rotation
+    pub fn install_key_ring(&self, keys: KeyRing) {
+        *self.keys.write().unwrap_or_else(|e| e.into_inner()) = keys;
+    }
+
+    /// Public keys currently accepted for verification
+    pub fn jwks(&self) -> JwkSet {
+        self.keys.read().unwrap_or_else(|e| e.into_inner()).jwks()
+    }
+
+    /// Sign claims with the active key
+    fn sign(&self, claims: &Claims) -> Result<String, AuthError> {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let (header, key) = keys.signing_key(Utc::now())?;
+
+        encode(&header, claims, key).map_err(|e| AuthError::TokenGenerationError(e.to_string()))
+    }
+
     /// Generate an access token (15 minutes)
     pub fn generate_access_token(&self, user_id: i32, email: &str, role: Role) -> Result<String, AuthError> {
+        self.generate_access_token_with_permissions(user_id, email, role, &[])
+    }
+
+    /// Generate an access token (15 minutes) carrying the role's permissions
+    pub fn generate_access_token_with_permissions(
+        &self,
+        user_id: i32,
+        email: &str,
+        role: Role,
+        permissions: &[Permission],
+    ) -> Result<String, AuthError> {
+        self.generate_session_access_token(user_id, email, role, permissions, None)
+    }
+
+    /// Generate an access token (15 minutes) bound to a login session
+    /// Revoking the session invalidates the token before it expires
+    pub fn generate_session_access_token(
+        &self,
+        user_id: i32,
+        email: &str,
+        role: Role,
+        permissions: &[Permission],
+        session_id: Option<i32>,
+    ) -> Result<String, AuthError> {
         let now = Utc::now().timestamp();
         let exp = now + self.access_token_duration;
 
@@ -46,14 +103,11 @@ impl TokenService {
             role,
             iat: now,
             exp,
+            permissions: permissions.to_vec(),
+            sid: session_id,
         };
 
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
-        .map_err(|e| AuthError::TokenGenerationError(e.to_string()))
+        self.sign(&claims)
     }
 
     /// Generate a refresh token (7 days)
@@ -67,14 +121,11 @@ impl TokenService {
             role,
             iat: now,
             exp,
+            permissions: Vec::new(),
+            sid: None,
         };
 
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
-        .map_err(|e| AuthError::TokenGenerationError(e.to_string()))
+        self.sign(&claims)
     }
 
     /// Validate an access token
@@ -89,13 +140,13 @@ impl TokenService {
 
     /// Internal helper to validate any token
     fn validate_token(&self, token: &str) -> Result<Claims, AuthError> {
-        let validation = Validation::default();
+        // Pick the key named by the token's kid; unknown keys are rejected
+        let header = decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let (key, algorithm) = keys.verification_key(&header).ok_or(AuthError::InvalidToken)?;
+        let validation = Validation::new(algorithm);
         
-        decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &validation,
-        )
+        decode::<Claims>(token, key, &validation)
         .map(|data| data.claims)
         .map_err(|e| {
             // Check if the error is due to expiration
@@ -109,7 +160,20 @@ impl TokenService {
 
     /// Generate both access and refresh tokens
     pub fn generate_token_pair(&self, user_id: i32, email: &str, role: Role) -> Result<(String, String), AuthError> {
-        let access_token = self.generate_access_token(user_id, email, role)?;
+        let access_token = self.generate_access_token(user_id, email, role.clone())?;
+        let refresh_token = self.generate_refresh_token(user_id, email, role)?;
+        Ok((access_token, refresh_token))
+    }
+
+    /// Generate both tokens, embedding permissions in the access token
+    pub fn generate_token_pair_with_permissions(
+        &self,
+        user_id: i32,
+        email: &str,
+        role: Role,
+        permissions: &[Permission],
+    ) -> Result<(String, String), AuthError> {
+        let access_token = self.generate_access_token_with_permissions(user_id, email, role.clone(), permissions)?;
         let refresh_token = self.generate_refresh_token(user_id, email, role)?;
         Ok((access_token, refresh_token))
     }
@@ -119,6 +183,8 @@ impl TokenService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::keys::{generate_signing_key, GeneratedKey, SigningAlgorithm};
+    use crate::auth::models::SigningKeyRecord;
     use proptest::prelude::*;
 
     // Helper to create a test token service
@@ -130,7 +196,7 @@ mod tests {
     #[test]
     fn test_access_token_expiration_is_15_minutes() {
         let service = test_token_service();
-        let token = service.generate_access_token(1, "test@example.com", Role::User).unwrap();
+        let token = service.generate_access_token(1, "test@example.com", Role::user()).unwrap();
         let claims = service.validate_access_token(&token).unwrap();
         
         // Verify expiration is 15 minutes (900 seconds) from issued time
@@ -142,7 +208,7 @@ mod tests {
     #[test]
     fn test_refresh_token_expiration_is_7_days() {
         let service = test_token_service();
-        let token = service.generate_refresh_token(1, "test@example.com", Role::User).unwrap();
+        let token = service.generate_refresh_token(1, "test@example.com", Role::user()).unwrap();
         let claims = service.validate_refresh_token(&token).unwrap();
         
         // Verify expiration is 7 days (604800 seconds) from issued time
@@ -156,15 +222,15 @@ mod tests {
         let service = test_token_service();
         let user_id = 42;
         let email = "user@example.com";
-        let role = Role::Admin;
+        let role = Role::admin();
         
-        let access_token = service.generate_access_token(user_id, email, role).unwrap();
+        let access_token = service.generate_access_token(user_id, email, role.clone()).unwrap();
         let access_claims = service.validate_access_token(&access_token).unwrap();
         assert_eq!(access_claims.sub, user_id);
         assert_eq!(access_claims.email, email);
         assert_eq!(access_claims.role, role);
         
-        let refresh_token = service.generate_refresh_token(user_id, email, role).unwrap();
+        let refresh_token = service.generate_refresh_token(user_id, email, role.clone()).unwrap();
         let refresh_claims = service.validate_refresh_token(&refresh_token).unwrap();
         assert_eq!(refresh_claims.sub, user_id);
         assert_eq!(refresh_claims.email, email);
@@ -175,7 +241,7 @@ mod tests {
     #[test]
     fn test_generate_token_pair() {
         let service = test_token_service();
-        let (access_token, refresh_token) = service.generate_token_pair(1, "test@example.com", Role::User).unwrap();
+        let (access_token, refresh_token) = service.generate_token_pair(1, "test@example.com", Role::user()).unwrap();
         
         // Both tokens should be valid
         assert!(service.validate_access_token(&access_token).is_ok());
@@ -185,6 +251,105 @@ mod tests {
         assert_ne!(access_token, refresh_token);
     }
 
+    // Permissions are embedded in access tokens only
+    #[test]
+    fn test_token_pair_with_permissions() {
+        let service = test_token_service();
+        let permissions = [Permission::OrdersManage, Permission::MenuWrite];
+        let (access_token, refresh_token) = service
+            .generate_token_pair_with_permissions(1, "test@example.com", Role::barista(), &permissions)
+            .unwrap();
+
+        let access_claims = service.validate_access_token(&access_token).unwrap();
+        assert_eq!(access_claims.permissions, permissions.to_vec());
+
+        let refresh_claims = service.validate_refresh_token(&refresh_token).unwrap();
+        assert!(refresh_claims.permissions.is_empty());
+    }
+
+    fn stored(key: &GeneratedKey, retired: bool) -> SigningKeyRecord {
+        let activated_at = Utc::now() - chrono::Duration::minutes(1);
+        SigningKeyRecord {
+            kid: key.kid.clone(),
+            algorithm: key.algorithm.as_str().to_string(),
+            private_key: key.private_key.clone(),
+            public_jwk: serde_json::to_string(&key.public_jwk).unwrap(),
+            created_at: activated_at,
+            activated_at,
+            retired_at: retired.then(Utc::now),
+            expires_at: retired.then(|| Utc::now() + chrono::Duration::days(7)),
+        }
+    }
+
+    fn asymmetric_service(records: &[SigningKeyRecord], legacy_secret: Option<&str>) -> TokenService {
+        TokenService::with_key_ring(KeyRing::from_records(records, Utc::now(), legacy_secret).unwrap())
+    }
+
+    // Asymmetric tokens carry the kid of the signing key
+    #[test]
+    fn test_asymmetric_tokens_round_trip() {
+        for algorithm in [SigningAlgorithm::EdDSA, SigningAlgorithm::RS256] {
+            let key = generate_signing_key(algorithm).unwrap();
+            let service = asymmetric_service(&[stored(&key, false)], None);
+
+            let token = service.generate_access_token(7, "test@example.com", Role::user()).unwrap();
+            let header = decode_header(&token).unwrap();
+            assert_eq!(header.kid.as_deref(), Some(key.kid.as_str()));
+            assert_eq!(format!("{:?}", header.alg), algorithm.as_str());
+            assert_eq!(service.validate_access_token(&token).unwrap().sub, 7);
+        }
+    }
+
+    // Tokens signed by a retired key keep verifying during the grace period
+    #[test]
+    fn test_rotation_keeps_old_tokens_valid() {
+        let old = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let service = asymmetric_service(&[stored(&old, false)], None);
+        let old_token = service.generate_access_token(1, "test@example.com", Role::user()).unwrap();
+
+        let new = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        service.install_key_ring(
+            KeyRing::from_records(&[stored(&new, false), stored(&old, true)], Utc::now(), None).unwrap(),
+        );
+
+        let new_token = service.generate_access_token(1, "test@example.com", Role::user()).unwrap();
+        assert_eq!(decode_header(&new_token).unwrap().kid.as_deref(), Some(new.kid.as_str()));
+        assert!(service.validate_access_token(&old_token).is_ok());
+        assert!(service.validate_access_token(&new_token).is_ok());
+        assert_eq!(service.jwks().keys.len(), 2);
+    }
+
+    // Keys from another ring and HS256 tokens without a legacy secret are rejected
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let key = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let other = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let service = asymmetric_service(&[stored(&key, false)], None);
+        let other_service = asymmetric_service(&[stored(&other, false)], None);
+
+        let foreign = other_service.generate_access_token(1, "test@example.com", Role::user()).unwrap();
+        assert!(matches!(service.validate_access_token(&foreign), Err(AuthError::InvalidToken)));
+
+        let hmac = test_token_service().generate_access_token(1, "test@example.com", Role::user()).unwrap();
+        assert!(service.validate_access_token(&hmac).is_err());
+
+        let with_legacy = asymmetric_service(&[stored(&key, false)], Some("test_secret_key_for_testing_purposes"));
+        assert!(with_legacy.validate_access_token(&hmac).is_ok());
+    }
+
+    // Session-bound access tokens carry the session id
+    #[test]
+    fn test_session_access_token_carries_sid() {
+        let service = test_token_service();
+        let token = service
+            .generate_session_access_token(1, "test@example.com", Role::user(), &[], Some(42))
+            .unwrap();
+        assert_eq!(service.validate_access_token(&token).unwrap().sid, Some(42));
+
+        let token = service.generate_access_token(1, "test@example.com", Role::user()).unwrap();
+        assert_eq!(service.validate_access_token(&token).unwrap().sid, None);
+    }
+
     // Feature: authentication-system, Property 15: Malformed tokens are rejected
     #[test]
     fn test_malformed_tokens_are_rejected() {
@@ -204,7 +369,7 @@ mod tests {
         let service2 = TokenService::new("secret2".to_string());
         
         // Generate token with service1
-        let token = service1.generate_access_token(1, "test@example.com", Role::User).unwrap();
+        let token = service1.generate_access_token(1, "test@example.com", Role::user()).unwrap();
         
         // service1 should validate it
         assert!(service1.validate_access_token(&token).is_ok());
@@ -223,7 +388,7 @@ mod tests {
             email in "[a-z]{3,10}@[a-z]{3,10}\\.(com|org|net)"
         ) {
             let service = test_token_service();
-            let token = service.generate_access_token(user_id, &email, Role::User)?;
+            let token = service.generate_access_token(user_id, &email, Role::user())?;
             let claims = service.validate_access_token(&token)?;
             
             let duration = claims.exp - claims.iat;
@@ -237,7 +402,7 @@ mod tests {
             email in "[a-z]{3,10}@[a-z]{3,10}\\.(com|org|net)"
         ) {
             let service = test_token_service();
-            let token = service.generate_refresh_token(user_id, &email, Role::User)?;
+            let token = service.generate_refresh_token(user_id, &email, Role::user())?;
             let claims = service.validate_refresh_token(&token)?;
             
             let duration = claims.exp - claims.iat;
@@ -252,15 +417,15 @@ mod tests {
             is_admin in proptest::bool::ANY
         ) {
             let service = test_token_service();
-            let role = if is_admin { Role::Admin } else { Role::User };
+            let role = if is_admin { Role::admin() } else { Role::user() };
             
-            let access_token = service.generate_access_token(user_id, &email, role)?;
+            let access_token = service.generate_access_token(user_id, &email, role.clone())?;
             let access_claims = service.validate_access_token(&access_token)?;
             prop_assert_eq!(access_claims.sub, user_id);
             prop_assert_eq!(access_claims.email, email.clone());
-            prop_assert_eq!(access_claims.role, role);
+            prop_assert_eq!(&access_claims.role, &role);
             
-            let refresh_token = service.generate_refresh_token(user_id, &email, role)?;
+            let refresh_token = service.generate_refresh_token(user_id, &email, role.clone())?;
             let refresh_claims = service.validate_refresh_token(&refresh_token)?;
             prop_assert_eq!(refresh_claims.sub, user_id);
             prop_assert_eq!(refresh_claims.email, email);
@@ -275,11 +440,11 @@ mod tests {
         ) {
             let service = test_token_service();
             
-            let access_token = service.generate_access_token(user_id, &email, Role::User)?;
+            let access_token = service.generate_access_token(user_id, &email, Role::user())?;
             let result = service.validate_access_token(&access_token);
             prop_assert!(result.is_ok());
             
-            let refresh_token = service.generate_refresh_token(user_id, &email, Role::User)?;
+            let refresh_token = service.generate_refresh_token(user_id, &email, Role::user())?;
             let result = service.validate_refresh_token(&refresh_token);
             prop_assert!(result.is_ok());
         }
@@ -0,0 +1,227 @@
+// RFC 6238 time-based one-time passwords for two-factor authentication
+// Pure helpers for secrets, codes, provisioning URIs and recovery codes
+
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use rand::{Rng, RngCore};
+use ring::hmac;
+
+/// Number of digits in a TOTP code
+pub const TOTP_DIGITS: u32 = 6;
+
+/// Length of a TOTP time step in seconds
+pub const TOTP_PERIOD_SECS: i64 = 30;
+
+/// Time steps accepted on either side of the current one (clock drift)
+pub const TOTP_SKEW_STEPS: i64 = 1;
+
+/// Size of a generated shared secret (160 bits, as recommended for SHA-1)
+const SECRET_BYTES: usize = 20;
+
+/// Number of recovery codes issued at enrolment
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Alphabet for recovery codes (no 0/o, 1/l/i to avoid transcription mistakes)
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Generate a random shared secret, base32 encoded without padding
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// Time step containing `now`
+pub fn time_step(now: DateTime<Utc>) -> i64 {
+    now.timestamp().div_euclid(TOTP_PERIOD_SECS)
+}
+
+/// HOTP value (RFC 4226) for a raw secret and counter, truncated to `digits`
+fn hotp(secret: &[u8], counter: u64, digits: u32) -> u32 {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let tag = hmac::sign(&key, &counter.to_be_bytes());
+    let digest = tag.as_ref();
+
+    // Dynamic truncation
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    binary % 10u32.pow(digits)
+}
+
+/// TOTP code for a base32 secret at the given time step
+/// Returns None when the secret is not valid base32
+pub fn code_at(secret: &str, step: i64) -> Option<String> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code = hotp(&key, step as u64, TOTP_DIGITS);
+    Some(format!("{:0width$}", code, width = TOTP_DIGITS as usize))
+}
+
+/// Check a submitted code against the steps around `now`
+///
+/// Steps at or before `last_used_step` are skipped so a code cannot be
+/// replayed. Returns the matching step, which the caller records as used.
+pub fn verify_code(
+    secret: &str,
+    code: &str,
+    now: DateTime<Utc>,
+    last_used_step: Option<i64>,
+) -> Option<i64> {
+    let code = code.trim().replace(' ', "");
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = time_step(now);
+    (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|used| *step > used))
+        .find(|step| code_at(secret, *step).is_some_and(|expected| constant_time_eq(&expected, &code)))
+}
+
+/// Compare two codes without short-circuiting on the first difference
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// `otpauth://` URI for enrolling the secret in an authenticator app (rendered as a QR code)
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        TOTP_DIGITS,
+        TOTP_PERIOD_SECS,
+    )
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Generate single-use recovery codes formatted as `xxxxx-xxxxx`
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    let mut random_chunk = || -> String {
+        (0..5)
+            .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+            .collect()
+    };
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| format!("{}-{}", random_chunk(), random_chunk()))
+        .collect()
+}
+
+/// Canonical form of a user-entered recovery code (case and separators ignored)
+pub fn normalize_recovery_code(code: &str) -> String {
+    let compact: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    match compact.len() {
+        10 => format!("{}-{}", &compact[..5], &compact[5..]),
+        _ => compact,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B SHA-1 secret "12345678901234567890"
+    fn rfc_secret() -> String {
+        BASE32_NOPAD.encode(b"12345678901234567890")
+    }
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        let key = b"12345678901234567890";
+        assert_eq!(hotp(key, time_step(at(59)) as u64, 8), 94287082);
+        assert_eq!(hotp(key, time_step(at(1111111109)) as u64, 8), 7081804);
+        assert_eq!(hotp(key, time_step(at(1234567890)) as u64, 8), 89005924);
+        assert_eq!(hotp(key, time_step(at(20000000000)) as u64, 8), 65353130);
+    }
+
+    #[test]
+    fn test_code_is_zero_padded() {
+        assert_eq!(code_at(&rfc_secret(), time_step(at(1111111109))).as_deref(), Some("081804"));
+    }
+
+    #[test]
+    fn test_verify_accepts_adjacent_steps() {
+        let secret = rfc_secret();
+        let now = at(1111111109);
+        let previous = code_at(&secret, time_step(now) - 1).unwrap();
+        assert_eq!(verify_code(&secret, &previous, now, None), Some(time_step(now) - 1));
+
+        let too_old = code_at(&secret, time_step(now) - 2).unwrap();
+        assert_eq!(verify_code(&secret, &too_old, now, None), None);
+    }
+
+    #[test]
+    fn test_verify_rejects_replayed_step() {
+        let secret = rfc_secret();
+        let now = at(1111111109);
+        let code = code_at(&secret, time_step(now)).unwrap();
+        assert_eq!(verify_code(&secret, &code, now, Some(time_step(now))), None);
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_codes() {
+        let secret = rfc_secret();
+        let now = at(1111111109);
+        assert_eq!(verify_code(&secret, "12345", now, None), None);
+        assert_eq!(verify_code(&secret, "abcdef", now, None), None);
+        assert_eq!(verify_code("not base32!", "081804", now, None), None);
+        assert_eq!(verify_code(&secret, "081 804", now, None), Some(time_step(now)));
+    }
+
+    #[test]
+    fn test_generated_secret_round_trips() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 32);
+        assert!(code_at(&secret, 1).is_some());
+        assert_ne!(secret, generate_secret());
+    }
+
+    #[test]
+    fn test_provisioning_uri_encodes_label() {
+        let uri = provisioning_uri("Coffee App", "admin@example.com", "JBSWY3DPEHPK3PXP");
+        assert_eq!(
+            uri,
+            "otpauth://totp/Coffee%20App:admin%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=Coffee%20App&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn test_recovery_codes_format() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        for code in &codes {
+            assert_eq!(code.len(), 11);
+            assert_eq!(normalize_recovery_code(code), *code);
+        }
+        assert_eq!(normalize_recovery_code(" ABCDE FGHJK "), "abcde-fghjk");
+    }
+}
@@ -73,6 +73,7 @@ pub use metrics::PerformanceMetrics;
 // 
 // Coordinates all business rules engines and provides a unified interface.
 
+use chrono_tz::Tz;
 use sqlx::PgPool;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -115,6 +116,12 @@ impl BusinessRulesEngine {
         }
     }
     
+    /// Evaluate time-based pricing rules in the shop timezone
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.pricing_engine = self.pricing_engine.with_timezone(timezone);
+        self
+    }
+
     /// Get performance metrics
     pub fn metrics(&self) -> &PerformanceMetrics {
         &self.metrics
@@ -168,20 +175,38 @@ impl BusinessRulesEngine {
         Ok(result)
     }
     
+    /// Check whether a single coffee can currently be ordered
+    ///
+    /// Unlike `validate_order`, nothing is logged since no order is involved.
+    pub async fn check_availability(&self, coffee_id: i32) -> BRResult<CoffeeAvailability> {
+        let _timer = self.metrics.start_availability_check();
+
+        self.availability_engine.check_coffee_availability(coffee_id).await
+    }
+
     /// Calculate order price with applicable rules
     /// 
-    /// Applies pricing rules and logs the calculation result.
+    /// Applies pricing rules. The result is logged with `log_pricing` once the
+    /// order exists, since audit records reference the order.
     pub async fn calculate_price(
         &self,
-        order_id: Uuid,
         items: &[PricingOrderItem],
         strategy: CombinationStrategy,
     ) -> BRResult<OrderPricingResult> {
         let _timer = self.metrics.start_pricing_calculation();
         
-        // Calculate price
-        let result = self.pricing_engine.calculate_order_price(items, strategy).await?;
-        
+        self.pricing_engine.calculate_order_price(items, strategy).await
+    }
+    
+    /// Log a pricing result for an order
+    /// 
+    /// Records each applied rule with its discount amount, then the overall result.
+    pub async fn log_pricing(
+        &self,
+        order_id: Uuid,
+        result: &OrderPricingResult,
+        strategy: CombinationStrategy,
+    ) {
         // Log pricing application
         let rule_data = json!({
             "base_price": result.base_price,
@@ -218,8 +243,6 @@ impl BusinessRulesEngine {
             rule_data,
             &effect,
         ).await;
-        
-        Ok(result)
     }
     
     /// Estimate preparation time for an order
@@ -11,7 +11,8 @@ use crate::business_rules::{
     error::{BRResult, BusinessRulesError},
     types::{CombinationStrategy, DiscountType, PricingRuleType},
 };
-use chrono::{Local, NaiveTime, Utc};
+use chrono::{NaiveTime, Utc};
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -47,12 +48,22 @@ pub struct OrderPricingResult {
 /// Evaluates pricing rules and calculates order prices with discounts.
 pub struct PricingEngine {
     config_store: Arc<RuleConfigurationStore>,
+    timezone: Tz,
 }
 
 impl PricingEngine {
     /// Create a new PricingEngine
     pub fn new(config_store: Arc<RuleConfigurationStore>) -> Self {
-        Self { config_store }
+        Self {
+            config_store,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Evaluate time-based rules against the wall clock in `timezone`
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
     
     /// Calculate order price with all applicable rules
@@ -199,8 +210,8 @@ impl PricingEngine {
     fn evaluate_time_based_rule(&self, rule: &PricingRule) -> BRResult<Option<AppliedPricingRule>> {
         let config: TimeBasedRuleConfig = serde_json::from_value(rule.rule_config.clone())?;
         
-        // Get current local time
-        let now = Local::now().time();
+        // Get current time in the shop timezone
+        let now = Utc::now().with_timezone(&self.timezone).time();
         
         // Check if current time falls within any of the time ranges
         let is_in_range = config.time_ranges.iter().any(|range| {
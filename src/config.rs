@@ -1,3 +1,7 @@
+use crate::auth::keys::SigningAlgorithm;
+use crate::reviews::RatingScoreMethod;
+use chrono_tz::Tz;
+use std::net::IpAddr;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -108,3 +112,486 @@ impl PerformanceConfig {
         })
     }
 }
+
+/// Account policy switches for authentication-gated features
+#[derive(Debug, Clone, Default)]
+pub struct AuthPolicyConfig {
+    /// Block order placement until the user's email address is verified
+    pub require_verified_email_for_orders: bool,
+    /// Admins must enable two-factor authentication before using permission-gated endpoints
+    pub require_two_factor_for_admins: bool,
+    /// Reverse proxies whose `X-Forwarded-For` / `X-Real-IP` headers are believed;
+    /// from anyone else the socket peer address is the client IP
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl AuthPolicyConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let require_verified_email_for_orders = std::env::var("REQUIRE_VERIFIED_EMAIL_FOR_ORDERS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .map_err(|e| ConfigError::ParseError(format!("REQUIRE_VERIFIED_EMAIL_FOR_ORDERS: {}", e)))?;
+        
+        let require_two_factor_for_admins = std::env::var("REQUIRE_ADMIN_TWO_FACTOR")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .map_err(|e| ConfigError::ParseError(format!("REQUIRE_ADMIN_TWO_FACTOR: {}", e)))?;
+        
+        let trusted_proxies = std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .parse::<IpAddr>()
+                    .map_err(|e| ConfigError::ParseError(format!("TRUSTED_PROXIES: {}: {}", entry, e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(Self {
+            require_verified_email_for_orders,
+            require_two_factor_for_admins,
+            trusted_proxies,
+        })
+    }
+}
+
+/// Login session lifetime settings
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// Sessions whose refresh token has not been used for this long expire
+    pub idle_timeout: Duration,
+    /// How often expired and idle sessions are deleted
+    pub cleanup_interval: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(604800),
+            cleanup_interval: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let idle_secs = std::env::var("SESSION_IDLE_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "604800".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("SESSION_IDLE_TIMEOUT_SECONDS: {}", e)))?;
+        
+        let cleanup_secs = std::env::var("SESSION_CLEANUP_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("SESSION_CLEANUP_INTERVAL_SECONDS: {}", e)))?;
+        
+        let config = Self {
+            idle_timeout: Duration::from_secs(idle_secs),
+            cleanup_interval: Duration::from_secs(cleanup_secs),
+        };
+        
+        config.validate()?;
+        Ok(config)
+    }
+    
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.idle_timeout.is_zero() {
+            return Err(ConfigError::InvalidConfig(
+                "session idle timeout must be greater than 0".to_string()
+            ));
+        }
+        if self.cleanup_interval.is_zero() {
+            return Err(ConfigError::InvalidConfig(
+                "session cleanup interval must be greater than 0".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Who may write reviews
+#[derive(Debug, Clone, Default)]
+pub struct ReviewPolicyConfig {
+    /// Only customers with a completed order containing the coffee may review it
+    pub require_verified_purchase: bool,
+}
+
+impl ReviewPolicyConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let require_verified_purchase = std::env::var("REQUIRE_PURCHASE_FOR_REVIEWS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .map_err(|e| ConfigError::ParseError(format!("REQUIRE_PURCHASE_FOR_REVIEWS: {}", e)))?;
+        
+        Ok(Self { require_verified_purchase })
+    }
+}
+
+/// Automatic review moderation settings
+#[derive(Debug, Clone)]
+pub struct ReviewModerationConfig {
+    /// Words or phrases that hold a review for moderation instead of publishing it
+    pub blocked_words: Vec<String>,
+    /// Open reports after which a published review is held for moderation
+    pub flag_threshold: i64,
+}
+
+impl Default for ReviewModerationConfig {
+    fn default() -> Self {
+        Self {
+            blocked_words: Vec::new(),
+            flag_threshold: 3,
+        }
+    }
+}
+
+impl ReviewModerationConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let blocked_words = std::env::var("REVIEW_BLOCKED_WORDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        
+        let flag_threshold = std::env::var("REVIEW_FLAG_THRESHOLD")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<i64>()
+            .map_err(|e| ConfigError::ParseError(format!("REVIEW_FLAG_THRESHOLD: {}", e)))?;
+        
+        let config = Self {
+            blocked_words,
+            flag_threshold,
+        };
+        
+        config.validate()?;
+        Ok(config)
+    }
+    
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.flag_threshold < 1 {
+            return Err(ConfigError::InvalidConfig(
+                "review flag threshold must be at least 1".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Confidence-weighted coffee score used for rating sorts and rankings
+#[derive(Debug, Clone)]
+pub struct RatingScoreConfig {
+    /// Bayesian average or Wilson lower bound
+    pub method: RatingScoreMethod,
+    /// Rating a coffee with few reviews is pulled towards (Bayesian only)
+    pub prior_mean: f64,
+    /// Number of virtual reviews at the prior mean (Bayesian only)
+    pub prior_weight: f64,
+}
+
+impl Default for RatingScoreConfig {
+    fn default() -> Self {
+        Self {
+            method: RatingScoreMethod::Bayesian,
+            prior_mean: 3.0,
+            prior_weight: 10.0,
+        }
+    }
+}
+
+impl RatingScoreConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let method = std::env::var("RATING_SCORE_METHOD")
+            .unwrap_or_else(|_| "bayesian".to_string());
+        let method = RatingScoreMethod::from_str(&method)
+            .map_err(|e| ConfigError::ParseError(format!("RATING_SCORE_METHOD: {}", e)))?;
+        
+        let prior_mean = std::env::var("RATING_PRIOR_MEAN")
+            .unwrap_or_else(|_| "3.0".to_string())
+            .parse::<f64>()
+            .map_err(|e| ConfigError::ParseError(format!("RATING_PRIOR_MEAN: {}", e)))?;
+        
+        let prior_weight = std::env::var("RATING_PRIOR_WEIGHT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<f64>()
+            .map_err(|e| ConfigError::ParseError(format!("RATING_PRIOR_WEIGHT: {}", e)))?;
+        
+        let config = Self {
+            method,
+            prior_mean,
+            prior_weight,
+        };
+        
+        config.validate()?;
+        Ok(config)
+    }
+    
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(1.0..=5.0).contains(&self.prior_mean) {
+            return Err(ConfigError::InvalidConfig(
+                "rating prior mean must be between 1 and 5".to_string()
+            ));
+        }
+        if !(self.prior_weight >= 0.0 && self.prior_weight.is_finite()) {
+            return Err(ConfigError::InvalidConfig(
+                "rating prior weight must be zero or positive".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Shop locale settings
+#[derive(Debug, Clone)]
+pub struct ShopConfig {
+    /// IANA timezone whose wall clock is used for analytics day, week and month
+    /// buckets and for time-based pricing rules
+    pub timezone: Tz,
+    /// How often the daily sales rollups of days with changed orders are recomputed
+    pub rollup_refresh_interval: Duration,
+}
+
+impl Default for ShopConfig {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            rollup_refresh_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ShopConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let timezone = std::env::var("SHOP_TIMEZONE")
+            .unwrap_or_else(|_| "UTC".to_string())
+            .parse::<Tz>()
+            .map_err(|e| ConfigError::ParseError(format!("SHOP_TIMEZONE: {}", e)))?;
+
+        let refresh_secs = std::env::var("SALES_ROLLUP_REFRESH_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("SALES_ROLLUP_REFRESH_SECONDS: {}", e)))?;
+
+        let config = Self {
+            timezone,
+            rollup_refresh_interval: Duration::from_secs(refresh_secs),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.rollup_refresh_interval.is_zero() {
+            return Err(ConfigError::InvalidConfig(
+                "sales rollup refresh interval must be greater than 0".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Frequently-bought-together (market basket) analysis settings
+#[derive(Debug, Clone)]
+pub struct MarketBasketConfig {
+    /// Trailing window of completed orders the coffee pairs are computed over
+    pub window_days: i64,
+    /// Pairs bought together in fewer orders than this are not stored
+    pub min_pair_orders: i64,
+    /// How often the coffee pairs are recomputed
+    pub refresh_interval: Duration,
+}
+
+impl Default for MarketBasketConfig {
+    fn default() -> Self {
+        Self {
+            window_days: 90,
+            min_pair_orders: 3,
+            refresh_interval: Duration::from_secs(86400),
+        }
+    }
+}
+
+impl MarketBasketConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let window_days = std::env::var("MARKET_BASKET_WINDOW_DAYS")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse::<i64>()
+            .map_err(|e| ConfigError::ParseError(format!("MARKET_BASKET_WINDOW_DAYS: {}", e)))?;
+
+        let min_pair_orders = std::env::var("MARKET_BASKET_MIN_PAIR_ORDERS")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<i64>()
+            .map_err(|e| ConfigError::ParseError(format!("MARKET_BASKET_MIN_PAIR_ORDERS: {}", e)))?;
+
+        let refresh_secs = std::env::var("MARKET_BASKET_REFRESH_SECONDS")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("MARKET_BASKET_REFRESH_SECONDS: {}", e)))?;
+
+        let config = Self {
+            window_days,
+            min_pair_orders,
+            refresh_interval: Duration::from_secs(refresh_secs),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.window_days < 1 {
+            return Err(ConfigError::InvalidConfig(
+                "market basket window must be at least 1 day".to_string()
+            ));
+        }
+        if self.min_pair_orders < 1 {
+            return Err(ConfigError::InvalidConfig(
+                "market basket minimum pair orders must be at least 1".to_string()
+            ));
+        }
+        if self.refresh_interval.is_zero() {
+            return Err(ConfigError::InvalidConfig(
+                "market basket refresh interval must be greater than 0".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// JWT signing algorithm and key rotation settings
+#[derive(Debug, Clone)]
+pub struct JwtSigningConfig {
+    /// HS256 signs with JWT_SECRET; RS256/EdDSA use rotating keys stored in the database
+    pub algorithm: SigningAlgorithm,
+    /// How long tokens signed by a retired key keep verifying
+    pub key_grace_period: Duration,
+    /// How often each instance reloads keys rotated by other instances
+    pub key_refresh_interval: Duration,
+}
+
+impl Default for JwtSigningConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: SigningAlgorithm::HS256,
+            key_grace_period: Duration::from_secs(604800),
+            key_refresh_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl JwtSigningConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let algorithm = std::env::var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".to_string());
+        let algorithm = SigningAlgorithm::from_str(&algorithm)
+            .map_err(|e| ConfigError::ParseError(format!("JWT_ALGORITHM: {}", e)))?;
+        
+        let grace_secs = std::env::var("JWT_KEY_GRACE_PERIOD_SECONDS")
+            .unwrap_or_else(|_| "604800".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("JWT_KEY_GRACE_PERIOD_SECONDS: {}", e)))?;
+        
+        let refresh_secs = std::env::var("JWT_KEY_REFRESH_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("JWT_KEY_REFRESH_SECONDS: {}", e)))?;
+        
+        let config = Self {
+            algorithm,
+            key_grace_period: Duration::from_secs(grace_secs),
+            key_refresh_interval: Duration::from_secs(refresh_secs),
+        };
+        
+        config.validate()?;
+        Ok(config)
+    }
+    
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.key_refresh_interval.is_zero() {
+            return Err(ConfigError::InvalidConfig(
+                "JWT key refresh interval must be greater than 0".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Login brute-force protection thresholds
+#[derive(Debug, Clone)]
+pub struct LoginProtectionConfig {
+    /// Failed attempts allowed per account before it is temporarily locked
+    pub max_failures_per_account: i32,
+    /// Failed attempts allowed per client IP before it is temporarily blocked
+    pub max_failures_per_ip: i32,
+    /// How long a lockout lasts; also the window after which counters reset
+    pub lockout_duration: Duration,
+    /// Delay applied after the first failure, doubled for each further failure
+    pub base_delay: Duration,
+    /// Upper bound for the progressive delay
+    pub max_delay: Duration,
+}
+
+impl Default for LoginProtectionConfig {
+    fn default() -> Self {
+        Self {
+            max_failures_per_account: 5,
+            max_failures_per_ip: 20,
+            lockout_duration: Duration::from_secs(900),
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl LoginProtectionConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let max_failures_per_account = std::env::var("LOGIN_MAX_FAILURES_PER_ACCOUNT")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i32>()
+            .map_err(|e| ConfigError::ParseError(format!("LOGIN_MAX_FAILURES_PER_ACCOUNT: {}", e)))?;
+        
+        let max_failures_per_ip = std::env::var("LOGIN_MAX_FAILURES_PER_IP")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse::<i32>()
+            .map_err(|e| ConfigError::ParseError(format!("LOGIN_MAX_FAILURES_PER_IP: {}", e)))?;
+        
+        let lockout_secs = std::env::var("LOGIN_LOCKOUT_SECONDS")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("LOGIN_LOCKOUT_SECONDS: {}", e)))?;
+        
+        let base_delay_ms = std::env::var("LOGIN_DELAY_BASE_MS")
+            .unwrap_or_else(|_| "200".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("LOGIN_DELAY_BASE_MS: {}", e)))?;
+        
+        let max_delay_ms = std::env::var("LOGIN_DELAY_MAX_MS")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse::<u64>()
+            .map_err(|e| ConfigError::ParseError(format!("LOGIN_DELAY_MAX_MS: {}", e)))?;
+        
+        let config = Self {
+            max_failures_per_account,
+            max_failures_per_ip,
+            lockout_duration: Duration::from_secs(lockout_secs),
+            base_delay: Duration::from_millis(base_delay_ms),
+            max_delay: Duration::from_millis(max_delay_ms),
+        };
+        
+        config.validate()?;
+        Ok(config)
+    }
+    
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_failures_per_account < 1 || self.max_failures_per_ip < 1 {
+            return Err(ConfigError::InvalidConfig(
+                "login failure thresholds must be at least 1".to_string()
+            ));
+        }
+        Ok(())
+    }
+
+}
@@ -1,3 +1,4 @@
+mod account;
 mod auth;
 mod db;
 mod models;
@@ -11,6 +12,8 @@ mod analytics;
 mod config;
 mod cache;
 mod pagination;
+mod notifications;
+mod recommendations;
 
 use axum::{
     extract::{Path, Query, State},
@@ -23,7 +26,7 @@ use sqlx::PgPool;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use models::{Coffee, CreateCoffee, UpdateCoffee};
+use models::{Coffee, CoffeeDetail, CreateCoffee, PairedCoffee, PairedCoffeeQuery, UpdateCoffee};
 use query::{QueryParams, QueryValidator};
 use error::ApiError;
 use validator::Validate;
@@ -36,6 +39,8 @@ use std::sync::Arc;
         create_coffee,
         get_all_coffees,
         get_coffee_by_id,
+        get_frequently_bought_with,
+        recommendations::handlers::get_recommendations_handler,
         update_coffee,
         delete_coffee,
         get_favorite_coffee,
@@ -43,10 +48,41 @@ use std::sync::Arc;
         auth::handlers::login_handler,
         auth::handlers::refresh_handler,
         auth::handlers::me_handler,
+        auth::handlers::verify_email_handler,
+        auth::handlers::resend_verification_handler,
+        auth::handlers::list_sessions_handler,
+        auth::handlers::revoke_session_handler,
+        auth::handlers::verify_two_factor_handler,
+        account::handlers::export_personal_data_handler,
+        account::handlers::delete_account_handler,
+        auth::handlers::two_factor_status_handler,
+        auth::handlers::setup_two_factor_handler,
+        auth::handlers::enable_two_factor_handler,
+        auth::handlers::disable_two_factor_handler,
+        auth::handlers::regenerate_recovery_codes_handler,
+        auth::handlers::unlock_user_handler,
+        auth::handlers::list_users_handler,
+        auth::handlers::get_user_handler,
+        auth::handlers::update_user_role_handler,
+        auth::handlers::disable_user_handler,
+        auth::handlers::enable_user_handler,
+        auth::handlers::force_logout_handler,
+        auth::handlers::list_roles_handler,
+        auth::handlers::update_role_permissions_handler,
+        auth::handlers::jwks_handler,
+        auth::handlers::list_signing_keys_handler,
+        auth::handlers::rotate_signing_key_handler,
     ),
     components(
         schemas(
             Coffee, 
+            CoffeeDetail,
+            reviews::models::ReviewResponse,
+            reviews::models::ReviewStatus,
+            reviews::models::ReviewReply,
+            PairedCoffee,
+            recommendations::models::CoffeeRecommendation,
+            recommendations::models::RecommendationReason,
             CreateCoffee, 
             UpdateCoffee,
             auth::models::RegisterRequest,
@@ -54,11 +90,39 @@ use std::sync::Arc;
             auth::models::RefreshRequest,
             auth::models::AuthResponse,
             auth::models::UserResponse,
+            auth::models::VerifyEmailRequest,
+            auth::models::MessageResponse,
+            auth::models::AdminUserDetail,
+            auth::models::UpdateRoleRequest,
+            auth::models::Role,
+            auth::models::Permission,
+            auth::models::RoleDefinition,
+            auth::models::UpdateRolePermissionsRequest,
+            auth::models::SigningKeyInfo,
+            auth::models::SessionResponse,
+            auth::models::LoginResponse,
+            auth::models::TwoFactorChallengeResponse,
+            auth::models::TwoFactorVerifyRequest,
+            auth::models::TwoFactorCodeRequest,
+            auth::models::TwoFactorSetupResponse,
+            auth::models::RecoveryCodesResponse,
+            auth::models::TwoFactorStatusResponse,
+            account::models::PersonalDataExport,
+            account::models::ExportedOrder,
+            account::models::ExportedOrderItem,
+            account::models::ExportedReview,
+            account::models::ExportedReviewFlag,
+            account::models::ExportedReviewVote,
+            account::models::ExportedReviewReply,
+            account::models::ExportedLoyalty,
+            account::models::ExportedSecurityEvent,
+            account::models::DeleteAccountRequest,
         )
     ),
     tags(
         (name = "coffees", description = "Coffee menu management endpoints"),
-        (name = "auth", description = "Authentication and user management endpoints")
+        (name = "auth", description = "Authentication and user management endpoints"),
+        (name = "account", description = "Personal data export and account deletion")
     ),
     info(
         title = "Coffee Menu API",
@@ -101,6 +165,8 @@ pub struct AppState {
     pub order_service: orders::OrderService,
     pub order_items_repo: orders::OrderItemsRepository,
     pub business_rules_engine: Arc<business_rules::BusinessRulesEngine>,
+    pub account_service: account::AccountService,
+    pub recommendation_service: recommendations::RecommendationService,
 }
 
 /// Handler for POST /api/coffees
@@ -252,7 +318,7 @@ async fn get_coffees_with_query(
         ("id" = i32, Path, description = "Coffee ID")
     ),
     responses(
-        (status = 200, description = "Coffee found", body = Coffee),
+        (status = 200, description = "Coffee found; includes `my_review` when called with a valid bearer token and the caller reviewed it", body = CoffeeDetail),
         (status = 404, description = "Coffee not found", body = String, example = json!({"error": "Coffee with id 1 not found"})),
         (status = 500, description = "Internal server error", body = String, example = json!({"error": "Database error"}))
     ),
@@ -260,8 +326,9 @@ async fn get_coffees_with_query(
 )]
 async fn get_coffee_by_id(
     State(state): State<AppState>,
+    user: Option<auth::middleware::AuthenticatedUser>,
     Path(id): Path<i32>,
-) -> Result<Json<Coffee>, ApiError> {
+) -> Result<Json<CoffeeDetail>, ApiError> {
     tracing::debug!("Fetching coffee with id: {}", id);
     
     let coffee = sqlx::query_as::<_, Coffee>(
@@ -282,8 +349,91 @@ async fn get_coffee_by_id(
         }
     })?;
 
+    // The caller's own review, when authenticated
+    let my_review = match user {
+        Some(user) => state.review_service
+            .find_user_review(user.user_id, id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load review of coffee {} for user {}: {}", id, user.user_id, e);
+                None
+            }),
+        None => None,
+    };
+
     tracing::debug!("Successfully retrieved coffee: {}", coffee.name);
-    Ok(Json(coffee))
+    Ok(Json(CoffeeDetail { coffee, my_review }))
+}
+
+/// Handler for GET /api/coffees/:id/frequently-bought-with
+/// Suggests add-ons: currently available coffees bought together with this one
+/// more often than by chance, most likely first, from the last market basket refresh
+#[utoipa::path(
+    get,
+    path = "/api/coffees/{id}/frequently-bought-with",
+    params(
+        ("id" = i32, Path, description = "Coffee ID"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of coffees to return (1-20, defaults to 5)")
+    ),
+    responses(
+        (status = 200, description = "Coffees frequently bought with this one; empty until enough orders contain both", body = [PairedCoffee]),
+        (status = 400, description = "Invalid limit", body = String, example = json!({"error": "Limit must be between 1 and 20"})),
+        (status = 404, description = "Coffee not found", body = String, example = json!({"error": "Coffee with id 1 not found"})),
+        (status = 500, description = "Internal server error", body = String, example = json!({"error": "Database error"}))
+    ),
+    tag = "coffees"
+)]
+async fn get_frequently_bought_with(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(query): Query<PairedCoffeeQuery>,
+) -> Result<Json<Vec<PairedCoffee>>, ApiError> {
+    query.validate()?;
+
+    let exists: Option<(i32,)> = sqlx::query_as("SELECT id FROM coffees WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?;
+    if exists.is_none() {
+        return Err(ApiError::NotFound {
+            resource: "Coffee".to_string(),
+            id: id.to_string(),
+        });
+    }
+
+    let candidates = sqlx::query_as::<_, PairedCoffee>(
+        r#"
+        SELECT c.id, c.image_url, c.name, c.coffee_type, c.price, c.rating,
+               p.confidence::FLOAT8 AS confidence, p.lift::FLOAT8 AS lift
+        FROM coffee_pairs p
+        INNER JOIN coffees c ON c.id = p.paired_coffee_id
+        WHERE p.coffee_id = $1
+          AND p.lift > 1
+        ORDER BY p.confidence DESC, p.lift DESC, c.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    // Only suggest coffees the availability rules currently allow
+    let limit = query.limit.unwrap_or(5) as usize;
+    let mut coffees = Vec::with_capacity(limit);
+    for coffee in candidates {
+        if coffees.len() == limit {
+            break;
+        }
+        let availability = state.business_rules_engine
+            .check_availability(coffee.coffee.id)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to check availability: {}", e)))?;
+        if availability.status == business_rules::AvailabilityStatus::Available {
+            coffees.push(coffee);
+        }
+    }
+
+    tracing::debug!("Coffee {} has {} frequently-bought-with suggestions", id, coffees.len());
+    Ok(Json(coffees))
 }
 
 /// Handler for PUT /api/coffees/:id
@@ -499,6 +649,17 @@ fn create_auth_router() -> Router<AppState> {
         .route("/api/auth/login", post(auth::handlers::login_handler))
         .route("/api/auth/refresh", post(auth::handlers::refresh_handler))
         .route("/api/auth/me", get(auth::handlers::me_handler))
+        .route("/api/auth/verify-email", post(auth::handlers::verify_email_handler))
+        .route("/api/auth/resend-verification", post(auth::handlers::resend_verification_handler))
+        .route("/api/auth/sessions", get(auth::handlers::list_sessions_handler))
+        .route("/api/auth/sessions/:id", delete(auth::handlers::revoke_session_handler))
+        .route("/api/auth/2fa", get(auth::handlers::two_factor_status_handler))
+        .route("/api/auth/2fa/setup", post(auth::handlers::setup_two_factor_handler))
+        .route("/api/auth/2fa/enable", post(auth::handlers::enable_two_factor_handler))
+        .route("/api/auth/2fa/disable", post(auth::handlers::disable_two_factor_handler))
+        .route("/api/auth/2fa/recovery-codes", post(auth::handlers::regenerate_recovery_codes_handler))
+        .route("/api/auth/2fa/verify", post(auth::handlers::verify_two_factor_handler))
+        .route("/.well-known/jwks.json", get(auth::handlers::jwks_handler))
 }
 
 /// Creates and configures the application router
@@ -506,15 +667,34 @@ fn create_auth_router() -> Router<AppState> {
 async fn create_router(db: PgPool, auth_service: Arc<auth::service::AuthService>) -> Router {
     use tower_http::cors::{CorsLayer, Any};
     use axum::middleware::from_fn;
+    use axum::Extension;
+
+    // Account policy (e.g. email verification required for orders)
+    let auth_policy = auth_service.policy().clone();
 
     // Initialize review service
     let review_repository = reviews::ReviewRepository::new(db.clone());
-    let rating_calculator = reviews::RatingCalculator::new(review_repository.clone());
-    let review_service = reviews::ReviewService::new(review_repository, rating_calculator);
+    let rating_score = config::RatingScoreConfig::from_env()
+        .expect("Invalid rating score configuration");
+    let rating_calculator = reviews::RatingCalculator::new(review_repository.clone())
+        .with_score(rating_score);
+    let review_moderation = config::ReviewModerationConfig::from_env()
+        .expect("Invalid review moderation configuration");
+    let review_policy = config::ReviewPolicyConfig::from_env()
+        .expect("Invalid review policy configuration");
+    let review_service = reviews::ReviewService::new(review_repository, rating_calculator)
+        .with_moderation(review_moderation)
+        .with_policy(review_policy);
+
+    // Shop timezone for time-based pricing and analytics buckets
+    let shop = config::ShopConfig::from_env()
+        .expect("Invalid shop configuration");
 
     // Initialize business rules engine
     tracing::info!("Initializing business rules engine...");
-    let business_rules_engine = Arc::new(business_rules::BusinessRulesEngine::new(db.clone()));
+    let business_rules_engine = Arc::new(
+        business_rules::BusinessRulesEngine::new(db.clone()).with_timezone(shop.timezone),
+    );
     
     // Warm up the cache
     tracing::info!("Warming business rules cache...");
@@ -534,13 +714,95 @@ async fn create_router(db: PgPool, auth_service: Arc<auth::service::AuthService>
         business_rules_engine.clone(),
     );
 
+    // Initialize account service (personal data export and deletion)
+    let account_service = account::AccountService::new(
+        account::AccountRepository::new(db.clone()),
+        auth_service.clone(),
+    );
+
+    // Initialize analytics reports
+    let orders_analytics = analytics::repositories::OrdersAnalyticsRepository::new(db.clone());
+    let reviews_analytics = analytics::repositories::ReviewsAnalyticsRepository::new(db.clone());
+
+    // Initialize personalised recommendations (history, ratings, pairs and popularity)
+    let recommendation_service = recommendations::RecommendationService::new(
+        recommendations::RecommendationRepository::new(db.clone()),
+        analytics::services::PopularCoffeesService::new(orders_analytics.clone(), reviews_analytics.clone()),
+        analytics::services::TrendCalculationService::new(orders_analytics.clone()),
+        business_rules_engine.clone(),
+    )
+    .with_timezone(shop.timezone);
+    let analytics_routes = analytics::create_analytics_router(analytics::AnalyticsControllers {
+        sales: Arc::new(
+            analytics::controllers::SalesStatisticsController::new(Arc::new(
+                analytics::services::SalesAggregationService::new(orders_analytics.clone()),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+        popular_coffees: Arc::new(
+            analytics::controllers::PopularCoffeesController::new(
+                Arc::new(analytics::services::PopularCoffeesService::new(
+                    orders_analytics.clone(),
+                    reviews_analytics.clone(),
+                )),
+                Arc::new(analytics::services::TrendCalculationService::new(orders_analytics.clone())),
+            )
+            .with_timezone(shop.timezone),
+        ),
+        revenue: Arc::new(
+            analytics::controllers::RevenueReportsController::new(Arc::new(
+                analytics::services::RevenueCalculationService::new(orders_analytics.clone()),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+        rating_insights: Arc::new(
+            analytics::controllers::RatingInsightsController::new(Arc::new(
+                analytics::services::RatingAnalysisService::new(reviews_analytics),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+        basket: Arc::new(
+            analytics::controllers::BasketAnalyticsController::new(Arc::new(
+                analytics::services::BasketAnalysisService::new(orders_analytics.clone()),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+        pricing_rules: Arc::new(
+            analytics::controllers::PricingRuleReportsController::new(Arc::new(
+                analytics::services::PricingRuleAnalysisService::new(orders_analytics.clone()),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+        customers: Arc::new(
+            analytics::controllers::CustomerAnalyticsController::new(Arc::new(
+                analytics::services::CustomerAnalyticsService::new(
+                    analytics::repositories::CustomersAnalyticsRepository::new(db.clone()),
+                ),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+        market_basket: Arc::new(analytics::controllers::MarketBasketController::new(Arc::new(
+            analytics::services::MarketBasketService::new(
+                analytics::repositories::MarketBasketRepository::new(db.clone()),
+            ),
+        ))),
+        forecasts: Arc::new(
+            analytics::controllers::DemandForecastController::new(Arc::new(
+                analytics::services::DemandForecastService::new(orders_analytics),
+            ))
+            .with_timezone(shop.timezone),
+        ),
+    });
+
     let state = AppState { 
         db,
-        auth_service,
+        auth_service: auth_service.clone(),
         review_service,
         order_service,
         order_items_repo,
         business_rules_engine,
+        account_service,
+        recommendation_service,
     };
 
     // Configure CORS to allow all origins, methods, and headers
@@ -549,36 +811,83 @@ async fn create_router(db: PgPool, auth_service: Arc<auth::service::AuthService>
         .allow_methods(Any)
         .allow_headers(Any);
 
-    // Create protected admin routes with RequireRole middleware
-    let admin_routes = Router::new()
+    // Create protected management routes, grouped by the permission they require
+    let require = |permission: auth::Permission| {
+        from_fn(move |req, next| {
+            auth::middleware::RequirePermission::new(permission).middleware(req, next)
+        })
+    };
+
+    let menu_routes = Router::new()
         .route("/api/coffees", post(create_coffee))
         .route("/api/coffees/:id", put(update_coffee))
         .route("/api/coffees/:id", delete(delete_coffee))
+        .route("/api/business-rules/availability", post(business_rules::handlers::update_availability_handler))
+        .route("/api/business-rules/prep-time/:id", put(business_rules::handlers::update_prep_time_handler))
+        .route_layer(require(auth::Permission::MenuWrite));
+
+    let order_management_routes = Router::new()
         .route("/api/orders/:id/status", patch(orders::update_order_status_handler))
         .route("/api/orders/:id/payment", patch(orders::update_payment_status_handler))
-        .route("/api/business-rules/availability", post(business_rules::handlers::update_availability_handler))
+        .route_layer(require(auth::Permission::OrdersManage));
+
+    let pricing_routes = Router::new()
         .route("/api/business-rules/pricing", post(business_rules::handlers::create_pricing_rule_handler))
         .route("/api/business-rules/pricing/:id", put(business_rules::handlers::update_pricing_rule_handler))
         .route("/api/business-rules/pricing/:id", delete(business_rules::handlers::delete_pricing_rule_handler))
         .route("/api/business-rules/loyalty-config", put(business_rules::handlers::update_loyalty_config_handler))
-        .route("/api/business-rules/prep-time/:id", put(business_rules::handlers::update_prep_time_handler))
-        .route_layer(from_fn(move |req, next| {
-            auth::middleware::RequireRole::admin().middleware(req, next)
-        }));
+        .route_layer(require(auth::Permission::PricingWrite));
+
+    let user_management_routes = Router::new()
+        .route("/api/admin/users", get(auth::handlers::list_users_handler))
+        .route("/api/admin/users/:id", get(auth::handlers::get_user_handler))
+        .route("/api/admin/users/:id/role", put(auth::handlers::update_user_role_handler))
+        .route("/api/admin/users/:id/disable", post(auth::handlers::disable_user_handler))
+        .route("/api/admin/users/:id/enable", post(auth::handlers::enable_user_handler))
+        .route("/api/admin/users/:id/logout", post(auth::handlers::force_logout_handler))
+        .route("/api/admin/users/:id/unlock", post(auth::handlers::unlock_user_handler))
+        .route("/api/admin/roles", get(auth::handlers::list_roles_handler))
+        .route("/api/admin/roles/:role/permissions", put(auth::handlers::update_role_permissions_handler))
+        .route_layer(require(auth::Permission::UsersManage));
+
+    let key_management_routes = Router::new()
+        .route("/api/admin/signing-keys", get(auth::handlers::list_signing_keys_handler))
+        .route("/api/admin/signing-keys/rotate", post(auth::handlers::rotate_signing_key_handler))
+        .route_layer(require(auth::Permission::KeysManage));
+
+    let review_moderation_routes = Router::new()
+        .route("/api/admin/reviews/moderation", get(reviews::get_moderation_queue_handler))
+        .route("/api/admin/reviews/:id/flags", get(reviews::get_review_flags_handler))
+        .route("/api/admin/reviews/:id/status", put(reviews::moderate_review_handler))
+        .route_layer(require(auth::Permission::ReviewsModerate));
+
+    let review_reply_routes = Router::new()
+        .route("/api/reviews/:id/reply", put(reviews::reply_to_review_handler))
+        .route("/api/reviews/:id/reply", delete(reviews::delete_reply_handler))
+        .route_layer(require(auth::Permission::ReviewsReply));
 
     // Create protected user routes (authenticated users only)
     let user_routes = Router::new()
         .route("/api/reviews", post(reviews::create_review_handler))
         .route("/api/reviews/:id", put(reviews::update_review_handler))
         .route("/api/reviews/:id", delete(reviews::delete_review_handler))
+        .route("/api/reviews/:id/flags", post(reviews::flag_review_handler))
+        .route("/api/reviews/:id/helpful", post(reviews::vote_helpful_handler))
+        .route("/api/reviews/:id/helpful", delete(reviews::remove_helpful_vote_handler))
         .route("/api/orders", post(orders::create_order_handler))
         .route("/api/orders", get(orders::get_order_history_handler))
-        .route("/api/orders/:id", get(orders::get_order_by_id_handler));
+        .route("/api/orders/:id", get(orders::get_order_by_id_handler))
+        .route("/api/me/reviews", get(reviews::get_my_reviews_handler))
+        .route("/api/coffees/:id/reviews/mine", get(reviews::get_my_review_for_coffee_handler))
+        .route("/api/me", delete(account::delete_account_handler))
+        .route("/api/me/export", get(account::export_personal_data_handler))
+        .route("/api/me/recommendations", get(recommendations::get_recommendations_handler));
 
     // Create public routes (no authorization required)
     let public_routes = Router::new()
         .route("/api/coffees", get(get_coffees_with_query))
         .route("/api/coffees/:id", get(get_coffee_by_id))
+        .route("/api/coffees/:id/frequently-bought-with", get(get_frequently_bought_with))
         .route("/api/coffees/favorites/:id", get(get_favorite_coffee))
         .route("/api/coffees/:id/reviews", get(reviews::get_reviews_for_coffee_handler))
         .route("/api/business-rules/availability/:id", get(business_rules::handlers::get_availability_handler))
@@ -590,12 +899,23 @@ async fn create_router(db: PgPool, auth_service: Arc<auth::service::AuthService>
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui")
             .url("/api-docs/openapi.json", ApiDoc::openapi()))
-        // Merge admin and public routes
-        .merge(admin_routes)
+        // Merge management, user and public routes
+        .merge(menu_routes)
+        .merge(order_management_routes)
+        .merge(pricing_routes)
+        .merge(user_management_routes)
+        .merge(key_management_routes)
+        .merge(review_moderation_routes)
+        .merge(review_reply_routes)
         .merge(user_routes)
         .merge(public_routes)
+        // Admin analytics reports (guarded by analytics:read inside the router)
+        .nest_service("/api/v1/admin/analytics", analytics_routes)
         // Authentication routes
         .merge(create_auth_router())
+        // Account state and policy consumed by the AuthenticatedUser extractors
+        .layer(Extension(auth_service))
+        .layer(Extension(auth_policy))
         .layer(cors)
         .with_state(state)
 }
@@ -617,8 +937,13 @@ async fn main() {
     // Get configuration from environment variables
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set in environment");
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .expect("JWT_SECRET must be set in environment");
+    let jwt_signing = config::JwtSigningConfig::from_env()
+        .expect("Invalid JWT signing configuration");
+    // Required for HS256; with RS256/EdDSA it only keeps older HS256 tokens verifying
+    let jwt_secret = std::env::var("JWT_SECRET").ok();
+    if !jwt_signing.algorithm.is_asymmetric() && jwt_secret.is_none() {
+        panic!("JWT_SECRET must be set in environment");
+    }
     let host = std::env::var("HOST")
         .unwrap_or_else(|_| "0.0.0.0".to_string());
     let port = std::env::var("PORT")
@@ -640,19 +965,162 @@ async fn main() {
         }
     }
 
+    // Refresh weighted coffee ratings so score setting changes take effect
+    let rating_score = config::RatingScoreConfig::from_env()
+        .expect("Invalid rating score configuration");
+    let rating_calculator = reviews::RatingCalculator::new(reviews::ReviewRepository::new(db_pool.clone()))
+        .with_score(rating_score.clone());
+
+    // `coffee-api rebuild-ratings` rebuilds rating totals from the reviews table and exits
+    if std::env::args().nth(1).as_deref() == Some("rebuild-ratings") {
+        let repaired = rating_calculator.repair_all()
+            .await
+            .expect("Failed to rebuild coffee ratings");
+        tracing::info!("Rebuilt coffee ratings ({} coffees were out of date)", repaired);
+        return;
+    }
+
+    // Daily sales rollups are bucketed by the shop's local days
+    let shop = config::ShopConfig::from_env()
+        .expect("Invalid shop configuration");
+    let orders_analytics = analytics::repositories::OrdersAnalyticsRepository::new(db_pool.clone());
+    match orders_analytics.sync_rollup_timezone(shop.timezone).await {
+        Ok(true) => tracing::info!("Rebuilt daily sales rollups for shop timezone {}", shop.timezone),
+        Ok(false) => {}
+        Err(e) => tracing::warn!("Failed to apply shop timezone to daily sales rollups: {}", e),
+    }
+
+    // `coffee-api rebuild-sales-rollups` rebuilds the daily sales rollups from the orders tables and exits
+    if std::env::args().nth(1).as_deref() == Some("rebuild-sales-rollups") {
+        let days = orders_analytics
+            .rebuild_daily_rollups()
+            .await
+            .expect("Failed to rebuild daily sales rollups");
+        println!("Rebuilt daily sales rollups ({} days with completed orders)", days);
+        return;
+    }
+
+    // Frequently-bought-together pairs over a trailing window of completed orders
+    let market_basket = config::MarketBasketConfig::from_env()
+        .expect("Invalid market basket configuration");
+    let market_basket_service = analytics::services::MarketBasketService::new(
+        analytics::repositories::MarketBasketRepository::new(db_pool.clone()),
+    )
+    .with_config(market_basket.clone());
+
+    // `coffee-api rebuild-coffee-pairs` recomputes the frequently-bought-together pairs and exits
+    if std::env::args().nth(1).as_deref() == Some("rebuild-coffee-pairs") {
+        let rows = market_basket_service
+            .refresh_pairs()
+            .await
+            .expect("Failed to rebuild coffee pairs");
+        tracing::info!("Rebuilt coffee pairs ({} pairs over the last {} days)", rows / 2, market_basket.window_days);
+        return;
+    }
+
+    match rating_calculator.recalculate_all().await {
+        Ok(count) => tracing::info!("Recalculated {} ratings ({} score)", count, rating_score.method.as_str()),
+        Err(e) => tracing::warn!("Failed to recalculate coffee ratings: {}", e),
+    }
+
     // Initialize authentication service
     tracing::info!("Initializing authentication service...");
-    let token_service = auth::token::TokenService::new(jwt_secret);
+    let token_service = match (&jwt_secret, jwt_signing.algorithm.is_asymmetric()) {
+        (Some(secret), false) => auth::token::TokenService::new(secret.clone()),
+        // Keys are loaded from the database below
+        _ => auth::token::TokenService::with_key_ring(auth::keys::KeyRing::default()),
+    };
     let password_service = auth::password::PasswordService;
     let user_repository = auth::repository::UserRepository::new(db_pool.clone());
     let token_repository = auth::repository::TokenRepository::new(db_pool.clone());
-    let auth_service = Arc::new(auth::service::AuthService::new(
+    let security_repository = auth::repository::SecurityRepository::new(db_pool.clone());
+    let two_factor_repository = auth::repository::TwoFactorRepository::new(db_pool.clone());
+    let auth_policy = config::AuthPolicyConfig::from_env()
+        .expect("Invalid authentication policy configuration");
+    let login_protection = config::LoginProtectionConfig::from_env()
+        .expect("Invalid login protection configuration");
+    let session_config = config::SessionConfig::from_env()
+        .expect("Invalid session configuration");
+    let mut auth_service = auth::service::AuthService::new(
         user_repository,
         token_repository,
+        security_repository,
+        two_factor_repository,
         password_service,
         token_service,
-    ));
-    tracing::info!("Authentication service initialized");
+    )
+    .with_policy(auth_policy)
+    .with_login_protection(login_protection)
+    .with_session_config(session_config.clone());
+    if jwt_signing.algorithm.is_asymmetric() {
+        let signing_key_repository = auth::repository::SigningKeyRepository::new(db_pool.clone());
+        auth_service = auth_service.with_signing_keys(signing_key_repository, jwt_signing.clone(), jwt_secret);
+    }
+    let auth_service = Arc::new(auth_service);
+    auth_service.load_signing_keys()
+        .await
+        .expect("Failed to load JWT signing keys");
+    tracing::info!("Authentication service initialized ({} tokens)", jwt_signing.algorithm);
+
+    // `coffee-api rotate-signing-key` rotates the key and exits
+    if std::env::args().nth(1).as_deref() == Some("rotate-signing-key") {
+        let key = auth_service.rotate_signing_key(None)
+            .await
+            .expect("Failed to rotate signing key");
+        println!("New signing key {} ({}) signs from {}", key.kid, key.algorithm, key.activated_at);
+        return;
+    }
+
+    // Pick up keys rotated by other instances
+    if jwt_signing.algorithm.is_asymmetric() {
+        let auth_service = auth_service.clone();
+        let mut interval = tokio::time::interval(jwt_signing.key_refresh_interval);
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                if let Err(e) = auth_service.load_signing_keys().await {
+                    tracing::warn!("Failed to refresh JWT signing keys: {}", e);
+                }
+            }
+        });
+    }
+
+    // Delete expired and idle sessions
+    let cleanup_auth_service = auth_service.clone();
+    let mut interval = tokio::time::interval(session_config.cleanup_interval);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            match cleanup_auth_service.delete_expired_sessions().await {
+                Ok(deleted) if deleted > 0 => tracing::info!("Deleted {} expired sessions", deleted),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to delete expired sessions: {}", e),
+            }
+        }
+    });
+
+    // Recompute the daily sales rollups of days whose orders changed
+    let mut interval = tokio::time::interval(shop.rollup_refresh_interval);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            if let Err(e) = orders_analytics.refresh_changed_rollups().await {
+                tracing::warn!("Failed to refresh daily sales rollups: {}", e);
+            }
+        }
+    });
+
+    // Recompute the frequently-bought-together pairs now and then periodically
+    let mut interval = tokio::time::interval(market_basket.refresh_interval);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            match market_basket_service.refresh_pairs().await {
+                Ok(rows) => tracing::info!("Refreshed frequently-bought-together coffee pairs ({} pairs)", rows / 2),
+                Err(e) => tracing::warn!("Failed to refresh frequently-bought-together coffee pairs: {}", e),
+            }
+        }
+    });
 
     // Create the application router
     let app = create_router(db_pool, auth_service).await;
@@ -668,7 +1136,8 @@ async fn main() {
     tracing::info!("Coffee API is running on http://{}", addr);
     tracing::info!("Swagger UI available at http://{}/swagger-ui", addr);
     
-    axum::serve(listener, app)
+    // Connect info provides the client IP for login protection when no proxy headers are set
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>())
         .await
         .expect("Server error");
 }
@@ -1,3 +1,4 @@
+use crate::reviews::ReviewResponse;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
@@ -21,6 +22,38 @@ pub struct Coffee {
     pub rating: f64,
 }
 
+/// Coffee returned by the detail endpoint
+/// Includes the caller's own review when the request is authenticated
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CoffeeDetail {
+    #[serde(flatten)]
+    pub coffee: Coffee,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub my_review: Option<ReviewResponse>,
+}
+
+/// Coffee often bought together with another one, suggested as an add-on
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct PairedCoffee {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub coffee: Coffee,
+    /// Share of the other coffee's orders that also contained this one
+    #[schema(example = 0.42, minimum = 0.0, maximum = 1.0)]
+    pub confidence: f64,
+    /// How many times more often the two are bought together than by chance
+    #[schema(example = 1.8)]
+    pub lift: f64,
+}
+
+/// Query parameters for the frequently-bought-with endpoint
+#[derive(Debug, Deserialize, Validate)]
+pub struct PairedCoffeeQuery {
+    /// Maximum number of coffees to return (defaults to 5)
+    #[validate(range(min = 1, max = 20, message = "Limit must be between 1 and 20"))]
+    pub limit: Option<i64>,
+}
+
 /// Represents the data needed to create a new coffee product
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateCoffee {
@@ -0,0 +1,71 @@
+// Notifications sent to users about activity on their account or content
+
+use axum::async_trait;
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("Notification delivery failed: {0}")]
+    DeliveryFailed(String),
+}
+
+/// Event a user is notified about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// The shop replied to the user's review
+    ReviewReply { review_id: i32, coffee_id: i32 },
+}
+
+impl Notification {
+    /// Short human-readable text for the notification
+    pub fn message(&self) -> String {
+        match self {
+            Notification::ReviewReply { coffee_id, .. } => {
+                format!("The shop replied to your review of coffee #{}", coffee_id)
+            }
+        }
+    }
+}
+
+/// Notifier used to deliver notifications to users
+///
+/// Implementations wrap a concrete channel (push, email, in-app inbox, ...).
+/// Services only depend on this trait so delivery can be swapped or stubbed
+/// in tests.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Deliver a notification to the given user
+    async fn notify(&self, user_id: i32, notification: Notification) -> Result<(), NotificationError>;
+}
+
+/// Notifier that writes notifications to the application log
+///
+/// Used as the default when no channel is configured, e.g. in development.
+#[derive(Debug, Clone, Default)]
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, user_id: i32, notification: Notification) -> Result<(), NotificationError> {
+        info!("Notification for user {}: {}", user_id, notification.message());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_reply_message() {
+        let notification = Notification::ReviewReply { review_id: 7, coffee_id: 3 };
+        assert_eq!(notification.message(), "The shop replied to your review of coffee #3");
+    }
+
+    #[tokio::test]
+    async fn test_log_notifier_succeeds() {
+        let notification = Notification::ReviewReply { review_id: 7, coffee_id: 3 };
+        assert!(LogNotifier.notify(1, notification).await.is_ok());
+    }
+}
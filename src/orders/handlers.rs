@@ -9,7 +9,7 @@ use serde::Deserialize;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::middleware::{AuthenticatedUser, VerifiedUser};
 use crate::orders::{
     CreateOrderRequest, OrderError, OrderResponse, OrderStatus, PaymentStatus,
     UpdatePaymentRequest, UpdateStatusRequest,
@@ -24,9 +24,10 @@ pub struct OrderHistoryQuery {
 
 /// Handler for POST /api/orders
 /// Creates a new order for the authenticated user
+/// Requires a verified email when REQUIRE_VERIFIED_EMAIL_FOR_ORDERS is enabled
 pub async fn create_order_handler(
     State(state): State<crate::AppState>,
-    user: AuthenticatedUser,
+    VerifiedUser(user): VerifiedUser,
     Json(request): Json<CreateOrderRequest>,
 ) -> Result<(StatusCode, Json<OrderResponse>), OrderError> {
     // Validate request
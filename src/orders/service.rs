@@ -137,6 +137,7 @@ impl OrderService {
         let base_price = PriceCalculator::calculate_total(&subtotals);
         let mut final_price = base_price;
         let mut estimated_prep_minutes: Option<i32> = None;
+        let mut pricing_result = None;
 
         // Generate a temporary order ID for business rules validation
         let temp_order_id = Uuid::new_v4();
@@ -180,12 +181,13 @@ impl OrderService {
                 })
                 .collect();
 
-            let pricing_result = engine
-                .calculate_price(temp_order_id, &pricing_items, CombinationStrategy::BestPrice)
+            let result = engine
+                .calculate_price(&pricing_items, CombinationStrategy::BestPrice)
                 .await
                 .map_err(|e| OrderError::ValidationError(format!("Pricing calculation failed: {}", e)))?;
 
-            final_price = pricing_result.final_price;
+            final_price = result.final_price;
+            pricing_result = Some(result);
 
             // 3. Estimate prep time
             let prep_items: Vec<PrepTimeOrderItem> = request
@@ -217,6 +219,13 @@ impl OrderService {
             )
             .await?;
 
+        // Log applied pricing rules against the stored order
+        if let (Some(engine), Some(result)) = (&self.business_rules_engine, &pricing_result) {
+            engine
+                .log_pricing(order.id, result, CombinationStrategy::BestPrice)
+                .await;
+        }
+
         // TODO: Store base_price, final_price, and estimated_prep_minutes in orders table
         // This requires a database migration to add these columns
 
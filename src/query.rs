@@ -59,17 +59,16 @@ impl SQLQueryBuilder {
     /// Sets the sort order for the query
     /// Adds an ORDER BY clause with the specified field and order
     pub fn set_sort(&mut self, field: SortField, order: SortOrder) {
-        let field_name = match field {
-            SortField::Price => "price",
-            SortField::Rating => "rating",
-        };
-        
         let order_str = match order {
             SortOrder::Asc => "ASC",
             SortOrder::Desc => "DESC",
         };
         
-        self.order_clause = Some(format!("{} {}", field_name, order_str));
+        // Ratings sort by the confidence-weighted score; unrated coffees go last
+        self.order_clause = Some(match field {
+            SortField::Price => format!("price {}", order_str),
+            SortField::Rating => format!("weighted_rating {} NULLS LAST, id", order_str),
+        });
     }
     
     /// Sets pagination parameters
@@ -409,7 +408,7 @@ mod tests {
         assert!(query.contains("coffee_type ILIKE $2"));
         assert!(query.contains("price >= $3"));
         assert!(query.contains("price <= $4"));
-        assert!(query.contains("ORDER BY rating DESC"));
+        assert!(query.contains("ORDER BY weighted_rating DESC NULLS LAST"));
         assert!(query.contains("LIMIT"));
         assert!(query.contains("OFFSET"));
         
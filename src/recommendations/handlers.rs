@@ -0,0 +1,44 @@
+// HTTP handlers for coffee recommendations
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use validator::Validate;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::ApiError;
+use crate::recommendations::models::{CoffeeRecommendation, RecommendationQuery};
+use crate::AppState;
+
+/// Recommend coffees to the current user (protected endpoint)
+/// GET /api/me/recommendations?limit=10
+#[utoipa::path(
+    get,
+    path = "/api/me/recommendations",
+    params(
+        ("limit" = Option<i64>, Query, description = "Maximum number of coffees to return (1-50, defaults to 10)")
+    ),
+    responses(
+        (status = 200, description = "Available coffees ranked for the user, best match first", body = [CoffeeRecommendation]),
+        (status = 400, description = "Invalid limit", body = String, example = json!({"error": "Limit must be between 1 and 50"})),
+        (status = 401, description = "Unauthorized - invalid or missing token", body = String)
+    ),
+    tag = "coffees",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_recommendations_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(query): Query<RecommendationQuery>,
+) -> Result<Json<Vec<CoffeeRecommendation>>, ApiError> {
+    query.validate()?;
+
+    let recommendations = state.recommendation_service
+        .recommend(user.user_id, query.limit.unwrap_or(10) as usize)
+        .await?;
+
+    Ok(Json(recommendations))
+}
@@ -0,0 +1,11 @@
+// Recommendations module
+// Personalised coffee recommendations for signed-in users
+
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod service;
+
+pub use repository::*;
+pub use service::*;
+pub use handlers::*;
@@ -0,0 +1,41 @@
+// Data models for personalised coffee recommendations
+
+use crate::models::Coffee;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// The signal that contributed most to a recommendation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationReason {
+    /// The user orders it regularly
+    OrderedBefore,
+    /// The user rated it 4 or 5 stars
+    RatedHighly,
+    /// Often bought together with coffees the user likes
+    BoughtTogether,
+    /// Among the most ordered coffees lately
+    Popular,
+    /// Orders are growing week over week
+    Trending,
+}
+
+/// Coffee recommended to the signed-in user, returned by GET /api/me/recommendations
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CoffeeRecommendation {
+    #[serde(flatten)]
+    pub coffee: Coffee,
+    /// Blended score from 0 to 1 the recommendations are ranked by
+    #[schema(example = 0.6125)]
+    pub score: f64,
+    pub reason: RecommendationReason,
+}
+
+/// Query parameters for GET /api/me/recommendations
+#[derive(Debug, Deserialize, Validate)]
+pub struct RecommendationQuery {
+    /// Maximum number of coffees to return (defaults to 10)
+    #[validate(range(min = 1, max = 50, message = "Limit must be between 1 and 50"))]
+    pub limit: Option<i64>,
+}
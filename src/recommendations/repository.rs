@@ -0,0 +1,87 @@
+use crate::models::Coffee;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// Repository reading the per-user signals coffee recommendations are built from
+#[derive(Clone)]
+pub struct RecommendationRepository {
+    pool: PgPool,
+}
+
+impl RecommendationRepository {
+    /// Create a new RecommendationRepository
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Every coffee on the menu, by id
+    pub async fn find_coffees(&self) -> Result<Vec<Coffee>, sqlx::Error> {
+        sqlx::query_as::<_, Coffee>(
+            "SELECT id, image_url, name, coffee_type, price, rating FROM coffees ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Number of completed orders the user placed
+    pub async fn count_completed_orders(&self, user_id: i32) -> Result<i64, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM orders WHERE user_id = $1 AND status = 'completed'",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Completed orders of the user containing each coffee, as (coffee_id, orders)
+    pub async fn find_coffee_order_counts(&self, user_id: i32) -> Result<Vec<(i32, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (i32, i64)>(
+            r#"
+            SELECT oi.coffee_item_id, COUNT(DISTINCT o.id)
+            FROM orders o
+            INNER JOIN order_items oi ON oi.order_id = o.id
+            WHERE o.user_id = $1
+              AND o.status = 'completed'
+            GROUP BY oi.coffee_item_id
+            ORDER BY oi.coffee_item_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// The user's own star rating of each coffee they reviewed, as (coffee_id, rating)
+    /// Held or hidden reviews still say what the user thinks of the coffee
+    pub async fn find_ratings(&self, user_id: i32) -> Result<Vec<(i32, i32)>, sqlx::Error> {
+        sqlx::query_as::<_, (i32, i32)>(
+            "SELECT coffee_id, rating::INT FROM reviews WHERE user_id = $1 ORDER BY coffee_id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Coffees bought together with any of `coffee_ids` more often than by
+    /// chance, from the last market basket refresh
+    /// Returns (coffee_id, paired_coffee_id, confidence)
+    pub async fn find_paired_coffees(
+        &self,
+        coffee_ids: &[i32],
+    ) -> Result<Vec<(i32, i32, Decimal)>, sqlx::Error> {
+        sqlx::query_as::<_, (i32, i32, Decimal)>(
+            r#"
+            SELECT coffee_id, paired_coffee_id, confidence
+            FROM coffee_pairs
+            WHERE coffee_id = ANY($1)
+              AND lift > 1
+            ORDER BY coffee_id, paired_coffee_id
+            "#,
+        )
+        .bind(coffee_ids)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,430 @@
+use crate::analytics::{
+    services::{PopularCoffeesService, TrendCalculationService},
+    types::DateRange,
+};
+use crate::business_rules::{AvailabilityStatus, BusinessRulesEngine};
+use crate::error::ApiError;
+use crate::models::Coffee;
+use crate::recommendations::{
+    models::{CoffeeRecommendation, RecommendationReason},
+    repository::RecommendationRepository,
+};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Days of orders "current popularity" is measured over
+const POPULARITY_DAYS: i64 = 30;
+/// Days per period compared to find trending coffees
+const TRENDING_DAYS: i64 = 7;
+/// Most ordered or trending coffees considered per request
+const CANDIDATE_LIMIT: i32 = 20;
+
+/// Everything a user's recommendations are ranked from
+#[derive(Debug, Clone, Default)]
+pub struct RecommendationSignals {
+    /// Completed orders of the user
+    pub order_count: i64,
+    /// Completed orders of the user containing each coffee
+    pub coffee_orders: HashMap<i32, i64>,
+    /// The user's own star rating (1-5) of each coffee they reviewed
+    pub ratings: HashMap<i32, i32>,
+    /// Best confidence that each coffee is bought with a coffee the user likes
+    pub bought_together: HashMap<i32, Decimal>,
+    /// Recent orders of the most ordered coffees
+    pub popularity: HashMap<i32, i64>,
+    /// Coffees with growing orders, fastest growing first (new users only)
+    pub trending: Vec<i32>,
+}
+
+impl RecommendationSignals {
+    /// Users without completed orders or reviews get trending coffees
+    pub fn is_new_user(&self) -> bool {
+        self.order_count == 0 && self.ratings.is_empty()
+    }
+
+    /// Coffees the user ordered without rating them 1 or 2 stars, or rated 4 or 5 stars
+    pub fn liked_coffees(&self) -> Vec<i32> {
+        let mut liked: Vec<i32> = self
+            .coffee_orders
+            .keys()
+            .filter(|id| !self.dislikes(**id))
+            .chain(self.ratings.iter().filter(|(_, rating)| **rating >= 4).map(|(id, _)| id))
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        liked.sort_unstable();
+        liked
+    }
+
+    fn dislikes(&self, coffee_id: i32) -> bool {
+        self.ratings.get(&coffee_id).is_some_and(|rating| *rating <= 2)
+    }
+}
+
+/// Service ranking coffees for a signed-in user
+///
+/// Returning users are scored on their order history, their own ratings,
+/// co-occurrence with coffees they like and current popularity; users with
+/// no orders or reviews get trending coffees instead. Coffees that cannot be
+/// ordered right now are never recommended.
+#[derive(Clone)]
+pub struct RecommendationService {
+    repository: RecommendationRepository,
+    popular_coffees: PopularCoffeesService,
+    trends: TrendCalculationService,
+    business_rules_engine: Arc<BusinessRulesEngine>,
+    timezone: Tz,
+}
+
+impl RecommendationService {
+    /// Create a new RecommendationService
+    pub fn new(
+        repository: RecommendationRepository,
+        popular_coffees: PopularCoffeesService,
+        trends: TrendCalculationService,
+        business_rules_engine: Arc<BusinessRulesEngine>,
+    ) -> Self {
+        Self {
+            repository,
+            popular_coffees,
+            trends,
+            business_rules_engine,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Measure popularity and trends over the shop's days
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Up to `limit` available coffees for the user, best match first
+    pub async fn recommend(&self, user_id: i32, limit: usize) -> Result<Vec<CoffeeRecommendation>, ApiError> {
+        let coffees = self.repository.find_coffees().await?;
+        let unavailable = self.unavailable_coffees(&coffees).await?;
+        let signals = self.gather_signals(user_id).await?;
+
+        Ok(Self::rank(coffees, &signals, &unavailable, limit))
+    }
+
+    /// Coffees the availability rules currently block
+    async fn unavailable_coffees(&self, coffees: &[Coffee]) -> Result<HashSet<i32>, ApiError> {
+        let mut unavailable = HashSet::new();
+        for coffee in coffees {
+            let availability = self.business_rules_engine
+                .check_availability(coffee.id)
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to check availability: {}", e)))?;
+            if availability.status != AvailabilityStatus::Available {
+                unavailable.insert(coffee.id);
+            }
+        }
+        Ok(unavailable)
+    }
+
+    /// Load the user's history and ratings, then the co-occurrence, popularity
+    /// and (for new users) trending signals
+    async fn gather_signals(&self, user_id: i32) -> Result<RecommendationSignals, ApiError> {
+        let mut signals = RecommendationSignals {
+            order_count: self.repository.count_completed_orders(user_id).await?,
+            coffee_orders: self.repository.find_coffee_order_counts(user_id).await?.into_iter().collect(),
+            ratings: self.repository.find_ratings(user_id).await?.into_iter().collect(),
+            ..Default::default()
+        };
+
+        let liked = signals.liked_coffees();
+        if !liked.is_empty() {
+            for (_, paired_coffee_id, confidence) in self.repository.find_paired_coffees(&liked).await? {
+                let best = signals.bought_together.entry(paired_coffee_id).or_insert(Decimal::ZERO);
+                *best = (*best).max(confidence);
+            }
+        }
+
+        let now = Utc::now();
+        let recent = self.days_before(now, POPULARITY_DAYS);
+        signals.popularity = self.popular_coffees
+            .get_most_ordered(recent, CANDIDATE_LIMIT)
+            .await?
+            .into_iter()
+            .map(|coffee| (coffee.coffee_id, coffee.order_count))
+            .collect();
+
+        if signals.is_new_user() {
+            let current = self.days_before(now, TRENDING_DAYS);
+            let previous = self.days_before(current.start_date, TRENDING_DAYS);
+            signals.trending = self.trends
+                .calculate_trending_items(current, previous, CANDIDATE_LIMIT)
+                .await?
+                .into_iter()
+                .filter(|coffee| coffee.trend_percentage.is_some_and(|trend| trend > Decimal::ZERO))
+                .map(|coffee| coffee.coffee_id)
+                .collect();
+        }
+
+        Ok(signals)
+    }
+
+    /// The `days` before `end`
+    fn days_before(&self, end: DateTime<Utc>, days: i64) -> DateRange {
+        DateRange {
+            start_date: end - Duration::days(days),
+            end_date: end,
+            timezone: self.timezone,
+        }
+    }
+
+    /// Score every available coffee and keep the best `limit`
+    /// Equal scores are ordered by coffee id, so the ranking only depends on
+    /// the signals
+    pub fn rank(
+        coffees: Vec<Coffee>,
+        signals: &RecommendationSignals,
+        unavailable: &HashSet<i32>,
+        limit: usize,
+    ) -> Vec<CoffeeRecommendation> {
+        let max_popularity = signals.popularity.values().copied().max().unwrap_or(0);
+
+        let mut scored: Vec<(Decimal, RecommendationReason, Coffee)> = coffees
+            .into_iter()
+            .filter(|coffee| !unavailable.contains(&coffee.id) && !signals.dislikes(coffee.id))
+            .map(|coffee| {
+                let popularity = Self::ratio(
+                    signals.popularity.get(&coffee.id).copied().unwrap_or(0),
+                    max_popularity,
+                );
+                let (score, reason) = if signals.is_new_user() {
+                    Self::score_new_user(coffee.id, signals, popularity)
+                } else {
+                    Self::score_returning_user(coffee.id, signals, popularity)
+                };
+                (score, reason, coffee)
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.2.id.cmp(&b.2.id)));
+        scored.truncate(limit);
+
+        scored
+            .into_iter()
+            .map(|(score, reason, coffee)| CoffeeRecommendation {
+                coffee,
+                score: score.round_dp(4).to_f64().unwrap_or(0.0),
+                reason,
+            })
+            .collect()
+    }
+
+    /// 35% order history, 25% own rating, 25% bought together, 15% popularity
+    /// The reason is the largest weighted part (popularity when all are zero)
+    fn score_returning_user(
+        coffee_id: i32,
+        signals: &RecommendationSignals,
+        popularity: Decimal,
+    ) -> (Decimal, RecommendationReason) {
+        let history = Self::ratio(
+            signals.coffee_orders.get(&coffee_id).copied().unwrap_or(0),
+            signals.order_count,
+        );
+        // 1 star -> 0, 5 stars -> 1
+        let rating = signals
+            .ratings
+            .get(&coffee_id)
+            .map(|rating| Decimal::from(rating - 1) / Decimal::from(4))
+            .unwrap_or(Decimal::ZERO);
+        let bought_together = signals
+            .bought_together
+            .get(&coffee_id)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+
+        let parts = [
+            (Decimal::new(35, 2) * history, RecommendationReason::OrderedBefore),
+            (Decimal::new(25, 2) * rating, RecommendationReason::RatedHighly),
+            (Decimal::new(25, 2) * bought_together, RecommendationReason::BoughtTogether),
+            (Decimal::new(15, 2) * popularity, RecommendationReason::Popular),
+        ];
+        Self::blend(&parts, RecommendationReason::Popular)
+    }
+
+    /// 60% trending rank, 40% popularity
+    fn score_new_user(
+        coffee_id: i32,
+        signals: &RecommendationSignals,
+        popularity: Decimal,
+    ) -> (Decimal, RecommendationReason) {
+        // Fastest growing -> 1, down to 1/n for the last trending coffee
+        let trending = signals
+            .trending
+            .iter()
+            .position(|id| *id == coffee_id)
+            .map(|position| {
+                let count = signals.trending.len() as i64;
+                Self::ratio(count - position as i64, count)
+            })
+            .unwrap_or(Decimal::ZERO);
+
+        let parts = [
+            (Decimal::new(60, 2) * trending, RecommendationReason::Trending),
+            (Decimal::new(40, 2) * popularity, RecommendationReason::Popular),
+        ];
+        Self::blend(&parts, RecommendationReason::Trending)
+    }
+
+    /// Sum of the weighted parts, with the reason of the largest one (the
+    /// first on ties, `fallback` when all are zero)
+    fn blend(
+        parts: &[(Decimal, RecommendationReason)],
+        fallback: RecommendationReason,
+    ) -> (Decimal, RecommendationReason) {
+        let score = parts.iter().map(|(part, _)| *part).sum();
+        let reason = parts
+            .iter()
+            .fold(None, |best: Option<&(Decimal, RecommendationReason)>, part| match best {
+                Some(best) if best.0 >= part.0 => Some(best),
+                _ if part.0 > Decimal::ZERO => Some(part),
+                _ => best,
+            })
+            .map(|(_, reason)| *reason)
+            .unwrap_or(fallback);
+
+        (score, reason)
+    }
+
+    /// `value / total`, 0 when the total is 0
+    fn ratio(value: i64, total: i64) -> Decimal {
+        if total <= 0 {
+            Decimal::ZERO
+        } else {
+            Decimal::from(value) / Decimal::from(total)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    /// Menu of six coffees
+    fn menu() -> Vec<Coffee> {
+        ["Latte", "Mocha", "Flat White", "Americano", "Cortado", "Cold Brew"]
+            .iter()
+            .enumerate()
+            .map(|(index, name)| Coffee {
+                id: index as i32 + 1,
+                image_url: "https://test.com/image.jpg".to_string(),
+                name: name.to_string(),
+                coffee_type: "Test Type".to_string(),
+                price: 4.0,
+                rating: 4.5,
+            })
+            .collect()
+    }
+
+    fn ids(recommendations: &[CoffeeRecommendation]) -> Vec<i32> {
+        recommendations.iter().map(|r| r.coffee.id).collect()
+    }
+
+    /// Ordered the Latte in 4 of 5 orders and the Mocha once, loves the
+    /// Cortado, dislikes the Americano; the Flat White is often bought with the
+    /// Latte and the Cold Brew is the most popular coffee
+    fn returning_user() -> RecommendationSignals {
+        RecommendationSignals {
+            order_count: 5,
+            coffee_orders: HashMap::from([(1, 4), (2, 1)]),
+            ratings: HashMap::from([(5, 5), (4, 1)]),
+            bought_together: HashMap::from([(3, dec!(0.8))]),
+            popularity: HashMap::from([(6, 100), (1, 50), (4, 80)]),
+            trending: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_returning_user_blends_signals() {
+        let recommendations = RecommendationService::rank(menu(), &returning_user(), &HashSet::new(), 10);
+
+        // Latte: 0.35 * 0.8 + 0.15 * 0.5 = 0.355
+        // Cortado: 0.25 * 1 = 0.25
+        // Flat White: 0.25 * 0.8 = 0.2
+        // Cold Brew: 0.15 * 1 = 0.15
+        // Mocha: 0.35 * 0.2 = 0.07
+        assert_eq!(ids(&recommendations), vec![1, 5, 3, 6, 2]);
+        assert_eq!(
+            recommendations.iter().map(|r| r.score).collect::<Vec<_>>(),
+            vec![0.355, 0.25, 0.2, 0.15, 0.07]
+        );
+        assert_eq!(
+            recommendations.iter().map(|r| r.reason).collect::<Vec<_>>(),
+            vec![
+                RecommendationReason::OrderedBefore,
+                RecommendationReason::RatedHighly,
+                RecommendationReason::BoughtTogether,
+                RecommendationReason::Popular,
+                RecommendationReason::OrderedBefore,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unavailable_and_disliked_coffees_are_excluded() {
+        let unavailable = HashSet::from([1, 6]);
+
+        let recommendations = RecommendationService::rank(menu(), &returning_user(), &unavailable, 10);
+
+        // Americano (rated 1 star) is left out despite its popularity
+        assert_eq!(ids(&recommendations), vec![5, 3, 2]);
+    }
+
+    #[test]
+    fn test_new_user_gets_trending_coffees() {
+        let signals = RecommendationSignals {
+            popularity: HashMap::from([(6, 100), (2, 40)]),
+            trending: vec![3, 2, 4],
+            ..Default::default()
+        };
+        assert!(signals.is_new_user());
+
+        let recommendations = RecommendationService::rank(menu(), &signals, &HashSet::from([4]), 3);
+
+        // Flat White: 0.6, Mocha: 0.6 * 2/3 + 0.4 * 0.4 = 0.56, Cold Brew: 0.4
+        assert_eq!(ids(&recommendations), vec![3, 2, 6]);
+        assert_eq!(
+            recommendations.iter().map(|r| r.score).collect::<Vec<_>>(),
+            vec![0.6, 0.56, 0.4]
+        );
+        assert_eq!(
+            recommendations.iter().map(|r| r.reason).collect::<Vec<_>>(),
+            vec![
+                RecommendationReason::Trending,
+                RecommendationReason::Trending,
+                RecommendationReason::Popular,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ties_are_ranked_by_coffee_id() {
+        let recommendations = RecommendationService::rank(
+            menu(),
+            &RecommendationSignals::default(),
+            &HashSet::new(),
+            3,
+        );
+
+        assert_eq!(ids(&recommendations), vec![1, 2, 3]);
+        assert!(recommendations.iter().all(|r| r.score == 0.0));
+        assert!(recommendations.iter().all(|r| r.reason == RecommendationReason::Trending));
+    }
+
+    #[test]
+    fn test_liked_coffees() {
+        assert_eq!(returning_user().liked_coffees(), vec![1, 2, 5]);
+        assert!(RecommendationSignals::default().liked_coffees().is_empty());
+    }
+}
@@ -24,6 +24,12 @@ pub enum ServiceError {
     /// Coffee not found
     CoffeeNotFound,
     
+    /// User has already reported this review
+    DuplicateFlag,
+    
+    /// Reviews require a completed purchase of the coffee
+    PurchaseRequired,
+    
     /// Database error
     DatabaseError(sqlx::Error),
 }
@@ -40,6 +46,12 @@ impl fmt::Display for ServiceError {
             }
             ServiceError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             ServiceError::CoffeeNotFound => write!(f, "Coffee not found"),
+            ServiceError::DuplicateFlag => {
+                write!(f, "Duplicate flag: user has already reported this review")
+            }
+            ServiceError::PurchaseRequired => {
+                write!(f, "Purchase required: user has not bought this coffee")
+            }
             ServiceError::DatabaseError(e) => write!(f, "Database error: {}", e),
         }
     }
@@ -111,6 +123,14 @@ impl From<ServiceError> for ErrorResponse {
                 "COFFEE_NOT_FOUND",
                 "Coffee not found".to_string(),
             ),
+            ServiceError::DuplicateFlag => (
+                "DUPLICATE_FLAG",
+                "User has already reported this review".to_string(),
+            ),
+            ServiceError::PurchaseRequired => (
+                "PURCHASE_REQUIRED",
+                "Only customers who have bought this coffee can review it".to_string(),
+            ),
             ServiceError::DatabaseError(e) => {
                 tracing::error!("Database error: {:?}", e);
                 (
@@ -129,8 +149,8 @@ impl IntoResponse for ErrorResponse {
     fn into_response(self) -> Response {
         let status = match self.error.as_str() {
             "NOT_FOUND" | "COFFEE_NOT_FOUND" => StatusCode::NOT_FOUND,
-            "DUPLICATE_REVIEW" => StatusCode::CONFLICT,
-            "FORBIDDEN" => StatusCode::FORBIDDEN,
+            "DUPLICATE_REVIEW" | "DUPLICATE_FLAG" => StatusCode::CONFLICT,
+            "FORBIDDEN" | "PURCHASE_REQUIRED" => StatusCode::FORBIDDEN,
             "VALIDATION_ERROR" => StatusCode::BAD_REQUEST,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
@@ -168,6 +188,16 @@ impl IntoResponse for ServiceError {
                 "COFFEE_NOT_FOUND",
                 "Coffee not found".to_string(),
             ),
+            ServiceError::DuplicateFlag => (
+                StatusCode::CONFLICT,
+                "DUPLICATE_FLAG",
+                "User has already reported this review".to_string(),
+            ),
+            ServiceError::PurchaseRequired => (
+                StatusCode::FORBIDDEN,
+                "PURCHASE_REQUIRED",
+                "Only customers who have bought this coffee can review it".to_string(),
+            ),
             ServiceError::DatabaseError(e) => {
                 tracing::error!("Database error: {:?}", e);
                 (
@@ -1,16 +1,21 @@
 // HTTP handlers for review endpoints
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use validator::Validate;
 
 use crate::auth::middleware::AuthenticatedUser;
+use crate::pagination::{PaginatedResponse, PaginationQuery};
 use crate::reviews::{
     error::ErrorResponse,
-    models::{CreateReviewRequest, ReviewResponse, UpdateReviewRequest},
+    models::{
+        CreateReviewRequest, FlagReviewRequest, HelpfulVoteResponse, ModerateReviewRequest,
+        ModerationQueueItem, ModerationQueueQuery, ReviewFlag, ReviewListQuery, ReviewListResponse,
+        ReviewReply, ReviewReplyRequest, ReviewResponse, UpdateReviewRequest,
+    },
     ServiceError,
 };
 use crate::AppState;
@@ -37,6 +42,10 @@ pub async fn create_review_handler(
         coffee_id: review.coffee_id,
         rating: review.rating,
         comment: review.comment,
+        status: review.status,
+        verified_purchase: review.verified_purchase,
+        helpful_count: review.helpful_count,
+        reply: None,
         created_at: review.created_at,
         updated_at: review.updated_at,
     };
@@ -69,6 +78,10 @@ pub async fn update_review_handler(
         coffee_id: review.coffee_id,
         rating: review.rating,
         comment: review.comment,
+        status: review.status,
+        verified_purchase: review.verified_purchase,
+        helpful_count: review.helpful_count,
+        reply: None,
         created_at: review.created_at,
         updated_at: review.updated_at,
     };
@@ -89,28 +102,147 @@ pub async fn delete_review_handler(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Get all reviews for a coffee
-/// GET /api/coffees/{id}/reviews
+/// Get a page of published reviews for a coffee with its rating summary
+/// GET /api/coffees/{id}/reviews?sort=helpful&rating=5&verified=true&page=1&limit=20&cursor=...
 pub async fn get_reviews_for_coffee_handler(
     State(state): State<AppState>,
     Path(coffee_id): Path<i32>,
-) -> Result<Json<Vec<ReviewResponse>>, ErrorResponse> {
-    // Get reviews
-    let reviews = state.review_service.get_reviews_for_coffee(coffee_id).await?;
-
-    // Convert to responses
-    let responses: Vec<ReviewResponse> = reviews
-        .into_iter()
-        .map(|review| ReviewResponse {
-            id: review.id,
-            user_id: review.user_id,
-            coffee_id: review.coffee_id,
-            rating: review.rating,
-            comment: review.comment,
-            created_at: review.created_at,
-            updated_at: review.updated_at,
-        })
-        .collect();
-
-    Ok(Json(responses))
+    Query(query): Query<ReviewListQuery>,
+) -> Result<Json<ReviewListResponse>, ErrorResponse> {
+    let reviews = state.review_service
+        .list_reviews_for_coffee(coffee_id, &query)
+        .await?;
+
+    Ok(Json(reviews))
+}
+
+/// List the authenticated user's reviews, newest first
+/// GET /api/me/reviews?page=1&limit=20
+pub async fn get_my_reviews_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<PaginatedResponse<ReviewResponse>>, ErrorResponse> {
+    let reviews = state.review_service
+        .list_user_reviews(user.user_id, &query)
+        .await?;
+
+    Ok(Json(reviews))
+}
+
+/// Get the authenticated user's review of a coffee
+/// GET /api/coffees/{id}/reviews/mine
+pub async fn get_my_review_for_coffee_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(coffee_id): Path<i32>,
+) -> Result<Json<ReviewResponse>, ErrorResponse> {
+    let review = state.review_service
+        .find_user_review(user.user_id, coffee_id)
+        .await?
+        .ok_or(ServiceError::NotFound)?;
+
+    Ok(Json(review))
+}
+
+/// Mark a review as helpful
+/// POST /api/reviews/{id}/helpful
+pub async fn vote_helpful_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(review_id): Path<i32>,
+) -> Result<Json<HelpfulVoteResponse>, ErrorResponse> {
+    let vote = state.review_service.vote_helpful(review_id, user.user_id).await?;
+
+    Ok(Json(vote))
+}
+
+/// Withdraw a helpful vote
+/// DELETE /api/reviews/{id}/helpful
+pub async fn remove_helpful_vote_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(review_id): Path<i32>,
+) -> Result<Json<HelpfulVoteResponse>, ErrorResponse> {
+    let vote = state.review_service.remove_helpful_vote(review_id, user.user_id).await?;
+
+    Ok(Json(vote))
+}
+
+/// Post or edit the shop's reply to a review (reviews:reply)
+/// PUT /api/reviews/{id}/reply
+pub async fn reply_to_review_handler(
+    State(state): State<AppState>,
+    author: AuthenticatedUser,
+    Path(review_id): Path<i32>,
+    Json(request): Json<ReviewReplyRequest>,
+) -> Result<Json<ReviewReply>, ErrorResponse> {
+    let reply = state.review_service
+        .reply_to_review(review_id, author.user_id, request)
+        .await?;
+
+    Ok(Json(reply))
+}
+
+/// Remove the shop's reply from a review (reviews:reply)
+/// DELETE /api/reviews/{id}/reply
+pub async fn delete_reply_handler(
+    State(state): State<AppState>,
+    Path(review_id): Path<i32>,
+) -> Result<StatusCode, ErrorResponse> {
+    state.review_service.delete_reply(review_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Report a review
+/// POST /api/reviews/{id}/flags
+pub async fn flag_review_handler(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(review_id): Path<i32>,
+    Json(request): Json<FlagReviewRequest>,
+) -> Result<(StatusCode, Json<ReviewFlag>), ErrorResponse> {
+    let flag = state.review_service
+        .flag_review(review_id, user.user_id, request)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(flag)))
+}
+
+/// List reviews awaiting moderation (reviews:moderate)
+/// GET /api/admin/reviews/moderation
+pub async fn get_moderation_queue_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ModerationQueueQuery>,
+) -> Result<Json<PaginatedResponse<ModerationQueueItem>>, ErrorResponse> {
+    let queue = state.review_service.get_moderation_queue(&query).await?;
+
+    Ok(Json(queue))
+}
+
+/// List the reports filed against a review (reviews:moderate)
+/// GET /api/admin/reviews/{id}/flags
+pub async fn get_review_flags_handler(
+    State(state): State<AppState>,
+    Path(review_id): Path<i32>,
+) -> Result<Json<Vec<ReviewFlag>>, ErrorResponse> {
+    let flags = state.review_service.get_review_flags(review_id).await?;
+
+    Ok(Json(flags))
+}
+
+/// Publish, hide or remove a review (reviews:moderate)
+/// PUT /api/admin/reviews/{id}/status
+pub async fn moderate_review_handler(
+    State(state): State<AppState>,
+    moderator: AuthenticatedUser,
+    Path(review_id): Path<i32>,
+    Json(request): Json<ModerateReviewRequest>,
+) -> Result<Json<ReviewResponse>, ErrorResponse> {
+    let review = state.review_service
+        .moderate_review(review_id, moderator.user_id, request)
+        .await?;
+
+    Ok(Json(ReviewResponse::from(review)))
 }
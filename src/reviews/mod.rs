@@ -2,6 +2,7 @@ pub mod models;
 pub mod error;
 pub mod repository;
 pub mod rating_calculator;
+pub mod moderation;
 pub mod service;
 pub mod handlers;
 
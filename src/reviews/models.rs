@@ -1,16 +1,65 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::pagination::PaginatedResponse;
+
+/// Publication state of a review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStatus {
+    /// Visible to everyone and counted in the coffee's rating
+    Published,
+    /// Held for moderation (blocked words or too many reports)
+    Pending,
+    /// Hidden by a moderator; can be published again
+    Hidden,
+    /// Removed by a moderator for breaking the rules
+    Removed,
+}
+
+impl ReviewStatus {
+    /// Convert status to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Published => "published",
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Hidden => "hidden",
+            ReviewStatus::Removed => "removed",
+        }
+    }
+}
+
+/// Reason given when reporting a review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FlagReason {
+    Spam,
+    Offensive,
+    OffTopic,
+    Other,
+}
+
 /// Domain model representing a review in the database
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Review {
     pub id: i32,
-    pub user_id: i32,
+    /// Author; None once the author has deleted their account
+    pub user_id: Option<i32>,
     pub coffee_id: i32,
     pub rating: i16,
     pub comment: Option<String>,
+    pub status: ReviewStatus,
+    /// Why the review was held, hidden or removed
+    pub moderation_reason: Option<String>,
+    /// The author had a completed order containing the coffee when writing the review
+    pub verified_purchase: bool,
+    /// Number of users who marked the review as helpful
+    pub helpful_count: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -35,13 +84,19 @@ pub struct UpdateReviewRequest {
 }
 
 /// Response DTO for API responses
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ReviewResponse {
     pub id: i32,
-    pub user_id: i32,
+    pub user_id: Option<i32>,
     pub coffee_id: i32,
     pub rating: i16,
     pub comment: Option<String>,
+    pub status: ReviewStatus,
+    pub verified_purchase: bool,
+    pub helpful_count: i32,
+    /// Shop reply; only loaded for review listings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<ReviewReply>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -54,8 +109,252 @@ impl From<Review> for ReviewResponse {
             coffee_id: review.coffee_id,
             rating: review.rating,
             comment: review.comment,
+            status: review.status,
+            verified_purchase: review.verified_purchase,
+            helpful_count: review.helpful_count,
+            reply: None,
             created_at: review.created_at,
             updated_at: review.updated_at,
         }
     }
 }
+
+/// Public reply from the shop to a review
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ReviewReply {
+    pub review_id: i32,
+    /// Staff member who last wrote the reply; None once their account is deleted
+    pub author_id: Option<i32>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request DTO for posting or editing a reply
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReviewReplyRequest {
+    #[validate(length(min = 1, max = 1000, message = "Reply must be between 1 and 1000 characters"))]
+    pub body: String,
+}
+
+/// A user's report against a review
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReviewFlag {
+    pub id: i32,
+    pub review_id: i32,
+    /// Reporter; None once the reporter has deleted their account
+    pub user_id: Option<i32>,
+    pub reason: FlagReason,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Request DTO for reporting a review
+#[derive(Debug, Deserialize, Validate)]
+pub struct FlagReviewRequest {
+    pub reason: FlagReason,
+    #[validate(length(max = 500, message = "Details must not exceed 500 characters"))]
+    pub details: Option<String>,
+}
+
+/// Request DTO for a moderator decision
+#[derive(Debug, Deserialize, Validate)]
+pub struct ModerateReviewRequest {
+    pub status: ReviewStatus,
+    #[validate(length(max = 500, message = "Reason must not exceed 500 characters"))]
+    pub reason: Option<String>,
+}
+
+/// Sort order for review listings; ties are broken newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewSort {
+    #[default]
+    Newest,
+    Highest,
+    Lowest,
+    Helpful,
+}
+
+/// Query parameters for listing a coffee's reviews
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ReviewListQuery {
+    /// Only reviews from verified purchases
+    #[serde(default)]
+    pub verified: bool,
+    /// Only reviews with this star rating
+    #[validate(range(min = 1, max = 5, message = "Rating must be between 1 and 5"))]
+    pub rating: Option<i16>,
+    #[serde(default)]
+    pub sort: ReviewSort,
+    /// Page number (1-indexed, defaults to 1); ignored when a cursor is given
+    pub page: Option<u32>,
+    /// Items per page (defaults to 20, max 100)
+    pub limit: Option<u32>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+/// Position of a review in a listing's sort order, carried by `next_cursor`
+///
+/// The cursor holds the review's sort values rather than just its id, so the
+/// next page still starts in the right place after that review is deleted,
+/// hidden or voted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReviewCursor {
+    /// Value of the sort key (0 for newest first)
+    pub sort_value: i32,
+    pub created_at: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl ReviewCursor {
+    /// Cursor for the page following `review` in `sort` order
+    pub fn after(review: &Review, sort: ReviewSort) -> Self {
+        let sort_value = match sort {
+            ReviewSort::Newest => 0,
+            ReviewSort::Highest => review.rating as i32,
+            ReviewSort::Lowest => -(review.rating as i32),
+            ReviewSort::Helpful => review.helpful_count,
+        };
+
+        Self {
+            sort_value,
+            created_at: review.created_at,
+            id: review.id,
+        }
+    }
+
+    /// Encode as `<sort value>_<created_at in microseconds>_<id>`
+    pub fn encode(&self) -> String {
+        format!("{}_{}_{}", self.sort_value, self.created_at.timestamp_micros(), self.id)
+    }
+
+    /// Parse a cursor produced by `encode`
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let mut parts = cursor.splitn(3, '_');
+        let sort_value = parts.next()?.parse().ok()?;
+        let created_at = DateTime::from_timestamp_micros(parts.next()?.parse().ok()?)?;
+        let id = parts.next()?.parse().ok()?;
+
+        Some(Self { sort_value, created_at, id })
+    }
+}
+
+/// Published rating totals kept on a coffee by the reviews trigger
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct RatingAggregate {
+    pub rating_sum: i64,
+    pub review_count: i32,
+    pub rating_1_count: i32,
+    pub rating_2_count: i32,
+    pub rating_3_count: i32,
+    pub rating_4_count: i32,
+    pub rating_5_count: i32,
+}
+
+impl RatingAggregate {
+    /// Mean rating, None without reviews
+    pub fn average(&self) -> Option<f64> {
+        if self.review_count == 0 {
+            None
+        } else {
+            Some(self.rating_sum as f64 / self.review_count as f64)
+        }
+    }
+
+    /// (rating, count) pairs in the shape returned by the star count query
+    pub fn star_counts(&self) -> Vec<(i16, i64)> {
+        vec![
+            (1, self.rating_1_count as i64),
+            (2, self.rating_2_count as i64),
+            (3, self.rating_3_count as i64),
+            (4, self.rating_4_count as i64),
+            (5, self.rating_5_count as i64),
+        ]
+    }
+}
+
+/// Number of published reviews with a given star rating
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StarCount {
+    pub rating: i16,
+    pub count: i64,
+}
+
+/// Per-star breakdown shown above a review listing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RatingSummary {
+    pub average_rating: Option<f64>,
+    pub total_reviews: i64,
+    /// One entry per star value, 5 down to 1
+    pub stars: Vec<StarCount>,
+}
+
+impl RatingSummary {
+    /// Build the summary from (rating, count) pairs; missing star values count as 0
+    pub fn from_counts(counts: &[(i16, i64)]) -> Self {
+        let stars: Vec<StarCount> = (1..=5)
+            .rev()
+            .map(|rating| StarCount {
+                rating,
+                count: counts
+                    .iter()
+                    .filter(|(r, _)| *r == rating)
+                    .map(|(_, count)| count)
+                    .sum(),
+            })
+            .collect();
+
+        let total_reviews: i64 = stars.iter().map(|star| star.count).sum();
+        let average_rating = if total_reviews == 0 {
+            None
+        } else {
+            let sum: i64 = stars.iter().map(|star| star.rating as i64 * star.count).sum();
+            Some(sum as f64 / total_reviews as f64)
+        };
+
+        Self {
+            average_rating,
+            total_reviews,
+            stars,
+        }
+    }
+}
+
+/// A page of reviews with the coffee's rating summary
+#[derive(Debug, Serialize)]
+pub struct ReviewListResponse {
+    pub summary: RatingSummary,
+    #[serde(flatten)]
+    pub page: PaginatedResponse<ReviewResponse>,
+}
+
+/// Helpful vote state of a review for the current user
+#[derive(Debug, Serialize)]
+pub struct HelpfulVoteResponse {
+    pub review_id: i32,
+    pub helpful_count: i32,
+    pub voted: bool,
+}
+
+/// Query parameters for the moderation queue
+#[derive(Debug, Deserialize)]
+pub struct ModerationQueueQuery {
+    /// Only reviews in this state; defaults to pending reviews and reviews with open reports
+    pub status: Option<ReviewStatus>,
+    /// Page number (1-indexed, defaults to 1)
+    pub page: Option<u32>,
+    /// Items per page (defaults to 20, max 100)
+    pub limit: Option<u32>,
+}
+
+/// Review in the moderation queue with its open reports
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ModerationQueueItem {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub review: Review,
+    pub open_flags: i64,
+}
@@ -0,0 +1,61 @@
+// Word-list matching used to hold reviews for moderation
+// Pure helpers, independent of the database
+
+/// Lowercase the text and collapse everything that is not a letter or digit into
+/// single spaces, padded on both sides so whole words can be matched with `contains`
+fn normalize(text: &str) -> String {
+    let mut normalized = String::from(" ");
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        normalized.push_str(&word.to_lowercase());
+        normalized.push(' ');
+    }
+    normalized
+}
+
+/// Blocked words or phrases that appear in the text as whole words
+///
+/// Matching ignores case and punctuation, so "Scam!" matches "scam" but
+/// "scampi" does not. Phrases match across any separators ("rip off" matches "rip-off").
+pub fn blocked_words_in(text: &str, blocked_words: &[String]) -> Vec<String> {
+    let text = normalize(text);
+    blocked_words
+        .iter()
+        .filter(|word| {
+            let word = normalize(word);
+            word.len() > 1 && text.contains(&word)
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matches_whole_words_ignoring_case() {
+        let blocked = words(&["scam", "awful"]);
+        assert_eq!(blocked_words_in("What a SCAM!", &blocked), vec!["scam"]);
+        assert!(blocked_words_in("Lovely scampi", &blocked).is_empty());
+    }
+
+    #[test]
+    fn test_matches_phrases_across_separators() {
+        let blocked = words(&["rip off"]);
+        assert_eq!(blocked_words_in("Total rip-off, avoid", &blocked), vec!["rip off"]);
+        assert!(blocked_words_in("rip the bag off", &blocked).is_empty());
+    }
+
+    #[test]
+    fn test_empty_list_or_entries_match_nothing() {
+        assert!(blocked_words_in("anything", &[]).is_empty());
+        assert!(blocked_words_in("anything", &words(&["", " - "])).is_empty());
+    }
+}
@@ -1,50 +1,154 @@
-use crate::reviews::{ReviewRepository, ServiceError};
+use crate::config::RatingScoreConfig;
+use crate::reviews::{RatingAggregate, ReviewRepository, ServiceError};
+use sqlx::{Postgres, Transaction};
+
+/// z-score for the 95% confidence level used by the Wilson lower bound
+const WILSON_Z: f64 = 1.96;
+
+/// How the confidence-weighted coffee score is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatingScoreMethod {
+    /// Mean pulled towards a prior mean by a fixed number of virtual reviews
+    Bayesian,
+    /// Lower bound of the 95% Wilson score interval, scaled to 1-5 stars
+    Wilson,
+}
+
+impl RatingScoreMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RatingScoreMethod::Bayesian => "bayesian",
+            RatingScoreMethod::Wilson => "wilson",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "bayesian" => Ok(RatingScoreMethod::Bayesian),
+            "wilson" => Ok(RatingScoreMethod::Wilson),
+            other => Err(format!("unknown rating score method '{}'", other)),
+        }
+    }
+}
+
+/// Bayesian average: (prior_weight * prior_mean + sum) / (prior_weight + count)
+pub fn bayesian_average(sum: i64, count: i32, prior_mean: f64, prior_weight: f64) -> Option<f64> {
+    if count == 0 {
+        return None;
+    }
+    Some((prior_weight * prior_mean + sum as f64) / (prior_weight + count as f64))
+}
+
+/// Wilson score lower bound on 1-5 star ratings
+///
+/// Each rating counts as a fractional positive ((rating - 1) / 4), the lower
+/// bound of the positive share is computed and mapped back onto 1-5 stars.
+pub fn wilson_lower_bound(sum: i64, count: i32) -> Option<f64> {
+    if count == 0 {
+        return None;
+    }
+    let n = count as f64;
+    let positive = (sum as f64 - n) / (4.0 * n);
+    let z2 = WILSON_Z * WILSON_Z;
+    let centre = positive + z2 / (2.0 * n);
+    let margin = WILSON_Z * ((positive * (1.0 - positive) + z2 / (4.0 * n)) / n).sqrt();
+    let lower = (centre - margin) / (1.0 + z2 / n);
+    Some(1.0 + 4.0 * lower.max(0.0))
+}
 
 /// Calculator for computing and updating average ratings
 #[derive(Clone)]
 pub struct RatingCalculator {
     repository: ReviewRepository,
+    score: RatingScoreConfig,
 }
 
 impl RatingCalculator {
     /// Create a new RatingCalculator
     pub fn new(repository: ReviewRepository) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            score: RatingScoreConfig::default(),
+        }
+    }
+
+    /// Use the given method for the confidence-weighted score
+    pub fn with_score(mut self, score: RatingScoreConfig) -> Self {
+        self.score = score;
+        self
     }
 
-    /// Recalculate and update the average rating for a coffee
-    /// 
-    /// This method:
-    /// 1. Fetches all ratings for the given coffee
-    /// 2. Calculates the arithmetic mean
-    /// 3. Updates the coffees table with the new average and count
-    /// 4. Returns the calculated average (or None if no reviews exist)
+    /// Confidence-weighted score for a coffee's rating totals, None without ratings
+    pub fn weighted_score(&self, aggregate: &RatingAggregate) -> Option<f64> {
+        let (sum, count) = (aggregate.rating_sum, aggregate.review_count);
+        match self.score.method {
+            RatingScoreMethod::Bayesian => {
+                bayesian_average(sum, count, self.score.prior_mean, self.score.prior_weight)
+            }
+            RatingScoreMethod::Wilson => wilson_lower_bound(sum, count),
+        }
+    }
+
+    /// Refresh the weighted score of a coffee from its current rating totals
+    /// Returns the average (or None if no reviews exist)
     pub async fn recalculate_average(&self, coffee_id: i32) -> Result<Option<f64>, ServiceError> {
-        // Get all ratings for this coffee
-        let ratings = self.repository.get_ratings_for_coffee(coffee_id).await?;
-
-        // Calculate average and count
-        let count = ratings.len() as i32;
-        let average = if ratings.is_empty() {
-            None
-        } else {
-            let sum: i32 = ratings.iter().map(|&r| r as i32).sum();
-            let avg = sum as f64 / ratings.len() as f64;
-            Some(avg)
-        };
+        let mut tx = self.repository.begin().await?;
+        let average = self.recalculate_average_tx(&mut tx, coffee_id).await?;
+        tx.commit().await?;
+
+        Ok(average)
+    }
+
+    /// Refresh the rating of a coffee in the transaction that changed one of its reviews
+    ///
+    /// The rating sum, review count, per-star counts and average are kept
+    /// up to date by the database trigger on the review write. This method:
+    /// 1. Reads those totals for the given coffee within the transaction
+    /// 2. Calculates the confidence-weighted score from them
+    /// 3. Updates the coffee's weighted score within the transaction
+    /// 4. Returns the average (or None if no reviews exist)
+    pub async fn recalculate_average_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        coffee_id: i32,
+    ) -> Result<Option<f64>, ServiceError> {
+        let aggregate = self.repository.get_rating_aggregate_tx(tx, coffee_id).await?;
+        let weighted = self.weighted_score(&aggregate);
 
-        // Update the coffees table
         self.repository
-            .update_coffee_rating(coffee_id, average, count)
+            .update_weighted_rating_tx(tx, coffee_id, weighted)
             .await?;
 
-        Ok(average)
+        Ok(aggregate.average())
+    }
+
+    /// Recalculate every coffee's weighted score, e.g. after the score settings change
+    /// Runs as a single update over all coffees; returns the number of coffees updated
+    pub async fn recalculate_all(&self) -> Result<usize, ServiceError> {
+        let updated = match self.score.method {
+            RatingScoreMethod::Bayesian => {
+                self.repository
+                    .update_all_bayesian_ratings(self.score.prior_mean, self.score.prior_weight)
+                    .await?
+            }
+            RatingScoreMethod::Wilson => self.repository.update_all_wilson_ratings(WILSON_Z).await?,
+        };
+        Ok(updated as usize)
+    }
+
+    /// Rebuild every coffee's rating totals from its reviews, then its weighted score
+    /// Returns the number of coffees whose totals had drifted
+    pub async fn repair_all(&self) -> Result<i32, ServiceError> {
+        let repaired = self.repository.rebuild_rating_aggregates().await?;
+        self.recalculate_all().await?;
+        Ok(repaired)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::reviews::ReviewStatus;
     use sqlx::PgPool;
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -114,6 +218,36 @@ mod tests {
         coffee_id.0
     }
 
+    #[test]
+    fn test_bayesian_average_prefers_many_good_reviews() {
+        let single_five = bayesian_average(5, 1, 3.0, 10.0).unwrap();
+        // 320 five-star and 80 four-star reviews
+        let many_good = bayesian_average(1920, 400, 3.0, 10.0).unwrap();
+
+        assert!((single_five - 35.0 / 11.0).abs() < 1e-9);
+        assert!(many_good > single_five);
+        assert_eq!(bayesian_average(0, 0, 3.0, 10.0), None);
+        assert_eq!(bayesian_average(6, 2, 3.0, 0.0), Some(3.0));
+    }
+
+    #[test]
+    fn test_wilson_lower_bound_grows_with_evidence() {
+        let single_five = wilson_lower_bound(5, 1).unwrap();
+        let many_fives = wilson_lower_bound(500, 100).unwrap();
+
+        assert!(single_five < many_fives);
+        assert!(many_fives < 5.0);
+        assert!((wilson_lower_bound(10, 10).unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(wilson_lower_bound(0, 0), None);
+    }
+
+    #[test]
+    fn test_rating_score_method_from_str() {
+        assert_eq!(RatingScoreMethod::from_str("Wilson"), Ok(RatingScoreMethod::Wilson));
+        assert_eq!(RatingScoreMethod::from_str("bayesian"), Ok(RatingScoreMethod::Bayesian));
+        assert!(RatingScoreMethod::from_str("median").is_err());
+    }
+
     #[tokio::test]
     async fn test_recalculate_average_with_reviews() {
         let pool = create_test_pool().await;
@@ -264,4 +398,204 @@ mod tests {
         // Average should be (5 + 4) / 2 = 4.5
         assert_eq!(average, Some(4.5));
     }
+
+    #[tokio::test]
+    async fn test_recalculate_average_ignores_unpublished_reviews() {
+        let pool = create_test_pool().await;
+        let user1 = create_test_user(&pool).await;
+        let user2 = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        
+        let repository = ReviewRepository::new(pool.clone());
+        
+        // Only the published rating of 5 counts; the held rating of 1 does not
+        repository.create(user1, coffee_id, 5, None).await.unwrap();
+        repository
+            .create_with_status(user2, coffee_id, 1, None, ReviewStatus::Pending, None)
+            .await
+            .unwrap();
+        
+        let calculator = RatingCalculator::new(repository);
+        
+        let average = calculator
+            .recalculate_average(coffee_id)
+            .await
+            .expect("Failed to recalculate average");
+        
+        assert_eq!(average, Some(5.0));
+    }
+
+    #[tokio::test]
+    async fn test_recalculate_stores_weighted_score() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        
+        let repository = ReviewRepository::new(pool.clone());
+        repository.create(user_id, coffee_id, 5, None).await.unwrap();
+        
+        let calculator = RatingCalculator::new(repository);
+        let average = calculator.recalculate_average(coffee_id).await.unwrap();
+        
+        // The raw mean is kept alongside the score pulled towards the prior
+        let (stored_average, weighted): (f64, f64) = sqlx::query_as(
+            "SELECT average_rating::FLOAT8, weighted_rating::FLOAT8 FROM coffees WHERE id = $1"
+        )
+        .bind(coffee_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to fetch ratings");
+        
+        assert_eq!(average, Some(5.0));
+        assert_eq!(stored_average, 5.0);
+        assert_eq!(weighted, 3.18);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_score_is_written_with_the_review() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        
+        let repository = ReviewRepository::new(pool.clone());
+        let calculator = RatingCalculator::new(repository.clone());
+        
+        // A rolled back write leaves neither the review nor its score behind
+        let mut tx = repository.begin().await.unwrap();
+        repository
+            .create_with_status_tx(&mut tx, user_id, coffee_id, 5, None, ReviewStatus::Published, None)
+            .await
+            .unwrap();
+        let average = calculator.recalculate_average_tx(&mut tx, coffee_id).await.unwrap();
+        assert_eq!(average, Some(5.0));
+        tx.rollback().await.unwrap();
+        
+        let weighted: Option<f64> =
+            sqlx::query_scalar("SELECT weighted_rating::FLOAT8 FROM coffees WHERE id = $1")
+                .bind(coffee_id)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to fetch weighted rating");
+        assert_eq!(weighted, None);
+        assert_eq!(repository.get_rating_aggregate(coffee_id).await.unwrap(), RatingAggregate::default());
+        
+        let mut tx = repository.begin().await.unwrap();
+        repository
+            .create_with_status_tx(&mut tx, user_id, coffee_id, 5, None, ReviewStatus::Published, None)
+            .await
+            .unwrap();
+        calculator.recalculate_average_tx(&mut tx, coffee_id).await.unwrap();
+        tx.commit().await.unwrap();
+        
+        let weighted: Option<f64> =
+            sqlx::query_scalar("SELECT weighted_rating::FLOAT8 FROM coffees WHERE id = $1")
+                .bind(coffee_id)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to fetch weighted rating");
+        assert_eq!(weighted, Some(3.18));
+    }
+
+    #[tokio::test]
+    async fn test_aggregates_follow_review_writes() {
+        let pool = create_test_pool().await;
+        let user1 = create_test_user(&pool).await;
+        let user2 = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        
+        let repository = ReviewRepository::new(pool.clone());
+        let first = repository.create(user1, coffee_id, 5, None).await.unwrap();
+        let second = repository.create(user2, coffee_id, 2, None).await.unwrap();
+        repository.update(first.id, Some(4), None).await.unwrap();
+        repository.hold_for_moderation(second.id, "Reported").await.unwrap();
+        
+        let aggregate = repository.get_rating_aggregate(coffee_id).await.unwrap();
+        assert_eq!(aggregate.rating_sum, 4);
+        assert_eq!(aggregate.review_count, 1);
+        assert_eq!(aggregate.rating_4_count, 1);
+        assert_eq!(aggregate.rating_2_count, 0);
+        assert_eq!(aggregate.rating_5_count, 0);
+        
+        repository.delete(first.id).await.unwrap();
+        let aggregate = repository.get_rating_aggregate(coffee_id).await.unwrap();
+        assert_eq!(aggregate, RatingAggregate::default());
+    }
+
+    /// Stored weighted score of a coffee
+    async fn fetch_weighted_rating(pool: &PgPool, coffee_id: i32) -> Option<f64> {
+        sqlx::query_scalar("SELECT weighted_rating::FLOAT8 FROM coffees WHERE id = $1")
+            .bind(coffee_id)
+            .fetch_one(pool)
+            .await
+            .expect("Failed to fetch weighted rating")
+    }
+
+    #[tokio::test]
+    async fn test_recalculate_all_matches_per_coffee_scores() {
+        let pool = create_test_pool().await;
+        let user1 = create_test_user(&pool).await;
+        let user2 = create_test_user(&pool).await;
+        let user3 = create_test_user(&pool).await;
+        let rated = create_test_coffee(&pool).await;
+        let unrated = create_test_coffee(&pool).await;
+        
+        let repository = ReviewRepository::new(pool.clone());
+        repository.create(user1, rated, 5, None).await.unwrap();
+        repository.create(user2, rated, 4, None).await.unwrap();
+        repository.create(user3, rated, 2, None).await.unwrap();
+        repository.update_weighted_rating(unrated, Some(4.0)).await.unwrap();
+        
+        for method in [RatingScoreMethod::Bayesian, RatingScoreMethod::Wilson] {
+            let calculator = RatingCalculator::new(repository.clone()).with_score(RatingScoreConfig {
+                method,
+                ..Default::default()
+            });
+            
+            let updated = calculator.recalculate_all().await.unwrap();
+            assert!(updated >= 2);
+            let all = fetch_weighted_rating(&pool, rated).await;
+            assert_eq!(fetch_weighted_rating(&pool, unrated).await, None);
+            
+            calculator.recalculate_average(rated).await.unwrap();
+            assert_eq!(all, fetch_weighted_rating(&pool, rated).await, "{} score", method.as_str());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repair_rebuilds_drifted_aggregates() {
+        let pool = create_test_pool().await;
+        let user1 = create_test_user(&pool).await;
+        let user2 = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        
+        let repository = ReviewRepository::new(pool.clone());
+        repository.create(user1, coffee_id, 5, None).await.unwrap();
+        repository.create(user2, coffee_id, 3, None).await.unwrap();
+        
+        // Simulate totals that drifted from the reviews table
+        sqlx::query(
+            "UPDATE coffees SET rating_sum = 0, review_count = 0, rating_5_count = 0, average_rating = NULL WHERE id = $1"
+        )
+        .bind(coffee_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to corrupt totals");
+        
+        let calculator = RatingCalculator::new(repository.clone());
+        let repaired = calculator.repair_all().await.expect("Failed to repair totals");
+        
+        assert!(repaired >= 1);
+        let aggregate = repository.get_rating_aggregate(coffee_id).await.unwrap();
+        assert_eq!(aggregate.rating_sum, 8);
+        assert_eq!(aggregate.review_count, 2);
+        assert_eq!(aggregate.rating_5_count, 1);
+        assert_eq!(aggregate.rating_3_count, 1);
+        
+        let average: f64 = sqlx::query_scalar("SELECT average_rating::FLOAT8 FROM coffees WHERE id = $1")
+            .bind(coffee_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to fetch average");
+        assert_eq!(average, 4.0);
+    }
 }
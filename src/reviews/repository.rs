@@ -1,5 +1,16 @@
-use sqlx::PgPool;
-use crate::reviews::{Review, ServiceError};
+use sqlx::{PgPool, Postgres, Transaction};
+use crate::reviews::{
+    FlagReason, ModerationQueueItem, RatingAggregate, Review, ReviewCursor, ReviewFlag,
+    ReviewListQuery, ReviewReply, ReviewSort, ReviewStatus, ServiceError,
+};
+
+/// Published rating totals of a coffee, bound to its id
+const RATING_AGGREGATE_SQL: &str = r#"
+    SELECT rating_sum, review_count, rating_1_count, rating_2_count,
+           rating_3_count, rating_4_count, rating_5_count
+    FROM coffees
+    WHERE id = $1
+"#;
 
 /// Repository for database operations on reviews
 #[derive(Clone)]
@@ -13,26 +24,74 @@ impl ReviewRepository {
         Self { pool }
     }
 
-    /// Create a new review
+    /// Start a transaction for a review write and the rating updates that follow it
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>, ServiceError> {
+        Ok(self.pool.begin().await?)
+    }
+
+    /// Create a new published review
     pub async fn create(
         &self,
         user_id: i32,
         coffee_id: i32,
         rating: i16,
         comment: Option<String>,
+    ) -> Result<Review, ServiceError> {
+        self.create_with_status(user_id, coffee_id, rating, comment, ReviewStatus::Published, None)
+            .await
+    }
+
+    /// Create a new review in the given moderation state
+    pub async fn create_with_status(
+        &self,
+        user_id: i32,
+        coffee_id: i32,
+        rating: i16,
+        comment: Option<String>,
+        status: ReviewStatus,
+        moderation_reason: Option<String>,
+    ) -> Result<Review, ServiceError> {
+        let mut tx = self.begin().await?;
+        let review = self
+            .create_with_status_tx(&mut tx, user_id, coffee_id, rating, comment, status, moderation_reason)
+            .await?;
+        tx.commit().await?;
+
+        Ok(review)
+    }
+
+    /// Create a new review in the given moderation state within a transaction
+    /// The review is marked as a verified purchase if the user has a completed order containing the coffee
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_with_status_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        coffee_id: i32,
+        rating: i16,
+        comment: Option<String>,
+        status: ReviewStatus,
+        moderation_reason: Option<String>,
     ) -> Result<Review, ServiceError> {
         let review = sqlx::query_as::<_, Review>(
             r#"
-            INSERT INTO reviews (user_id, coffee_id, rating, comment)
-            VALUES ($1, $2, $3, $4)
-            RETURNING id, user_id, coffee_id, rating, comment, created_at, updated_at
+            INSERT INTO reviews (user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase)
+            VALUES ($1, $2, $3, $4, $5, $6, EXISTS (
+                SELECT 1
+                FROM orders o
+                JOIN order_items oi ON oi.order_id = o.id
+                WHERE o.user_id = $1 AND oi.coffee_item_id = $2 AND o.status = 'completed'
+            ))
+            RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
             "#,
         )
         .bind(user_id)
         .bind(coffee_id)
         .bind(rating)
         .bind(comment)
-        .fetch_one(&self.pool)
+        .bind(status)
+        .bind(moderation_reason)
+        .fetch_one(&mut **tx)
         .await?;
 
         Ok(review)
@@ -42,7 +101,7 @@ impl ReviewRepository {
     pub async fn find_by_id(&self, id: i32) -> Result<Option<Review>, ServiceError> {
         let review = sqlx::query_as::<_, Review>(
             r#"
-            SELECT id, user_id, coffee_id, rating, comment, created_at, updated_at
+            SELECT id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
             FROM reviews
             WHERE id = $1
             "#,
@@ -62,7 +121,7 @@ impl ReviewRepository {
     ) -> Result<Option<Review>, ServiceError> {
         let review = sqlx::query_as::<_, Review>(
             r#"
-            SELECT id, user_id, coffee_id, rating, comment, created_at, updated_at
+            SELECT id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
             FROM reviews
             WHERE user_id = $1 AND coffee_id = $2
             "#,
@@ -75,12 +134,58 @@ impl ReviewRepository {
         Ok(review)
     }
 
+    /// Find a page of a user's own reviews, newest first, in any moderation state
+    /// Returns the page and the user's total number of reviews
+    pub async fn find_by_user(
+        &self,
+        user_id: i32,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<Review>, i64), ServiceError> {
+        let reviews = sqlx::query_as::<_, Review>(
+            r#"
+            SELECT id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
+            FROM reviews
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reviews WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((reviews, total))
+    }
+
     /// Update a review
     pub async fn update(
         &self,
         id: i32,
         rating: Option<i16>,
         comment: Option<String>,
+    ) -> Result<Review, ServiceError> {
+        let mut tx = self.begin().await?;
+        let review = self.update_tx(&mut tx, id, rating, comment).await?;
+        tx.commit().await?;
+
+        Ok(review)
+    }
+
+    /// Update a review within a transaction
+    pub async fn update_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        rating: Option<i16>,
+        comment: Option<String>,
     ) -> Result<Review, ServiceError> {
         // Build dynamic update query based on what fields are provided
         let review = match (rating, comment) {
@@ -91,13 +196,13 @@ impl ReviewRepository {
                     UPDATE reviews
                     SET rating = $1, comment = $2, updated_at = NOW()
                     WHERE id = $3
-                    RETURNING id, user_id, coffee_id, rating, comment, created_at, updated_at
+                    RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
                     "#,
                 )
                 .bind(new_rating)
                 .bind(new_comment)
                 .bind(id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **tx)
                 .await?
             }
             (Some(new_rating), None) => {
@@ -107,12 +212,12 @@ impl ReviewRepository {
                     UPDATE reviews
                     SET rating = $1, updated_at = NOW()
                     WHERE id = $2
-                    RETURNING id, user_id, coffee_id, rating, comment, created_at, updated_at
+                    RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
                     "#,
                 )
                 .bind(new_rating)
                 .bind(id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **tx)
                 .await?
             }
             (None, Some(new_comment)) => {
@@ -122,12 +227,12 @@ impl ReviewRepository {
                     UPDATE reviews
                     SET comment = $1, updated_at = NOW()
                     WHERE id = $2
-                    RETURNING id, user_id, coffee_id, rating, comment, created_at, updated_at
+                    RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
                     "#,
                 )
                 .bind(new_comment)
                 .bind(id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **tx)
                 .await?
             }
             (None, None) => {
@@ -138,11 +243,11 @@ impl ReviewRepository {
                     UPDATE reviews
                     SET updated_at = NOW()
                     WHERE id = $1
-                    RETURNING id, user_id, coffee_id, rating, comment, created_at, updated_at
+                    RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
                     "#,
                 )
                 .bind(id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **tx)
                 .await?
             }
         };
@@ -152,9 +257,18 @@ impl ReviewRepository {
 
     /// Delete a review
     pub async fn delete(&self, id: i32) -> Result<(), ServiceError> {
+        let mut tx = self.begin().await?;
+        self.delete_tx(&mut tx, id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Delete a review within a transaction
+    pub async fn delete_tx(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<(), ServiceError> {
         let result = sqlx::query("DELETE FROM reviews WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut **tx)
             .await?;
 
         if result.rows_affected() == 0 {
@@ -164,62 +278,363 @@ impl ReviewRepository {
         Ok(())
     }
 
-    /// Find all reviews for a coffee
-    pub async fn find_by_coffee(&self, coffee_id: i32) -> Result<Vec<Review>, ServiceError> {
-        let reviews = sqlx::query_as::<_, Review>(
+    /// Find a page of published reviews for a coffee
+    ///
+    /// Reviews are filtered by the query's verified and rating filters and
+    /// ordered by its sort. With `after` set, the page starts after that
+    /// position in sort order (keyset pagination) instead of at `offset`.
+    pub async fn find_by_coffee(
+        &self,
+        coffee_id: i32,
+        query: &ReviewListQuery,
+        limit: u32,
+        offset: u32,
+        after: Option<ReviewCursor>,
+    ) -> Result<Vec<Review>, ServiceError> {
+        // Sort keys are fixed expressions, never user input
+        let (order_by, keyset) = match sort_key(query.sort) {
+            Some(key) => (
+                format!("{} DESC, r.created_at DESC, r.id DESC", key("r")),
+                format!("({}, r.created_at, r.id) < ($4, $5, $6)", key("r")),
+            ),
+            None => (
+                "r.created_at DESC, r.id DESC".to_string(),
+                "(r.created_at, r.id) < ($5, $6)".to_string(),
+            ),
+        };
+
+        let reviews = sqlx::query_as::<_, Review>(&format!(
             r#"
-            SELECT id, user_id, coffee_id, rating, comment, created_at, updated_at
-            FROM reviews
-            WHERE coffee_id = $1
-            ORDER BY created_at DESC
+            SELECT r.id, r.user_id, r.coffee_id, r.rating, r.comment, r.status, r.moderation_reason,
+                   r.verified_purchase, r.helpful_count, r.created_at, r.updated_at
+            FROM reviews r
+            WHERE r.coffee_id = $1
+              AND r.status = 'published'
+              AND (NOT $2 OR r.verified_purchase)
+              AND ($3::SMALLINT IS NULL OR r.rating = $3)
+              AND ($6::INT IS NULL OR {})
+            ORDER BY {}
+            LIMIT $7 OFFSET $8
             "#,
-        )
+            keyset, order_by
+        ))
         .bind(coffee_id)
+        .bind(query.verified)
+        .bind(query.rating)
+        .bind(after.map(|cursor| cursor.sort_value))
+        .bind(after.map(|cursor| cursor.created_at))
+        .bind(after.map(|cursor| cursor.id))
+        .bind(limit as i64)
+        .bind(offset as i64)
         .fetch_all(&self.pool)
         .await?;
 
         Ok(reviews)
     }
 
-    /// Get all rating values for a coffee (for average calculation)
-    pub async fn get_ratings_for_coffee(&self, coffee_id: i32) -> Result<Vec<i16>, ServiceError> {
-        let ratings: Vec<(i16,)> = sqlx::query_as(
+    /// Count the published reviews for a coffee matching the query's filters
+    pub async fn count_by_coffee(
+        &self,
+        coffee_id: i32,
+        query: &ReviewListQuery,
+    ) -> Result<i64, ServiceError> {
+        let count: i64 = sqlx::query_scalar(
             r#"
-            SELECT rating
+            SELECT COUNT(*)
             FROM reviews
             WHERE coffee_id = $1
+              AND status = 'published'
+              AND (NOT $2 OR verified_purchase)
+              AND ($3::SMALLINT IS NULL OR rating = $3)
             "#,
         )
         .bind(coffee_id)
-        .fetch_all(&self.pool)
+        .bind(query.verified)
+        .bind(query.rating)
+        .fetch_one(&self.pool)
         .await?;
 
-        Ok(ratings.into_iter().map(|(r,)| r).collect())
+        Ok(count)
     }
 
-    /// Update the average rating and review count for a coffee
-    pub async fn update_coffee_rating(
+    /// Number of published reviews per star rating for a coffee
+    /// Read from the coffee's running totals unless limited to verified purchases
+    pub async fn get_star_counts(
         &self,
         coffee_id: i32,
-        average: Option<f64>,
-        count: i32,
-    ) -> Result<(), ServiceError> {
-        sqlx::query(
+        verified_only: bool,
+    ) -> Result<Vec<(i16, i64)>, ServiceError> {
+        if !verified_only {
+            return Ok(self.get_rating_aggregate(coffee_id).await?.star_counts());
+        }
+
+        let counts = sqlx::query_as::<_, (i16, i64)>(
             r#"
-            UPDATE coffees
-            SET average_rating = $1, review_count = $2
-            WHERE id = $3
+            SELECT rating, COUNT(*)
+            FROM reviews
+            WHERE coffee_id = $1 AND status = 'published' AND verified_purchase
+            GROUP BY rating
             "#,
         )
-        .bind(average)
-        .bind(count)
         .bind(coffee_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Get the published rating totals kept on a coffee
+    pub async fn get_rating_aggregate(&self, coffee_id: i32) -> Result<RatingAggregate, ServiceError> {
+        let aggregate = sqlx::query_as::<_, RatingAggregate>(RATING_AGGREGATE_SQL)
+            .bind(coffee_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(aggregate.unwrap_or_default())
+    }
+
+    /// Get the rating totals as seen by a transaction, including its own review writes
+    pub async fn get_rating_aggregate_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        coffee_id: i32,
+    ) -> Result<RatingAggregate, ServiceError> {
+        let aggregate = sqlx::query_as::<_, RatingAggregate>(RATING_AGGREGATE_SQL)
+            .bind(coffee_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+
+        Ok(aggregate.unwrap_or_default())
+    }
+
+    /// Rebuild every coffee's rating totals from the reviews table
+    /// Returns the number of coffees whose totals were out of date
+    pub async fn rebuild_rating_aggregates(&self) -> Result<i32, ServiceError> {
+        let repaired: i32 = sqlx::query_scalar("SELECT rebuild_coffee_rating_aggregates()")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(repaired)
+    }
+
+    /// Set every coffee's weighted score to its Bayesian average in one statement
+    /// Mirrors `bayesian_average`; returns the number of coffees updated
+    pub async fn update_all_bayesian_ratings(
+        &self,
+        prior_mean: f64,
+        prior_weight: f64,
+    ) -> Result<u64, ServiceError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE coffees c
+            SET weighted_rating = CASE
+                WHEN t.n = 0 THEN NULL
+                ELSE ($2 * $1 + t.sum) / ($2 + t.n)
+            END
+            FROM (SELECT id, rating_sum::FLOAT8 AS sum, review_count::FLOAT8 AS n FROM coffees) t
+            WHERE t.id = c.id
+            "#,
+        )
+        .bind(prior_mean)
+        .bind(prior_weight)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Set every coffee's weighted score to its Wilson lower bound in one statement
+    /// Mirrors `wilson_lower_bound`; returns the number of coffees updated
+    pub async fn update_all_wilson_ratings(&self, z: f64) -> Result<u64, ServiceError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE coffees c
+            SET weighted_rating = CASE
+                WHEN t.n = 0 THEN NULL
+                ELSE 1 + 4 * GREATEST(0, (
+                    t.p + $1 * $1 / (2 * t.n)
+                    - $1 * SQRT((t.p * (1 - t.p) + $1 * $1 / (4 * t.n)) / t.n)
+                ) / (1 + $1 * $1 / t.n))
+            END
+            FROM (
+                SELECT id,
+                       review_count::FLOAT8 AS n,
+                       (rating_sum - review_count)::FLOAT8 / NULLIF(4 * review_count, 0) AS p
+                FROM coffees
+            ) t
+            WHERE t.id = c.id
+            "#,
+        )
+        .bind(z)
         .execute(&self.pool)
         .await?;
 
+        Ok(result.rows_affected())
+    }
+
+    /// Update the confidence-weighted score for a coffee
+    pub async fn update_weighted_rating(
+        &self,
+        coffee_id: i32,
+        weighted: Option<f64>,
+    ) -> Result<(), ServiceError> {
+        let mut tx = self.begin().await?;
+        self.update_weighted_rating_tx(&mut tx, coffee_id, weighted).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Update the confidence-weighted score for a coffee within a transaction
+    pub async fn update_weighted_rating_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        coffee_id: i32,
+        weighted: Option<f64>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query("UPDATE coffees SET weighted_rating = $1 WHERE id = $2")
+            .bind(weighted)
+            .bind(coffee_id)
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create or replace the shop reply on a review
+    /// Returns the reply and whether it was newly created
+    pub async fn upsert_reply(
+        &self,
+        review_id: i32,
+        author_id: i32,
+        body: &str,
+    ) -> Result<(ReviewReply, bool), ServiceError> {
+        let (created,): (bool,) = sqlx::query_as(
+            r#"
+            INSERT INTO review_replies (review_id, author_id, body)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (review_id) DO UPDATE
+            SET author_id = EXCLUDED.author_id, body = EXCLUDED.body, updated_at = NOW()
+            RETURNING (xmax = 0)
+            "#,
+        )
+        .bind(review_id)
+        .bind(author_id)
+        .bind(body)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let reply = self
+            .find_replies(&[review_id])
+            .await?
+            .pop()
+            .ok_or(ServiceError::NotFound)?;
+
+        Ok((reply, created))
+    }
+
+    /// Delete the shop reply on a review
+    pub async fn delete_reply(&self, review_id: i32) -> Result<(), ServiceError> {
+        let result = sqlx::query("DELETE FROM review_replies WHERE review_id = $1")
+            .bind(review_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::NotFound);
+        }
+
         Ok(())
     }
 
+    /// Shop replies for the given reviews
+    pub async fn find_replies(&self, review_ids: &[i32]) -> Result<Vec<ReviewReply>, ServiceError> {
+        let replies = sqlx::query_as::<_, ReviewReply>(
+            r#"
+            SELECT review_id, author_id, body, created_at, updated_at
+            FROM review_replies
+            WHERE review_id = ANY($1)
+            "#,
+        )
+        .bind(review_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(replies)
+    }
+
+    /// Record a helpful vote and return the review's vote count
+    /// Voting again is a no-op
+    pub async fn add_helpful_vote(&self, review_id: i32, user_id: i32) -> Result<i32, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO review_votes (review_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        )
+        .bind(review_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let helpful_count: i32 = sqlx::query_scalar(
+            "UPDATE reviews SET helpful_count = helpful_count + $2 WHERE id = $1 RETURNING helpful_count"
+        )
+        .bind(review_id)
+        .bind(inserted as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(helpful_count)
+    }
+
+    /// Withdraw a helpful vote and return the review's vote count
+    /// Withdrawing a vote that was never cast is a no-op
+    pub async fn remove_helpful_vote(&self, review_id: i32, user_id: i32) -> Result<i32, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = sqlx::query("DELETE FROM review_votes WHERE review_id = $1 AND user_id = $2")
+            .bind(review_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let helpful_count: i32 = sqlx::query_scalar(
+            "UPDATE reviews SET helpful_count = helpful_count - $2 WHERE id = $1 RETURNING helpful_count"
+        )
+        .bind(review_id)
+        .bind(deleted as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(helpful_count)
+    }
+
+    /// Check if the user has a completed order containing the coffee
+    pub async fn has_completed_purchase(&self, user_id: i32, coffee_id: i32) -> Result<bool, ServiceError> {
+        let purchased: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1
+                FROM orders o
+                JOIN order_items oi ON oi.order_id = o.id
+                WHERE o.user_id = $1 AND oi.coffee_item_id = $2 AND o.status = 'completed'
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(coffee_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(purchased)
+    }
+
     /// Check if a coffee exists
     pub async fn coffee_exists(&self, coffee_id: i32) -> Result<bool, ServiceError> {
         let exists: Option<bool> = sqlx::query_scalar(
@@ -231,4 +646,199 @@ impl ReviewRepository {
 
         Ok(exists.unwrap_or(false))
     }
+
+    /// Hold a published review for moderation
+    /// Returns None when the review is not currently published
+    pub async fn hold_for_moderation(
+        &self,
+        id: i32,
+        reason: &str,
+    ) -> Result<Option<Review>, ServiceError> {
+        let mut tx = self.begin().await?;
+        let review = self.hold_for_moderation_tx(&mut tx, id, reason).await?;
+        tx.commit().await?;
+
+        Ok(review)
+    }
+
+    /// Hold a published review for moderation within a transaction
+    pub async fn hold_for_moderation_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        reason: &str,
+    ) -> Result<Option<Review>, ServiceError> {
+        let review = sqlx::query_as::<_, Review>(
+            r#"
+            UPDATE reviews
+            SET status = 'pending', moderation_reason = $2
+            WHERE id = $1 AND status = 'published'
+            RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(reason)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(review)
+    }
+
+    /// Apply a moderator decision and resolve the review's open reports
+    pub async fn moderate(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        status: ReviewStatus,
+        reason: Option<String>,
+        moderator_id: i32,
+    ) -> Result<Review, ServiceError> {
+
+        let review = sqlx::query_as::<_, Review>(
+            r#"
+            UPDATE reviews
+            SET status = $2, moderation_reason = $3, moderated_by = $4, moderated_at = NOW()
+            WHERE id = $1
+            RETURNING id, user_id, coffee_id, rating, comment, status, moderation_reason, verified_purchase, helpful_count, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(reason)
+        .bind(moderator_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(ServiceError::NotFound)?;
+
+        sqlx::query("UPDATE review_flags SET resolved_at = NOW() WHERE review_id = $1 AND resolved_at IS NULL")
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(review)
+    }
+
+    /// Record a user's report against a review
+    /// Returns None when the user has already reported this review
+    pub async fn create_flag(
+        &self,
+        review_id: i32,
+        user_id: i32,
+        reason: FlagReason,
+        details: Option<String>,
+    ) -> Result<Option<ReviewFlag>, ServiceError> {
+        let flag = sqlx::query_as::<_, ReviewFlag>(
+            r#"
+            INSERT INTO review_flags (review_id, user_id, reason, details)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (review_id, user_id) DO NOTHING
+            RETURNING id, review_id, user_id, reason, details, created_at, resolved_at
+            "#,
+        )
+        .bind(review_id)
+        .bind(user_id)
+        .bind(reason)
+        .bind(details)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(flag)
+    }
+
+    /// Count the unresolved reports against a review
+    pub async fn count_open_flags(&self, review_id: i32) -> Result<i64, ServiceError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM review_flags WHERE review_id = $1 AND resolved_at IS NULL"
+        )
+        .bind(review_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// All reports against a review, newest first
+    pub async fn find_flags(&self, review_id: i32) -> Result<Vec<ReviewFlag>, ServiceError> {
+        let flags = sqlx::query_as::<_, ReviewFlag>(
+            r#"
+            SELECT id, review_id, user_id, reason, details, created_at, resolved_at
+            FROM review_flags
+            WHERE review_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(review_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(flags)
+    }
+
+    /// Reviews awaiting a moderator, most reported first
+    ///
+    /// Without a status filter the queue holds pending reviews and any
+    /// review with open reports. Returns the page and the total count.
+    pub async fn find_moderation_queue(
+        &self,
+        status: Option<ReviewStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<ModerationQueueItem>, i64), ServiceError> {
+        let filter = "CASE WHEN $1::TEXT IS NULL
+                 THEN r.status = 'pending' OR open_flags > 0
+                 ELSE r.status = $1
+             END";
+
+        let items = sqlx::query_as::<_, ModerationQueueItem>(&format!(
+            r#"
+            SELECT r.id, r.user_id, r.coffee_id, r.rating, r.comment, r.status, r.moderation_reason, r.verified_purchase, r.helpful_count,
+                   r.created_at, r.updated_at, open_flags
+            FROM reviews r
+            CROSS JOIN LATERAL (
+                SELECT COUNT(*) AS open_flags
+                FROM review_flags f
+                WHERE f.review_id = r.id AND f.resolved_at IS NULL
+            ) flags
+            WHERE {}
+            ORDER BY open_flags DESC, r.updated_at ASC, r.id ASC
+            LIMIT $2 OFFSET $3
+            "#,
+            filter
+        ))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*)
+            FROM reviews r
+            CROSS JOIN LATERAL (
+                SELECT COUNT(*) AS open_flags
+                FROM review_flags f
+                WHERE f.review_id = r.id AND f.resolved_at IS NULL
+            ) flags
+            WHERE {}
+            "#,
+            filter
+        ))
+        .bind(status.map(|s| s.as_str()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((items, total))
+    }
+}
+
+/// Leading sort expression for a listing order, qualified with the table alias
+/// None for newest first, which only orders by creation time
+fn sort_key(sort: ReviewSort) -> Option<fn(&str) -> String> {
+    match sort {
+        ReviewSort::Newest => None,
+        ReviewSort::Highest => Some(|alias| format!("{}.rating", alias)),
+        ReviewSort::Lowest => Some(|alias| format!("-{}.rating", alias)),
+        ReviewSort::Helpful => Some(|alias| format!("{}.helpful_count", alias)),
+    }
 }
@@ -1,7 +1,16 @@
+use crate::config::{ReviewModerationConfig, ReviewPolicyConfig};
+use crate::notifications::{LogNotifier, Notification, Notifier};
+use crate::pagination::{generate_next_cursor, PaginatedResponse, PaginationQuery};
+use crate::reviews::moderation::blocked_words_in;
 use crate::reviews::{
-    CreateReviewRequest, Review, ReviewRepository, RatingCalculator, ServiceError,
-    UpdateReviewRequest,
+    CreateReviewRequest, FlagReviewRequest, HelpfulVoteResponse, ModerateReviewRequest,
+    ModerationQueueItem, ModerationQueueQuery, RatingSummary, Review, ReviewCursor, ReviewFlag,
+    ReviewListQuery, ReviewListResponse, ReviewReply, ReviewReplyRequest, ReviewRepository,
+    ReviewResponse, ReviewStatus, RatingCalculator, ServiceError, UpdateReviewRequest,
 };
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
 use validator::Validate;
 
 /// Service layer for review business logic
@@ -9,6 +18,9 @@ use validator::Validate;
 pub struct ReviewService {
     repository: ReviewRepository,
     rating_calculator: RatingCalculator,
+    moderation: ReviewModerationConfig,
+    policy: ReviewPolicyConfig,
+    notifier: Arc<dyn Notifier>,
 }
 
 impl ReviewService {
@@ -17,6 +29,37 @@ impl ReviewService {
         Self {
             repository,
             rating_calculator,
+            moderation: ReviewModerationConfig::default(),
+            policy: ReviewPolicyConfig::default(),
+            notifier: Arc::new(LogNotifier),
+        }
+    }
+
+    /// Deliver reviewer notifications through the given notifier
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Use the given review policy (e.g. purchase required)
+    pub fn with_policy(mut self, policy: ReviewPolicyConfig) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Use the given blocked word list and report threshold
+    pub fn with_moderation(mut self, moderation: ReviewModerationConfig) -> Self {
+        self.moderation = moderation;
+        self
+    }
+
+    /// Reason for holding a comment for moderation, if it contains blocked words
+    fn blocked_words_reason(&self, comment: Option<&str>) -> Option<String> {
+        let matched = blocked_words_in(comment?, &self.moderation.blocked_words);
+        if matched.is_empty() {
+            None
+        } else {
+            Some(format!("Contains blocked words: {}", matched.join(", ")))
         }
     }
 
@@ -25,9 +68,9 @@ impl ReviewService {
     /// This method:
     /// 1. Validates the request
     /// 2. Checks for duplicate reviews (user already reviewed this coffee)
-    /// 3. Verifies the coffee exists
-    /// 4. Creates the review
-    /// 5. Recalculates the average rating for the coffee
+    /// 3. Verifies the coffee exists and, if the policy requires it, that the user bought it
+    /// 4. Creates the review, held for moderation if the comment contains blocked words
+    /// 5. Recalculates the average rating for the coffee in the same transaction
     pub async fn create_review(
         &self,
         user_id: i32,
@@ -52,16 +95,38 @@ impl ReviewService {
             return Err(ServiceError::CoffeeNotFound);
         }
 
+        if self.policy.require_verified_purchase
+            && !self.repository.has_completed_purchase(user_id, request.coffee_id).await?
+        {
+            return Err(ServiceError::PurchaseRequired);
+        }
+
         // 4. Create the review
+        let held_reason = self.blocked_words_reason(request.comment.as_deref());
+        let status = if held_reason.is_some() {
+            ReviewStatus::Pending
+        } else {
+            ReviewStatus::Published
+        };
+        let mut tx = self.repository.begin().await?;
         let review = self
             .repository
-            .create(user_id, request.coffee_id, request.rating, request.comment)
+            .create_with_status_tx(
+                &mut tx,
+                user_id,
+                request.coffee_id,
+                request.rating,
+                request.comment,
+                status,
+                held_reason,
+            )
             .await?;
 
         // 5. Recalculate average rating
         self.rating_calculator
-            .recalculate_average(request.coffee_id)
+            .recalculate_average_tx(&mut tx, request.coffee_id)
             .await?;
+        tx.commit().await?;
 
         Ok(review)
     }
@@ -72,8 +137,8 @@ impl ReviewService {
     /// 1. Validates the request
     /// 2. Fetches the existing review
     /// 3. Verifies the user owns the review
-    /// 4. Updates the review
-    /// 5. Recalculates the average rating if the rating changed
+    /// 4. Updates the review, holding it for moderation if the new comment contains blocked words
+    /// 5. Recalculates the average rating in the same transaction if the rating or visibility changed
     pub async fn update_review(
         &self,
         review_id: i32,
@@ -93,22 +158,36 @@ impl ReviewService {
             .ok_or(ServiceError::NotFound)?;
 
         // 3. Verify ownership
-        if existing.user_id != user_id {
+        if existing.user_id != Some(user_id) {
             return Err(ServiceError::Unauthorized);
         }
 
         // 4. Update the review
-        let updated = self
+        let held_reason = self.blocked_words_reason(request.comment.as_deref());
+        let mut tx = self.repository.begin().await?;
+        let mut updated = self
             .repository
-            .update(review_id, request.rating, request.comment)
+            .update_tx(&mut tx, review_id, request.rating, request.comment)
             .await?;
 
-        // 5. Recalculate average rating if rating changed
-        if request.rating.is_some() && request.rating != Some(existing.rating) {
+        if let Some(reason) = held_reason {
+            if let Some(held) = self
+                .repository
+                .hold_for_moderation_tx(&mut tx, review_id, &reason)
+                .await?
+            {
+                updated = held;
+            }
+        }
+
+        // 5. Recalculate average rating if rating or visibility changed
+        let rating_changed = request.rating.is_some() && request.rating != Some(existing.rating);
+        if rating_changed || updated.status != existing.status {
             self.rating_calculator
-                .recalculate_average(existing.coffee_id)
+                .recalculate_average_tx(&mut tx, existing.coffee_id)
                 .await?;
         }
+        tx.commit().await?;
 
         Ok(updated)
     }
@@ -119,7 +198,7 @@ impl ReviewService {
     /// 1. Fetches the existing review
     /// 2. Verifies the user owns the review
     /// 3. Deletes the review
-    /// 4. Recalculates the average rating
+    /// 4. Recalculates the average rating in the same transaction
     pub async fn delete_review(&self, review_id: i32, user_id: i32) -> Result<(), ServiceError> {
         // 1. Fetch existing review
         let existing = self
@@ -129,32 +208,368 @@ impl ReviewService {
             .ok_or(ServiceError::NotFound)?;
 
         // 2. Verify ownership
-        if existing.user_id != user_id {
+        if existing.user_id != Some(user_id) {
             return Err(ServiceError::Unauthorized);
         }
 
         let coffee_id = existing.coffee_id;
 
         // 3. Delete the review
-        self.repository.delete(review_id).await?;
+        let mut tx = self.repository.begin().await?;
+        self.repository.delete_tx(&mut tx, review_id).await?;
 
         // 4. Recalculate average rating
         self.rating_calculator
-            .recalculate_average(coffee_id)
+            .recalculate_average_tx(&mut tx, coffee_id)
             .await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
-    /// Get all reviews for a coffee
-    pub async fn get_reviews_for_coffee(&self, coffee_id: i32) -> Result<Vec<Review>, ServiceError> {
-        self.repository.find_by_coffee(coffee_id).await
+    /// Get a page of published reviews for a coffee with its rating summary
+    ///
+    /// Uses keyset pagination when the query carries a cursor (the sort
+    /// position of the last review on the previous page) and offset
+    /// pagination otherwise.
+    /// The summary honours the verified filter but not the star filter.
+    pub async fn list_reviews_for_coffee(
+        &self,
+        coffee_id: i32,
+        query: &ReviewListQuery,
+    ) -> Result<ReviewListResponse, ServiceError> {
+        query
+            .validate()
+            .map_err(|e| ServiceError::ValidationError(format!("Validation failed: {}", e)))?;
+
+        let pagination = PaginationQuery {
+            page: query.page.unwrap_or(1),
+            limit: query.limit.unwrap_or(20),
+            cursor: query.cursor.clone(),
+        }
+        .validate_and_normalize();
+
+        let after = pagination
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                ReviewCursor::parse(cursor)
+                    .ok_or_else(|| ServiceError::ValidationError("Invalid cursor".to_string()))
+            })
+            .transpose()?;
+        let (page, offset) = if after.is_some() {
+            (1, 0)
+        } else {
+            (pagination.page, pagination.offset)
+        };
+
+        // Fetch one extra review to know whether another page follows
+        let mut reviews = self
+            .repository
+            .find_by_coffee(coffee_id, query, pagination.limit + 1, offset, after)
+            .await?;
+        let has_more = reviews.len() > pagination.limit as usize;
+        reviews.truncate(pagination.limit as usize);
+        let next_cursor = if has_more {
+            generate_next_cursor(&reviews, |review| ReviewCursor::after(review, query.sort).encode())
+        } else {
+            None
+        };
+
+        let data = self.with_replies(reviews).await?;
+
+        let total = self.repository.count_by_coffee(coffee_id, query).await?;
+        let star_counts = self.repository.get_star_counts(coffee_id, query.verified).await?;
+
+        Ok(ReviewListResponse {
+            summary: RatingSummary::from_counts(&star_counts),
+            page: PaginatedResponse::with_cursor(
+                data,
+                total as u64,
+                page,
+                pagination.limit,
+                next_cursor,
+            ),
+        })
+    }
+
+    /// Convert reviews to responses with their shop replies attached
+    async fn with_replies(&self, reviews: Vec<Review>) -> Result<Vec<ReviewResponse>, ServiceError> {
+        let review_ids: Vec<i32> = reviews.iter().map(|review| review.id).collect();
+        let mut replies: HashMap<i32, ReviewReply> = self
+            .repository
+            .find_replies(&review_ids)
+            .await?
+            .into_iter()
+            .map(|reply| (reply.review_id, reply))
+            .collect();
+
+        Ok(reviews
+            .into_iter()
+            .map(|review| {
+                let reply = replies.remove(&review.id);
+                ReviewResponse {
+                    reply,
+                    ..ReviewResponse::from(review)
+                }
+            })
+            .collect())
+    }
+
+    /// Get a page of the user's own reviews, newest first
+    ///
+    /// Includes reviews held or hidden by moderation so users can see their status.
+    pub async fn list_user_reviews(
+        &self,
+        user_id: i32,
+        query: &PaginationQuery,
+    ) -> Result<PaginatedResponse<ReviewResponse>, ServiceError> {
+        query
+            .validate()
+            .map_err(|e| ServiceError::ValidationError(format!("Validation failed: {}", e)))?;
+        let pagination = query.validate_and_normalize();
+
+        let (reviews, total) = self
+            .repository
+            .find_by_user(user_id, pagination.limit, pagination.offset)
+            .await?;
+
+        Ok(PaginatedResponse::new(
+            self.with_replies(reviews).await?,
+            total as u64,
+            pagination.page,
+            pagination.limit,
+        ))
+    }
+
+    /// Get the user's review of a coffee, if they wrote one
+    pub async fn find_user_review(
+        &self,
+        user_id: i32,
+        coffee_id: i32,
+    ) -> Result<Option<ReviewResponse>, ServiceError> {
+        let review = self
+            .repository
+            .find_by_user_and_coffee(user_id, coffee_id)
+            .await?;
+
+        Ok(self
+            .with_replies(review.into_iter().collect())
+            .await?
+            .pop())
+    }
+
+    /// Post or edit the shop's public reply to a review
+    ///
+    /// A review has at most one reply; posting again replaces its body. The
+    /// reviewer is notified when a reply is first posted, not on later edits.
+    /// Notification failures are logged and do not fail the reply.
+    pub async fn reply_to_review(
+        &self,
+        review_id: i32,
+        author_id: i32,
+        request: ReviewReplyRequest,
+    ) -> Result<ReviewReply, ServiceError> {
+        request
+            .validate()
+            .map_err(|e| ServiceError::ValidationError(format!("Validation failed: {}", e)))?;
+
+        let review = self
+            .repository
+            .find_by_id(review_id)
+            .await?
+            .ok_or(ServiceError::NotFound)?;
+
+        let (reply, created) = self
+            .repository
+            .upsert_reply(review_id, author_id, &request.body)
+            .await?;
+
+        if let (true, Some(reviewer_id)) = (created, review.user_id) {
+            let notification = Notification::ReviewReply {
+                review_id,
+                coffee_id: review.coffee_id,
+            };
+            if let Err(e) = self.notifier.notify(reviewer_id, notification).await {
+                warn!("Failed to notify user {} of reply to review {}: {}", reviewer_id, review_id, e);
+            }
+        }
+
+        Ok(reply)
+    }
+
+    /// Remove the shop's reply from a review
+    pub async fn delete_reply(&self, review_id: i32) -> Result<(), ServiceError> {
+        self.repository.delete_reply(review_id).await
+    }
+
+    /// Mark a published review as helpful (one vote per user)
+    pub async fn vote_helpful(
+        &self,
+        review_id: i32,
+        user_id: i32,
+    ) -> Result<HelpfulVoteResponse, ServiceError> {
+        let review = self
+            .repository
+            .find_by_id(review_id)
+            .await?
+            .filter(|review| review.status == ReviewStatus::Published)
+            .ok_or(ServiceError::NotFound)?;
+
+        if review.user_id == Some(user_id) {
+            return Err(ServiceError::ValidationError(
+                "You cannot vote for your own review".to_string(),
+            ));
+        }
+
+        let helpful_count = self.repository.add_helpful_vote(review_id, user_id).await?;
+
+        Ok(HelpfulVoteResponse {
+            review_id,
+            helpful_count,
+            voted: true,
+        })
+    }
+
+    /// Withdraw a helpful vote
+    pub async fn remove_helpful_vote(
+        &self,
+        review_id: i32,
+        user_id: i32,
+    ) -> Result<HelpfulVoteResponse, ServiceError> {
+        if self.repository.find_by_id(review_id).await?.is_none() {
+            return Err(ServiceError::NotFound);
+        }
+
+        let helpful_count = self.repository.remove_helpful_vote(review_id, user_id).await?;
+
+        Ok(HelpfulVoteResponse {
+            review_id,
+            helpful_count,
+            voted: false,
+        })
+    }
+
+    /// Report a published review
+    ///
+    /// Each user can report a review once. When the number of open reports
+    /// reaches the configured threshold the review is held for moderation.
+    pub async fn flag_review(
+        &self,
+        review_id: i32,
+        user_id: i32,
+        request: FlagReviewRequest,
+    ) -> Result<ReviewFlag, ServiceError> {
+        request
+            .validate()
+            .map_err(|e| ServiceError::ValidationError(format!("Validation failed: {}", e)))?;
+
+        let review = self
+            .repository
+            .find_by_id(review_id)
+            .await?
+            .filter(|review| review.status == ReviewStatus::Published)
+            .ok_or(ServiceError::NotFound)?;
+
+        if review.user_id == Some(user_id) {
+            return Err(ServiceError::ValidationError(
+                "You cannot report your own review".to_string(),
+            ));
+        }
+
+        let flag = self
+            .repository
+            .create_flag(review_id, user_id, request.reason, request.details)
+            .await?
+            .ok_or(ServiceError::DuplicateFlag)?;
+
+        let open_flags = self.repository.count_open_flags(review_id).await?;
+        if open_flags >= self.moderation.flag_threshold {
+            let reason = format!("Reported by {} users", open_flags);
+            let mut tx = self.repository.begin().await?;
+            if self
+                .repository
+                .hold_for_moderation_tx(&mut tx, review_id, &reason)
+                .await?
+                .is_some()
+            {
+                self.rating_calculator
+                    .recalculate_average_tx(&mut tx, review.coffee_id)
+                    .await?;
+            }
+            tx.commit().await?;
+        }
+
+        Ok(flag)
+    }
+
+    /// Reviews awaiting moderation, most reported first
+    pub async fn get_moderation_queue(
+        &self,
+        query: &ModerationQueueQuery,
+    ) -> Result<PaginatedResponse<ModerationQueueItem>, ServiceError> {
+        let pagination = PaginationQuery {
+            page: query.page.unwrap_or(1),
+            limit: query.limit.unwrap_or(20),
+            cursor: None,
+        }
+        .validate_and_normalize();
+
+        let (items, total) = self
+            .repository
+            .find_moderation_queue(query.status, pagination.limit, pagination.offset)
+            .await?;
+
+        Ok(PaginatedResponse::new(
+            items,
+            total as u64,
+            pagination.page,
+            pagination.limit,
+        ))
+    }
+
+    /// All reports filed against a review
+    pub async fn get_review_flags(&self, review_id: i32) -> Result<Vec<ReviewFlag>, ServiceError> {
+        if self.repository.find_by_id(review_id).await?.is_none() {
+            return Err(ServiceError::NotFound);
+        }
+
+        self.repository.find_flags(review_id).await
+    }
+
+    /// Publish, hide or remove a review
+    ///
+    /// Resolves the review's open reports and recalculates the coffee's
+    /// rating in the same transaction, since only published reviews are counted.
+    pub async fn moderate_review(
+        &self,
+        review_id: i32,
+        moderator_id: i32,
+        request: ModerateReviewRequest,
+    ) -> Result<Review, ServiceError> {
+        request
+            .validate()
+            .map_err(|e| ServiceError::ValidationError(format!("Validation failed: {}", e)))?;
+
+        let mut tx = self.repository.begin().await?;
+        let review = self
+            .repository
+            .moderate(&mut tx, review_id, request.status, request.reason, moderator_id)
+            .await?;
+
+        self.rating_calculator
+            .recalculate_average_tx(&mut tx, review.coffee_id)
+            .await?;
+        tx.commit().await?;
+
+        Ok(review)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::reviews::{FlagReason, ReviewSort};
     use sqlx::PgPool;
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -257,7 +672,7 @@ mod tests {
             .await
             .expect("Failed to create review");
 
-        assert_eq!(review.user_id, user_id);
+        assert_eq!(review.user_id, Some(user_id));
         assert_eq!(review.coffee_id, coffee_id);
         assert_eq!(review.rating, 5);
         assert_eq!(review.comment, Some("Excellent!".to_string()));
@@ -545,11 +960,12 @@ mod tests {
 
         // Get all reviews
         let reviews = service
-            .get_reviews_for_coffee(coffee_id)
+            .list_reviews_for_coffee(coffee_id, &ReviewListQuery::default())
             .await
             .expect("Failed to get reviews");
 
-        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews.page.data.len(), 2);
+        assert_eq!(reviews.summary.total_reviews, 2);
     }
 
     // ============================================================================
@@ -1938,5 +2354,515 @@ mod tests {
 
         assert_eq!(count, 2, "Review count should be 2");
     }
-}
 
+    /// Helper function to create a service with a blocked word list and report threshold of 2
+    fn create_moderated_service(pool: PgPool) -> ReviewService {
+        create_service(pool).with_moderation(ReviewModerationConfig {
+            blocked_words: vec!["scam".to_string()],
+            flag_threshold: 2,
+        })
+    }
+
+    /// Fetch the cached review count of a coffee
+    async fn review_count(pool: &PgPool, coffee_id: i32) -> i32 {
+        sqlx::query_scalar("SELECT review_count FROM coffees WHERE id = $1")
+            .bind(coffee_id)
+            .fetch_one(pool)
+            .await
+            .expect("Failed to fetch review count")
+    }
+
+    #[tokio::test]
+    async fn test_blocked_words_hold_review_for_moderation() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_moderated_service(pool.clone());
+
+        let review = service
+            .create_review(user_id, CreateReviewRequest {
+                coffee_id,
+                rating: 1,
+                comment: Some("This shop is a SCAM!".to_string()),
+            })
+            .await
+            .expect("Failed to create review");
+
+        assert_eq!(review.status, ReviewStatus::Pending);
+        assert_eq!(review.moderation_reason.as_deref(), Some("Contains blocked words: scam"));
+        assert!(service.list_reviews_for_coffee(coffee_id, &ReviewListQuery::default()).await.unwrap().page.data.is_empty());
+        assert_eq!(review_count(&pool, coffee_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_with_blocked_words_holds_published_review() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_moderated_service(pool.clone());
+
+        let review = service
+            .create_review(user_id, CreateReviewRequest { coffee_id, rating: 4, comment: None })
+            .await
+            .expect("Failed to create review");
+        assert_eq!(review_count(&pool, coffee_id).await, 1);
+
+        let updated = service
+            .update_review(review.id, user_id, UpdateReviewRequest {
+                rating: None,
+                comment: Some("Actually a scam".to_string()),
+            })
+            .await
+            .expect("Failed to update review");
+
+        assert_eq!(updated.status, ReviewStatus::Pending);
+        assert_eq!(review_count(&pool, coffee_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_flags_hold_review_at_threshold() {
+        let pool = create_test_pool().await;
+        let author = create_test_user(&pool).await;
+        let reporter1 = create_test_user(&pool).await;
+        let reporter2 = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_moderated_service(pool.clone());
+
+        let review = service
+            .create_review(author, CreateReviewRequest { coffee_id, rating: 5, comment: None })
+            .await
+            .expect("Failed to create review");
+
+        let flag = |reason| FlagReviewRequest { reason, details: None };
+
+        service.flag_review(review.id, reporter1, flag(FlagReason::Spam)).await.unwrap();
+        let after_one = service.repository.find_by_id(review.id).await.unwrap().unwrap();
+        assert_eq!(after_one.status, ReviewStatus::Published);
+
+        // The same user cannot report twice, and authors cannot report themselves
+        assert!(matches!(
+            service.flag_review(review.id, reporter1, flag(FlagReason::Spam)).await,
+            Err(ServiceError::DuplicateFlag)
+        ));
+        assert!(matches!(
+            service.flag_review(review.id, author, flag(FlagReason::Other)).await,
+            Err(ServiceError::ValidationError(_))
+        ));
+
+        service.flag_review(review.id, reporter2, flag(FlagReason::Offensive)).await.unwrap();
+        let after_two = service.repository.find_by_id(review.id).await.unwrap().unwrap();
+        assert_eq!(after_two.status, ReviewStatus::Pending);
+        assert_eq!(review_count(&pool, coffee_id).await, 0);
+
+        // Held reviews can no longer be reported
+        let reporter3 = create_test_user(&pool).await;
+        assert!(matches!(
+            service.flag_review(review.id, reporter3, flag(FlagReason::Spam)).await,
+            Err(ServiceError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_moderation_decision_resolves_flags_and_updates_rating() {
+        let pool = create_test_pool().await;
+        let author = create_test_user(&pool).await;
+        let reporter = create_test_user(&pool).await;
+        let moderator = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_moderated_service(pool.clone());
+
+        let review = service
+            .create_review(author, CreateReviewRequest { coffee_id, rating: 2, comment: None })
+            .await
+            .expect("Failed to create review");
+        service
+            .flag_review(review.id, reporter, FlagReviewRequest { reason: FlagReason::OffTopic, details: None })
+            .await
+            .unwrap();
+
+        let queue = service
+            .get_moderation_queue(&ModerationQueueQuery { status: None, page: None, limit: Some(100) })
+            .await
+            .unwrap();
+        let item = queue.data.iter().find(|item| item.review.id == review.id).expect("Review not queued");
+        assert_eq!(item.open_flags, 1);
+
+        let hidden = service
+            .moderate_review(review.id, moderator, ModerateReviewRequest {
+                status: ReviewStatus::Hidden,
+                reason: Some("Off topic".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(hidden.status, ReviewStatus::Hidden);
+        assert_eq!(review_count(&pool, coffee_id).await, 0);
+        assert_eq!(service.repository.count_open_flags(review.id).await.unwrap(), 0);
+
+        let flags = service.get_review_flags(review.id).await.unwrap();
+        assert_eq!(flags.len(), 1);
+        assert!(flags[0].resolved_at.is_some());
+
+        service
+            .moderate_review(review.id, moderator, ModerateReviewRequest {
+                status: ReviewStatus::Published,
+                reason: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(review_count(&pool, coffee_id).await, 1);
+        assert_eq!(service.list_reviews_for_coffee(coffee_id, &ReviewListQuery::default()).await.unwrap().page.data.len(), 1);
+    }
+
+    /// Place a completed order for one unit of the coffee
+    async fn create_completed_order(pool: &PgPool, user_id: i32, coffee_id: i32) {
+        let order_id: (uuid::Uuid,) = sqlx::query_as(
+            "INSERT INTO orders (user_id, status, payment_status, total_price)
+             VALUES ($1, 'completed', 'paid', 3.50) RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create test order");
+
+        sqlx::query(
+            "INSERT INTO order_items (order_id, coffee_item_id, quantity, price_snapshot, subtotal)
+             VALUES ($1, $2, 1, 3.50, 3.50)",
+        )
+        .bind(order_id.0)
+        .bind(coffee_id)
+        .execute(pool)
+        .await
+        .expect("Failed to create test order item");
+    }
+
+    #[tokio::test]
+    async fn test_review_marked_as_verified_purchase() {
+        let pool = create_test_pool().await;
+        let buyer = create_test_user(&pool).await;
+        let visitor = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        create_completed_order(&pool, buyer, coffee_id).await;
+
+        let service = create_service(pool.clone());
+        let request = CreateReviewRequest { coffee_id, rating: 5, comment: None };
+
+        let verified = service.create_review(buyer, request.clone()).await.unwrap();
+        let unverified = service.create_review(visitor, request).await.unwrap();
+        assert!(verified.verified_purchase);
+        assert!(!unverified.verified_purchase);
+
+        let verified_only = ReviewListQuery { verified: true, ..Default::default() };
+        let listed = service.list_reviews_for_coffee(coffee_id, &verified_only).await.unwrap();
+        assert_eq!(listed.page.data.len(), 1);
+        assert_eq!(listed.page.data[0].id, verified.id);
+        assert_eq!(listed.summary.total_reviews, 1);
+
+        let all = service.list_reviews_for_coffee(coffee_id, &ReviewListQuery::default()).await.unwrap();
+        assert_eq!(all.page.data.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_purchase_required_policy() {
+        let pool = create_test_pool().await;
+        let buyer = create_test_user(&pool).await;
+        let visitor = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        create_completed_order(&pool, buyer, coffee_id).await;
+
+        let service = create_service(pool.clone()).with_policy(ReviewPolicyConfig {
+            require_verified_purchase: true,
+        });
+        let request = CreateReviewRequest { coffee_id, rating: 4, comment: None };
+
+        assert!(matches!(
+            service.create_review(visitor, request.clone()).await,
+            Err(ServiceError::PurchaseRequired)
+        ));
+
+        let review = service.create_review(buyer, request).await.unwrap();
+        assert!(review.verified_purchase);
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_sorted_and_paginated_by_cursor() {
+        let pool = create_test_pool().await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_service(pool.clone());
+
+        let mut ids = Vec::new();
+        for rating in [3, 5, 1, 4, 5] {
+            let user_id = create_test_user(&pool).await;
+            let review = service
+                .create_review(user_id, CreateReviewRequest { coffee_id, rating, comment: None })
+                .await
+                .unwrap();
+            ids.push(review.id);
+        }
+
+        let mut query = ReviewListQuery {
+            sort: ReviewSort::Highest,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut ratings = Vec::new();
+        let mut seen = Vec::new();
+        loop {
+            let page = service.list_reviews_for_coffee(coffee_id, &query).await.unwrap();
+            assert_eq!(page.page.pagination.total, 5);
+            ratings.extend(page.page.data.iter().map(|review| review.rating));
+            seen.extend(page.page.data.iter().map(|review| review.id));
+            match page.page.pagination.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(ratings, vec![5, 5, 4, 3, 1]);
+        // Equal ratings are ordered newest first
+        assert_eq!(&seen[..2], &[ids[4], ids[1]]);
+
+        // Offset pagination and the lowest-first order
+        let lowest = ReviewListQuery {
+            sort: ReviewSort::Lowest,
+            page: Some(2),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = service.list_reviews_for_coffee(coffee_id, &lowest).await.unwrap();
+        let ratings: Vec<i16> = page.page.data.iter().map(|review| review.rating).collect();
+        assert_eq!(ratings, vec![4, 5]);
+
+        let bad_cursor = ReviewListQuery { cursor: Some("abc".to_string()), ..Default::default() };
+        assert!(matches!(
+            service.list_reviews_for_coffee(coffee_id, &bad_cursor).await,
+            Err(ServiceError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_review_cursor_survives_removal_of_its_review() {
+        let pool = create_test_pool().await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_service(pool.clone());
+
+        for rating in [5, 4, 3, 2] {
+            let user_id = create_test_user(&pool).await;
+            service
+                .create_review(user_id, CreateReviewRequest { coffee_id, rating, comment: None })
+                .await
+                .unwrap();
+        }
+
+        let mut query = ReviewListQuery {
+            sort: ReviewSort::Highest,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let first = service.list_reviews_for_coffee(coffee_id, &query).await.unwrap();
+        let last_on_page = first.page.data.last().unwrap().id;
+
+        sqlx::query("DELETE FROM reviews WHERE id = $1")
+            .bind(last_on_page)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        query.cursor = first.page.pagination.next_cursor;
+        let second = service.list_reviews_for_coffee(coffee_id, &query).await.unwrap();
+        let ratings: Vec<i16> = second.page.data.iter().map(|review| review.rating).collect();
+        assert_eq!(ratings, vec![3, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_star_filter_and_summary() {
+        let pool = create_test_pool().await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_service(pool.clone());
+
+        for rating in [5, 5, 2] {
+            let user_id = create_test_user(&pool).await;
+            service
+                .create_review(user_id, CreateReviewRequest { coffee_id, rating, comment: None })
+                .await
+                .unwrap();
+        }
+
+        let five_stars = ReviewListQuery { rating: Some(5), ..Default::default() };
+        let page = service.list_reviews_for_coffee(coffee_id, &five_stars).await.unwrap();
+        assert_eq!(page.page.data.len(), 2);
+        assert_eq!(page.page.pagination.total, 2);
+
+        // The summary covers every star value, regardless of the star filter
+        assert_eq!(page.summary.total_reviews, 3);
+        assert_eq!(page.summary.stars[0].count, 2);
+        assert_eq!(page.summary.stars[3].count, 1);
+        assert_eq!(page.summary.average_rating, Some(4.0));
+
+        let invalid = ReviewListQuery { rating: Some(6), ..Default::default() };
+        assert!(service.list_reviews_for_coffee(coffee_id, &invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_helpful_votes_counted_once_per_user() {
+        let pool = create_test_pool().await;
+        let author = create_test_user(&pool).await;
+        let voter1 = create_test_user(&pool).await;
+        let voter2 = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let service = create_service(pool.clone());
+
+        let helpful = service
+            .create_review(author, CreateReviewRequest { coffee_id, rating: 4, comment: None })
+            .await
+            .unwrap();
+        let other = service
+            .create_review(voter1, CreateReviewRequest { coffee_id, rating: 4, comment: None })
+            .await
+            .unwrap();
+
+        assert_eq!(service.vote_helpful(helpful.id, voter1).await.unwrap().helpful_count, 1);
+        assert_eq!(service.vote_helpful(helpful.id, voter1).await.unwrap().helpful_count, 1);
+        assert_eq!(service.vote_helpful(helpful.id, voter2).await.unwrap().helpful_count, 2);
+        assert!(matches!(
+            service.vote_helpful(helpful.id, author).await,
+            Err(ServiceError::ValidationError(_))
+        ));
+
+        let by_helpfulness = ReviewListQuery { sort: ReviewSort::Helpful, ..Default::default() };
+        let page = service.list_reviews_for_coffee(coffee_id, &by_helpfulness).await.unwrap();
+        assert_eq!(page.page.data[0].id, helpful.id);
+        assert_eq!(page.page.data[1].id, other.id);
+
+        let removed = service.remove_helpful_vote(helpful.id, voter2).await.unwrap();
+        assert_eq!(removed.helpful_count, 1);
+        assert!(!removed.voted);
+        assert_eq!(service.remove_helpful_vote(helpful.id, voter2).await.unwrap().helpful_count, 1);
+    }
+
+    /// Notifier that records what it was asked to deliver
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: std::sync::Mutex<Vec<(i32, Notification)>>,
+    }
+
+    #[axum::async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(
+            &self,
+            user_id: i32,
+            notification: Notification,
+        ) -> Result<(), crate::notifications::NotificationError> {
+            self.sent.lock().unwrap().push((user_id, notification));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reply_is_single_editable_and_notifies_once() {
+        let pool = create_test_pool().await;
+        let reviewer = create_test_user(&pool).await;
+        let staff = create_test_user(&pool).await;
+        let coffee_id = create_test_coffee(&pool).await;
+        let notifier = Arc::new(RecordingNotifier::default());
+        let service = create_service(pool.clone()).with_notifier(notifier.clone());
+
+        let review = service
+            .create_review(reviewer, CreateReviewRequest { coffee_id, rating: 3, comment: None })
+            .await
+            .unwrap();
+
+        let first = service
+            .reply_to_review(review.id, staff, ReviewReplyRequest { body: "Thanks!".to_string() })
+            .await
+            .unwrap();
+        let edited = service
+            .reply_to_review(review.id, staff, ReviewReplyRequest { body: "Thanks, see you soon!".to_string() })
+            .await
+            .unwrap();
+
+        assert_eq!(edited.body, "Thanks, see you soon!");
+        assert_eq!(edited.author_id, Some(staff));
+        assert_eq!(edited.created_at, first.created_at);
+        assert!(edited.updated_at >= first.updated_at);
+        assert_eq!(
+            *notifier.sent.lock().unwrap(),
+            vec![(reviewer, Notification::ReviewReply { review_id: review.id, coffee_id })]
+        );
+
+        let page = service
+            .list_reviews_for_coffee(coffee_id, &ReviewListQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(page.page.data[0].reply.as_ref().map(|reply| reply.body.as_str()), Some("Thanks, see you soon!"));
+
+        let empty = ReviewReplyRequest { body: String::new() };
+        assert!(matches!(
+            service.reply_to_review(review.id, staff, empty).await,
+            Err(ServiceError::ValidationError(_))
+        ));
+
+        service.delete_reply(review.id).await.unwrap();
+        assert!(matches!(service.delete_reply(review.id).await, Err(ServiceError::NotFound)));
+        let page = service
+            .list_reviews_for_coffee(coffee_id, &ReviewListQuery::default())
+            .await
+            .unwrap();
+        assert!(page.page.data[0].reply.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reply_to_missing_review_not_found() {
+        let pool = create_test_pool().await;
+        let staff = create_test_user(&pool).await;
+        let service = create_service(pool);
+
+        let result = service
+            .reply_to_review(i32::MAX, staff, ReviewReplyRequest { body: "Hello".to_string() })
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_user_reviews_listed_newest_first_with_status() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let other_user = create_test_user(&pool).await;
+        let coffee1 = create_test_coffee(&pool).await;
+        let coffee2 = create_test_coffee(&pool).await;
+        let coffee3 = create_test_coffee(&pool).await;
+        let service = create_moderated_service(pool.clone());
+
+        let first = service
+            .create_review(user_id, CreateReviewRequest { coffee_id: coffee1, rating: 4, comment: None })
+            .await
+            .unwrap();
+        let held = service
+            .create_review(user_id, CreateReviewRequest {
+                coffee_id: coffee2,
+                rating: 1,
+                comment: Some("What a scam".to_string()),
+            })
+            .await
+            .unwrap();
+        service
+            .create_review(other_user, CreateReviewRequest { coffee_id: coffee1, rating: 2, comment: None })
+            .await
+            .unwrap();
+
+        let query = PaginationQuery { page: 1, limit: 1, cursor: None };
+        let page = service.list_user_reviews(user_id, &query).await.unwrap();
+        assert_eq!(page.pagination.total, 2);
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.data[0].id, held.id);
+        assert_eq!(page.data[0].status, ReviewStatus::Pending);
+
+        let query = PaginationQuery { page: 2, limit: 1, cursor: None };
+        let page = service.list_user_reviews(user_id, &query).await.unwrap();
+        assert_eq!(page.data[0].id, first.id);
+
+        let mine = service.find_user_review(user_id, coffee1).await.unwrap().unwrap();
+        assert_eq!(mine.id, first.id);
+        assert_eq!(mine.rating, 4);
+        assert!(service.find_user_review(user_id, coffee3).await.unwrap().is_none());
+    }
+}
@@ -86,7 +86,7 @@ async fn test_create_review_success() {
         .expect("Failed to create review");
     
     assert!(review.id > 0);
-    assert_eq!(review.user_id, user_id);
+    assert_eq!(review.user_id, Some(user_id));
     assert_eq!(review.coffee_id, coffee_id);
     assert_eq!(review.rating, 5);
     assert_eq!(review.comment, Some("Great coffee!".to_string()));
@@ -114,7 +114,7 @@ async fn test_find_by_id_existing() {
         .expect("Review not found");
     
     assert_eq!(found.id, created.id);
-    assert_eq!(found.user_id, user_id);
+    assert_eq!(found.user_id, Some(user_id));
     assert_eq!(found.coffee_id, coffee_id);
     assert_eq!(found.rating, 4);
 }
@@ -151,7 +151,7 @@ async fn test_find_by_user_and_coffee_existing() {
         .expect("Failed to find review")
         .expect("Review not found");
     
-    assert_eq!(found.user_id, user_id);
+    assert_eq!(found.user_id, Some(user_id));
     assert_eq!(found.coffee_id, coffee_id);
 }
 
@@ -267,7 +267,7 @@ async fn test_find_by_coffee() {
     repository.create(user2, coffee_id, 4, Some("Good".to_string())).await.unwrap();
     
     let reviews = repository
-        .find_by_coffee(coffee_id)
+        .find_by_coffee(coffee_id, &ReviewListQuery::default(), 20, 0, None)
         .await
         .expect("Failed to find reviews");
     
@@ -281,7 +281,7 @@ async fn test_find_by_coffee_no_reviews() {
     let repository = ReviewRepository::new(pool.clone());
     
     let reviews = repository
-        .find_by_coffee(coffee_id)
+        .find_by_coffee(coffee_id, &ReviewListQuery::default(), 20, 0, None)
         .await
         .expect("Failed to find reviews");
     
@@ -289,7 +289,7 @@ async fn test_find_by_coffee_no_reviews() {
 }
 
 #[tokio::test]
-async fn test_get_ratings_for_coffee() {
+async fn test_get_rating_aggregate() {
     let pool = create_test_pool().await;
     let user1 = create_test_user(&pool).await;
     let user2 = create_test_user(&pool).await;
@@ -300,51 +300,40 @@ async fn test_get_ratings_for_coffee() {
     
     repository.create(user1, coffee_id, 5, None).await.unwrap();
     repository.create(user2, coffee_id, 4, None).await.unwrap();
-    repository.create(user3, coffee_id, 3, None).await.unwrap();
+    repository.create(user3, coffee_id, 4, None).await.unwrap();
     
-    let ratings = repository
-        .get_ratings_for_coffee(coffee_id)
+    let aggregate = repository
+        .get_rating_aggregate(coffee_id)
         .await
-        .expect("Failed to get ratings");
+        .expect("Failed to get rating totals");
     
-    assert_eq!(ratings.len(), 3);
-    assert!(ratings.contains(&5));
-    assert!(ratings.contains(&4));
-    assert!(ratings.contains(&3));
+    assert_eq!(aggregate.rating_sum, 13);
+    assert_eq!(aggregate.review_count, 3);
+    assert_eq!(aggregate.rating_5_count, 1);
+    assert_eq!(aggregate.rating_4_count, 2);
+    assert_eq!(aggregate.star_counts(), vec![(1, 0), (2, 0), (3, 0), (4, 2), (5, 1)]);
 }
 
 #[tokio::test]
-async fn test_update_coffee_rating() {
+async fn test_update_weighted_rating() {
     let pool = create_test_pool().await;
     let coffee_id = create_test_coffee(&pool).await;
     let repository = ReviewRepository::new(pool.clone());
     
     repository
-        .update_coffee_rating(coffee_id, Some(4.5), 10)
+        .update_weighted_rating(coffee_id, Some(3.9))
         .await
-        .expect("Failed to update coffee rating");
+        .expect("Failed to update weighted rating");
     
-    // Query the raw values to verify they were set
-    let count: i32 = sqlx::query_scalar(
-        "SELECT review_count FROM coffees WHERE id = $1"
+    let weighted: f64 = sqlx::query_scalar(
+        "SELECT weighted_rating::FLOAT8 FROM coffees WHERE id = $1"
     )
     .bind(coffee_id)
     .fetch_one(&pool)
     .await
-    .expect("Failed to fetch review count");
+    .expect("Failed to fetch weighted rating");
     
-    assert_eq!(count, 10);
-    
-    // Verify average_rating is not null
-    let has_rating: bool = sqlx::query_scalar(
-        "SELECT average_rating IS NOT NULL FROM coffees WHERE id = $1"
-    )
-    .bind(coffee_id)
-    .fetch_one(&pool)
-    .await
-    .expect("Failed to check rating");
-    
-    assert!(has_rating);
+    assert_eq!(weighted, 3.9);
 }
 
 #[tokio::test]
@@ -373,3 +362,22 @@ async fn test_coffee_exists_false() {
     
     assert!(!exists);
 }
+
+#[test]
+fn test_rating_summary_fills_missing_stars() {
+    let summary = RatingSummary::from_counts(&[(5, 3), (1, 1)]);
+
+    let stars: Vec<(i16, i64)> = summary.stars.iter().map(|star| (star.rating, star.count)).collect();
+    assert_eq!(stars, vec![(5, 3), (4, 0), (3, 0), (2, 0), (1, 1)]);
+    assert_eq!(summary.total_reviews, 4);
+    assert_eq!(summary.average_rating, Some(4.0));
+}
+
+#[test]
+fn test_rating_summary_empty() {
+    let summary = RatingSummary::from_counts(&[]);
+
+    assert_eq!(summary.total_reviews, 0);
+    assert_eq!(summary.average_rating, None);
+    assert_eq!(summary.stars.len(), 5);
+}
@@ -57,9 +57,13 @@ async fn create_test_app(pool: PgPool) -> TestServer {
     let password_service = crate::auth::password::PasswordService;
     let user_repository = crate::auth::repository::UserRepository::new(pool.clone());
     let token_repository = crate::auth::repository::TokenRepository::new(pool.clone());
+    let security_repository = crate::auth::repository::SecurityRepository::new(pool.clone());
+    let two_factor_repository = crate::auth::repository::TwoFactorRepository::new(pool.clone());
     let auth_service = std::sync::Arc::new(crate::auth::service::AuthService::new(
         user_repository,
         token_repository,
+        security_repository,
+        two_factor_repository,
         password_service,
         token_service,
     ));
@@ -80,6 +84,18 @@ async fn create_test_app(pool: PgPool) -> TestServer {
         crate::business_rules::BusinessRulesEngine::new(pool.clone())
     );
     
+    // Initialize recommendation service
+    let orders_analytics = crate::analytics::repositories::OrdersAnalyticsRepository::new(pool.clone());
+    let recommendation_service = crate::recommendations::RecommendationService::new(
+        crate::recommendations::RecommendationRepository::new(pool.clone()),
+        crate::analytics::services::PopularCoffeesService::new(
+            orders_analytics.clone(),
+            crate::analytics::repositories::ReviewsAnalyticsRepository::new(pool.clone()),
+        ),
+        crate::analytics::services::TrendCalculationService::new(orders_analytics),
+        business_rules_engine.clone(),
+    );
+    
     let state = AppState { 
         db: pool.clone(),
         auth_service: auth_service.clone(),
@@ -87,6 +103,11 @@ async fn create_test_app(pool: PgPool) -> TestServer {
         order_service,
         order_items_repo,
         business_rules_engine,
+        account_service: crate::account::AccountService::new(
+            crate::account::AccountRepository::new(pool.clone()),
+            auth_service.clone(),
+        ),
+        recommendation_service,
     };
     
     use axum::middleware::from_fn;